@@ -0,0 +1,125 @@
+// Compares the SIMD and naive implementations of the byte scanning used by
+// the OBJ and STL parsers, on the text OBJ and STL files of the assimp test
+// models.
+//
+// Run `cargo test --test assimp` first to clone the assimp test models, then
+// run with `cargo bench --bench bytes`.
+
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use mesh_loader::__private::simd;
+
+const ITERATIONS: u32 = 100;
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    for e in fs::read_dir(dir).unwrap() {
+        let path = e.unwrap().path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else if matches!(
+            path.extension().and_then(OsStr::to_str),
+            Some("obj" | "OBJ" | "stl" | "STL")
+        ) {
+            files.push(path);
+        }
+    }
+}
+
+/// Splits the inputs into lines and words in the same way as the parsers.
+fn scan(inputs: &[Vec<u8>], memchr2: fn(u8, u8, &[u8]) -> Option<usize>) -> usize {
+    let mut words = 0;
+    for mut s in inputs.iter().map(|s| &s[..]) {
+        while !s.is_empty() {
+            let (mut line, s_next) = match memchr2(b'\n', b'\r', s) {
+                Some(n) => (&s[..n], &s[n + 1..]),
+                None => (s, &[][..]),
+            };
+            while let Some(n) = memchr2(b' ', b'\t', line) {
+                words += 1;
+                line = &line[n + 1..];
+            }
+            s = s_next;
+        }
+    }
+    words
+}
+
+/// Skips the spaces at the start of each word in the same way as the parsers.
+fn skip(inputs: &[Vec<u8>], count_spaces: fn(&[u8]) -> usize) -> usize {
+    let mut spaces = 0;
+    for s in inputs {
+        let mut s = &s[..];
+        while !s.is_empty() {
+            let n = count_spaces(s);
+            spaces += n;
+            s = s.get(n + 1..).unwrap_or_default();
+        }
+    }
+    spaces
+}
+
+fn count(inputs: &[Vec<u8>], bytecount: fn(u8, &[u8]) -> usize) -> usize {
+    inputs.iter().map(|s| bytecount(b'\n', s)).sum()
+}
+
+fn bench<T: PartialEq + std::fmt::Debug>(name: &str, naive: impl Fn() -> T, simd: impl Fn() -> T) {
+    fn run<T: PartialEq + std::fmt::Debug>(f: impl Fn() -> T) -> (T, Duration) {
+        let start = Instant::now();
+        let result = f();
+        for _ in 1..ITERATIONS {
+            // Use the result so that the calls are not optimized out.
+            assert_eq!(f(), result);
+        }
+        (result, start.elapsed() / ITERATIONS)
+    }
+    let (expected, naive) = run(naive);
+    let (actual, simd) = run(simd);
+    assert_eq!(expected, actual);
+    println!(
+        "{name}: naive {naive:?}, simd {simd:?} ({:.2}x)",
+        naive.as_secs_f64() / simd.as_secs_f64()
+    );
+}
+
+fn main() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let models = manifest_dir.join("tests/fixtures/assimp/test/models");
+    if !models.exists() {
+        eprintln!(
+            "{} not found; run `cargo test --test assimp` first",
+            models.display()
+        );
+        return;
+    }
+    let mut files = vec![];
+    collect_files(&models, &mut files);
+    // Binary STL files are not scanned by these functions.
+    let inputs: Vec<_> = files
+        .iter()
+        .map(|path| fs::read(path).unwrap())
+        .filter(|s| s.is_ascii())
+        .collect();
+    let size: usize = inputs.iter().map(Vec::len).sum();
+    println!("{} files, {size} bytes", inputs.len());
+
+    bench(
+        "memchr2 (lines and words)",
+        || scan(&inputs, simd::memchr2_naive),
+        || scan(&inputs, simd::memchr2),
+    );
+    bench(
+        "count_spaces (spaces between words)",
+        || skip(&inputs, simd::count_spaces_naive),
+        || skip(&inputs, simd::count_spaces),
+    );
+    bench(
+        "bytecount (line count)",
+        || count(&inputs, simd::bytecount_naive),
+        || count(&inputs, simd::bytecount),
+    );
+}
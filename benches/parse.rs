@@ -0,0 +1,143 @@
+// Compares the sequential and parallel builds of the parsers.
+//
+// Run `cargo bench --bench parse` to measure the sequential build (without the
+// `rayon` feature), then `cargo bench --features rayon --bench parse` to
+// measure the parallel build and compare it with the sequential results.
+
+#![allow(clippy::cast_precision_loss)]
+
+use std::{
+    fmt::Write as _,
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use mesh_loader::Loader;
+
+const ITERATIONS: u32 = 10;
+
+fn stl(num_triangles: u32) -> Vec<u8> {
+    let mut stl = vec![0; 80];
+    stl.extend_from_slice(&num_triangles.to_le_bytes());
+    for i in 0..num_triangles {
+        for f in [
+            0., 0., 1., i as f32, 0., 0., 0., i as f32, 0., 0., 0., i as f32,
+        ] {
+            stl.extend_from_slice(&f32::to_le_bytes(f));
+        }
+        stl.extend_from_slice(&[0; 2]);
+    }
+    stl
+}
+
+fn obj(num_vertices: u32) -> Vec<u8> {
+    let mut obj = String::new();
+    for i in 0..num_vertices {
+        let _ = writeln!(obj, "v {i} 0.5 -1e-3\nvn 0 0 1\nvt 0.{i} 1");
+        if i >= 2 {
+            let _ = writeln!(obj, "f -3/-3/-3 -2/-2/-2 -1/-1/-1");
+        }
+    }
+    obj.into_bytes()
+}
+
+fn collada(num_vertices: u32) -> Vec<u8> {
+    let mut positions = String::new();
+    let mut indices = String::new();
+    for i in 0..num_vertices {
+        let _ = write!(positions, "{i} 0.5 -1e-3 ");
+        let _ = write!(indices, "{i} ");
+    }
+    format!(
+        r##"<?xml version="1.0" encoding="utf-8"?>
+<COLLADA xmlns="http://www.collada.org/2005/11/COLLADASchema" version="1.4.1">
+  <library_geometries>
+    <geometry id="mesh">
+      <mesh>
+        <source id="positions">
+          <float_array id="positions-array" count="{count}">{positions}</float_array>
+          <technique_common>
+            <accessor source="#positions-array" count="{num_vertices}" stride="3">
+              <param name="X" type="float"/>
+              <param name="Y" type="float"/>
+              <param name="Z" type="float"/>
+            </accessor>
+          </technique_common>
+        </source>
+        <vertices id="vertices">
+          <input semantic="POSITION" source="#positions"/>
+        </vertices>
+        <triangles count="{num_triangles}">
+          <input semantic="VERTEX" source="#vertices" offset="0"/>
+          <p>{indices}</p>
+        </triangles>
+      </mesh>
+    </geometry>
+  </library_geometries>
+  <library_visual_scenes>
+    <visual_scene id="scene">
+      <node id="node">
+        <instance_geometry url="#mesh"/>
+      </node>
+    </visual_scene>
+  </library_visual_scenes>
+  <scene>
+    <instance_visual_scene url="#scene"/>
+  </scene>
+</COLLADA>
+"##,
+        count = num_vertices * 3,
+        num_triangles = num_vertices / 3,
+    )
+    .into_bytes()
+}
+
+fn bench(bytes: &[u8], path: &str) -> Duration {
+    let loader = Loader::default();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        loader.load_from_slice(bytes, path).unwrap();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    // The results of the sequential build, which the parallel build compares with.
+    let results = Path::new(env!("CARGO_TARGET_TMPDIR")).join("parse-sequential.txt");
+    let sequential = fs::read_to_string(&results).unwrap_or_default();
+    let mut out = String::new();
+    for (name, bytes, path) in [
+        ("binary stl", stl(2_000_000), "a.stl"),
+        ("obj", obj(1_000_000), "a.obj"),
+        ("collada", collada(1_500_000), "a.dae"),
+    ] {
+        let size = bytes.len() as f64 / 1024. / 1024.;
+        let time = bench(&bytes, path);
+        let _ = writeln!(out, "{name}\t{}", time.as_nanos());
+        if cfg!(feature = "rayon") {
+            let sequential = sequential
+                .lines()
+                .filter_map(|l| l.split_once('\t'))
+                .find(|&(n, _)| n == name)
+                .and_then(|(_, t)| t.parse::<u64>().ok())
+                .map(Duration::from_nanos);
+            match sequential {
+                Some(sequential) => println!(
+                    "{name} ({size:.1} MiB): sequential {sequential:?}, parallel {time:?} ({:.2}x)",
+                    sequential.as_secs_f64() / time.as_secs_f64()
+                ),
+                None => println!("{name} ({size:.1} MiB): parallel {time:?}"),
+            }
+        } else {
+            println!("{name} ({size:.1} MiB): sequential {time:?}");
+        }
+    }
+    if cfg!(feature = "rayon") {
+        if sequential.is_empty() {
+            println!("run `cargo bench --bench parse` first to compare with the sequential build");
+        }
+    } else {
+        fs::write(&results, out).unwrap();
+    }
+}
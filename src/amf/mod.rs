@@ -0,0 +1,496 @@
+//! [AMF] (.amf) parser.
+//!
+//! Both plain AMF and zip-compressed AMF are supported. Objects are placed
+//! according to `<constellation>` elements if present, and coordinates are
+//! converted to meters according to the `unit` attribute.
+//!
+//! Color formulas, textures, and curved triangles (`<edge>`) are not
+//! supported.
+//!
+//! [AMF]: https://en.wikipedia.org/wiki/Additive_manufacturing_file_format
+
+#![allow(clippy::many_single_char_names)]
+
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+    path::Path,
+};
+
+use crate::{
+    common::{self, DEFAULT_COLOR},
+    utils::{
+        float,
+        matrix::{normalize, Matrix4x4},
+        utf16::decode_string,
+        xml::{self, XmlNodeExt},
+        zip,
+    },
+    Color4, Vec3,
+};
+
+/// Parses meshes from bytes of AMF text or zip-compressed AMF.
+#[inline]
+pub fn from_slice(bytes: &[u8]) -> io::Result<common::Scene> {
+    from_slice_internal(bytes, None)
+}
+
+pub(crate) fn from_slice_internal(bytes: &[u8], path: Option<&Path>) -> io::Result<common::Scene> {
+    read_amf(bytes).map_err(|e| crate::error::with_path(e, path))
+}
+
+fn read_amf(bytes: &[u8]) -> io::Result<common::Scene> {
+    if zip::is_zip(bytes) {
+        let archive = zip::Archive::new(bytes)?;
+        // A zip-compressed AMF contains a single AMF file, which usually has
+        // the same name as the archive.
+        let entry = archive
+            .entries()
+            .find(|e| {
+                Path::new(&e.name)
+                    .extension()
+                    .map_or(false, |ext| ext.eq_ignore_ascii_case("amf"))
+            })
+            .or_else(|| archive.entries().next())
+            .ok_or_else(|| format_err!("no AMF file found in zip archive"))?;
+        let bytes = archive.read(entry)?;
+        return from_str(&decode_string(&bytes)?);
+    }
+    from_str(&decode_string(bytes)?)
+}
+
+/// Parses meshes from a string of AMF text.
+#[inline]
+pub fn from_str(s: &str) -> io::Result<common::Scene> {
+    let xml = xml::Document::parse(s).map_err(crate::error::invalid_data)?;
+    let amf = Amf::parse(&xml)?;
+    Ok(amf.build())
+}
+
+/// The `<amf>` element.
+struct Amf<'a> {
+    /// Scale factor to convert coordinates to meters.
+    unit: f32,
+    objects: Vec<Object<'a>>,
+    materials: HashMap<&'a str, Material<'a>>,
+    constellations: Vec<Constellation<'a>>,
+}
+
+/// The `<object>` element.
+struct Object<'a> {
+    id: &'a str,
+    name: Option<&'a str>,
+    color: Option<Color4>,
+    vertices: Vec<Vec3>,
+    normals: Vec<Option<Vec3>>,
+    colors: Vec<Option<Color4>>,
+    volumes: Vec<Volume<'a>>,
+}
+
+/// The `<volume>` element.
+struct Volume<'a> {
+    name: Option<&'a str>,
+    material_id: Option<&'a str>,
+    color: Option<Color4>,
+    triangles: Vec<Triangle>,
+}
+
+/// The `<triangle>` element.
+struct Triangle {
+    indices: [usize; 3],
+    color: Option<Color4>,
+}
+
+/// The `<material>` element.
+struct Material<'a> {
+    name: Option<&'a str>,
+    color: Option<Color4>,
+}
+
+/// The `<constellation>` element.
+struct Constellation<'a> {
+    id: &'a str,
+    instances: Vec<Instance<'a>>,
+}
+
+/// The `<instance>` element.
+struct Instance<'a> {
+    object_id: &'a str,
+    transform: Matrix4x4,
+}
+
+impl<'a> Amf<'a> {
+    fn parse(doc: &'a xml::Document<'_>) -> io::Result<Self> {
+        let node = doc.root_element();
+        if node.tag_name().name() != "amf" {
+            bail!(
+                "expected <amf> element, found <{}> element at {}",
+                node.tag_name().name(),
+                node.node_location()
+            );
+        }
+
+        let unit = match node.attribute("unit") {
+            None | Some("millimeter") => 0.001,
+            Some("meter") => 1.,
+            Some("inch") => 0.0254,
+            Some("feet") => 0.3048,
+            Some("micron") => 0.000_001,
+            Some(unit) => bail!(
+                "unknown unit {:?} in <amf> element at {}",
+                unit,
+                node.attr_value_location("unit")
+            ),
+        };
+
+        let mut amf = Self {
+            unit,
+            objects: vec![],
+            materials: HashMap::new(),
+            constellations: vec![],
+        };
+        for child in node.element_children() {
+            match child.tag_name().name() {
+                "object" => amf.objects.push(parse_object(child)?),
+                "material" => {
+                    let id = child.required_attribute("id")?;
+                    let material = Material {
+                        name: metadata(child, "name"),
+                        color: child.child("color").map(parse_color).transpose()?,
+                    };
+                    amf.materials.insert(id, material);
+                }
+                "constellation" => amf.constellations.push(parse_constellation(child)?),
+                _ => { /* ignore */ }
+            }
+        }
+
+        Ok(amf)
+    }
+
+    fn build(&self) -> common::Scene {
+        let mut scene = common::Scene::default();
+        let root = Matrix4x4::scaling([self.unit; 3]);
+
+        if self.constellations.is_empty() {
+            for object in &self.objects {
+                self.push_object(&mut scene, object, root);
+            }
+            return scene;
+        }
+
+        // Constellations that are not referenced by other constellations are
+        // the roots of the scene. Objects that are not referenced by any
+        // constellation are placed at the origin.
+        let referenced: HashSet<_> = self
+            .constellations
+            .iter()
+            .flat_map(|c| &c.instances)
+            .map(|i| i.object_id)
+            .collect();
+        for constellation in &self.constellations {
+            if !referenced.contains(constellation.id) {
+                self.push_constellation(&mut scene, constellation, root, &mut vec![]);
+            }
+        }
+        for object in &self.objects {
+            if !referenced.contains(object.id) {
+                self.push_object(&mut scene, object, root);
+            }
+        }
+        scene
+    }
+
+    fn push_constellation(
+        &self,
+        scene: &mut common::Scene,
+        constellation: &Constellation<'a>,
+        transform: Matrix4x4,
+        visited: &mut Vec<&'a str>,
+    ) {
+        // Guard against cyclic references.
+        if visited.contains(&constellation.id) {
+            return;
+        }
+        visited.push(constellation.id);
+        for instance in &constellation.instances {
+            let mut transform = transform;
+            transform *= instance.transform;
+            if let Some(object) = self.objects.iter().find(|o| o.id == instance.object_id) {
+                self.push_object(scene, object, transform);
+            } else if let Some(constellation) = self
+                .constellations
+                .iter()
+                .find(|c| c.id == instance.object_id)
+            {
+                self.push_constellation(scene, constellation, transform, visited);
+            }
+        }
+        visited.pop();
+    }
+
+    fn push_object(&self, scene: &mut common::Scene, object: &Object<'_>, transform: Matrix4x4) {
+        let has_vertex_colors = object.colors.iter().any(Option::is_some);
+        let has_normals = !object.normals.is_empty() && object.normals.iter().all(Option::is_some);
+        for volume in &object.volumes {
+            let material = volume
+                .material_id
+                .and_then(|id| self.materials.get(id))
+                .unwrap_or(&Material {
+                    name: None,
+                    color: None,
+                });
+            let base_color = volume.color.or(object.color).or(material.color);
+            let has_colors =
+                has_vertex_colors || volume.triangles.iter().any(|t| t.color.is_some());
+
+            let mut mesh = common::Mesh {
+                name: volume.name.or(object.name).unwrap_or(object.id).to_owned(),
+                ..Default::default()
+            };
+            for triangle in &volume.triangles {
+                #[allow(clippy::cast_possible_truncation)]
+                let vertices_indices = [
+                    mesh.vertices.len() as u32,
+                    (mesh.vertices.len() + 1) as u32,
+                    (mesh.vertices.len() + 2) as u32,
+                ];
+                for &i in &triangle.indices {
+                    let mut v = object.vertices[i];
+                    v *= transform;
+                    mesh.vertices.push(v);
+                    if has_normals {
+                        let [x, y, z] = object.normals[i].unwrap();
+                        let mut n = [x, y, z, 0.];
+                        n *= transform;
+                        mesh.normals.push(normalize([n[0], n[1], n[2]]));
+                    }
+                    if has_colors {
+                        mesh.colors[0].push(
+                            triangle
+                                .color
+                                .or(object.colors[i])
+                                .or(base_color)
+                                .unwrap_or(DEFAULT_COLOR),
+                        );
+                    }
+                }
+                mesh.faces.push(vertices_indices);
+            }
+            scene.meshes.push(mesh);
+
+            let mut mat = common::Material {
+                name: material.name.unwrap_or_default().to_owned(),
+                ..Default::default()
+            };
+            mat.color.diffuse = base_color;
+            scene.materials.push(mat);
+        }
+    }
+}
+
+/*
+<object id="...">
+  <metadata type="name">...</metadata>
+  <color>...</color>
+  <mesh>
+    <vertices>
+      <vertex>
+        <coordinates><x>...</x><y>...</y><z>...</z></coordinates>
+        <normal><nx>...</nx><ny>...</ny><nz>...</nz></normal>
+        <color>...</color>
+      </vertex>
+      ...
+    </vertices>
+    <volume materialid="...">
+      <metadata type="name">...</metadata>
+      <color>...</color>
+      <triangle><v1>...</v1><v2>...</v2><v3>...</v3><color>...</color></triangle>
+      ...
+    </volume>
+    ...
+  </mesh>
+</object>
+*/
+fn parse_object<'a>(node: xml::Node<'a, '_>) -> io::Result<Object<'a>> {
+    let mut object = Object {
+        id: node.required_attribute("id")?,
+        name: metadata(node, "name"),
+        color: node.child("color").map(parse_color).transpose()?,
+        vertices: vec![],
+        normals: vec![],
+        colors: vec![],
+        volumes: vec![],
+    };
+    let mesh = match node.child("mesh") {
+        Some(mesh) => mesh,
+        None => return Ok(object),
+    };
+
+    if let Some(vertices) = mesh.child("vertices") {
+        for vertex in vertices
+            .element_children()
+            .filter(|n| n.tag_name().name() == "vertex")
+        {
+            let coordinates = required_child(vertex, "coordinates")?;
+            object
+                .vertices
+                .push(parse_vec3(coordinates, ["x", "y", "z"])?);
+            object.normals.push(
+                vertex
+                    .child("normal")
+                    .map(|n| parse_vec3(n, ["nx", "ny", "nz"]))
+                    .transpose()?,
+            );
+            object
+                .colors
+                .push(vertex.child("color").map(parse_color).transpose()?);
+        }
+    }
+
+    for volume in mesh
+        .element_children()
+        .filter(|n| n.tag_name().name() == "volume")
+    {
+        let mut triangles = vec![];
+        for triangle in volume
+            .element_children()
+            .filter(|n| n.tag_name().name() == "triangle")
+        {
+            let mut indices = [0; 3];
+            for (index, name) in indices.iter_mut().zip(["v1", "v2", "v3"]) {
+                let v = required_child(triangle, name)?;
+                *index = v.trimmed_text().parse().map_err(|e| {
+                    format_err!(
+                        "{} in <{}> element at {}: {:?}",
+                        e,
+                        name,
+                        v.text_location(),
+                        v.trimmed_text()
+                    )
+                })?;
+                if *index >= object.vertices.len() {
+                    bail!(
+                        "vertex index out of bounds ({}) in <{}> element at {}",
+                        index,
+                        name,
+                        v.text_location()
+                    );
+                }
+            }
+            triangles.push(Triangle {
+                indices,
+                color: triangle.child("color").map(parse_color).transpose()?,
+            });
+        }
+        object.volumes.push(Volume {
+            name: metadata(volume, "name"),
+            material_id: volume.attribute("materialid"),
+            color: volume.child("color").map(parse_color).transpose()?,
+            triangles,
+        });
+    }
+
+    Ok(object)
+}
+
+/*
+<constellation id="...">
+  <instance objectid="...">
+    <deltax>...</deltax><deltay>...</deltay><deltaz>...</deltaz>
+    <rx>...</rx><ry>...</ry><rz>...</rz>
+  </instance>
+  ...
+</constellation>
+*/
+fn parse_constellation<'a>(node: xml::Node<'a, '_>) -> io::Result<Constellation<'a>> {
+    let mut instances = vec![];
+    for instance in node
+        .element_children()
+        .filter(|n| n.tag_name().name() == "instance")
+    {
+        let mut delta = [0.; 3];
+        let mut rotation = [0.; 3];
+        for child in instance.element_children() {
+            match child.tag_name().name() {
+                "deltax" => delta[0] = parse_float(child)?,
+                "deltay" => delta[1] = parse_float(child)?,
+                "deltaz" => delta[2] = parse_float(child)?,
+                "rx" => rotation[0] = parse_float(child)?,
+                "ry" => rotation[1] = parse_float(child)?,
+                "rz" => rotation[2] = parse_float(child)?,
+                _ => { /* ignore */ }
+            }
+        }
+        // Rotations are in degrees and applied in the order of x, y, z.
+        let mut transform = Matrix4x4::translation(delta);
+        transform *= Matrix4x4::rotation(rotation[2].to_radians(), [0., 0., 1.]);
+        transform *= Matrix4x4::rotation(rotation[1].to_radians(), [0., 1., 0.]);
+        transform *= Matrix4x4::rotation(rotation[0].to_radians(), [1., 0., 0.]);
+        instances.push(Instance {
+            object_id: instance.required_attribute("objectid")?,
+            transform,
+        });
+    }
+    Ok(Constellation {
+        id: node.required_attribute("id")?,
+        instances,
+    })
+}
+
+/// Returns the text of the `<metadata type="...">` child element.
+fn metadata<'a>(node: xml::Node<'a, '_>, ty: &str) -> Option<&'a str> {
+    node.element_children()
+        .find(|n| n.tag_name().name() == "metadata" && n.attribute("type") == Some(ty))
+        .map(|n| n.trimmed_text())
+}
+
+fn required_child<'a, 'input>(
+    node: xml::Node<'a, 'input>,
+    name: &str,
+) -> io::Result<xml::Node<'a, 'input>> {
+    match node.child(name) {
+        Some(child) => Ok(child),
+        None => bail!(
+            "expected <{}> element in <{}> element at {}",
+            name,
+            node.tag_name().name(),
+            node.node_location()
+        ),
+    }
+}
+
+fn parse_float(node: xml::Node<'_, '_>) -> io::Result<f32> {
+    let text = node.trimmed_text();
+    match float::parse(text.as_bytes()) {
+        Some(v) => Ok(v),
+        None => bail!(
+            "error while parsing a float in <{}> element at {}: {:?}",
+            node.tag_name().name(),
+            node.text_location(),
+            text
+        ),
+    }
+}
+
+fn parse_vec3(node: xml::Node<'_, '_>, names: [&str; 3]) -> io::Result<Vec3> {
+    let mut v = [0.; 3];
+    for (v, name) in v.iter_mut().zip(names) {
+        *v = parse_float(required_child(node, name)?)?;
+    }
+    Ok(v)
+}
+
+/*
+<color><r>...</r><g>...</g><b>...</b><a>...</a></color>
+*/
+fn parse_color(node: xml::Node<'_, '_>) -> io::Result<Color4> {
+    let mut color = [0., 0., 0., 1.];
+    for (c, name) in color.iter_mut().zip(["r", "g", "b"]) {
+        *c = parse_float(required_child(node, name)?)?;
+    }
+    if let Some(a) = node.child("a") {
+        color[3] = parse_float(a)?;
+    }
+    Ok(color)
+}
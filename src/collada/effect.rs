@@ -129,14 +129,14 @@ pub(super) struct Texture<'a> {
 
 pub(super) fn parse_library_effects<'a>(
     cx: &mut Context<'a>,
-    node: xml::Node<'a, '_>,
+    node: stream::Node<'a, '_>,
 ) -> io::Result<()> {
-    debug_assert_eq!(node.tag_name().name(), "library_effects");
+    debug_assert_eq!(node.name(), "library_effects");
     // cx.library_effects.id = node.attribute("id");
     // cx.library_effects.name = node.attribute("name");
 
     for child in node.element_children() {
-        match child.tag_name().name() {
+        match child.name() {
             "effect" => {
                 let effect = parse_effect(cx, child)?;
                 cx.library_effects.effects.insert(effect.id, effect);
@@ -174,13 +174,13 @@ Child elements must appear in the following order if present:
     - <profile_COMMON>
 - `<extra>` (0 or more)
 */
-fn parse_effect<'a>(cx: &mut Context<'a>, node: xml::Node<'a, '_>) -> io::Result<Effect<'a>> {
-    debug_assert_eq!(node.tag_name().name(), "effect");
+fn parse_effect<'a>(cx: &mut Context<'a>, node: stream::Node<'a, '_>) -> io::Result<Effect<'a>> {
+    debug_assert_eq!(node.name(), "effect");
     let id = node.required_attribute("id")?;
     let mut profile = None;
 
     for child in node.element_children() {
-        if child.tag_name().name() == "profile_COMMON" {
+        if child.name() == "profile_COMMON" {
             profile = Some(parse_profile_common(cx, child)?);
         }
     }
@@ -218,21 +218,21 @@ Child elements must appear in the following order if present:
 */
 fn parse_profile_common<'a>(
     cx: &mut Context<'a>,
-    node: xml::Node<'a, '_>,
+    node: stream::Node<'a, '_>,
 ) -> io::Result<ProfileCommon<'a>> {
-    debug_assert_eq!(node.tag_name().name(), "profile_COMMON");
+    debug_assert_eq!(node.name(), "profile_COMMON");
     let mut surfaces = HashMap::default();
     let mut samplers = HashMap::default();
     let mut technique = None;
 
     for child in node.element_children() {
-        match child.tag_name().name() {
+        match child.name() {
             "newparam" => {
                 parse_newparam(cx, child, &mut surfaces, &mut samplers)?;
             }
             "technique" => {
                 for t in child.element_children() {
-                    let name = t.tag_name().name();
+                    let name = t.name();
                     match name {
                         "constant" | "lambert" | "phong" | "blinn" => {
                             technique = Some(parse_technique(t, name.parse().unwrap())?);
@@ -263,15 +263,15 @@ fn parse_profile_common<'a>(
 
 fn parse_newparam<'a>(
     _cx: &mut Context<'a>,
-    node: xml::Node<'a, '_>,
+    node: stream::Node<'a, '_>,
     surfaces: &mut HashMap<&'a str, Surface<'a>>,
     samplers: &mut HashMap<&'a str, Sampler<'a>>,
 ) -> io::Result<()> {
-    debug_assert_eq!(node.tag_name().name(), "newparam");
+    debug_assert_eq!(node.name(), "newparam");
     let sid = node.required_attribute("sid")?;
 
     for child in node.element_children() {
-        match child.tag_name().name() {
+        match child.name() {
             "surface" => {
                 // image ID given inside <init_from> tags
                 if let Some(init) = child.child("init_from") {
@@ -328,12 +328,12 @@ Child elements must appear in the following order if present:
 - <transparency> (0 or 1, fx_common_float_or_param_type 0.0 ..= 1.0)
 - <index_of_refraction> (0 or 1, fx_common_float_or_param_type)
 */
-fn parse_technique<'a>(node: xml::Node<'a, '_>, ty: ShadeType) -> io::Result<Technique<'a>> {
-    debug_assert_eq!(node.tag_name().name().parse::<ShadeType>().unwrap(), ty);
+fn parse_technique<'a>(node: stream::Node<'a, '_>, ty: ShadeType) -> io::Result<Technique<'a>> {
+    debug_assert_eq!(node.name().parse::<ShadeType>().unwrap(), ty);
     let mut effect = Technique::new(ty);
 
     for child in node.element_children() {
-        let name = child.tag_name().name();
+        let name = child.name();
         match name {
             // fx_common_color_or_texture_type
             "emission" => {
@@ -475,12 +475,12 @@ impl Technique<'_> {
 //
 // See also fx_common_color_or_texture_type in specification.
 fn parse_effect_color<'a>(
-    node: xml::Node<'a, '_>,
+    node: stream::Node<'a, '_>,
     color: &mut Color4,
     texture: &mut Texture<'a>,
 ) -> io::Result<()> {
     for child in node.element_children() {
-        match child.tag_name().name() {
+        match child.name() {
             "color" => {
                 let content = xml::comma_to_period(child.trimmed_text());
                 let mut iter = xml::parse_float_array_exact(&content, 4);
@@ -488,7 +488,7 @@ fn parse_effect_color<'a>(
                 let map_err = |e| {
                     format_err!(
                         "{e} in <{}> element ({})",
-                        child.tag_name().name(),
+                        child.name(),
                         child.text_location(),
                     )
                 };
@@ -508,8 +508,8 @@ fn parse_effect_color<'a>(
             "param" => {
                 // warn!(
                 //     "<{}> child element in <{}> element is unsupported ({})",
-                //     child.tag_name().name(),
-                //     child.parent_element().unwrap().tag_name().name(),
+                //     child.name(),
+                //     child.parent_element().unwrap().name(),
                 //     child.node_location()
                 // );
             }
@@ -519,11 +519,11 @@ fn parse_effect_color<'a>(
     Ok(())
 }
 
-fn parse_effect_float(node: xml::Node<'_, '_>) -> io::Result<Option<f32>> {
+fn parse_effect_float(node: stream::Node<'_, '_>) -> io::Result<Option<f32>> {
     let mut float = None;
 
     for child in node.element_children() {
-        match child.tag_name().name() {
+        match child.name() {
             "float" => {
                 let content = xml::comma_to_period(child.trimmed_text());
                 float = Some(
@@ -534,8 +534,8 @@ fn parse_effect_float(node: xml::Node<'_, '_>) -> io::Result<Option<f32>> {
             "param" => {
                 // warn!(
                 //     "<{}> child element in <{}> element is unsupported ({})",
-                //     child.tag_name().name(),
-                //     child.parent_element().unwrap().tag_name().name(),
+                //     child.name(),
+                //     child.parent_element().unwrap().name(),
                 //     child.node_location()
                 // );
             }
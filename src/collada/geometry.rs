@@ -140,14 +140,14 @@ pub(super) struct Primitive<'a> {
 
 pub(super) fn parse_library_geometries<'a>(
     cx: &mut Context<'a>,
-    node: xml::Node<'a, '_>,
+    node: stream::Node<'a, '_>,
 ) -> io::Result<()> {
-    debug_assert_eq!(node.tag_name().name(), "library_geometries");
+    debug_assert_eq!(node.name(), "library_geometries");
     // cx.library_geometries.id = node.attribute("id");
     // cx.library_geometries.name = node.attribute("name");
 
     for node in node.element_children() {
-        match node.tag_name().name() {
+        match node.name() {
             "geometry" => {
                 if let Some(geometry) = parse_geometry(cx, node)? {
                     cx.library_geometries
@@ -169,23 +169,23 @@ pub(super) fn parse_library_geometries<'a>(
 
 fn parse_geometry<'a>(
     cx: &mut Context<'a>,
-    node: xml::Node<'a, '_>,
+    node: stream::Node<'a, '_>,
 ) -> io::Result<Option<Geometry<'a>>> {
-    debug_assert_eq!(node.tag_name().name(), "geometry");
+    debug_assert_eq!(node.name(), "geometry");
     // The specification say it is optional, but it is actually required.
     let id = node.required_attribute("id")?;
     let mut mesh = None;
 
     for node in node.element_children() {
-        match node.tag_name().name() {
+        match node.name() {
             "mesh" => {
                 mesh = Some(parse_mesh(cx, node)?);
             }
             "convex_mesh" | "spline" | "brep" => {
                 // warn!(
                 //     "<{}> child element in <{}> element is unsupported ({})",
-                //     child.tag_name().name(),
-                //     child.parent_element().unwrap().tag_name().name(),
+                //     child.name(),
+                //     child.parent_element().unwrap().name(),
                 //     child.node_location()
                 // );
                 return Ok(None);
@@ -207,18 +207,18 @@ fn parse_geometry<'a>(
     }))
 }
 
-fn parse_mesh<'a>(cx: &mut Context<'a>, node: xml::Node<'a, '_>) -> io::Result<Mesh<'a>> {
-    debug_assert_eq!(node.tag_name().name(), "mesh");
+fn parse_mesh<'a>(cx: &mut Context<'a>, node: stream::Node<'a, '_>) -> io::Result<Mesh<'a>> {
+    debug_assert_eq!(node.name(), "mesh");
     let mut primitives = vec![];
     let mut has_source = false;
     let mut vertices = None;
 
     for node in node.element_children() {
-        let name = node.tag_name().name();
+        let name = node.name();
         match name {
             "source" => {
                 has_source = true;
-                let s = Source::parse(node)?;
+                let s = Source::parse(node, &mut cx.arrays)?;
                 if let Some(acc) = s.accessor {
                     cx.library_geometries.accessors.insert(s.id, acc);
                 }
@@ -231,7 +231,11 @@ fn parse_mesh<'a>(cx: &mut Context<'a>, node: xml::Node<'a, '_>) -> io::Result<M
             }
             "lines" | "linestrips" | "polygons" | "polylist" | "triangles" | "trifans"
             | "tristrips" => {
-                primitives.push(parse_primitive(node, name.parse().unwrap())?);
+                primitives.push(parse_primitive(
+                    node,
+                    name.parse().unwrap(),
+                    &mut cx.arrays,
+                )?);
             }
             "extra" => { /* skip */ }
             _ => return Err(error::unexpected_child_elem(node)),
@@ -252,8 +256,8 @@ fn parse_mesh<'a>(cx: &mut Context<'a>, node: xml::Node<'a, '_>) -> io::Result<M
     })
 }
 
-fn parse_vertices<'a>(node: xml::Node<'a, '_>) -> io::Result<Vertices<'a>> {
-    debug_assert_eq!(node.tag_name().name(), "vertices");
+fn parse_vertices<'a>(node: stream::Node<'a, '_>) -> io::Result<Vertices<'a>> {
+    debug_assert_eq!(node.name(), "vertices");
     let id = node.required_attribute("id")?;
 
     let mut input_position = None;
@@ -262,7 +266,7 @@ fn parse_vertices<'a>(node: xml::Node<'a, '_>) -> io::Result<Vertices<'a>> {
     let mut input_color = None;
 
     for node in node.element_children() {
-        match node.tag_name().name() {
+        match node.name() {
             "input" => {
                 let i = UnsharedInput::parse(node)?;
                 match i.semantic {
@@ -319,8 +323,12 @@ impl FromStr for PrimitiveType {
     }
 }
 
-fn parse_primitive<'a>(node: xml::Node<'a, '_>, ty: PrimitiveType) -> io::Result<Primitive<'a>> {
-    debug_assert_eq!(node.tag_name().name().parse::<PrimitiveType>().unwrap(), ty);
+fn parse_primitive<'a>(
+    node: stream::Node<'a, '_>,
+    ty: PrimitiveType,
+    arrays: &mut stream::Arrays,
+) -> io::Result<Primitive<'a>> {
+    debug_assert_eq!(node.name().parse::<PrimitiveType>().unwrap(), ty);
     let count: u32 = node.parse_required_attribute("count")?;
     let mut vcount = vec![];
     let mut p = vec![];
@@ -332,7 +340,7 @@ fn parse_primitive<'a>(node: xml::Node<'a, '_>, ty: PrimitiveType) -> io::Result
     let mut input_texcoord = vec![];
 
     for node in node.element_children() {
-        match node.tag_name().name() {
+        match node.name() {
             "input" => {
                 let i = SharedInput::parse(node)?;
                 stride = cmp::max(stride, i.offset + 1);
@@ -375,13 +383,15 @@ fn parse_primitive<'a>(node: xml::Node<'a, '_>, ty: PrimitiveType) -> io::Result
 
                 vcount.reserve(count as usize);
 
-                // TODO: use parse_int_array_exact?
-                let mut iter = xml::parse_int_array::<u32>(node.trimmed_text());
+                let (values, error) = arrays
+                    .take_indices(node)
+                    .unwrap_or_else(|| parse_indices(node.trimmed_text()));
+                let mut iter = values.into_iter().map(Ok).chain(error.map(Err));
                 for _ in 0..count {
                     let value = iter.next().ok_or_else(|| {
                         format_err!(
                             "expected more values while reading <{}> contents at {}",
-                            node.tag_name().name(),
+                            node.name(),
                             node.node_location()
                         )
                     })??;
@@ -428,19 +438,7 @@ fn parse_primitive<'a>(node: xml::Node<'a, '_>, ty: PrimitiveType) -> io::Result
                         _ => unreachable!(),
                     }
 
-                    p.reserve(expected_count * stride as usize);
-
-                    // TODO: It seems some exporters put negative indices sometimes.
-                    // TODO: use parse_int_array_exact?
-                    for value in xml::parse_int_array(node.trimmed_text()) {
-                        p.push(value.map_err(|e| {
-                            format_err!(
-                                "{e} in <{}> element ({})",
-                                node.tag_name().name(),
-                                node.text_location(),
-                            )
-                        })?);
-                    }
+                    parse_p(node, arrays, &mut p)?;
 
                     if p.len() != expected_count * stride as usize {
                         // TODO: It seems SketchUp 15.3.331 writes the wrong 'count' for 'lines'.
@@ -463,17 +461,7 @@ fn parse_primitive<'a>(node: xml::Node<'a, '_>, ty: PrimitiveType) -> io::Result
 
                     let prev_len = p.len();
 
-                    // TODO: It seems some exporters put negative indices sometimes.
-                    // TODO: use parse_int_array_exact?
-                    for value in xml::parse_int_array(node.trimmed_text()) {
-                        p.push(value.map_err(|e| {
-                            format_err!(
-                                "{e} in <{}> element ({})",
-                                node.tag_name().name(),
-                                node.text_location(),
-                            )
-                        })?);
-                    }
+                    parse_p(node, arrays, &mut p)?;
 
                     #[allow(clippy::cast_possible_truncation)]
                     let added = (p.len() - prev_len) as u32;
@@ -499,8 +487,8 @@ fn parse_primitive<'a>(node: xml::Node<'a, '_>, ty: PrimitiveType) -> io::Result
             "ph" => {
                 // warn!(
                 //     "<{}> child element in <{}> element is unsupported ({})",
-                //     child.tag_name().name(),
-                //     child.parent_element().unwrap().tag_name().name(),
+                //     child.name(),
+                //     child.parent_element().unwrap().name(),
                 //     child.node_location()
                 // );
             }
@@ -515,7 +503,7 @@ fn parse_primitive<'a>(node: xml::Node<'a, '_>, ty: PrimitiveType) -> io::Result
     {
         bail!(
             "one <input> in <{}> element must specify semantic=\"VERTEX\" ({})",
-            node.tag_name().name(),
+            node.name(),
             node.node_location()
         );
     }
@@ -540,3 +528,48 @@ fn parse_primitive<'a>(node: xml::Node<'a, '_>, ty: PrimitiveType) -> io::Result
         stride,
     })
 }
+
+/// Parses the indices in the `<p>` element.
+fn parse_p(
+    node: stream::Node<'_, '_>,
+    arrays: &mut stream::Arrays,
+    p: &mut Vec<u32>,
+) -> io::Result<()> {
+    // The contents of <p> are usually parsed while the document is read.
+    let (values, error) = arrays
+        .take_indices(node)
+        .unwrap_or_else(|| parse_indices(node.trimmed_text()));
+    if p.is_empty() {
+        *p = values;
+    } else {
+        p.extend_from_slice(&values);
+    }
+    match error {
+        Some(e) => Err(format_err!(
+            "{e} in <{}> element ({})",
+            node.name(),
+            node.text_location(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Parses the indices in the `<p>` or `<vcount>` element.
+///
+/// Returns the values before the first error, and the error.
+pub(super) fn parse_indices(text: &str) -> (Vec<u32>, Option<io::Error>) {
+    #[cfg(feature = "rayon")]
+    if let Some(values) = xml::parse_array_parallel(text, xml::parse_int_array::<u32>) {
+        return (values, None);
+    }
+    // TODO: It seems some exporters put negative indices sometimes.
+    // TODO: use parse_int_array_exact?
+    let mut values = vec![];
+    for value in xml::parse_int_array(text) {
+        match value {
+            Ok(value) => values.push(value),
+            Err(e) => return (values, Some(e)),
+        }
+    }
+    (values, None)
+}
@@ -52,14 +52,14 @@ pub(super) enum ImageSource<'a> {
 
 pub(super) fn parse_library_images<'a>(
     cx: &mut Context<'a>,
-    node: xml::Node<'a, '_>,
+    node: stream::Node<'a, '_>,
 ) -> io::Result<()> {
-    debug_assert_eq!(node.tag_name().name(), "library_images");
+    debug_assert_eq!(node.name(), "library_images");
     // cx.library_images.id = node.attribute("id");
     // cx.library_images.name = node.attribute("name");
 
     for node in node.element_children() {
-        match node.tag_name().name() {
+        match node.name() {
             "image" => {
                 let image = parse_image(cx, node)?;
                 cx.library_images.images.insert(image.id, image);
@@ -75,8 +75,8 @@ pub(super) fn parse_library_images<'a>(
     Ok(())
 }
 
-fn parse_image<'a>(cx: &Context<'a>, node: xml::Node<'a, '_>) -> io::Result<Image<'a>> {
-    debug_assert_eq!(node.tag_name().name(), "image");
+fn parse_image<'a>(cx: &Context<'a>, node: stream::Node<'a, '_>) -> io::Result<Image<'a>> {
+    debug_assert_eq!(node.name(), "image");
     let id = node.required_attribute("id")?;
     // let name = node.attribute("name");
     let is_1_4 = cx.version.is_1_4();
@@ -91,7 +91,7 @@ fn parse_image<'a>(cx: &Context<'a>, node: xml::Node<'a, '_>) -> io::Result<Imag
     let mut source = None;
 
     for node in node.element_children() {
-        let tag_name = node.tag_name().name();
+        let tag_name = node.name();
         match tag_name {
             "init_from" => {
                 if is_1_4 {
@@ -99,7 +99,7 @@ fn parse_image<'a>(cx: &Context<'a>, node: xml::Node<'a, '_>) -> io::Result<Imag
                     continue;
                 }
                 for node in node.element_children() {
-                    match node.tag_name().name() {
+                    match node.name() {
                         "ref" => {
                             source = Some(ImageSource::InitFrom(node.trimmed_text()));
                         }
@@ -128,7 +128,7 @@ fn parse_image<'a>(cx: &Context<'a>, node: xml::Node<'a, '_>) -> io::Result<Imag
             if is_1_4 {
                 bail!(
                     "<{}> element must be contain <data> or <init_from> element ({})",
-                    node.tag_name().name(),
+                    node.name(),
                     node.node_location()
                 )
             }
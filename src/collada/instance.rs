@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use super::*;
-use crate::ShadingModel;
+use crate::{resolver, ShadingModel};
 
 pub(super) fn build(doc: &mut Document<'_>, dir: Option<&Path>) -> common::Scene {
     let mut meshes = Vec::with_capacity(doc.library_geometries.geometries.len());
@@ -97,7 +97,11 @@ pub(super) fn build(doc: &mut Document<'_>, dir: Option<&Path>) -> common::Scene
         }
     }
 
-    common::Scene { materials, meshes }
+    common::Scene {
+        materials,
+        meshes,
+        ..Default::default()
+    }
 }
 
 fn build_mesh(
@@ -222,8 +226,6 @@ fn build_material(
                     }
                     match dir {
                         Some(dir) => {
-                            // TODO
-                            p = p.strip_prefix("file://").unwrap_or(p);
                             let tmp: String;
                             if p.contains('\\') {
                                 tmp = p.replace('\\', "/");
@@ -232,10 +234,7 @@ fn build_material(
                             if p.starts_with("/..") {
                                 p = p.strip_prefix('/').unwrap_or(p);
                             }
-                            let p = dir.join(p);
-                            if p.exists() {
-                                return Some(p);
-                            }
+                            return Some(resolver::join(dir, p));
                         }
                         None => return Some(p.into()),
                     }
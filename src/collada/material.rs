@@ -46,14 +46,14 @@ pub(super) struct InstanceEffect<'a> {
 
 pub(super) fn parse_library_materials<'a>(
     cx: &mut Context<'a>,
-    node: xml::Node<'a, '_>,
+    node: stream::Node<'a, '_>,
 ) -> io::Result<()> {
-    debug_assert_eq!(node.tag_name().name(), "library_materials");
+    debug_assert_eq!(node.name(), "library_materials");
     // cx.library_materials.id = node.attribute("id");
     // cx.library_materials.name = node.attribute("name");
 
     for node in node.element_children() {
-        match node.tag_name().name() {
+        match node.name() {
             "material" => {
                 let material = parse_material(node)?;
                 cx.library_materials.materials.insert(material.id, material);
@@ -69,14 +69,14 @@ pub(super) fn parse_library_materials<'a>(
     Ok(())
 }
 
-fn parse_material<'a>(node: xml::Node<'a, '_>) -> io::Result<Material<'a>> {
-    debug_assert_eq!(node.tag_name().name(), "material");
+fn parse_material<'a>(node: stream::Node<'a, '_>) -> io::Result<Material<'a>> {
+    debug_assert_eq!(node.name(), "material");
     // The specification say it is optional, but it is actually required.
     let id = node.required_attribute("id")?;
     let mut instance_effect = None;
 
     for node in node.element_children() {
-        match node.tag_name().name() {
+        match node.name() {
             "instance_effect" => {
                 instance_effect = Some(parse_instance_effect(node)?);
             }
@@ -97,8 +97,8 @@ fn parse_material<'a>(node: xml::Node<'a, '_>) -> io::Result<Material<'a>> {
     })
 }
 
-fn parse_instance_effect<'a>(node: xml::Node<'a, '_>) -> io::Result<InstanceEffect<'a>> {
-    debug_assert_eq!(node.tag_name().name(), "instance_effect");
+fn parse_instance_effect<'a>(node: stream::Node<'a, '_>) -> io::Result<InstanceEffect<'a>> {
+    debug_assert_eq!(node.name(), "instance_effect");
     let url = node.parse_url("url")?;
     Ok(InstanceEffect {
         // sid: node.attribute("sid"),
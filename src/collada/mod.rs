@@ -13,6 +13,7 @@ mod instance;
 mod iter;
 mod material;
 mod scene;
+mod stream;
 
 use std::{
     cmp,
@@ -27,11 +28,7 @@ use std::{
 use self::{effect::*, geometry::*, image::*, material::*, scene::*};
 use crate::{
     common,
-    utils::{
-        float, hex,
-        utf16::decode_string,
-        xml::{self, XmlNodeExt},
-    },
+    utils::{float, hex, matrix::Matrix4x4, utf16::decode_string, xml},
     Color4,
 };
 
@@ -55,11 +52,86 @@ pub(crate) fn from_slice_internal(bytes: &[u8], path: Option<&Path>) -> io::Resu
 
 #[inline]
 pub(crate) fn from_str_internal(s: &str, path: Option<&Path>) -> io::Result<common::Scene> {
-    let xml = xml::Document::parse(s).map_err(crate::error::invalid_data)?;
-    let mut collada = Document::parse(&xml)?;
+    let mut arrays = stream::Arrays::default();
+    let xml = stream::Tree::parse(s, Some(&mut arrays))?;
+    let mut collada = Document::parse(&xml, arrays)?;
     Ok(instance::build(&mut collada, path.and_then(Path::parent)))
 }
 
+/// Returns a summary of the given bytes of COLLADA text.
+///
+/// This reports the `<asset>`, the ids of the geometries with the sum of the
+/// `count` attributes of their primitives, and the images, without building
+/// meshes.
+pub(crate) fn probe(bytes: &[u8]) -> io::Result<common::Summary> {
+    let s = &decode_string(bytes)?;
+    let xml = stream::Tree::parse(s, None)?;
+    let node = xml.root_element();
+    if node.name() != "COLLADA" {
+        bail!("root element is not <COLLADA>");
+    }
+
+    let mut summary = common::Summary::default();
+    for node in node.element_children() {
+        match node.name() {
+            "asset" => {
+                summary.unit = Some(Asset::parse(node)?.unit);
+                for node in node.descendants() {
+                    let name = node.name();
+                    if let "author" | "authoring_tool" | "created" | "modified" | "title"
+                    | "up_axis" = name
+                    {
+                        let value = node.trimmed_text().to_owned();
+                        summary.metadata.push((name.to_owned(), value));
+                    }
+                }
+            }
+            "library_geometries" => {
+                for geometry in node.element_children() {
+                    if geometry.name() != "geometry" {
+                        continue;
+                    }
+                    let mut num_faces = 0_u64;
+                    for mesh in geometry.element_children() {
+                        if mesh.name() != "mesh" {
+                            continue;
+                        }
+                        for primitive in mesh.element_children() {
+                            if let "triangles" | "polylist" | "polygons" | "trifans" | "tristrips" =
+                                primitive.name()
+                            {
+                                let count = primitive.parse_required_attribute("count")?;
+                                num_faces = num_faces.saturating_add(count);
+                            }
+                        }
+                    }
+                    summary.meshes.push(common::MeshSummary {
+                        name: geometry.required_attribute("id")?.to_owned(),
+                        num_faces,
+                    });
+                }
+            }
+            "library_images" => {
+                for node in node.descendants() {
+                    if node.name() != "init_from" {
+                        continue;
+                    }
+                    // 1.4: <init_from>uri</init_from>, 1.5: <init_from><ref>uri</ref></init_from>
+                    let uri = match node.child("ref") {
+                        Some(node) => node.trimmed_text(),
+                        None => node.trimmed_text(),
+                    };
+                    if !uri.is_empty() {
+                        summary.references.push(uri.to_owned());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(summary)
+}
+
 // Inspired by gltf-json's `Get` trait.
 /// Helper trait for retrieving top-level objects by a universal identifier.
 trait Get<T> {
@@ -148,7 +220,7 @@ trait ColladaXmlNodeExt<'a, 'input> {
     // fn parse_url_opt<T>(&self, name: &str) -> io::Result<Option<Uri<'a, T>>>;
 }
 
-impl<'a, 'input> ColladaXmlNodeExt<'a, 'input> for xml::Node<'a, 'input> {
+impl<'a, 'input> ColladaXmlNodeExt<'a, 'input> for stream::Node<'a, 'input> {
     fn parse_url<T>(&self, name: &str) -> io::Result<Uri<'a, T>> {
         let url = self.required_attribute(name)?;
         Uri::parse(url).map_err(|e| {
@@ -156,7 +228,7 @@ impl<'a, 'input> ColladaXmlNodeExt<'a, 'input> for xml::Node<'a, 'input> {
                 "{} in {} attribute of <{}> element at {}",
                 e,
                 name,
-                self.tag_name().name(),
+                self.name(),
                 self.attr_value_location(name),
             )
         })
@@ -169,7 +241,7 @@ impl<'a, 'input> ColladaXmlNodeExt<'a, 'input> for xml::Node<'a, 'input> {
     //                 "{} in {} attribute of <{}> element at {}",
     //                 e,
     //                 name,
-    //                 self.tag_name().name(),
+    //                 self.name(),
     //                 self.attr_value_location(name),
     //             )
     //         })
@@ -229,6 +301,7 @@ struct Context<'a> {
     library_materials: LibraryMaterials<'a>,
     library_visual_scenes: LibraryVisualScenes<'a>,
     scene: Scene<'a>,
+    arrays: stream::Arrays,
 }
 
 struct Document<'a> {
@@ -277,9 +350,9 @@ impl<'a> Document<'a> {
     - `<scene>` (0 or 1)
     - `<extra>` (0 or more)
     */
-    fn parse(doc: &'a xml::Document<'_>) -> io::Result<Self> {
+    fn parse(doc: &'a stream::Tree<'_>, arrays: stream::Arrays) -> io::Result<Self> {
         let node = doc.root_element();
-        if node.tag_name().name() != "COLLADA" {
+        if node.name() != "COLLADA" {
             bail!("root element is not <COLLADA>");
         }
 
@@ -300,10 +373,11 @@ impl<'a> Document<'a> {
             library_materials: LibraryMaterials::default(),
             library_visual_scenes: LibraryVisualScenes::default(),
             scene: Scene::default(),
+            arrays,
         };
 
         for node in node.element_children() {
-            match node.tag_name().name() {
+            match node.name() {
                 "library_effects" => {
                     parse_library_effects(&mut cx, node)?;
                 }
@@ -371,12 +445,12 @@ struct Asset {
 }
 
 impl Asset {
-    fn parse(node: xml::Node<'_, '_>) -> io::Result<Self> {
-        debug_assert_eq!(node.tag_name().name(), "asset");
+    fn parse(node: stream::Node<'_, '_>) -> io::Result<Self> {
+        debug_assert_eq!(node.name(), "asset");
 
         let mut unit = None;
         for child in node.element_children() {
-            match child.tag_name().name() {
+            match child.name() {
                 "unit" => {
                     if let Some(v) = child.attribute("meter") {
                         let v = xml::comma_to_period(v);
@@ -384,7 +458,7 @@ impl Asset {
                             format_err!(
                                 "{} in <{}> element at {}: {:?}",
                                 e,
-                                child.tag_name().name(),
+                                child.name(),
                                 child.attr_value_location("meter"),
                                 v
                             )
@@ -436,20 +510,20 @@ impl<'a> Source<'a> {
     - `<technique_common>` (0 or 1)
     - `<technique>` (core) (0 or more)
     */
-    fn parse(node: xml::Node<'a, '_>) -> io::Result<Self> {
-        debug_assert_eq!(node.tag_name().name(), "source");
+    fn parse(node: stream::Node<'a, '_>, arrays: &mut stream::Arrays) -> io::Result<Self> {
+        debug_assert_eq!(node.name(), "source");
         let id = node.required_attribute("id")?;
         let mut array_element = None;
         let mut accessor = None;
 
         for child in node.element_children() {
-            match child.tag_name().name() {
+            match child.name() {
                 "float_array" | "IDREF_array" | "Name_array" => {
-                    array_element = Some(parse_array_element(child)?);
+                    array_element = Some(parse_array_element(child, arrays)?);
                 }
                 "technique_common" => {
                     for technique in child.element_children() {
-                        match technique.tag_name().name() {
+                        match technique.name() {
                             "accessor" => {
                                 accessor = Some(Accessor::parse(technique)?);
                             }
@@ -460,7 +534,7 @@ impl<'a> Source<'a> {
                 "bool_array" | "int_array" | "SIDREF_array" | "token_array" => {
                     // warn!(
                     //     "ignored array element {} ({})",
-                    //     child.tag_name().name(),
+                    //     child.name(),
                     //     child.node_location()
                     // );
                 }
@@ -486,14 +560,35 @@ struct ArrayElement<'a> {
     data: ArrayData<'a>,
 }
 
-fn parse_array_element<'a>(node: xml::Node<'a, '_>) -> io::Result<ArrayElement<'a>> {
-    let name = node.tag_name().name();
+fn parse_array_element<'a>(
+    node: stream::Node<'a, '_>,
+    arrays: &mut stream::Arrays,
+) -> io::Result<ArrayElement<'a>> {
+    let name = node.name();
     let is_string_array = name == "IDREF_array" || name == "Name_array";
 
     let id = node.required_attribute("id")?;
     let count: u32 = node.parse_required_attribute("count")?;
     let mut content = node.trimmed_text();
 
+    // TODO: include in parse_float_array_exact?
+    let map_err = |e| {
+        format_err!(
+            "{e} in <{}> element ({})",
+            node.name(),
+            node.text_location(),
+        )
+    };
+
+    // The contents of <float_array> are usually parsed while the document is read.
+    if let Some(values) = arrays.take_float(node) {
+        return Ok(ArrayElement {
+            id,
+            // count,
+            data: ArrayData::Float(values.map_err(map_err)?),
+        });
+    }
+
     // some exporters write empty data arrays, but we need to conserve them anyways because others might reference them
     if content.is_empty() {
         let data = if is_string_array {
@@ -515,7 +610,7 @@ fn parse_array_element<'a>(node: xml::Node<'a, '_>) -> io::Result<ArrayElement<'
             if content.is_empty() {
                 bail!(
                     "expected more values while reading <{}> contents at {}",
-                    node.tag_name().name(),
+                    node.name(),
                     node.node_location()
                 );
             }
@@ -539,30 +634,29 @@ fn parse_array_element<'a>(node: xml::Node<'a, '_>) -> io::Result<ArrayElement<'
             data: ArrayData::String(values),
         })
     } else {
-        // TODO: check large count
-        let mut values = Vec::with_capacity(count as usize);
-        let content = xml::comma_to_period(content);
-        // TODO: include in parse_float_array_exact?
-        let map_err = |e| {
-            format_err!(
-                "{e} in <{}> element ({})",
-                node.tag_name().name(),
-                node.text_location(),
-            )
-        };
-        for res in xml::parse_float_array_exact(&content, count as usize) {
-            let value = res.map_err(map_err)?;
-            values.push(value);
-        }
-
         Ok(ArrayElement {
             id,
             // count,
-            data: ArrayData::Float(values),
+            data: ArrayData::Float(parse_float_array(content, count).map_err(map_err)?),
         })
     }
 }
 
+/// Parses the contents of the `<float_array>` element.
+fn parse_float_array(content: &str, count: u32) -> io::Result<Vec<f32>> {
+    let content = xml::comma_to_period(content);
+    #[cfg(feature = "rayon")]
+    if let Some(values) = xml::parse_float_array_parallel(&content, count as usize) {
+        return Ok(values);
+    }
+    // TODO: check large count
+    let mut values = Vec::with_capacity(count as usize);
+    for res in xml::parse_float_array_exact(&content, count as usize) {
+        values.push(res?);
+    }
+    Ok(values)
+}
+
 /// Data source array.
 enum ArrayData<'a> {
     /// <float_array>
@@ -644,8 +738,8 @@ impl<'a> Accessor<'a> {
     Child elements:
     - `<param>` (0 or more)
     */
-    fn parse(node: xml::Node<'a, '_>) -> io::Result<Self> {
-        debug_assert_eq!(node.tag_name().name(), "accessor");
+    fn parse(node: stream::Node<'a, '_>) -> io::Result<Self> {
+        debug_assert_eq!(node.name(), "accessor");
         let count: u32 = node.parse_required_attribute("count")?;
         let source = node.parse_url("source")?;
         let _offset: u32 = node.parse_attribute("offset")?.unwrap_or(0);
@@ -653,7 +747,7 @@ impl<'a> Accessor<'a> {
         let mut params = vec![];
 
         for child in node.element_children() {
-            match child.tag_name().name() {
+            match child.name() {
                 "param" => {
                     params.push(Param::parse(child)?);
                 }
@@ -699,7 +793,7 @@ impl<'a> Param<'a> {
 
     Child elements: None
     */
-    fn parse(node: xml::Node<'a, '_>) -> io::Result<Self> {
+    fn parse(node: stream::Node<'a, '_>) -> io::Result<Self> {
         let ty = node.required_attribute("type")?;
         // let name = node.attribute("name");
         // let sid = node.attribute("sid");
@@ -745,8 +839,8 @@ impl<'a, T> SharedInput<'a, T> {
 
     Child elements: None
     */
-    fn parse(node: xml::Node<'a, '_>) -> io::Result<Self> {
-        debug_assert_eq!(node.tag_name().name(), "input");
+    fn parse(node: stream::Node<'a, '_>) -> io::Result<Self> {
+        debug_assert_eq!(node.name(), "input");
         let semantic = node.parse_required_attribute("semantic")?;
         let source = node.parse_url("source")?;
         let offset: u32 = node.parse_required_attribute("offset")?;
@@ -795,8 +889,8 @@ impl<'a> UnsharedInput<'a> {
 
     Child elements: None
     */
-    fn parse(node: xml::Node<'a, '_>) -> io::Result<Self> {
-        debug_assert_eq!(node.tag_name().name(), "input");
+    fn parse(node: stream::Node<'a, '_>) -> io::Result<Self> {
+        debug_assert_eq!(node.name(), "input");
         let semantic = node.parse_required_attribute("semantic")?;
         let source = node.parse_url("source")?;
         if let Some(child) = node.element_children().next() {
@@ -178,178 +178,6 @@ impl Transform {
     }
 }
 
-// Based on https://github.com/assimp/assimp/blob/v5.3.1/include/assimp/matrix4x4.inl
-#[derive(Clone, Copy)]
-pub(super) struct Matrix4x4 {
-    a1: f32,
-    a2: f32,
-    a3: f32,
-    a4: f32,
-    b1: f32,
-    b2: f32,
-    b3: f32,
-    b4: f32,
-    c1: f32,
-    c2: f32,
-    c3: f32,
-    c4: f32,
-    d1: f32,
-    d2: f32,
-    d3: f32,
-    d4: f32,
-}
-impl Matrix4x4 {
-    pub(super) const fn new(
-        a1: f32,
-        a2: f32,
-        a3: f32,
-        a4: f32,
-        b1: f32,
-        b2: f32,
-        b3: f32,
-        b4: f32,
-        c1: f32,
-        c2: f32,
-        c3: f32,
-        c4: f32,
-        d1: f32,
-        d2: f32,
-        d3: f32,
-        d4: f32,
-    ) -> Self {
-        Self {
-            a1,
-            a2,
-            a3,
-            a4,
-            b1,
-            b2,
-            b3,
-            b4,
-            c1,
-            c2,
-            c3,
-            c4,
-            d1,
-            d2,
-            d3,
-            d4,
-        }
-    }
-    fn rotation(a: f32, axis: [f32; 3]) -> Self {
-        let c = a.cos();
-        let s = a.sin();
-        let t = 1. - c;
-        let [x, y, z] = axis;
-        Self::new(
-            t * x * x + c,
-            t * x * y - s * z,
-            t * x * z + s * y,
-            0.,
-            t * x * y + s * z,
-            t * y * y + c,
-            t * y * z - s * x,
-            0.,
-            t * x * z - s * y,
-            t * y * z + s * x,
-            t * z * z + c,
-            0.,
-            0.,
-            0.,
-            0.,
-            1.,
-        )
-    }
-    fn translation(v: [f32; 3]) -> Self {
-        Self {
-            a4: v[0],
-            b4: v[1],
-            c4: v[2],
-            ..Default::default()
-        }
-    }
-    pub(super) fn is_identity(&self) -> bool {
-        // TODO: use f32::EPSILON?
-        const EPSILON: f32 = 10e-3;
-        self.a2 <= EPSILON
-            && self.a2 >= -EPSILON
-            && self.a3 <= EPSILON
-            && self.a3 >= -EPSILON
-            && self.a4 <= EPSILON
-            && self.a4 >= -EPSILON
-            && self.b1 <= EPSILON
-            && self.b1 >= -EPSILON
-            && self.b3 <= EPSILON
-            && self.b3 >= -EPSILON
-            && self.b4 <= EPSILON
-            && self.b4 >= -EPSILON
-            && self.c1 <= EPSILON
-            && self.c1 >= -EPSILON
-            && self.c2 <= EPSILON
-            && self.c2 >= -EPSILON
-            && self.c4 <= EPSILON
-            && self.c4 >= -EPSILON
-            && self.d1 <= EPSILON
-            && self.d1 >= -EPSILON
-            && self.d2 <= EPSILON
-            && self.d2 >= -EPSILON
-            && self.d3 <= EPSILON
-            && self.d3 >= -EPSILON
-            && self.a1 <= 1. + EPSILON
-            && self.a1 >= 1. - EPSILON
-            && self.b2 <= 1. + EPSILON
-            && self.b2 >= 1. - EPSILON
-            && self.c3 <= 1. + EPSILON
-            && self.c3 >= 1. - EPSILON
-            && self.d4 <= 1. + EPSILON
-            && self.d4 >= 1. - EPSILON
-    }
-}
-impl Default for Matrix4x4 {
-    fn default() -> Self {
-        Self::new(
-            1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.,
-        )
-    }
-}
-impl ops::MulAssign<Matrix4x4> for [f32; 4] {
-    fn mul_assign(&mut self, m: Matrix4x4) {
-        let [x, y, z, w] = *self;
-        self[0] = m.a1 * x + m.a2 * y + m.a3 * z + m.a4 * w;
-        self[1] = m.b1 * x + m.b2 * y + m.b3 * z + m.b4 * w;
-        self[2] = m.c1 * x + m.c2 * y + m.c3 * z + m.c4 * w;
-        self[3] = m.d1 * x + m.d2 * y + m.d3 * z + m.d4 * w;
-    }
-}
-impl ops::MulAssign<Matrix4x4> for [f32; 3] {
-    fn mul_assign(&mut self, m: Matrix4x4) {
-        let mut result = [self[0], self[1], self[2], 1.];
-        result *= m;
-        *self = [result[0], result[1], result[2]];
-    }
-}
-impl ops::MulAssign for Matrix4x4 {
-    fn mul_assign(&mut self, m: Self) {
-        let t = *self;
-        self.a1 = m.a1 * t.a1 + m.b1 * t.a2 + m.c1 * t.a3 + m.d1 * t.a4;
-        self.a2 = m.a2 * t.a1 + m.b2 * t.a2 + m.c2 * t.a3 + m.d2 * t.a4;
-        self.a3 = m.a3 * t.a1 + m.b3 * t.a2 + m.c3 * t.a3 + m.d3 * t.a4;
-        self.a4 = m.a4 * t.a1 + m.b4 * t.a2 + m.c4 * t.a3 + m.d4 * t.a4;
-        self.b1 = m.a1 * t.b1 + m.b1 * t.b2 + m.c1 * t.b3 + m.d1 * t.b4;
-        self.b2 = m.a2 * t.b1 + m.b2 * t.b2 + m.c2 * t.b3 + m.d2 * t.b4;
-        self.b3 = m.a3 * t.b1 + m.b3 * t.b2 + m.c3 * t.b3 + m.d3 * t.b4;
-        self.b4 = m.a4 * t.b1 + m.b4 * t.b2 + m.c4 * t.b3 + m.d4 * t.b4;
-        self.c1 = m.a1 * t.c1 + m.b1 * t.c2 + m.c1 * t.c3 + m.d1 * t.c4;
-        self.c2 = m.a2 * t.c1 + m.b2 * t.c2 + m.c2 * t.c3 + m.d2 * t.c4;
-        self.c3 = m.a3 * t.c1 + m.b3 * t.c2 + m.c3 * t.c3 + m.d3 * t.c4;
-        self.c4 = m.a4 * t.c1 + m.b4 * t.c2 + m.c4 * t.c3 + m.d4 * t.c4;
-        self.d1 = m.a1 * t.d1 + m.b1 * t.d2 + m.c1 * t.d3 + m.d1 * t.d4;
-        self.d2 = m.a2 * t.d1 + m.b2 * t.d2 + m.c2 * t.d3 + m.d2 * t.d4;
-        self.d3 = m.a3 * t.d1 + m.b3 * t.d2 + m.c3 * t.d3 + m.d3 * t.d4;
-        self.d4 = m.a4 * t.d1 + m.b4 * t.d2 + m.c4 * t.d3 + m.d4 * t.d4;
-    }
-}
-
 /// The `<instance_geometry>` element.
 ///
 /// See the [specification][1.4] for details.
@@ -450,21 +278,21 @@ pub(super) struct InstanceNode<'a> {
 
 pub(super) fn parse_scene<'a>(
     _cx: &mut Context<'a>,
-    node: xml::Node<'a, '_>,
+    node: stream::Node<'a, '_>,
 ) -> io::Result<Scene<'a>> {
-    debug_assert_eq!(node.tag_name().name(), "scene");
+    debug_assert_eq!(node.name(), "scene");
     let mut instance_visual_scene = None;
 
     for child in node.element_children() {
-        match child.tag_name().name() {
+        match child.name() {
             "instance_visual_scene" => {
                 instance_visual_scene = Some(parse_instance_visual_scene(child)?);
             }
             "instance_physics_scene" | "instance_kinematics_scene" => {
                 // warn!(
                 //     "<{}> child element in <{}> element is unsupported ({})",
-                //     child.tag_name().name(),
-                //     child.parent_element().unwrap().tag_name().name(),
+                //     child.name(),
+                //     child.parent_element().unwrap().name(),
                 //     child.node_location()
                 // );
             }
@@ -478,8 +306,10 @@ pub(super) fn parse_scene<'a>(
     })
 }
 
-fn parse_instance_visual_scene<'a>(node: xml::Node<'a, '_>) -> io::Result<InstanceVisualScene<'a>> {
-    debug_assert_eq!(node.tag_name().name(), "instance_visual_scene");
+fn parse_instance_visual_scene<'a>(
+    node: stream::Node<'a, '_>,
+) -> io::Result<InstanceVisualScene<'a>> {
+    debug_assert_eq!(node.name(), "instance_visual_scene");
     let url = node.parse_url("url")?;
     Ok(InstanceVisualScene {
         // sid: node.attribute("sid"),
@@ -490,14 +320,14 @@ fn parse_instance_visual_scene<'a>(node: xml::Node<'a, '_>) -> io::Result<Instan
 
 pub(super) fn parse_library_visual_scenes<'a>(
     cx: &mut Context<'a>,
-    node: xml::Node<'a, '_>,
+    node: stream::Node<'a, '_>,
 ) -> io::Result<()> {
-    debug_assert_eq!(node.tag_name().name(), "library_visual_scenes");
+    debug_assert_eq!(node.name(), "library_visual_scenes");
     // cx.library_visual_scenes.id = node.attribute("id");
     // cx.library_visual_scenes.name = node.attribute("name");
 
     for child in node.element_children() {
-        match child.tag_name().name() {
+        match child.name() {
             "visual_scene" => {
                 parse_visual_scene(child, &mut cx.library_visual_scenes.nodes)?;
             }
@@ -513,8 +343,8 @@ pub(super) fn parse_library_visual_scenes<'a>(
     Ok(())
 }
 
-fn parse_visual_scene<'a>(node: xml::Node<'a, '_>, nodes: &mut Vec<Node<'a>>) -> io::Result<()> {
-    debug_assert_eq!(node.tag_name().name(), "visual_scene");
+fn parse_visual_scene<'a>(node: stream::Node<'a, '_>, nodes: &mut Vec<Node<'a>>) -> io::Result<()> {
+    debug_assert_eq!(node.name(), "visual_scene");
     let id = node.attribute("id");
     let mut scene_nodes = vec![];
     let this = Node {
@@ -526,15 +356,15 @@ fn parse_visual_scene<'a>(node: xml::Node<'a, '_>, nodes: &mut Vec<Node<'a>>) ->
     nodes.push(this);
 
     for child in node.element_children() {
-        match child.tag_name().name() {
+        match child.name() {
             "node" => {
                 scene_nodes.push(parse_node(child, nodes, this_index)?);
             }
             "evaluate_scene" => {
                 // warn!(
                 //     "<{}> child element in <{}> element is unsupported ({})",
-                //     child.tag_name().name(),
-                //     child.parent_element().unwrap().tag_name().name(),
+                //     child.name(),
+                //     child.parent_element().unwrap().name(),
                 //     child.node_location()
                 // );
             }
@@ -577,11 +407,11 @@ Child elements must appear in the following order if present:
 - `<extra>` (0 or more)
 */
 fn parse_node<'a>(
-    node: xml::Node<'a, '_>,
+    node: stream::Node<'a, '_>,
     nodes: &mut Vec<Node<'a>>,
     parent: usize,
 ) -> io::Result<usize> {
-    debug_assert_eq!(node.tag_name().name(), "node");
+    debug_assert_eq!(node.name(), "node");
     let _ty: NodeType = node.parse_attribute("type")?.unwrap_or_default();
     let this = Node {
         // id: node.attribute("id"),
@@ -596,7 +426,7 @@ fn parse_node<'a>(
     let mut transforms = vec![];
 
     for child in node.element_children() {
-        match child.tag_name().name() {
+        match child.name() {
             "node" => {
                 let _c = parse_node(child, nodes, this_index)?;
                 // nodes[this_index].children.push(c);
@@ -610,7 +440,7 @@ fn parse_node<'a>(
                 let map_err = |e| {
                     format_err!(
                         "{e} in <{}> element ({})",
-                        child.tag_name().name(),
+                        child.name(),
                         child.text_location(),
                     )
                 };
@@ -634,7 +464,7 @@ fn parse_node<'a>(
                 let map_err = |e| {
                     format_err!(
                         "{e} in <{}> element ({})",
-                        child.tag_name().name(),
+                        child.name(),
                         child.text_location(),
                     )
                 };
@@ -665,7 +495,7 @@ fn parse_node<'a>(
                 let map_err = |e| {
                     format_err!(
                         "{e} in <{}> element ({})",
-                        child.tag_name().name(),
+                        child.name(),
                         child.text_location(),
                     )
                 };
@@ -684,7 +514,7 @@ fn parse_node<'a>(
                 let map_err = |e| {
                     format_err!(
                         "{e} in <{}> element ({})",
-                        child.tag_name().name(),
+                        child.name(),
                         child.text_location(),
                     )
                 };
@@ -702,7 +532,7 @@ fn parse_node<'a>(
                 let map_err = |e| {
                     format_err!(
                         "{e} in <{}> element ({})",
-                        child.tag_name().name(),
+                        child.name(),
                         child.text_location(),
                     )
                 };
@@ -724,7 +554,7 @@ fn parse_node<'a>(
                 let map_err = |e| {
                     format_err!(
                         "{e} in <{}> element ({})",
-                        child.tag_name().name(),
+                        child.name(),
                         child.text_location(),
                     )
                 };
@@ -770,13 +600,13 @@ impl FromStr for NodeType {
     }
 }
 
-fn parse_instance_geometry<'a>(node: xml::Node<'a, '_>) -> io::Result<InstanceGeometry<'a>> {
-    debug_assert_eq!(node.tag_name().name(), "instance_geometry");
+fn parse_instance_geometry<'a>(node: stream::Node<'a, '_>) -> io::Result<InstanceGeometry<'a>> {
+    debug_assert_eq!(node.name(), "instance_geometry");
     let url = node.parse_url("url")?;
     let mut materials = BTreeMap::new();
 
     for child in node.element_children() {
-        match child.tag_name().name() {
+        match child.name() {
             "bind_material" => {
                 parse_bind_material(child, &mut materials)?;
             }
@@ -806,15 +636,15 @@ Child Elements for <bind_material> / <technique_common>
 - `<instance_material>` (geometry) (1 or more)
 */
 fn parse_bind_material<'a>(
-    node: xml::Node<'a, '_>,
+    node: stream::Node<'a, '_>,
     materials: &mut BTreeMap<&'a str, SemanticMappingTable<'a>>,
 ) -> io::Result<()> {
-    debug_assert_eq!(node.tag_name().name(), "bind_material");
+    debug_assert_eq!(node.name(), "bind_material");
     for child in node.element_children() {
-        match child.tag_name().name() {
+        match child.name() {
             "technique_common" => {
                 for instance_mat_node in child.element_children() {
-                    match instance_mat_node.tag_name().name() {
+                    match instance_mat_node.name() {
                         "instance_material" => {
                             let table = parse_instance_material(instance_mat_node)?;
                             materials.insert(table.symbol, table);
@@ -845,14 +675,14 @@ Child elements must appear in the following order if present:
 - `<bind_vertex_input>` (0 or more)
 - `<extra>` (0 or more)
 */
-fn parse_instance_material<'a>(node: xml::Node<'a, '_>) -> io::Result<SemanticMappingTable<'a>> {
-    debug_assert_eq!(node.tag_name().name(), "instance_material");
+fn parse_instance_material<'a>(node: stream::Node<'a, '_>) -> io::Result<SemanticMappingTable<'a>> {
+    debug_assert_eq!(node.name(), "instance_material");
     let target = node.parse_url("target")?;
     let symbol = node.required_attribute("symbol")?;
     // let mut map = HashMap::default();
 
     for child in node.element_children() {
-        match child.tag_name().name() {
+        match child.name() {
             "bind_vertex_input" => {
                 /*
                 The <bind_vertex_input> element
@@ -879,8 +709,8 @@ fn parse_instance_material<'a>(node: xml::Node<'a, '_>) -> io::Result<SemanticMa
             "bind" => {
                 // warn!(
                 //     "<{}> child element in <{}> element is unsupported ({})",
-                //     child.tag_name().name(),
-                //     child.parent_element().unwrap().tag_name().name(),
+                //     child.name(),
+                //     child.parent_element().unwrap().name(),
                 //     child.node_location()
                 // );
             }
@@ -0,0 +1,671 @@
+// A pull parser that reads COLLADA text into a lightweight element tree.
+//
+// The contents of the <float_array>, <p>, and <vcount> elements in
+// <library_geometries> usually make up most of a COLLADA file. `Tree::parse`
+// reads the document with a pull parser and parses the contents of those
+// elements into numeric buffers as soon as their end tags are encountered;
+// only the buffers are kept, and they are looked up by the index of the
+// element in the tree.
+//
+// For other elements, the tree keeps only the metadata needed to resolve
+// cross-references and to report errors: the local name, the attributes, the
+// text before the first child element, and the positions in the original
+// text. Names, attribute values, and texts borrow from the original text
+// unless they contain references or CDATA sections, so no copy of the
+// document is made.
+
+use std::borrow::Cow;
+
+use super::*;
+
+/// The contents of the elements parsed by [`Tree::parse`], keyed by the
+/// index of the element in the tree.
+#[derive(Default)]
+pub(super) struct Arrays {
+    map: HashMap<usize, Content>,
+}
+
+enum Content {
+    /// The contents of `<float_array>`.
+    Float(io::Result<Vec<f32>>),
+    /// The contents of `<p>` or `<vcount>`, and the error that stopped parsing.
+    Indices(Vec<u32>, Option<io::Error>),
+}
+
+impl Arrays {
+    /// Takes the parsed contents of the given `<float_array>` element, if they
+    /// were parsed.
+    pub(super) fn take_float(&mut self, node: Node<'_, '_>) -> Option<io::Result<Vec<f32>>> {
+        match self.map.remove(&node.index)? {
+            Content::Float(values) => Some(values),
+            Content::Indices(..) => None,
+        }
+    }
+
+    /// Takes the parsed contents of the given `<p>` or `<vcount>` element, if
+    /// they were parsed.
+    pub(super) fn take_indices(
+        &mut self,
+        node: Node<'_, '_>,
+    ) -> Option<(Vec<u32>, Option<io::Error>)> {
+        match self.map.remove(&node.index)? {
+            Content::Indices(values, error) => Some((values, error)),
+            Content::Float(..) => None,
+        }
+    }
+}
+
+/// The elements of a COLLADA document, in document order.
+pub(super) struct Tree<'input> {
+    text: &'input str,
+    elements: Vec<Element<'input>>,
+    attributes: Vec<Attribute<'input>>,
+}
+
+struct Element<'input> {
+    /// The local name.
+    name: &'input str,
+    /// The position of `<` of the start tag.
+    start: usize,
+    /// The position after `>` of the start tag.
+    text_start: usize,
+    /// The text before the first child element.
+    text: Cow<'input, str>,
+    attributes: ops::Range<usize>,
+    parent: Option<usize>,
+    /// The index after the last descendant.
+    end: usize,
+}
+
+struct Attribute<'input> {
+    name: &'input str,
+    value: Cow<'input, str>,
+    /// The position of the value.
+    value_start: usize,
+}
+
+impl<'input> Tree<'input> {
+    /// Parses the given COLLADA text.
+    ///
+    /// If `arrays` is `None`, the contents of the array elements are
+    /// discarded without being parsed.
+    pub(super) fn parse(text: &'input str, mut arrays: Option<&mut Arrays>) -> io::Result<Self> {
+        let mut tree = Self {
+            text,
+            elements: vec![],
+            attributes: vec![],
+        };
+        let mut reader = Reader {
+            text,
+            pos: if text.starts_with('\u{FEFF}') { 3 } else { 0 },
+        };
+        // The open elements and their qualified names.
+        let mut stack: Vec<(usize, &str)> = vec![];
+
+        while let Some(event) = reader.next()? {
+            match event {
+                Event::Start {
+                    name,
+                    attrs,
+                    start,
+                    empty,
+                } => {
+                    if stack.is_empty() && !tree.elements.is_empty() {
+                        bail!(
+                            "unexpected element after the root element at {}",
+                            tree.pos(start)
+                        );
+                    }
+                    let index = tree.elements.len();
+                    let attributes = tree.parse_attributes(attrs, start + 1 + name.len())?;
+                    tree.elements.push(Element {
+                        name: name.rsplit(':').next().unwrap(),
+                        start,
+                        text_start: reader.pos,
+                        text: Cow::Borrowed(""),
+                        attributes,
+                        parent: stack.last().map(|&(parent, _)| parent),
+                        end: index + 1,
+                    });
+                    if !empty {
+                        stack.push((index, name));
+                    }
+                }
+                Event::End { name, start } => {
+                    let index = match stack.pop() {
+                        Some((index, open)) if open == name => index,
+                        Some((_, open)) => bail!(
+                            "expected </{}> but found </{}> at {}",
+                            open,
+                            name,
+                            tree.pos(start)
+                        ),
+                        None => bail!("unexpected </{}> at {}", name, tree.pos(start)),
+                    };
+                    tree.elements[index].end = tree.elements.len();
+                    tree.parse_array(index, &stack, arrays.as_deref_mut());
+                }
+                Event::Text { text: s, start } => {
+                    let &(index, _) = match stack.last() {
+                        Some(top) => top,
+                        None if xml::trim(s).is_empty() => continue,
+                        None => bail!(
+                            "unexpected text outside the root element at {}",
+                            tree.pos(start)
+                        ),
+                    };
+                    if tree.elements.len() == index + 1 {
+                        let s = tree.unescape(s, start, false)?;
+                        append(&mut tree.elements[index].text, s);
+                    }
+                }
+                Event::Cdata { text: s, start } => {
+                    let &(index, _) = match stack.last() {
+                        Some(top) => top,
+                        None => bail!(
+                            "unexpected CDATA section outside the root element at {}",
+                            tree.pos(start)
+                        ),
+                    };
+                    if tree.elements.len() == index + 1 {
+                        append(&mut tree.elements[index].text, Cow::Borrowed(s));
+                    }
+                }
+            }
+        }
+
+        if let Some(&(index, name)) = stack.last() {
+            bail!(
+                "<{}> element at {} is not closed",
+                name,
+                tree.pos(tree.elements[index].start)
+            );
+        }
+        if tree.elements.is_empty() {
+            bail!("no root element");
+        }
+        Ok(tree)
+    }
+
+    /// Parses the contents of the given element into `arrays` if it is a
+    /// `<float_array>`, `<p>`, or `<vcount>` element in `<library_geometries>`.
+    fn parse_array(&mut self, index: usize, stack: &[(usize, &str)], arrays: Option<&mut Arrays>) {
+        let element = &self.elements[index];
+        let parent = element.parent.map(|parent| self.elements[parent].name);
+        let is_float = match (element.name, parent) {
+            ("float_array", Some("source")) => true,
+            (
+                "p",
+                Some(
+                    "lines" | "linestrips" | "polygons" | "polylist" | "triangles" | "trifans"
+                    | "tristrips",
+                ),
+            )
+            | ("vcount", Some("polylist")) => false,
+            _ => return,
+        };
+        if !stack
+            .iter()
+            .any(|&(i, _)| self.elements[i].name == "library_geometries")
+        {
+            return;
+        }
+
+        let content = xml::trim(&element.text);
+        if let Some(arrays) = arrays {
+            let content = if is_float {
+                // The `count` attribute is checked when the document is parsed.
+                match self
+                    .node(index)
+                    .attribute("count")
+                    .and_then(|v| v.parse::<u32>().ok())
+                {
+                    Some(count) => Content::Float(parse_float_array(content, count)),
+                    None => return,
+                }
+            } else {
+                let (values, error) = parse_indices(content);
+                Content::Indices(values, error)
+            };
+            arrays.map.insert(index, content);
+        }
+        self.elements[index].text = Cow::Borrowed("");
+    }
+
+    /// Parses the attributes of a start tag, and returns their range in
+    /// `self.attributes`.
+    fn parse_attributes(
+        &mut self,
+        mut attrs: &'input str,
+        mut pos: usize,
+    ) -> io::Result<ops::Range<usize>> {
+        let first = self.attributes.len();
+        loop {
+            let trimmed = xml::trim_start(attrs);
+            if trimmed.is_empty() {
+                break;
+            }
+            if trimmed.len() == attrs.len() {
+                bail!("expected whitespace at {}", self.pos(pos));
+            }
+            pos += attrs.len() - trimmed.len();
+            attrs = trimmed;
+
+            let name_end = attrs
+                .find(|c| xml::is_whitespace(c) || c == '=')
+                .unwrap_or(attrs.len());
+            let name = &attrs[..name_end];
+            let rest = xml::trim_start(&attrs[name_end..]);
+            let rest = match rest.strip_prefix('=') {
+                Some(rest) if !name.is_empty() => xml::trim_start(rest),
+                _ => bail!("invalid attribute at {}", self.pos(pos)),
+            };
+            let value_start = pos + (attrs.len() - rest.len()) + 1;
+            let quote = match rest.chars().next() {
+                Some(c @ ('"' | '\'')) => c,
+                _ => bail!("expected quote at {}", self.pos(value_start - 1)),
+            };
+            let len = match rest[1..].find(quote) {
+                Some(len) => len,
+                None => bail!("attribute value is not closed at {}", self.pos(value_start)),
+            };
+            let value = &rest[1..=len];
+            if let Some(n) = value.find('<') {
+                bail!(
+                    "unexpected '<' in attribute value at {}",
+                    self.pos(value_start + n)
+                );
+            }
+            let rest = &rest[len + 2..];
+            pos += attrs.len() - rest.len();
+            attrs = rest;
+
+            if name == "xmlns" || name.starts_with("xmlns:") {
+                continue;
+            }
+            if self.attributes[first..].iter().any(|a| a.name == name) {
+                bail!("duplicated attribute {} at {}", name, self.pos(value_start));
+            }
+            let value = self.unescape(value, value_start, true)?;
+            self.attributes.push(Attribute {
+                name,
+                value,
+                value_start,
+            });
+        }
+        Ok(first..self.attributes.len())
+    }
+
+    /// Replaces references in the given text or attribute value, and
+    /// normalizes line breaks (and whitespace in attribute values).
+    fn unescape(&self, s: &'input str, pos: usize, is_attr: bool) -> io::Result<Cow<'input, str>> {
+        if !s.contains(&['&', '\r'][..]) && !(is_attr && s.contains(&['\t', '\n'][..])) {
+            return Ok(Cow::Borrowed(s));
+        }
+        let mut out = String::with_capacity(s.len());
+        let mut chars = s.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '&' => {
+                    let len = match s[i..].find(';') {
+                        Some(len) => len,
+                        None => bail!("invalid reference at {}", self.pos(pos + i)),
+                    };
+                    let c = match &s[i + 1..i + len] {
+                        "lt" => Some('<'),
+                        "gt" => Some('>'),
+                        "amp" => Some('&'),
+                        "apos" => Some('\''),
+                        "quot" => Some('"'),
+                        r => r
+                            .strip_prefix("#x")
+                            .map(|n| u32::from_str_radix(n, 16))
+                            .or_else(|| r.strip_prefix('#').map(str::parse))
+                            .and_then(Result::ok)
+                            .and_then(char::from_u32),
+                    };
+                    match c {
+                        Some(c) => out.push(c),
+                        None => bail!("invalid reference at {}", self.pos(pos + i)),
+                    }
+                    while chars.next_if(|&(j, _)| j <= i + len).is_some() {}
+                }
+                '\r' => {
+                    if chars.peek().map(|&(_, c)| c) != Some('\n') {
+                        out.push(if is_attr { ' ' } else { '\n' });
+                    }
+                }
+                '\t' | '\n' if is_attr => out.push(' '),
+                c => out.push(c),
+            }
+        }
+        Ok(Cow::Owned(out))
+    }
+
+    pub(super) fn root_element<'a>(&'a self) -> Node<'a, 'input> {
+        self.node(0)
+    }
+
+    fn node<'a>(&'a self, index: usize) -> Node<'a, 'input> {
+        Node { tree: self, index }
+    }
+
+    #[cold]
+    fn pos(&self, pos: usize) -> TextPos {
+        text_pos(self.text, pos)
+    }
+}
+
+/// A line and column in the text, starting at 1.
+#[derive(Clone, Copy)]
+pub(super) struct TextPos {
+    row: usize,
+    col: usize,
+}
+
+impl fmt::Display for TextPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.row, self.col)
+    }
+}
+
+/// Returns the line and column of the given position.
+#[cold]
+fn text_pos(text: &str, pos: usize) -> TextPos {
+    let before = &text[..pos];
+    TextPos {
+        row: before.bytes().filter(|&b| b == b'\n').count() + 1,
+        col: before[before.rfind('\n').map_or(0, |n| n + 1)..]
+            .chars()
+            .count()
+            + 1,
+    }
+}
+
+/// Appends `s` to the text of an element.
+fn append<'input>(text: &mut Cow<'input, str>, s: Cow<'input, str>) {
+    if text.is_empty() {
+        *text = s;
+    } else {
+        text.to_mut().push_str(&s);
+    }
+}
+
+/// An element in a [`Tree`].
+#[derive(Clone, Copy)]
+pub(super) struct Node<'a, 'input> {
+    tree: &'a Tree<'input>,
+    index: usize,
+}
+
+impl<'a, 'input> Node<'a, 'input> {
+    fn element(self) -> &'a Element<'input> {
+        &self.tree.elements[self.index]
+    }
+
+    /// Returns the local name of this element.
+    pub(super) fn name(&self) -> &'a str {
+        self.element().name
+    }
+
+    pub(super) fn attribute(&self, name: &str) -> Option<&'a str> {
+        self.find_attribute(name).map(|a| &*a.value)
+    }
+
+    fn find_attribute(&self, name: &str) -> Option<&'a Attribute<'input>> {
+        self.tree.attributes[self.element().attributes.clone()]
+            .iter()
+            .find(|a| a.name == name)
+    }
+
+    pub(super) fn parent_element(&self) -> Option<Self> {
+        self.element().parent.map(|index| self.tree.node(index))
+    }
+
+    pub(super) fn element_children(&self) -> Children<'a, 'input> {
+        Children {
+            tree: self.tree,
+            next: self.index + 1,
+            end: self.element().end,
+        }
+    }
+
+    /// Returns this element and its descendants in document order.
+    pub(super) fn descendants(&self) -> impl Iterator<Item = Node<'a, 'input>> {
+        let tree = self.tree;
+        (self.index..self.element().end).map(move |index| tree.node(index))
+    }
+
+    pub(super) fn child(&self, name: &str) -> Option<Self> {
+        self.element_children().find(|n| n.name() == name)
+    }
+
+    pub(super) fn required_attribute(&self, name: &str) -> io::Result<&'a str> {
+        match self.attribute(name) {
+            Some(v) => Ok(v),
+            None => {
+                bail!(
+                    "expected {} attribute in <{}> element at {}",
+                    name,
+                    self.name(),
+                    self.node_location(),
+                )
+            }
+        }
+    }
+
+    pub(super) fn parse_attribute<T>(&self, name: &str) -> io::Result<Option<T>>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        match self.attribute(name) {
+            Some(v) => Ok(Some(v.parse::<T>().map_err(|e| {
+                format_err!(
+                    "{} in <{}> element at {}: {:?}",
+                    e,
+                    self.name(),
+                    self.attr_value_location(name),
+                    v
+                )
+            })?)),
+            None => Ok(None),
+        }
+    }
+
+    pub(super) fn parse_required_attribute<T>(&self, name: &str) -> io::Result<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let v = self.required_attribute(name)?;
+        v.parse::<T>().map_err(|e| {
+            format_err!(
+                "{} in <{}> element at {}: {:?}",
+                e,
+                self.name(),
+                self.attr_value_location(name),
+                v
+            )
+        })
+    }
+
+    /// Returns the text before the first child element, without leading and
+    /// trailing whitespace.
+    ///
+    /// This is empty for array elements whose contents were parsed into
+    /// [`Arrays`].
+    pub(super) fn trimmed_text(&self) -> &'a str {
+        xml::trim(&self.element().text)
+    }
+
+    #[cold]
+    pub(super) fn node_location(&self) -> TextPos {
+        self.tree.pos(self.element().start)
+    }
+    #[cold]
+    pub(super) fn text_location(&self) -> TextPos {
+        self.tree.pos(self.element().text_start)
+    }
+    #[cold]
+    pub(super) fn attr_value_location(&self, name: &str) -> TextPos {
+        self.tree
+            .pos(self.find_attribute(name).unwrap().value_start)
+    }
+}
+
+/// An iterator over the child elements of a [`Node`].
+pub(super) struct Children<'a, 'input> {
+    tree: &'a Tree<'input>,
+    next: usize,
+    end: usize,
+}
+
+impl<'a, 'input> Iterator for Children<'a, 'input> {
+    type Item = Node<'a, 'input>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.end {
+            return None;
+        }
+        let node = self.tree.node(self.next);
+        self.next = node.element().end;
+        Some(node)
+    }
+}
+
+enum Event<'a> {
+    /// `<name ...>` or `<name .../>`.
+    Start {
+        name: &'a str,
+        attrs: &'a str,
+        /// The position of `<`.
+        start: usize,
+        empty: bool,
+    },
+    /// `</name>`.
+    End {
+        name: &'a str,
+        /// The position of `<`.
+        start: usize,
+    },
+    /// Character data, which may contain references.
+    Text { text: &'a str, start: usize },
+    /// The contents of a CDATA section.
+    Cdata { text: &'a str, start: usize },
+}
+
+struct Reader<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Returns the next event, skipping the XML declaration, comments,
+    /// processing instructions, and the document type declaration.
+    fn next(&mut self) -> io::Result<Option<Event<'a>>> {
+        loop {
+            let start = self.pos;
+            let rest = &self.text[start..];
+            if rest.is_empty() {
+                return Ok(None);
+            }
+            if !rest.starts_with('<') {
+                let len = rest.find('<').unwrap_or(rest.len());
+                self.pos += len;
+                return Ok(Some(Event::Text {
+                    text: &rest[..len],
+                    start,
+                }));
+            }
+            if rest.starts_with("<!--") {
+                self.pos += self.find(rest, "-->")? + 3;
+            } else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+                let len = self.find(cdata, "]]>")?;
+                self.pos += 9 + len + 3;
+                return Ok(Some(Event::Cdata {
+                    text: &cdata[..len],
+                    start,
+                }));
+            } else if rest.starts_with("<?") {
+                self.pos += self.find(rest, "?>")? + 2;
+            } else if rest.starts_with("<!DOCTYPE") {
+                self.pos += self.doctype_len(rest)?;
+            } else if let Some(tag) = rest.strip_prefix("</") {
+                let len = self.find(tag, ">")?;
+                let name = tag[..len].trim_end_matches(xml::is_whitespace);
+                self.pos += 2 + len + 1;
+                return Ok(Some(Event::End { name, start }));
+            } else {
+                let tag = &rest[1..];
+                let name_len = tag
+                    .find(|c| xml::is_whitespace(c) || c == '/' || c == '>')
+                    .unwrap_or(tag.len());
+                if name_len == 0 || tag[..name_len].starts_with(&['!', '<'][..]) {
+                    bail!("invalid markup at {}", self.pos());
+                }
+                // Find '>' that is not in an attribute value.
+                let bytes = tag.as_bytes();
+                let mut quote = None;
+                let mut i = name_len;
+                loop {
+                    let b = match bytes.get(i) {
+                        Some(&b) => b,
+                        None => bail!("start tag is not closed at {}", self.pos()),
+                    };
+                    match quote {
+                        Some(q) if b == q => quote = None,
+                        Some(_) => {}
+                        None if b == b'"' || b == b'\'' => quote = Some(b),
+                        None if b == b'>' => break,
+                        None => {}
+                    }
+                    i += 1;
+                }
+                let empty = bytes[i - 1] == b'/';
+                self.pos += 1 + i + 1;
+                return Ok(Some(Event::Start {
+                    name: &tag[..name_len],
+                    attrs: &tag[name_len..if empty { i - 1 } else { i }],
+                    start,
+                    empty,
+                }));
+            }
+        }
+    }
+
+    /// Returns the position of `pat` in `s`, which starts at the current
+    /// position.
+    fn find(&self, s: &str, pat: &str) -> io::Result<usize> {
+        match s.find(pat) {
+            Some(n) => Ok(n),
+            None => bail!("expected {:?} after {}", pat, self.pos()),
+        }
+    }
+
+    /// Returns the length of the document type declaration at the start of
+    /// `s`, including the internal subset.
+    fn doctype_len(&self, s: &str) -> io::Result<usize> {
+        let mut quote = None;
+        let mut in_subset = false;
+        for (i, b) in s.bytes().enumerate() {
+            match quote {
+                Some(q) if b == q => quote = None,
+                Some(_) => {}
+                None => match b {
+                    b'"' | b'\'' => quote = Some(b),
+                    b'[' => in_subset = true,
+                    b']' => in_subset = false,
+                    b'>' if !in_subset => return Ok(i + 1),
+                    _ => {}
+                },
+            }
+        }
+        bail!("document type declaration is not closed at {}", self.pos())
+    }
+
+    #[cold]
+    fn pos(&self) -> TextPos {
+        text_pos(self.text, self.pos)
+    }
+}
@@ -1,5 +1,7 @@
 use std::{fmt, path::PathBuf};
 
+use crate::Format;
+
 pub(crate) type Vec2 = [f32; 2];
 pub(crate) type Vec3 = [f32; 3];
 pub(crate) type Face = [u32; 3];
@@ -10,11 +12,25 @@ pub(crate) const MAX_NUMBER_OF_TEXCOORDS: usize = 2;
 // TODO: assimp uses 8 here
 pub(crate) const MAX_NUMBER_OF_COLOR_SETS: usize = 2;
 
+// The color of meshes without a material or a color. Use the same default
+// color (light gray) as STL and OFF.
+#[cfg(any(
+    feature = "amf",
+    feature = "dxf",
+    feature = "fbx",
+    feature = "off",
+    feature = "urdf",
+    feature = "usd",
+    feature = "vrml"
+))]
+pub(crate) const DEFAULT_COLOR: Color4 = [0.6, 0.6, 0.6, 1.];
+
 #[derive(Debug, Clone, Default)]
 #[non_exhaustive]
 pub struct Scene {
     pub materials: Vec<Material>,
     pub meshes: Vec<Mesh>,
+    pub point_clouds: Vec<PointCloud>,
 }
 
 /// Triangle mesh
@@ -100,6 +116,109 @@ impl fmt::Debug for Mesh {
     }
 }
 
+/// Point cloud
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub struct PointCloud {
+    pub name: String,
+    pub points: Vec<Vec3>,
+    /// Per-point normals. Empty if the file has no normals.
+    pub normals: Vec<Vec3>,
+    /// Per-point colors. Empty if the file has no colors.
+    pub colors: Vec<Color4>,
+    /// Per-point intensities. Empty if the file has no intensities.
+    pub intensities: Vec<f32>,
+}
+
+impl fmt::Debug for PointCloud {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PointCloud")
+            .field("name", &self.name)
+            .field("num_points", &self.points.len())
+            .field("num_normals", &self.normals.len())
+            .field("num_colors", &self.colors.len())
+            .field("num_intensities", &self.intensities.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A summary of a model file, returned by [`Loader::probe`](crate::Loader::probe).
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct Summary {
+    /// The format of the file. `None` if the file was loaded by a user-defined
+    /// importer.
+    pub format: Option<Format>,
+    /// The meshes (STL solids, OBJ groups, COLLADA geometries, etc.) in the file.
+    pub meshes: Vec<MeshSummary>,
+    /// The number of vertices, if known.
+    pub num_vertices: Option<u64>,
+    /// The minimum and maximum corners of the bounding box of the vertices,
+    /// if known.
+    pub bounds: Option<[Vec3; 2]>,
+    /// The length of a unit in meters, if specified by the file.
+    pub unit: Option<f32>,
+    /// Other information in the file, such as the header of binary STL and
+    /// the `<asset>` of COLLADA, as pairs of key and value.
+    pub metadata: Vec<(String, String)>,
+    /// The files referenced by the file, such as MTL files and textures.
+    pub references: Vec<String>,
+}
+
+/// A summary of a mesh in [`Summary`].
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct MeshSummary {
+    pub name: String,
+    /// The number of faces. Depending on the format, this is the number of
+    /// polygons, not triangles.
+    pub num_faces: u64,
+}
+
+impl Summary {
+    /// Summarizes the loaded scene.
+    pub(crate) fn from_scene(mut scene: Scene) -> Self {
+        let mut summary = Self::default();
+        let mut num_vertices = 0;
+        for mesh in &scene.meshes {
+            summary.meshes.push(MeshSummary {
+                name: mesh.name.clone(),
+                num_faces: mesh.faces.len() as u64,
+            });
+            num_vertices += mesh.vertices.len() as u64;
+            mesh.vertices.iter().for_each(|&v| summary.add_vertex(v));
+        }
+        for point_cloud in &scene.point_clouds {
+            num_vertices += point_cloud.points.len() as u64;
+            point_cloud
+                .points
+                .iter()
+                .for_each(|&v| summary.add_vertex(v));
+        }
+        summary.num_vertices = Some(num_vertices);
+        for material in &mut scene.materials {
+            material.texture.for_each_mut(|texture| {
+                if let Some(p) = texture {
+                    let p = p.to_string_lossy();
+                    if !summary.references.iter().any(|r| *r == p) {
+                        summary.references.push(p.into_owned());
+                    }
+                }
+            });
+        }
+        summary
+    }
+
+    /// Extends the bounds to contain the given vertex.
+    pub(crate) fn add_vertex(&mut self, v: Vec3) {
+        let [min, max] = self.bounds.get_or_insert([v, v]);
+        for ((min, max), v) in min.iter_mut().zip(max.iter_mut()).zip(v) {
+            *min = min.min(v);
+            *max = max.max(v);
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 #[non_exhaustive]
 pub struct Material {
@@ -144,6 +263,22 @@ pub struct Textures {
     pub reflection: Option<PathBuf>,
 }
 
+impl Textures {
+    pub(crate) fn for_each_mut<F: FnMut(&mut Option<PathBuf>)>(&mut self, mut f: F) {
+        f(&mut self.diffuse);
+        f(&mut self.specular);
+        f(&mut self.ambient);
+        f(&mut self.emissive);
+        f(&mut self.height);
+        f(&mut self.normal);
+        f(&mut self.shininess);
+        f(&mut self.opacity);
+        f(&mut self.displacement);
+        f(&mut self.lightmap);
+        f(&mut self.reflection);
+    }
+}
+
 // Refs: https://github.com/assimp/assimp/blob/v5.3.1/include/assimp/material.h#L355
 #[derive(Debug, Clone)]
 #[non_exhaustive]
@@ -0,0 +1,49 @@
+use std::{fmt, io, path::Path};
+
+#[cfg_attr(test, derive(Debug))]
+pub(super) enum ErrorKind {
+    Expected(&'static str, usize),
+    Float(usize),
+    Int(usize),
+    Oob(usize, usize),
+    TooDeep(usize),
+    TooMany(&'static str, usize),
+    Unsupported(&'static str, usize),
+}
+
+impl ErrorKind {
+    #[cold]
+    #[inline(never)]
+    pub(super) fn into_io_error(self, start: &[u8], path: Option<&Path>) -> io::Error {
+        let remaining = match self {
+            Self::Expected(.., n)
+            | Self::Float(n)
+            | Self::Int(n)
+            | Self::Oob(.., n)
+            | Self::TooDeep(n)
+            | Self::TooMany(.., n)
+            | Self::Unsupported(.., n) => n,
+        };
+        let e = if let Self::Unsupported(..) = self {
+            io::Error::new(io::ErrorKind::Unsupported, self.to_string())
+        } else {
+            crate::error::invalid_data(self.to_string())
+        };
+        crate::error::with_location(&e, &crate::error::Location::find(remaining, start, path))
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    #[cold]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Expected(msg, ..) => write!(f, "expected {msg}"),
+            Self::Float(..) => f.write_str("error while parsing a float"),
+            Self::Int(..) => f.write_str("error while parsing an integer"),
+            Self::Oob(i, ..) => write!(f, "vertex index out of bounds ({i})"),
+            Self::TooDeep(..) => f.write_str("too deeply nested block references"),
+            Self::TooMany(msg, ..) => write!(f, "too many {msg}"),
+            Self::Unsupported(msg, ..) => write!(f, "{msg} is not supported"),
+        }
+    }
+}
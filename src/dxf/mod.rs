@@ -0,0 +1,743 @@
+//! [DXF] (.dxf) parser.
+//!
+//! This supports ASCII DXF and reads the following entities as triangle
+//! meshes:
+//!
+//! - `3DFACE`
+//! - `POLYLINE` polyface meshes and polygon meshes
+//! - `MESH` (the control mesh; subdivision is not applied)
+//!
+//! `INSERT` block references are expanded with their transforms, and the
+//! drawing units (`$INSUNITS`) are converted to meters. Entities are grouped
+//! into one mesh per layer and color; the layer name is used as the mesh name
+//! and the color (AutoCAD Color Index or true color) as the diffuse color.
+//!
+//! Binary DXF and other entities (lines, curves, `3DSOLID`, etc.) are not
+//! supported.
+//!
+//! [DXF]: https://en.wikipedia.org/wiki/AutoCAD_DXF
+
+#![allow(clippy::many_single_char_names)]
+
+mod error;
+
+use std::{collections::HashMap, io, path::Path};
+
+use self::error::ErrorKind;
+use crate::{
+    common::DEFAULT_COLOR,
+    utils::{
+        bytes::starts_with,
+        float, int,
+        matrix::{normalize, Matrix4x4},
+    },
+    Material, Mesh, Scene, Vec3,
+};
+
+/// Parses meshes from bytes of ASCII DXF.
+#[inline]
+pub fn from_slice(bytes: &[u8]) -> io::Result<Scene> {
+    from_slice_internal(bytes, None)
+}
+
+pub(crate) fn from_slice_internal(bytes: &[u8], path: Option<&Path>) -> io::Result<Scene> {
+    read_dxf(bytes).map_err(|e| e.into_io_error(bytes, path))
+}
+const MAX_DEPTH: usize = 16;
+// The maximum number of block instances expanded by INSERT entities, including
+// the instances in nested blocks and the rows and columns of arrays.
+const MAX_INSTANCES: usize = 1 << 20;
+
+fn read_dxf(bytes: &[u8]) -> Result<Scene, ErrorKind> {
+    if starts_with(bytes, b"AutoCAD Binary DXF") {
+        return Err(ErrorKind::Unsupported("binary DXF", bytes.len()));
+    }
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let groups = tokenize(bytes)?;
+    let doc = Document::parse(&groups)?;
+    let mut builder = Builder {
+        doc: &doc,
+        meshes: vec![],
+        materials: vec![],
+        mesh_index: HashMap::new(),
+        instances: 0,
+    };
+    let cx = Context {
+        transform: Matrix4x4::scaling([doc.units; 3]),
+        layer: None,
+        color: None,
+        depth: 0,
+    };
+    builder.entities(&doc.entities, &cx)?;
+    Ok(builder.finish())
+}
+
+// -----------------------------------------------------------------------------
+// Tokenizer
+
+/*
+https://help.autodesk.com/view/OARX/2024/ENU/?guid=GUID-235B22E0-A567-4CF6-92D3-38A2306D73F3
+
+Each group is a pair of lines: a group code (integer) and its value.
+
+  0
+SECTION
+  2
+ENTITIES
+  0
+3DFACE
+  8
+Layer1
+ 10
+0.0
+...
+  0
+ENDSEC
+  0
+EOF
+*/
+struct Group<'a> {
+    code: i32,
+    value: &'a [u8],
+    /// The number of remaining bytes at the start of the value line (used
+    /// for error locations).
+    pos: usize,
+}
+
+impl Group<'_> {
+    fn int(&self) -> Result<i32, ErrorKind> {
+        int::parse(self.value).ok_or(ErrorKind::Int(self.pos))
+    }
+    fn float(&self) -> Result<f32, ErrorKind> {
+        float::parse(self.value).ok_or(ErrorKind::Float(self.pos))
+    }
+}
+
+fn tokenize(mut s: &[u8]) -> Result<Vec<Group<'_>>, ErrorKind> {
+    let mut groups = vec![];
+    loop {
+        let pos = s.len();
+        let code = match read_line(&mut s) {
+            Some([]) => continue,
+            Some(line) => int::parse::<i32>(line).ok_or(ErrorKind::Int(pos))?,
+            None => break,
+        };
+        let pos = s.len();
+        let value = read_line(&mut s).ok_or(ErrorKind::Expected("group value", pos))?;
+        if code == 999 {
+            // comment
+            continue;
+        }
+        let eof = code == 0 && value == b"EOF";
+        groups.push(Group { code, value, pos });
+        if eof {
+            break;
+        }
+    }
+    Ok(groups)
+}
+
+/// Reads a line and returns it without leading and trailing whitespace.
+fn read_line<'a>(s: &mut &'a [u8]) -> Option<&'a [u8]> {
+    if s.is_empty() {
+        return None;
+    }
+    let end = s.iter().position(|&b| b == b'\n').unwrap_or(s.len());
+    let mut line = &s[..end];
+    *s = s.get(end + 1..).unwrap_or_default();
+    while let Some((first, rest)) = line.split_first() {
+        if !first.is_ascii_whitespace() {
+            break;
+        }
+        line = rest;
+    }
+    while let Some((last, rest)) = line.split_last() {
+        if !last.is_ascii_whitespace() {
+            break;
+        }
+        line = rest;
+    }
+    Some(line)
+}
+
+// -----------------------------------------------------------------------------
+// Document
+
+/// An entity (or any other record that starts with a group with code 0).
+struct Entity<'a> {
+    groups: &'a [Group<'a>],
+    /// VERTEX entities following POLYLINE.
+    vertices: Vec<Entity<'a>>,
+}
+
+impl<'a> Entity<'a> {
+    fn ty(&self) -> &'a [u8] {
+        self.groups[0].value
+    }
+    fn get(&self, code: i32) -> Option<&'a Group<'a>> {
+        self.groups[1..].iter().find(|g| g.code == code)
+    }
+    fn string(&self, code: i32) -> Option<&'a [u8]> {
+        self.get(code).map(|g| g.value)
+    }
+    fn int(&self, code: i32, default: i32) -> Result<i32, ErrorKind> {
+        self.get(code).map_or(Ok(default), Group::int)
+    }
+    fn float(&self, code: i32, default: f32) -> Result<f32, ErrorKind> {
+        self.get(code).map_or(Ok(default), Group::float)
+    }
+    /// Reads a point from the groups with the given code (x), code + 10 (y),
+    /// and code + 20 (z).
+    fn point(&self, code: i32, default: Vec3) -> Result<Vec3, ErrorKind> {
+        Ok([
+            self.float(code, default[0])?,
+            self.float(code + 10, default[1])?,
+            self.float(code + 20, default[2])?,
+        ])
+    }
+    fn true_color(&self) -> Result<Option<[u8; 3]>, ErrorKind> {
+        match self.get(420) {
+            Some(g) => {
+                let [_, r, g, b] = g.int()?.to_be_bytes();
+                Ok(Some([r, g, b]))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+struct Block<'a> {
+    base: Vec3,
+    entities: Vec<Entity<'a>>,
+}
+
+struct Document<'a> {
+    /// The scale factor to convert drawing units to meters.
+    units: f32,
+    layers: HashMap<&'a [u8], Option<[u8; 3]>>,
+    blocks: HashMap<&'a [u8], Block<'a>>,
+    entities: Vec<Entity<'a>>,
+}
+
+impl<'a> Document<'a> {
+    fn parse(groups: &'a [Group<'a>]) -> Result<Self, ErrorKind> {
+        let mut doc = Self {
+            units: 1.,
+            layers: HashMap::new(),
+            blocks: HashMap::new(),
+            entities: vec![],
+        };
+        let mut section: &[u8] = b"";
+        let mut block: Option<(&[u8], Block<'a>)> = None;
+        let mut rest = groups;
+        while let Some((first, tail)) = rest.split_first() {
+            let len = tail
+                .iter()
+                .position(|g| g.code == 0)
+                .map_or(rest.len(), |n| n + 1);
+            let (record, next) = rest.split_at(len);
+            rest = next;
+            if first.code != 0 {
+                continue;
+            }
+            let entity = Entity {
+                groups: record,
+                vertices: vec![],
+            };
+            match entity.ty() {
+                b"SECTION" => {
+                    section = entity.string(2).unwrap_or_default();
+                    if section == b"HEADER" {
+                        doc.header(record)?;
+                    }
+                }
+                b"ENDSEC" => section = b"",
+                b"EOF" => break,
+                _ => match section {
+                    b"TABLES" if entity.ty() == b"LAYER" => {
+                        if let Some(name) = entity.string(2) {
+                            let color = match entity.true_color()? {
+                                Some(color) => Some(color),
+                                // Negative colors are used for layers that are turned off.
+                                None => aci_color(entity.int(62, 7)?.abs()),
+                            };
+                            doc.layers.insert(name, color);
+                        }
+                    }
+                    b"BLOCKS" => match entity.ty() {
+                        b"BLOCK" => {
+                            let name = entity.string(2).unwrap_or_default();
+                            let base = entity.point(10, [0.; 3])?;
+                            let entities = vec![];
+                            block = Some((name, Block { base, entities }));
+                        }
+                        b"ENDBLK" => {
+                            if let Some((name, block)) = block.take() {
+                                doc.blocks.insert(name, block);
+                            }
+                        }
+                        _ => {
+                            if let Some((_, block)) = &mut block {
+                                push_entity(&mut block.entities, entity);
+                            }
+                        }
+                    },
+                    b"ENTITIES" => push_entity(&mut doc.entities, entity),
+                    _ => {}
+                },
+            }
+        }
+        Ok(doc)
+    }
+
+    fn header(&mut self, groups: &[Group<'_>]) -> Result<(), ErrorKind> {
+        let mut groups = groups.iter();
+        while let Some(g) = groups.next() {
+            if g.code == 9 && g.value == b"$INSUNITS" {
+                if let Some(g) = groups.next().filter(|g| g.code == 70) {
+                    self.units = match g.int()? {
+                        1 => 0.0254,    // inches
+                        2 => 0.3048,    // feet
+                        3 => 1609.344,  // miles
+                        4 => 0.001,     // millimeters
+                        5 => 0.01,      // centimeters
+                        7 => 1000.,     // kilometers
+                        8 => 0.0254e-6, // microinches
+                        9 => 0.0254e-3, // mils
+                        10 => 0.9144,   // yards
+                        11 => 1e-10,    // angstroms
+                        12 => 1e-9,     // nanometers
+                        13 => 1e-6,     // microns
+                        14 => 0.1,      // decimeters
+                        15 => 10.,      // decameters
+                        16 => 100.,     // hectometers
+                        17 => 1e9,      // gigameters
+                        _ => 1.,        // meters or unitless
+                    };
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn push_entity<'a>(entities: &mut Vec<Entity<'a>>, entity: Entity<'a>) {
+    if entity.ty() == b"VERTEX" {
+        if let Some(polyline) = entities.last_mut().filter(|e| e.ty() == b"POLYLINE") {
+            polyline.vertices.push(entity);
+            return;
+        }
+    }
+    entities.push(entity);
+}
+
+// -----------------------------------------------------------------------------
+// Builder
+
+#[derive(Clone, Copy)]
+struct Context<'a> {
+    transform: Matrix4x4,
+    /// The layer of the INSERT entity, used by entities on layer "0" in blocks.
+    layer: Option<&'a [u8]>,
+    /// The color of the INSERT entity, used by entities with BYBLOCK color.
+    color: Option<[u8; 3]>,
+    depth: usize,
+}
+
+struct Builder<'a> {
+    doc: &'a Document<'a>,
+    meshes: Vec<Mesh>,
+    materials: Vec<Material>,
+    mesh_index: HashMap<(&'a [u8], Option<[u8; 3]>), usize>,
+    /// The number of block instances expanded so far.
+    instances: usize,
+}
+
+impl<'a> Builder<'a> {
+    fn entities(&mut self, entities: &'a [Entity<'a>], cx: &Context<'a>) -> Result<(), ErrorKind> {
+        for entity in entities {
+            match entity.ty() {
+                b"3DFACE" => self.face(entity, cx)?,
+                b"POLYLINE" => self.polyline(entity, cx)?,
+                b"MESH" => self.subd_mesh(entity, cx)?,
+                b"INSERT" => self.insert(entity, cx)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn layer_and_color(
+        &self,
+        entity: &Entity<'a>,
+        cx: &Context<'a>,
+    ) -> Result<(&'a [u8], Option<[u8; 3]>), ErrorKind> {
+        let mut layer = entity.string(8).unwrap_or(b"0");
+        if layer == b"0" {
+            if let Some(l) = cx.layer {
+                layer = l;
+            }
+        }
+        let color = match entity.true_color()? {
+            Some(color) => Some(color),
+            None => match entity.int(62, 256)? {
+                // BYBLOCK
+                0 => cx.color,
+                // BYLAYER
+                256 => self.doc.layers.get(layer).copied().flatten(),
+                i => aci_color(i),
+            },
+        };
+        Ok((layer, color))
+    }
+
+    /// Returns the mesh for the layer and color of the given entity.
+    fn mesh_for(&mut self, entity: &Entity<'a>, cx: &Context<'a>) -> Result<&mut Mesh, ErrorKind> {
+        let key = self.layer_and_color(entity, cx)?;
+        let index = match self.mesh_index.get(&key) {
+            Some(&index) => index,
+            None => {
+                let (layer, color) = key;
+                let name = String::from_utf8_lossy(layer).into_owned();
+                let mut material = Material {
+                    name: name.clone(),
+                    ..Default::default()
+                };
+                material.color.diffuse = Some(color.map_or(DEFAULT_COLOR, |[r, g, b]| {
+                    [
+                        f32::from(r) / 255.,
+                        f32::from(g) / 255.,
+                        f32::from(b) / 255.,
+                        1.,
+                    ]
+                }));
+                self.materials.push(material);
+                self.meshes.push(Mesh {
+                    name,
+                    ..Default::default()
+                });
+                self.mesh_index.insert(key, self.meshes.len() - 1);
+                self.meshes.len() - 1
+            }
+        };
+        Ok(&mut self.meshes[index])
+    }
+
+    fn face(&mut self, entity: &Entity<'a>, cx: &Context<'a>) -> Result<(), ErrorKind> {
+        let a = entity.point(10, [0.; 3])?;
+        let b = entity.point(11, [0.; 3])?;
+        let c = entity.point(12, [0.; 3])?;
+        let d = entity.point(13, c)?;
+        let mesh = self.mesh_for(entity, cx)?;
+        // The fourth corner is the same as the third one if the face is a triangle.
+        if c == d {
+            push_polygon(mesh, &[a, b, c], cx.transform);
+        } else {
+            push_polygon(mesh, &[a, b, c, d], cx.transform);
+        }
+        Ok(())
+    }
+
+    fn polyline(&mut self, entity: &Entity<'a>, cx: &Context<'a>) -> Result<(), ErrorKind> {
+        let flags = entity.int(70, 0)?;
+        if flags & 64 != 0 {
+            // Polyface mesh: vertices with flag 64 are locations and vertices
+            // with only flag 128 are faces.
+            let mut vertices = vec![];
+            for v in &entity.vertices {
+                if v.int(70, 0)? & 64 != 0 {
+                    vertices.push(v.point(10, [0.; 3])?);
+                }
+            }
+            let mesh = self.mesh_for(entity, cx)?;
+            let mut polygon = Vec::with_capacity(4);
+            for v in &entity.vertices {
+                if v.int(70, 0)? & (128 | 64) != 128 {
+                    continue;
+                }
+                polygon.clear();
+                for code in 71..=74 {
+                    let g = match v.get(code) {
+                        Some(g) => g,
+                        None => continue,
+                    };
+                    // Negative indices mark invisible edges, and 0 marks unused corners.
+                    let i = g.int()?.unsigned_abs() as usize;
+                    if i == 0 {
+                        continue;
+                    }
+                    match vertices.get(i - 1) {
+                        Some(&p) => polygon.push(p),
+                        None => return Err(ErrorKind::Oob(i, g.pos)),
+                    }
+                }
+                push_polygon(mesh, &polygon, cx.transform);
+            }
+        } else if flags & 16 != 0 {
+            // Polygon mesh: M x N vertices. Spline-fit surfaces use the
+            // smoothed vertices instead of the frame control points.
+            let (m, n) = if entity.int(75, 0)? == 0 {
+                (entity.int(71, 0)?, entity.int(72, 0)?)
+            } else {
+                (entity.int(73, 0)?, entity.int(74, 0)?)
+            };
+            let m = usize::try_from(m).unwrap_or(0);
+            let n = usize::try_from(n).unwrap_or(0);
+            let mut vertices = Vec::with_capacity(entity.vertices.len());
+            for v in &entity.vertices {
+                if v.int(70, 0)? & 16 == 0 {
+                    vertices.push(v.point(10, [0.; 3])?);
+                }
+            }
+            if m * n > vertices.len() {
+                return Err(ErrorKind::Oob(m * n - 1, entity.groups[0].pos));
+            }
+            let closed_m = flags & 1 != 0;
+            let closed_n = flags & 32 != 0;
+            let rows = if closed_m { m } else { m.saturating_sub(1) };
+            let columns = if closed_n { n } else { n.saturating_sub(1) };
+            let mesh = self.mesh_for(entity, cx)?;
+            for i in 0..rows {
+                let i1 = (i + 1) % m;
+                for j in 0..columns {
+                    let j1 = (j + 1) % n;
+                    let quad = [
+                        vertices[i * n + j],
+                        vertices[i * n + j1],
+                        vertices[i1 * n + j1],
+                        vertices[i1 * n + j],
+                    ];
+                    push_polygon(mesh, &quad, cx.transform);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn subd_mesh(&mut self, entity: &Entity<'a>, cx: &Context<'a>) -> Result<(), ErrorKind> {
+        // Groups of MESH are sequential: vertex count (92), vertices
+        // (10, 20, 30), face list size (93), face list (90), and so on.
+        let mut vertices = vec![];
+        let mut face_list = vec![];
+        let mut groups = entity.groups[1..].iter();
+        while let Some(g) = groups.next() {
+            match g.code {
+                92 => {
+                    for _ in 0..g.int()? {
+                        let mut v = [0.; 3];
+                        for (v, code) in v.iter_mut().zip([10, 20, 30]) {
+                            match groups.next() {
+                                Some(g) if g.code == code => *v = g.float()?,
+                                _ => return Err(ErrorKind::Expected("vertex position", g.pos)),
+                            }
+                        }
+                        vertices.push(v);
+                    }
+                }
+                93 => {
+                    for _ in 0..g.int()? {
+                        match groups.next() {
+                            Some(g) if g.code == 90 => face_list.push(g),
+                            _ => return Err(ErrorKind::Expected("face list", g.pos)),
+                        }
+                    }
+                    // Ignore edges, creases, and overrides.
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        let mesh = self.mesh_for(entity, cx)?;
+        let mut polygon = vec![];
+        let mut face_list = face_list.iter();
+        while let Some(g) = face_list.next() {
+            polygon.clear();
+            for _ in 0..g.int()? {
+                let g = match face_list.next() {
+                    Some(g) => g,
+                    None => return Err(ErrorKind::Expected("face list", g.pos)),
+                };
+                let i = g.int()?;
+                match usize::try_from(i).ok().and_then(|i| vertices.get(i)) {
+                    Some(&p) => polygon.push(p),
+                    None => return Err(ErrorKind::Oob(i.unsigned_abs() as usize, g.pos)),
+                }
+            }
+            push_polygon(mesh, &polygon, cx.transform);
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, entity: &Entity<'a>, cx: &Context<'a>) -> Result<(), ErrorKind> {
+        let block = match entity.string(2).and_then(|name| self.doc.blocks.get(name)) {
+            Some(block) => block,
+            // Ignore external references and undefined blocks.
+            None => return Ok(()),
+        };
+        if cx.depth >= MAX_DEPTH {
+            return Err(ErrorKind::TooDeep(entity.groups[0].pos));
+        }
+        let (layer, color) = self.layer_and_color(entity, cx)?;
+        let point = entity.point(10, [0.; 3])?;
+        let scale = [
+            entity.float(41, 1.)?,
+            entity.float(42, 1.)?,
+            entity.float(43, 1.)?,
+        ];
+        let rotation = entity.float(50, 0.)?.to_radians();
+        let columns = entity.int(70, 1)?.max(1);
+        let rows = entity.int(71, 1)?.max(1);
+        // Count the whole array before expanding it, so that large row and
+        // column counts are rejected without doing any work.
+        self.instances = (columns.unsigned_abs() as usize)
+            .checked_mul(rows.unsigned_abs() as usize)
+            .and_then(|n| n.checked_add(self.instances))
+            .filter(|&n| n <= MAX_INSTANCES)
+            .ok_or(ErrorKind::TooMany("block instances", entity.groups[0].pos))?;
+        let column_spacing = entity.float(44, 0.)?;
+        let row_spacing = entity.float(45, 0.)?;
+        let [x, y, z] = block.base;
+
+        let mut base = cx.transform;
+        base *= ocs(entity.point(210, [0., 0., 1.])?);
+        base *= Matrix4x4::translation(point);
+        base *= Matrix4x4::rotation(rotation, [0., 0., 1.]);
+        for row in 0..rows {
+            for column in 0..columns {
+                let mut transform = base;
+                #[allow(clippy::cast_precision_loss)]
+                let offset = [column as f32 * column_spacing, row as f32 * row_spacing, 0.];
+                transform *= Matrix4x4::translation(offset);
+                transform *= Matrix4x4::scaling(scale);
+                transform *= Matrix4x4::translation([-x, -y, -z]);
+                let cx = Context {
+                    transform,
+                    layer: Some(layer),
+                    color,
+                    depth: cx.depth + 1,
+                };
+                self.entities(&block.entities, &cx)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Scene {
+        let (meshes, materials) = self
+            .meshes
+            .into_iter()
+            .zip(self.materials)
+            .filter(|(mesh, _)| !mesh.faces.is_empty())
+            .unzip();
+        Scene {
+            materials,
+            meshes,
+            ..Default::default()
+        }
+    }
+}
+
+fn push_polygon(mesh: &mut Mesh, polygon: &[Vec3], transform: Matrix4x4) {
+    for i in 1..polygon.len().saturating_sub(1) {
+        #[allow(clippy::cast_possible_truncation)]
+        let vertices_indices = [
+            mesh.vertices.len() as u32,
+            (mesh.vertices.len() + 1) as u32,
+            (mesh.vertices.len() + 2) as u32,
+        ];
+        for mut v in [polygon[0], polygon[i], polygon[i + 1]] {
+            v *= transform;
+            mesh.vertices.push(v);
+        }
+        mesh.faces.push(vertices_indices);
+    }
+}
+
+/// Returns the transform from the object coordinate system (OCS) with the
+/// given extrusion direction to the world coordinate system.
+///
+/// See "Arbitrary Axis Algorithm" in the DXF reference.
+fn ocs(n: Vec3) -> Matrix4x4 {
+    let n = normalize(if n == [0.; 3] { [0., 0., 1.] } else { n });
+    let ax = if n[0].abs() < 1. / 64. && n[1].abs() < 1. / 64. {
+        cross([0., 1., 0.], n)
+    } else {
+        cross([0., 0., 1.], n)
+    };
+    let ax = normalize(ax);
+    let ay = normalize(cross(n, ax));
+    Matrix4x4::new(
+        ax[0], ay[0], n[0], 0., ax[1], ay[1], n[1], 0., ax[2], ay[2], n[2], 0., 0., 0., 0., 1.,
+    )
+}
+
+fn cross(a: Vec3, b: Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Converts an AutoCAD Color Index (ACI) to RGB.
+///
+/// Returns `None` if the index is out of range (including 0 (BYBLOCK) and 256
+/// (BYLAYER)).
+#[allow(clippy::cast_possible_truncation)]
+fn aci_color(index: i32) -> Option<[u8; 3]> {
+    const VALUES: [u32; 5] = [255, 204, 153, 127, 76];
+    const GRAYS: [u8; 6] = [0x33, 0x50, 0x69, 0x82, 0xBE, 0xFF];
+    let index = u8::try_from(index).ok()?;
+    Some(match index {
+        0 => return None,
+        1 => [255, 0, 0],
+        2 => [255, 255, 0],
+        3 => [0, 255, 0],
+        4 => [0, 255, 255],
+        5 => [0, 0, 255],
+        6 => [255, 0, 255],
+        7 => [255, 255, 255],
+        8 => [128, 128, 128],
+        9 => [192, 192, 192],
+        10..=249 => {
+            // 24 hues (15 degrees apart), each with 5 values, and each value
+            // with a saturated and a pale variant.
+            let i = index - 10;
+            let hue = u32::from(i / 10) * 15;
+            let v = VALUES[usize::from(i % 10 / 2)];
+            let min = if i % 2 == 0 { 0 } else { v / 2 };
+            let k = hue % 60 / 15;
+            let rise = min + (v - min) * k / 4;
+            let fall = min + (v - min) * (4 - k) / 4;
+            let [r, g, b] = match hue / 60 {
+                0 => [v, rise, min],
+                1 => [fall, v, min],
+                2 => [min, v, rise],
+                3 => [min, fall, v],
+                4 => [rise, min, v],
+                _ => [v, min, fall],
+            };
+            [r as u8, g as u8, b as u8]
+        }
+        250..=255 => [GRAYS[usize::from(index - 250)]; 3],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aci() {
+        assert_eq!(aci_color(0), None);
+        assert_eq!(aci_color(256), None);
+        assert_eq!(aci_color(1), Some([255, 0, 0]));
+        assert_eq!(aci_color(11), Some([255, 127, 127]));
+        assert_eq!(aci_color(23), Some([204, 127, 102]));
+        assert_eq!(aci_color(30), Some([255, 127, 0]));
+        assert_eq!(aci_color(70), Some([127, 255, 0]));
+        assert_eq!(aci_color(150), Some([0, 127, 255]));
+        assert_eq!(aci_color(250), Some([0x33; 3]));
+    }
+}
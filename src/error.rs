@@ -1,11 +1,51 @@
+#[cfg(any(
+    feature = "dxf",
+    feature = "obj",
+    feature = "off",
+    feature = "pcd",
+    feature = "stl",
+    feature = "usd",
+    feature = "vrml",
+    feature = "xyz"
+))]
+use std::fmt;
 use std::io;
-#[cfg(any(feature = "obj", feature = "stl"))]
-use std::{fmt, path::Path};
+#[cfg(any(
+    feature = "amf",
+    feature = "dxf",
+    feature = "fbx",
+    feature = "obj",
+    feature = "off",
+    feature = "pcd",
+    feature = "stl",
+    feature = "usd",
+    feature = "vrml",
+    feature = "xyz"
+))]
+use std::path::Path;
 
-#[cfg(any(feature = "obj", feature = "stl"))]
-use crate::utils::bytes::{bytecount_naive, memrchr_naive};
+#[cfg(any(
+    feature = "dxf",
+    feature = "obj",
+    feature = "off",
+    feature = "pcd",
+    feature = "stl",
+    feature = "usd",
+    feature = "vrml",
+    feature = "xyz"
+))]
+use crate::utils::{bytes::memrchr_naive, simd::bytecount};
 
-#[cfg(feature = "collada")]
+#[cfg(any(
+    feature = "3ds",
+    feature = "amf",
+    feature = "collada",
+    feature = "fbx",
+    feature = "pcd",
+    feature = "urdf",
+    feature = "usd",
+    feature = "vrml"
+))]
 macro_rules! format_err {
     ($msg:expr $(,)?) => {
         crate::error::invalid_data($msg)
@@ -15,7 +55,16 @@ macro_rules! format_err {
     };
 }
 
-#[cfg(feature = "collada")]
+#[cfg(any(
+    feature = "3ds",
+    feature = "amf",
+    feature = "collada",
+    feature = "fbx",
+    feature = "pcd",
+    feature = "urdf",
+    feature = "usd",
+    feature = "vrml"
+))]
 macro_rules! bail {
     ($($tt:tt)*) => {
         return Err(format_err!($($tt)*))
@@ -31,26 +80,64 @@ pub(crate) fn invalid_data(e: impl Into<Box<dyn std::error::Error + Send + Sync>
     io::Error::new(kind, e)
 }
 
-#[cfg(any(feature = "obj", feature = "stl"))]
+/// Appends the path of the file to the message of the given error, for formats
+/// whose errors do not have a line and column.
+#[cfg(any(feature = "amf", feature = "fbx"))]
+#[cold]
+pub(crate) fn with_path(e: io::Error, path: Option<&Path>) -> io::Error {
+    match path.filter(|&p| p != Path::new("")) {
+        Some(path) => io::Error::new(e.kind(), format!("{e} ({})", path.display())),
+        None => e,
+    }
+}
+
+#[cfg(any(
+    feature = "dxf",
+    feature = "obj",
+    feature = "off",
+    feature = "pcd",
+    feature = "stl",
+    feature = "usd",
+    feature = "vrml",
+    feature = "xyz"
+))]
 #[cold]
 pub(crate) fn with_location(e: &io::Error, location: &Location<'_>) -> io::Error {
     io::Error::new(e.kind(), format!("{e} ({location})"))
 }
 
-#[cfg(any(feature = "obj", feature = "stl"))]
+#[cfg(any(
+    feature = "dxf",
+    feature = "obj",
+    feature = "off",
+    feature = "pcd",
+    feature = "stl",
+    feature = "usd",
+    feature = "vrml",
+    feature = "xyz"
+))]
 pub(crate) struct Location<'a> {
     file: Option<&'a Path>,
     line: usize,
     column: usize,
 }
 
-#[cfg(any(feature = "obj", feature = "stl"))]
+#[cfg(any(
+    feature = "dxf",
+    feature = "obj",
+    feature = "off",
+    feature = "pcd",
+    feature = "stl",
+    feature = "usd",
+    feature = "vrml",
+    feature = "xyz"
+))]
 impl<'a> Location<'a> {
     #[cold]
     #[inline(never)]
     pub(crate) fn find(remaining: usize, start: &[u8], file: Option<&'a Path>) -> Self {
         let pos = start.len() - remaining;
-        let line = bytecount_naive(b'\n', &start[..pos]) + 1;
+        let line = bytecount(b'\n', &start[..pos]) + 1;
         let column = memrchr_naive(b'\n', &start[..pos]).unwrap_or(pos) + 1;
         Self {
             file: file.filter(|&p| p != Path::new("")),
@@ -58,9 +145,27 @@ impl<'a> Location<'a> {
             column,
         }
     }
+
+    /// Offsets the line by the number of lines before the input given to
+    /// `find`.
+    #[cfg(feature = "obj")]
+    #[cold]
+    pub(crate) fn offset_lines(mut self, lines: usize) -> Self {
+        self.line += lines;
+        self
+    }
 }
 
-#[cfg(any(feature = "obj", feature = "stl"))]
+#[cfg(any(
+    feature = "dxf",
+    feature = "obj",
+    feature = "off",
+    feature = "pcd",
+    feature = "stl",
+    feature = "usd",
+    feature = "vrml",
+    feature = "xyz"
+))]
 impl fmt::Display for Location<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(file) = self.file {
@@ -0,0 +1,587 @@
+//! [FBX] (.fbx) parser.
+//!
+//! This supports binary FBX 7.x. ASCII FBX and FBX 6.x or older are not
+//! supported.
+//!
+//! Geometries are placed according to the transforms of the models they
+//! are connected to, and coordinates are converted to meters according to
+//! `UnitScaleFactor`. The up axis is not converted. Textures, animations,
+//! and deformers (skinning, blend shapes) are not supported.
+//!
+//! [FBX]: https://en.wikipedia.org/wiki/FBX
+
+#![allow(clippy::many_single_char_names)]
+
+mod parse;
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    io,
+    path::Path,
+};
+
+use self::parse::{Node, Property};
+use crate::{
+    common::{self, DEFAULT_COLOR},
+    utils::matrix::{normalize, Matrix4x4},
+    Color4, ShadingModel, Vec2, Vec3,
+};
+
+/// Parses meshes from bytes of binary FBX.
+#[inline]
+pub fn from_slice(bytes: &[u8]) -> io::Result<common::Scene> {
+    from_slice_internal(bytes, None)
+}
+
+pub(crate) fn from_slice_internal(bytes: &[u8], path: Option<&Path>) -> io::Result<common::Scene> {
+    read_fbx(bytes).map_err(|e| crate::error::with_path(e, path))
+}
+
+fn read_fbx(bytes: &[u8]) -> io::Result<common::Scene> {
+    if !bytes.starts_with(parse::MAGIC) {
+        let n = bytes.iter().take_while(|b| b.is_ascii_whitespace()).count();
+        if bytes[n..].starts_with(b"; FBX") {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "ASCII FBX is not supported",
+            ));
+        }
+        bail!("expected binary FBX header");
+    }
+    let doc = parse::parse(bytes)?;
+    if doc.version < 7000 {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("FBX version {} is not supported", doc.version),
+        ));
+    }
+    let fbx = Fbx::parse(&doc.nodes)?;
+    fbx.build()
+}
+
+struct Fbx<'a> {
+    /// Scale factor to convert coordinates to meters.
+    unit: f32,
+    models: Vec<Model>,
+    geometries: HashMap<i64, Geometry<'a>>,
+    materials: HashMap<i64, common::Material>,
+}
+
+/// The `Model` object.
+struct Model {
+    name: String,
+    transform: Matrix4x4,
+    geometric_transform: Matrix4x4,
+    parent: Option<usize>,
+    geometry: Option<i64>,
+    materials: Vec<i64>,
+}
+
+/// The `Geometry` object of the `Mesh` class.
+struct Geometry<'a> {
+    vertices: Cow<'a, [f64]>,
+    polygon_vertex_index: Cow<'a, [i32]>,
+    normals: Option<LayerElement<'a>>,
+    uvs: Vec<LayerElement<'a>>,
+    colors: Option<LayerElement<'a>>,
+    materials: Option<(Mapping, Cow<'a, [i32]>)>,
+}
+
+/// `LayerElementNormal`, `LayerElementUV`, or `LayerElementColor`.
+struct LayerElement<'a> {
+    mapping: Mapping,
+    indices: Option<Cow<'a, [i32]>>,
+    values: Cow<'a, [f64]>,
+    stride: usize,
+}
+
+#[derive(Clone, Copy)]
+enum Mapping {
+    ByPolygonVertex,
+    ByVertex,
+    ByPolygon,
+    AllSame,
+}
+
+impl LayerElement<'_> {
+    fn get(&self, vertex: usize, polygon_vertex: usize, polygon: usize) -> Option<&[f64]> {
+        let i = self.mapping.index(vertex, polygon_vertex, polygon);
+        let i = match &self.indices {
+            Some(indices) => usize::try_from(*indices.get(i)?).ok()?,
+            None => i,
+        };
+        self.values
+            .get(i * self.stride..i * self.stride + self.stride)
+    }
+}
+
+impl Mapping {
+    fn parse(node: &Node<'_>) -> Option<Self> {
+        match string_child(node, "MappingInformationType")? {
+            b"ByPolygonVertex" => Some(Self::ByPolygonVertex),
+            b"ByVertice" | b"ByVertex" => Some(Self::ByVertex),
+            b"ByPolygon" => Some(Self::ByPolygon),
+            b"AllSame" => Some(Self::AllSame),
+            // ByEdge, NoMappingInformation, etc.
+            _ => None,
+        }
+    }
+
+    fn index(self, vertex: usize, polygon_vertex: usize, polygon: usize) -> usize {
+        match self {
+            Self::ByPolygonVertex => polygon_vertex,
+            Self::ByVertex => vertex,
+            Self::ByPolygon => polygon,
+            Self::AllSame => 0,
+        }
+    }
+}
+
+impl<'a> Fbx<'a> {
+    fn parse(nodes: &'a [Node<'a>]) -> io::Result<Self> {
+        let mut fbx = Self {
+            unit: 0.01,
+            models: vec![],
+            geometries: HashMap::new(),
+            materials: HashMap::new(),
+        };
+
+        if let Some(settings) = nodes.iter().find(|n| n.name == "GlobalSettings") {
+            // UnitScaleFactor is the size of a unit in centimeters.
+            if let Some(v) = properties70(settings)
+                .get(&b"UnitScaleFactor"[..])
+                .and_then(|v| v.first())
+                .and_then(Property::as_f64)
+            {
+                #[allow(clippy::cast_possible_truncation)]
+                let v = v as f32;
+                fbx.unit = v * 0.01;
+            }
+        }
+
+        let mut model_indices = HashMap::new();
+        if let Some(objects) = nodes.iter().find(|n| n.name == "Objects") {
+            for node in &objects.children {
+                let id = match node.properties.first().and_then(Property::as_i64) {
+                    Some(id) => id,
+                    None => continue,
+                };
+                match node.name {
+                    "Model" => {
+                        model_indices.insert(id, fbx.models.len());
+                        fbx.models.push(parse_model(node));
+                    }
+                    "Geometry"
+                        if node.properties.get(2).and_then(Property::as_str) == Some(b"Mesh") =>
+                    {
+                        fbx.geometries.insert(id, parse_geometry(node)?);
+                    }
+                    "Material" => {
+                        fbx.materials.insert(id, parse_material(node));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // C: "OO", <child>, <parent>
+        if let Some(connections) = nodes.iter().find(|n| n.name == "Connections") {
+            for c in connections.children_by_name("C") {
+                let (child, parent) = match (
+                    c.properties.get(1).and_then(Property::as_i64),
+                    c.properties.get(2).and_then(Property::as_i64),
+                ) {
+                    (Some(child), Some(parent)) => (child, parent),
+                    _ => continue,
+                };
+                let parent = match model_indices.get(&parent) {
+                    Some(&parent) => parent,
+                    None => continue,
+                };
+                if let Some(&child) = model_indices.get(&child) {
+                    fbx.models[child].parent = Some(parent);
+                } else if fbx.geometries.contains_key(&child) {
+                    fbx.models[parent].geometry.get_or_insert(child);
+                } else if fbx.materials.contains_key(&child) {
+                    fbx.models[parent].materials.push(child);
+                }
+            }
+        }
+
+        Ok(fbx)
+    }
+
+    fn build(&self) -> io::Result<common::Scene> {
+        let mut scene = common::Scene::default();
+        for model in &self.models {
+            let geometry = match model.geometry.and_then(|id| self.geometries.get(&id)) {
+                Some(geometry) => geometry,
+                None => continue,
+            };
+
+            let mut transform = model.transform;
+            let mut parent = model.parent;
+            // Guard against cyclic references.
+            let mut depth = 0;
+            while let Some(p) = parent {
+                if depth > self.models.len() {
+                    bail!("cyclic model hierarchy in model {:?}", model.name);
+                }
+                let mut t = self.models[p].transform;
+                t *= transform;
+                transform = t;
+                parent = self.models[p].parent;
+                depth += 1;
+            }
+            let mut t = Matrix4x4::scaling([self.unit; 3]);
+            t *= transform;
+            t *= model.geometric_transform;
+
+            for (slot, mut mesh) in build_meshes(geometry, t)? {
+                mesh.name.clone_from(&model.name);
+                scene.meshes.push(mesh);
+                let material = usize::try_from(slot)
+                    .ok()
+                    .and_then(|slot| model.materials.get(slot))
+                    .and_then(|id| self.materials.get(id));
+                scene.materials.push(material.cloned().unwrap_or_default());
+            }
+        }
+        Ok(scene)
+    }
+}
+
+/// Builds meshes from the geometry, split by material slot.
+fn build_meshes(
+    geometry: &Geometry<'_>,
+    transform: Matrix4x4,
+) -> io::Result<BTreeMap<i32, common::Mesh>> {
+    let mut meshes: BTreeMap<i32, common::Mesh> = BTreeMap::new();
+    let normal_transform = transform.normal_matrix();
+    let num_vertices = geometry.vertices.len() / 3;
+    let mut polygon = Vec::with_capacity(4);
+    let mut polygon_index = 0;
+    for (polygon_vertex, &i) in geometry.polygon_vertex_index.iter().enumerate() {
+        // A negative index marks the last vertex of a polygon (bitwise
+        // negated).
+        #[allow(clippy::cast_sign_loss)]
+        let vertex = if i < 0 { !i } else { i } as usize;
+        if vertex >= num_vertices {
+            bail!("vertex index out of bounds ({})", vertex);
+        }
+        polygon.push((vertex, polygon_vertex));
+        if i >= 0 {
+            continue;
+        }
+
+        let slot = match &geometry.materials {
+            Some((mapping, materials)) => materials
+                .get(mapping.index(0, polygon_vertex, polygon_index))
+                .copied()
+                .unwrap_or(0),
+            None => 0,
+        };
+        let mesh = meshes.entry(slot).or_default();
+        // Triangulate polygons in the same way as OBJ.
+        if polygon.len() >= 3 {
+            let a = polygon[0];
+            let mut b = polygon[1];
+            for &c in &polygon[2..] {
+                #[allow(clippy::cast_possible_truncation)]
+                let vertices_indices = [
+                    mesh.vertices.len() as u32,
+                    (mesh.vertices.len() + 1) as u32,
+                    (mesh.vertices.len() + 2) as u32,
+                ];
+                for (vertex, polygon_vertex) in [a, b, c] {
+                    push_vertex(
+                        mesh,
+                        geometry,
+                        transform,
+                        normal_transform,
+                        vertex,
+                        polygon_vertex,
+                        polygon_index,
+                    );
+                }
+                mesh.faces.push(vertices_indices);
+                b = c;
+            }
+        }
+        polygon.clear();
+        polygon_index += 1;
+    }
+    if !polygon.is_empty() {
+        bail!("last polygon in PolygonVertexIndex does not end with a negative index");
+    }
+    meshes.retain(|_, mesh| !mesh.faces.is_empty());
+    Ok(meshes)
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn push_vertex(
+    mesh: &mut common::Mesh,
+    geometry: &Geometry<'_>,
+    transform: Matrix4x4,
+    normal_transform: Matrix4x4,
+    vertex: usize,
+    polygon_vertex: usize,
+    polygon: usize,
+) {
+    let v = &geometry.vertices[vertex * 3..vertex * 3 + 3];
+    let mut v = [v[0] as f32, v[1] as f32, v[2] as f32];
+    v *= transform;
+    mesh.vertices.push(v);
+    if let Some(normals) = &geometry.normals {
+        let n = match normals.get(vertex, polygon_vertex, polygon) {
+            Some(n) => {
+                let mut n = [n[0] as f32, n[1] as f32, n[2] as f32];
+                n *= normal_transform;
+                normalize(n)
+            }
+            None => [0.; 3],
+        };
+        mesh.normals.push(n);
+    }
+    for (uvs, texcoords) in geometry.uvs.iter().zip(&mut mesh.texcoords) {
+        let uv: Vec2 = match uvs.get(vertex, polygon_vertex, polygon) {
+            Some(uv) => [uv[0] as f32, uv[1] as f32],
+            None => [0.; 2],
+        };
+        texcoords.push(uv);
+    }
+    if let Some(colors) = &geometry.colors {
+        let color = match colors.get(vertex, polygon_vertex, polygon) {
+            Some(c) => [c[0] as f32, c[1] as f32, c[2] as f32, c[3] as f32],
+            None => DEFAULT_COLOR,
+        };
+        mesh.colors[0].push(color);
+    }
+}
+
+fn parse_model(node: &Node<'_>) -> Model {
+    let props = properties70(node);
+    let vec3 = |name: &[u8], default: f32| -> Vec3 {
+        let mut v = [default; 3];
+        if let Some(values) = props.get(name) {
+            for (v, p) in v.iter_mut().zip(values.iter()) {
+                if let Some(p) = p.as_f64() {
+                    #[allow(clippy::cast_possible_truncation)]
+                    let p = p as f32;
+                    *v = p;
+                }
+            }
+        }
+        v
+    };
+    let rotation_order = props
+        .get(&b"RotationOrder"[..])
+        .and_then(|v| v.first())
+        .and_then(Property::as_i64)
+        .unwrap_or(0);
+
+    // Refs: https://help.autodesk.com/view/FBX/2020/ENU/?guid=FBX_Developer_Help_nodes_and_scene_graph_fbx_nodes_computing_transformation_matrix_html
+    // WorldTransform = ParentWorldTransform * T * Roff * Rp * Rpre * R * Rpost^-1 * Rp^-1 * Soff * Sp * S * Sp^-1
+    let rotation_pivot = vec3(b"RotationPivot", 0.);
+    let scaling_pivot = vec3(b"ScalingPivot", 0.);
+    let mut transform = Matrix4x4::translation(vec3(b"Lcl Translation", 0.));
+    transform *= Matrix4x4::translation(vec3(b"RotationOffset", 0.));
+    transform *= Matrix4x4::translation(rotation_pivot);
+    transform *= euler(vec3(b"PreRotation", 0.), 0);
+    transform *= euler(vec3(b"Lcl Rotation", 0.), rotation_order);
+    transform *= euler_inverse(vec3(b"PostRotation", 0.));
+    transform *= Matrix4x4::translation(neg(rotation_pivot));
+    transform *= Matrix4x4::translation(vec3(b"ScalingOffset", 0.));
+    transform *= Matrix4x4::translation(scaling_pivot);
+    transform *= Matrix4x4::scaling(vec3(b"Lcl Scaling", 1.));
+    transform *= Matrix4x4::translation(neg(scaling_pivot));
+
+    // The geometric transform is applied only to the geometry of the model,
+    // and is not inherited by children.
+    let mut geometric_transform = Matrix4x4::translation(vec3(b"GeometricTranslation", 0.));
+    geometric_transform *= euler(vec3(b"GeometricRotation", 0.), 0);
+    geometric_transform *= Matrix4x4::scaling(vec3(b"GeometricScaling", 1.));
+
+    Model {
+        name: object_name(node),
+        transform,
+        geometric_transform,
+        parent: None,
+        geometry: None,
+        materials: vec![],
+    }
+}
+
+fn parse_geometry<'a>(node: &'a Node<'a>) -> io::Result<Geometry<'a>> {
+    let vertices = node
+        .child("Vertices")
+        .and_then(|n| n.properties.first())
+        .and_then(Property::as_f64_array)
+        .unwrap_or_default();
+    let polygon_vertex_index = node
+        .child("PolygonVertexIndex")
+        .and_then(|n| n.properties.first())
+        .and_then(Property::as_i32_array)
+        .unwrap_or_default();
+    if vertices.len() % 3 != 0 {
+        bail!(
+            "invalid number of values in vertices of geometry {:?}",
+            object_name(node)
+        );
+    }
+
+    let materials = node.child("LayerElementMaterial").and_then(|n| {
+        let mapping = Mapping::parse(n)?;
+        let materials = n.child("Materials")?.properties.first()?.as_i32_array()?;
+        Some((mapping, materials))
+    });
+
+    Ok(Geometry {
+        vertices,
+        polygon_vertex_index,
+        normals: layer_elements(node, "LayerElementNormal", "Normals", "NormalsIndex", 3).next(),
+        uvs: layer_elements(node, "LayerElementUV", "UV", "UVIndex", 2)
+            .take(common::MAX_NUMBER_OF_TEXCOORDS)
+            .collect(),
+        colors: layer_elements(node, "LayerElementColor", "Colors", "ColorIndex", 4).next(),
+        materials,
+    })
+}
+
+fn parse_material(node: &Node<'_>) -> common::Material {
+    let props = properties70(node);
+    let float = |name: &[u8]| -> Option<f32> {
+        #[allow(clippy::cast_possible_truncation)]
+        props
+            .get(name)
+            .and_then(|v| v.first())
+            .and_then(Property::as_f64)
+            .map(|v| v as f32)
+    };
+    let color = |name: &[u8]| -> Option<Color4> {
+        let values = props.get(name)?;
+        let mut color = [0., 0., 0., 1.];
+        for (c, v) in color.iter_mut().zip(values.iter()).take(3) {
+            #[allow(clippy::cast_possible_truncation)]
+            let v = v.as_f64()? as f32;
+            *c = v;
+        }
+        Some(color)
+    };
+
+    let mut material = common::Material {
+        name: object_name(node),
+        ..Default::default()
+    };
+    material.shading_model = match string_child(node, "ShadingModel") {
+        Some(b"phong" | b"Phong") => Some(ShadingModel::Phong),
+        Some(b"lambert" | b"Lambert") => Some(ShadingModel::Gouraud),
+        _ => None,
+    };
+    material.color.diffuse = color(b"DiffuseColor").or_else(|| color(b"Diffuse"));
+    material.color.ambient = color(b"AmbientColor").or_else(|| color(b"Ambient"));
+    material.color.specular = color(b"SpecularColor").or_else(|| color(b"Specular"));
+    material.color.emissive = color(b"EmissiveColor").or_else(|| color(b"Emissive"));
+    material.color.transparent = color(b"TransparentColor");
+    material.color.reflective = color(b"ReflectionColor");
+    material.opacity = float(b"Opacity").or_else(|| float(b"TransparencyFactor").map(|v| 1. - v));
+    material.shininess = float(b"ShininessExponent").or_else(|| float(b"Shininess"));
+    material.reflectivity = float(b"ReflectionFactor");
+    material
+}
+
+fn layer_elements<'a>(
+    node: &'a Node<'a>,
+    name: &'static str,
+    values: &'static str,
+    indices: &'static str,
+    stride: usize,
+) -> impl Iterator<Item = LayerElement<'a>> {
+    node.children_by_name(name).filter_map(move |n| {
+        let mapping = Mapping::parse(n)?;
+        let values = n.child(values)?.properties.first()?.as_f64_array()?;
+        let indices = match string_child(n, "ReferenceInformationType") {
+            Some(b"IndexToDirect" | b"Index") => {
+                Some(n.child(indices)?.properties.first()?.as_i32_array()?)
+            }
+            _ => None,
+        };
+        Some(LayerElement {
+            mapping,
+            indices,
+            values,
+            stride,
+        })
+    })
+}
+
+/// Collects `P` records in the `Properties70` child node.
+///
+/// `P: <name>, <type>, <label>, <flags>, <values>...`
+fn properties70<'a, 'b>(node: &'b Node<'a>) -> HashMap<&'a [u8], &'b [Property<'a>]> {
+    let mut map = HashMap::new();
+    if let Some(props) = node.child("Properties70") {
+        for p in props.children_by_name("P") {
+            if let (Some(name), Some(values)) = (
+                p.properties.first().and_then(Property::as_str),
+                p.properties.get(4..),
+            ) {
+                map.insert(name, values);
+            }
+        }
+    }
+    map
+}
+
+fn string_child<'a>(node: &Node<'a>, name: &str) -> Option<&'a [u8]> {
+    node.child(name)?.properties.first()?.as_str()
+}
+
+/// Returns the name of an object.
+///
+/// In binary FBX, names are in the form of `<name>\x00\x01<class>`.
+fn object_name(node: &Node<'_>) -> String {
+    let name = node
+        .properties
+        .get(1)
+        .and_then(Property::as_str)
+        .unwrap_or_default();
+    let name = match name.windows(2).position(|w| w == b"\x00\x01") {
+        Some(pos) => &name[..pos],
+        None => name,
+    };
+    String::from_utf8_lossy(name).into_owned()
+}
+
+/// Returns a rotation matrix from Euler angles in degrees.
+fn euler(r: Vec3, order: i64) -> Matrix4x4 {
+    let x = Matrix4x4::rotation(r[0].to_radians(), [1., 0., 0.]);
+    let y = Matrix4x4::rotation(r[1].to_radians(), [0., 1., 0.]);
+    let z = Matrix4x4::rotation(r[2].to_radians(), [0., 0., 1.]);
+    // The first axis in the name of the order is applied first.
+    let (first, second, third) = match order {
+        1 => (x, z, y), // eEulerXZY
+        2 => (y, z, x), // eEulerYZX
+        3 => (y, x, z), // eEulerYXZ
+        4 => (z, x, y), // eEulerZXY
+        5 => (z, y, x), // eEulerZYX
+        _ => (x, y, z), // eEulerXYZ, eSphericXYZ
+    };
+    let mut m = third;
+    m *= second;
+    m *= first;
+    m
+}
+
+/// Returns the inverse of `euler(r, 0)`.
+fn euler_inverse(r: Vec3) -> Matrix4x4 {
+    let mut m = Matrix4x4::rotation(-r[0].to_radians(), [1., 0., 0.]);
+    m *= Matrix4x4::rotation(-r[1].to_radians(), [0., 1., 0.]);
+    m *= Matrix4x4::rotation(-r[2].to_radians(), [0., 0., 1.]);
+    m
+}
+
+fn neg([x, y, z]: Vec3) -> Vec3 {
+    [-x, -y, -z]
+}
@@ -0,0 +1,269 @@
+// Parser for the node-record tree of binary FBX.
+//
+// Refs:
+// - https://code.blender.org/2013/08/fbx-binary-file-format-specification/
+// - https://github.com/assimp/assimp/blob/v5.3.1/code/AssetLib/FBX/FBXBinaryTokenizer.cpp
+
+use std::{borrow::Cow, io, str};
+
+pub(super) const MAGIC: &[u8] = b"Kaydara FBX Binary  \0";
+const HEADER_SIZE: usize = 27;
+// Deeply nested files are almost certainly broken, so limit the depth to
+// avoid stack overflow.
+const MAX_DEPTH: usize = 128;
+
+pub(super) struct Document<'a> {
+    pub(super) version: u32,
+    pub(super) nodes: Vec<Node<'a>>,
+}
+
+pub(super) struct Node<'a> {
+    pub(super) name: &'a str,
+    pub(super) properties: Vec<Property<'a>>,
+    pub(super) children: Vec<Node<'a>>,
+}
+
+impl<'a> Node<'a> {
+    pub(super) fn child(&self, name: &str) -> Option<&Node<'a>> {
+        self.children.iter().find(|n| n.name == name)
+    }
+
+    pub(super) fn children_by_name<'b>(
+        &'b self,
+        name: &'b str,
+    ) -> impl Iterator<Item = &'b Node<'a>> + 'b {
+        self.children.iter().filter(move |n| n.name == name)
+    }
+}
+
+#[allow(dead_code)] // Values of some types are not used yet.
+pub(super) enum Property<'a> {
+    Bool(bool),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    BoolArray(Vec<bool>),
+    I32Array(Vec<i32>),
+    I64Array(Vec<i64>),
+    F32Array(Vec<f32>),
+    F64Array(Vec<f64>),
+    String(&'a [u8]),
+    Raw(&'a [u8]),
+}
+
+impl<'a> Property<'a> {
+    pub(super) fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Bool(v) => Some(f64::from(u8::from(v))),
+            Self::I16(v) => Some(f64::from(v)),
+            Self::I32(v) => Some(f64::from(v)),
+            #[allow(clippy::cast_precision_loss)]
+            Self::I64(v) => Some(v as f64),
+            Self::F32(v) => Some(f64::from(v)),
+            Self::F64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::Bool(v) => Some(i64::from(v)),
+            Self::I16(v) => Some(i64::from(v)),
+            Self::I32(v) => Some(i64::from(v)),
+            Self::I64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_str(&self) -> Option<&'a [u8]> {
+        match *self {
+            Self::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_f64_array(&self) -> Option<Cow<'_, [f64]>> {
+        match self {
+            Self::F64Array(v) => Some(Cow::Borrowed(v)),
+            Self::F32Array(v) => Some(v.iter().copied().map(f64::from).collect()),
+            _ => None,
+        }
+    }
+
+    pub(super) fn as_i32_array(&self) -> Option<Cow<'_, [i32]>> {
+        match self {
+            Self::I32Array(v) => Some(Cow::Borrowed(v)),
+            #[allow(clippy::cast_possible_truncation)]
+            Self::I64Array(v) => Some(v.iter().map(|&v| v as i32).collect()),
+            _ => None,
+        }
+    }
+}
+
+pub(super) fn parse(bytes: &[u8]) -> io::Result<Document<'_>> {
+    if bytes.len() < HEADER_SIZE || !bytes.starts_with(MAGIC) {
+        bail!("expected binary FBX header");
+    }
+    let version = u32::from_le_bytes(bytes[23..27].try_into().unwrap());
+    let mut reader = Reader {
+        bytes,
+        pos: HEADER_SIZE,
+        // FBX 7.5 and later use 64-bit offsets.
+        wide: version >= 7500,
+    };
+    let mut nodes = vec![];
+    // The top-level node list is terminated by a null record, followed by
+    // a footer that we don't need.
+    while reader.pos < bytes.len() {
+        match reader.read_node(0)? {
+            Some(node) => nodes.push(node),
+            None => break,
+        }
+    }
+    Ok(Document { version, nodes })
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    wide: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn read_node(&mut self, depth: usize) -> io::Result<Option<Node<'a>>> {
+        if depth > MAX_DEPTH {
+            bail!("too deeply nested node at offset {}", self.pos);
+        }
+        let start = self.pos;
+        let end_offset = self.read_offset()?;
+        let num_properties = self.read_offset()?;
+        let _property_list_len = self.read_offset()?;
+        let name_len = self.read_u8()?;
+        if end_offset == 0 {
+            // null record
+            return Ok(None);
+        }
+        if end_offset < self.pos || end_offset > self.bytes.len() {
+            bail!("invalid end offset of node at offset {}", start);
+        }
+        let name = self.read_bytes(name_len as usize)?;
+        let name = str::from_utf8(name)
+            .map_err(|_| format_err!("invalid node name at offset {}", start))?;
+
+        // Do not trust the count for the initial capacity, as a broken file
+        // may contain a huge count.
+        let mut properties = Vec::with_capacity(num_properties.min(16));
+        for _ in 0..num_properties {
+            properties.push(self.read_property()?);
+        }
+
+        let mut children = vec![];
+        while self.pos < end_offset {
+            match self.read_node(depth + 1)? {
+                Some(node) => children.push(node),
+                None => break,
+            }
+        }
+        if self.pos != end_offset {
+            bail!("unexpected end of node {:?} at offset {}", name, self.pos);
+        }
+
+        Ok(Some(Node {
+            name,
+            properties,
+            children,
+        }))
+    }
+
+    fn read_property(&mut self) -> io::Result<Property<'a>> {
+        let start = self.pos;
+        Ok(match self.read_u8()? {
+            b'C' => Property::Bool(self.read_u8()? != 0),
+            b'Y' => Property::I16(i16::from_le_bytes(self.read_array()?)),
+            b'I' => Property::I32(i32::from_le_bytes(self.read_array()?)),
+            b'L' => Property::I64(i64::from_le_bytes(self.read_array()?)),
+            b'F' => Property::F32(f32::from_le_bytes(self.read_array()?)),
+            b'D' => Property::F64(f64::from_le_bytes(self.read_array()?)),
+            b'b' => Property::BoolArray(self.read_typed_array(|b: [u8; 1]| b[0] != 0)?),
+            b'i' => Property::I32Array(self.read_typed_array(i32::from_le_bytes)?),
+            b'l' => Property::I64Array(self.read_typed_array(i64::from_le_bytes)?),
+            b'f' => Property::F32Array(self.read_typed_array(f32::from_le_bytes)?),
+            b'd' => Property::F64Array(self.read_typed_array(f64::from_le_bytes)?),
+            b'S' => {
+                let len = self.read_u32()? as usize;
+                Property::String(self.read_bytes(len)?)
+            }
+            b'R' => {
+                let len = self.read_u32()? as usize;
+                Property::Raw(self.read_bytes(len)?)
+            }
+            ty => bail!(
+                "unknown property type {:?} at offset {}",
+                char::from(ty),
+                start
+            ),
+        })
+    }
+
+    fn read_typed_array<T, F, const N: usize>(&mut self, f: F) -> io::Result<Vec<T>>
+    where
+        F: Fn([u8; N]) -> T,
+    {
+        let start = self.pos;
+        let len = self.read_u32()? as usize;
+        let encoding = self.read_u32()?;
+        let compressed_len = self.read_u32()? as usize;
+        let data = self.read_bytes(compressed_len)?;
+        let byte_len = len
+            .checked_mul(N)
+            .ok_or_else(|| format_err!("too large array at offset {}", start))?;
+        let data = match encoding {
+            0 => Cow::Borrowed(data),
+            1 => Cow::Owned(
+                miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(data, byte_len)
+                    .map_err(|e| format_err!("{} at offset {}", e, start))?,
+            ),
+            _ => bail!("unknown array encoding {} at offset {}", encoding, start),
+        };
+        if data.len() != byte_len {
+            bail!("invalid array length at offset {}", start);
+        }
+        Ok(data
+            .chunks_exact(N)
+            .map(|b| f(b.try_into().unwrap()))
+            .collect())
+    }
+
+    fn read_bytes(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        match self.bytes.get(self.pos..self.pos.saturating_add(len)) {
+            Some(bytes) => {
+                self.pos += len;
+                Ok(bytes)
+            }
+            None => bail!("unexpected end of file"),
+        }
+    }
+
+    fn read_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        Ok(self.read_bytes(N)?.try_into().unwrap())
+    }
+
+    fn read_u8(&mut self) -> io::Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_offset(&mut self) -> io::Result<usize> {
+        if self.wide {
+            let v = u64::from_le_bytes(self.read_array()?);
+            usize::try_from(v).map_err(|_| format_err!("too large offset at {}", self.pos))
+        } else {
+            Ok(self.read_u32()? as usize)
+        }
+    }
+}
@@ -0,0 +1,357 @@
+use std::{cmp, ffi::OsStr, path::Path, str};
+
+use crate::utils::bytes::starts_with;
+
+/// A file format supported by [`Loader`](crate::Loader).
+///
+/// Formats are detected by [`detect_format`] regardless of whether the
+/// feature to parse them is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Format {
+    /// [STL](https://en.wikipedia.org/wiki/STL_(file_format)) (.stl), ASCII or binary.
+    Stl,
+    /// [COLLADA](https://en.wikipedia.org/wiki/COLLADA) (.dae)
+    Collada,
+    /// [Wavefront OBJ](https://en.wikipedia.org/wiki/Wavefront_.obj_file) (.obj)
+    Obj,
+    /// [OFF](https://en.wikipedia.org/wiki/OFF_(file_format)) (.off)
+    Off,
+    /// [AMF](https://en.wikipedia.org/wiki/Additive_manufacturing_file_format) (.amf)
+    Amf,
+    /// [FBX](https://en.wikipedia.org/wiki/FBX) (.fbx)
+    Fbx,
+    /// [3DS](https://en.wikipedia.org/wiki/.3ds) (.3ds)
+    ThreeDs,
+    /// [VRML97](https://en.wikipedia.org/wiki/VRML) (.wrl) and
+    /// [X3D](https://en.wikipedia.org/wiki/X3D) (.x3d, .x3dv)
+    Vrml,
+    /// [USD](https://openusd.org) (.usda, .usd)
+    ///
+    /// Binary USD (usdc) is detected, but loading it returns an error of
+    /// [`io::ErrorKind::Unsupported`](std::io::ErrorKind::Unsupported).
+    Usd,
+    /// [DXF](https://en.wikipedia.org/wiki/AutoCAD_DXF) (.dxf)
+    ///
+    /// Binary DXF is detected, but loading it returns an error of
+    /// [`io::ErrorKind::Unsupported`](std::io::ErrorKind::Unsupported).
+    Dxf,
+    /// [PCD](https://pointclouds.org/documentation/tutorials/pcd_file_format.html) (.pcd)
+    Pcd,
+    /// XYZ (.xyz) and PTS (.pts) point clouds
+    Xyz,
+    /// [URDF](https://wiki.ros.org/urdf/XML) (.urdf)
+    Urdf,
+}
+
+impl Format {
+    pub(crate) const ALL: [Self; 13] = [
+        Self::Stl,
+        Self::Collada,
+        Self::Obj,
+        Self::Off,
+        Self::Amf,
+        Self::Fbx,
+        Self::ThreeDs,
+        Self::Vrml,
+        Self::Usd,
+        Self::Dxf,
+        Self::Pcd,
+        Self::Xyz,
+        Self::Urdf,
+    ];
+
+    /// Returns the file extensions of this format in lowercase.
+    #[must_use]
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::Stl => &["stl"],
+            Self::Collada => &["dae"],
+            Self::Obj => &["obj"],
+            Self::Off => &["off"],
+            Self::Amf => &["amf"],
+            Self::Fbx => &["fbx"],
+            Self::ThreeDs => &["3ds"],
+            Self::Vrml => &["wrl", "x3d", "x3dv"],
+            Self::Usd => &["usda", "usd"],
+            Self::Dxf => &["dxf"],
+            Self::Pcd => &["pcd"],
+            Self::Xyz => &["xyz", "pts"],
+            Self::Urdf => &["urdf"],
+        }
+    }
+
+    /// Returns the format that has the given file extension (case-insensitive).
+    #[must_use]
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| {
+            f.extensions()
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(extension))
+        })
+    }
+
+    /// Returns the name of this format (e.g., `"STL"`).
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Stl => "STL",
+            Self::Collada => "COLLADA",
+            Self::Obj => "OBJ",
+            Self::Off => "OFF",
+            Self::Amf => "AMF",
+            Self::Fbx => "FBX",
+            Self::ThreeDs => "3DS",
+            Self::Vrml => "VRML/X3D",
+            Self::Usd => "USD",
+            Self::Dxf => "DXF",
+            Self::Pcd => "PCD",
+            Self::Xyz => "XYZ/PTS",
+            Self::Urdf => "URDF",
+        }
+    }
+}
+
+/// The number of bytes inspected to detect text formats.
+const TEXT_HEAD_LEN: usize = 4096;
+
+/// Detects the format of the given file.
+///
+/// If `path` has a known extension, the format is determined by the
+/// extension. Otherwise, the format is detected from the contents in the
+/// same way as assimp does: magic numbers and the size of binary formats are
+/// checked first, then the root element of XML, the header of text formats,
+/// and finally line-based formats (ASCII STL, OBJ, and XYZ) are scored by
+/// the keywords at the start of the lines.
+///
+/// Returns `None` if the format could not be detected.
+///
+/// # Example
+///
+/// ```
+/// use mesh_loader::{detect_format, Format};
+///
+/// let obj = b"# comment\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+/// assert_eq!(detect_format(obj, None), Some(Format::Obj));
+/// assert_eq!(detect_format(b"", Some("a.STL".as_ref())), Some(Format::Stl));
+/// ```
+#[must_use]
+pub fn detect_format(bytes: &[u8], path: Option<&Path>) -> Option<Format> {
+    if let Some(format) = path
+        .and_then(Path::extension)
+        .and_then(OsStr::to_str)
+        .and_then(Format::from_extension)
+    {
+        return Some(format);
+    }
+    detect_binary(bytes).or_else(|| detect_text(bytes))
+}
+
+fn detect_binary(bytes: &[u8]) -> Option<Format> {
+    if starts_with(bytes, b"Kaydara FBX Binary") {
+        return Some(Format::Fbx);
+    }
+    if starts_with(bytes, b"PXR-USDC") {
+        return Some(Format::Usd);
+    }
+    if starts_with(bytes, b"AutoCAD Binary DXF") {
+        return Some(Format::Dxf);
+    }
+    // 3DS files start with the main chunk, whose length is the file size.
+    if bytes.len() >= 6
+        && starts_with(bytes, b"MM")
+        && u32::from_le_bytes(bytes[2..6].try_into().unwrap()) as usize == bytes.len()
+    {
+        return Some(Format::ThreeDs);
+    }
+    // Binary STL files have an 80-byte header, the number of triangles, and
+    // 50 bytes per triangle. This is checked before ASCII STL because the
+    // header of some binary STL files starts with "solid".
+    if bytes.len() >= 84 {
+        let num_triangles = u32::from_le_bytes(bytes[80..84].try_into().unwrap());
+        if 84 + num_triangles as u64 * 50 == bytes.len() as u64 {
+            return Some(Format::Stl);
+        }
+    }
+    None
+}
+
+fn detect_text(bytes: &[u8]) -> Option<Format> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let truncated = bytes.len() > TEXT_HEAD_LEN;
+    let s = &bytes[..cmp::min(bytes.len(), TEXT_HEAD_LEN)];
+
+    // Headers
+    if starts_with(s, b"#VRML") || starts_with(s, b"#X3D") {
+        return Some(Format::Vrml);
+    }
+    if starts_with(s, b"#usda ") {
+        return Some(Format::Usd);
+    }
+    if starts_with(s, b"# .PCD") || starts_with(s, b"VERSION ") {
+        return Some(Format::Pcd);
+    }
+    if trim_start(s).first() == Some(&b'<') {
+        return match xml_root_element(s)? {
+            b"COLLADA" => Some(Format::Collada),
+            b"amf" => Some(Format::Amf),
+            b"X3D" => Some(Format::Vrml),
+            b"robot" => Some(Format::Urdf),
+            _ => None,
+        };
+    }
+    if is_dxf(s) {
+        return Some(Format::Dxf);
+    }
+    if is_off(s) {
+        return Some(Format::Off);
+    }
+
+    // Line-based formats
+    let mut lines: Vec<_> = s.split(|&b| b == b'\n').map(trim).collect();
+    if truncated {
+        // The last line may be incomplete.
+        lines.pop();
+    }
+    score_lines(&lines)
+}
+
+/// Scores ASCII STL, OBJ, and XYZ by the keywords at the start of the lines,
+/// and returns the one with the highest positive score.
+fn score_lines(lines: &[&[u8]]) -> Option<Format> {
+    let mut stl = 0_i32;
+    let mut obj = 0_i32;
+    let mut xyz = 0_i32;
+    let mut first = true;
+    for &line in lines {
+        // Blank lines and comments (OBJ, XYZ)
+        if line.is_empty() || line[0] == b'#' || starts_with(line, b"//") {
+            continue;
+        }
+        let mut tokens = line
+            .split(u8::is_ascii_whitespace)
+            .filter(|t| !t.is_empty());
+        let keyword = tokens.next().unwrap_or_default();
+        match keyword {
+            b"solid" if first => stl += 1,
+            b"facet" | b"outer" | b"vertex" | b"endloop" | b"endfacet" | b"endsolid" if stl > 0 => {
+                stl += 1;
+            }
+            _ => stl -= 1,
+        }
+        match keyword {
+            b"v" | b"vn" | b"vt" | b"vp" | b"f" | b"l" | b"p" | b"o" | b"g" | b"s" | b"mtllib"
+            | b"usemtl" => obj += 1,
+            _ => obj -= 1,
+        }
+        // x y z [intensity] [r g b] ..., or the number of points at the
+        // start of PTS.
+        let num_numbers = if is_number(keyword) {
+            1 + tokens.clone().filter(|t| is_number(t)).count()
+        } else {
+            0
+        };
+        let num_tokens = 1 + tokens.count();
+        if num_numbers == num_tokens && (num_tokens >= 3 || first && num_tokens == 1) {
+            xyz += 1;
+        } else {
+            xyz -= 1;
+        }
+        first = false;
+    }
+    [(Format::Stl, stl), (Format::Obj, obj), (Format::Xyz, xyz)]
+        .into_iter()
+        .filter(|&(_, score)| score > 0)
+        .max_by_key(|&(_, score)| score)
+        .map(|(format, _)| format)
+}
+
+/// Returns the name of the root element (without the namespace prefix).
+fn xml_root_element(mut s: &[u8]) -> Option<&[u8]> {
+    loop {
+        s = trim_start(s);
+        if starts_with(s, b"<?") {
+            s = skip_past(s, b"?>")?;
+        } else if starts_with(s, b"<!--") {
+            s = skip_past(s, b"-->")?;
+        } else if starts_with(s, b"<!") {
+            // DOCTYPE
+            s = skip_past(s, b">")?;
+        } else if starts_with(s, b"<") {
+            let s = &s[1..];
+            let end = s
+                .iter()
+                .position(|&b| b.is_ascii_whitespace() || b == b'>' || b == b'/')?;
+            let name = &s[..end];
+            return Some(match name.iter().position(|&b| b == b':') {
+                Some(i) => &name[i + 1..],
+                None => name,
+            });
+        } else {
+            return None;
+        }
+    }
+}
+
+/// DXF files start with a section (group code 0 followed by SECTION),
+/// optionally preceded by comments (group code 999).
+fn is_dxf(s: &[u8]) -> bool {
+    let mut lines = s.split(|&b| b == b'\n').map(trim);
+    while let (Some(code), Some(value)) = (lines.next(), lines.next()) {
+        match code {
+            b"999" => {}
+            b"0" => return value == b"SECTION",
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// OFF files start with the header keyword ([ST][C][N][4][n]OFF),
+/// optionally preceded by comments.
+fn is_off(s: &[u8]) -> bool {
+    let line = s
+        .split(|&b| b == b'\n')
+        .map(trim)
+        .find(|l| !l.is_empty() && l[0] != b'#')
+        .unwrap_or_default();
+    let mut keyword = line;
+    for prefix in [&b"ST"[..], b"C", b"N", b"4", b"n"] {
+        keyword = keyword.strip_prefix(prefix).unwrap_or(keyword);
+    }
+    match keyword.strip_prefix(b"OFF") {
+        // Some files (e.g., ModelNet) have no line break between OFF and the counts.
+        Some(rest) => matches!(rest.first(), None | Some(b' ' | b'\t' | b'#' | b'0'..=b'9')),
+        None => false,
+    }
+}
+
+fn is_number(token: &[u8]) -> bool {
+    str::from_utf8(token).map_or(false, |t| t.parse::<f64>().is_ok())
+}
+
+fn skip_past<'a>(s: &'a [u8], needle: &[u8]) -> Option<&'a [u8]> {
+    let pos = s.windows(needle.len()).position(|w| w == needle)?;
+    Some(&s[pos + needle.len()..])
+}
+
+fn trim_start(mut s: &[u8]) -> &[u8] {
+    while let Some((first, rest)) = s.split_first() {
+        if !first.is_ascii_whitespace() {
+            break;
+        }
+        s = rest;
+    }
+    s
+}
+
+fn trim(s: &[u8]) -> &[u8] {
+    let mut s = trim_start(s);
+    while let Some((last, rest)) = s.split_last() {
+        if !last.is_ascii_whitespace() {
+            break;
+        }
+        s = rest;
+    }
+    s
+}
@@ -0,0 +1,302 @@
+//! Importers of file formats used by [`Loader`](crate::Loader).
+//!
+//! [`Loader`](crate::Loader) has a registry of [`Importer`]s, which includes
+//! the importers of the built-in formats. User-defined formats can be added by
+//! [`Loader::importer`](crate::Loader::importer), and get the same format
+//! detection and post-processing (texture resolution,
+//! [`merge_meshes`](crate::Loader::merge_meshes), etc.) as the built-in ones.
+
+#[cfg(feature = "urdf")]
+use std::collections::HashMap;
+#[cfg(feature = "obj")]
+use std::sync::Arc;
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    detect_format,
+    resolver::{self, Resource, ResourceKind},
+    Format, Scene, Summary,
+};
+
+/// An importer of a file format.
+///
+/// # Example
+///
+/// An importer of a format that has a single triangle per line:
+///
+/// ```
+/// use std::{io, path::Path};
+///
+/// use mesh_loader::{
+///     importer::{Context, Importer},
+///     Loader, Mesh, Scene,
+/// };
+///
+/// struct TriImporter;
+///
+/// impl Importer for TriImporter {
+///     fn extensions(&self) -> &[&str] {
+///         &["tri"]
+///     }
+///     fn sniff(&self, bytes: &[u8]) -> bool {
+///         bytes.starts_with(b"TRI\n")
+///     }
+///     fn load(&self, bytes: &[u8], _path: &Path, _cx: &mut Context<'_>) -> io::Result<Scene> {
+///         let s = std::str::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+///         let mut mesh = Mesh::default();
+///         for line in s.lines().skip(1) {
+///             let v: Vec<f32> = line.split_whitespace().filter_map(|v| v.parse().ok()).collect();
+///             if let [a0, a1, a2, b0, b1, b2, c0, c1, c2] = v[..] {
+///                 let i = mesh.vertices.len() as u32;
+///                 mesh.vertices.extend([[a0, a1, a2], [b0, b1, b2], [c0, c1, c2]]);
+///                 mesh.faces.push([i, i + 1, i + 2]);
+///             }
+///         }
+///         let mut scene = Scene::default();
+///         scene.meshes.push(mesh);
+///         scene.materials.push(Default::default());
+///         Ok(scene)
+///     }
+/// }
+///
+/// let loader = Loader::default().importer(TriImporter);
+/// let scene = loader.load_from_slice(b"TRI\n0 0 0 1 0 0 0 1 0\n", "model")?;
+/// assert_eq!(scene.meshes[0].faces.len(), 1);
+/// # Ok::<(), io::Error>(())
+/// ```
+pub trait Importer: Send + Sync {
+    /// Returns the file extensions of the format (case-insensitive, without
+    /// the leading dot).
+    fn extensions(&self) -> &[&str];
+
+    /// Returns `true` if the given bytes are in the format.
+    ///
+    /// This is called when the format could not be determined by the file
+    /// extension. The default implementation returns `false`.
+    fn sniff(&self, bytes: &[u8]) -> bool {
+        let _ = bytes;
+        false
+    }
+
+    /// Loads a scene from the given bytes of the file at `path`.
+    ///
+    /// Files referenced by the file can be read through `cx`.
+    fn load(&self, bytes: &[u8], path: &Path, cx: &mut Context<'_>) -> io::Result<Scene>;
+
+    /// Loads a scene from the given reader of the file at `path`.
+    ///
+    /// The default implementation reads the whole file into memory and calls
+    /// [`load`](Self::load). Importers that can parse the file incrementally
+    /// can override this to reduce memory usage.
+    fn load_from_reader(
+        &self,
+        reader: &mut dyn io::Read,
+        path: &Path,
+        cx: &mut Context<'_>,
+    ) -> io::Result<Scene> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+        self.load(&bytes, path, cx)
+    }
+
+    /// Returns a summary of the given bytes of the file at `path`, used by
+    /// [`Loader::probe`](crate::Loader::probe).
+    ///
+    /// The default implementation loads the file by [`load`](Self::load) and
+    /// summarizes the loaded scene. Importers that can get the summary
+    /// without fully parsing the file can override this.
+    fn probe(&self, bytes: &[u8], path: &Path, cx: &mut Context<'_>) -> io::Result<Summary> {
+        Ok(Summary::from_scene(self.load(bytes, path, cx)?))
+    }
+}
+
+/// The context of [`Importer::load`], which reads the files referenced by the
+/// loaded file through the [`Loader`](crate::Loader)'s resolver.
+pub struct Context<'a> {
+    pub(crate) path: &'a Path,
+    pub(crate) source: &'a mut (dyn Source + 'a),
+    #[cfg(feature = "stl")]
+    pub(crate) stl_parse_color: bool,
+}
+
+impl fmt::Debug for Context<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Context")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Context<'_> {
+    /// Reads the file referenced by the loaded file.
+    ///
+    /// `reference` is resolved relative to the directory of the loaded file,
+    /// in the same way as the built-in formats (e.g., `package://` URIs are
+    /// resolved).
+    pub fn read<P: AsRef<Path>>(
+        &mut self,
+        reference: P,
+        kind: ResourceKind,
+    ) -> io::Result<Vec<u8>> {
+        let uri = reference.as_ref();
+        let path = self.join(uri);
+        self.source.read(&Resource {
+            path: &path,
+            uri,
+            requester: Some(self.path),
+            kind,
+        })
+    }
+
+    /// Reads and loads the model file referenced by the loaded file, using
+    /// the importers of the [`Loader`](crate::Loader).
+    ///
+    /// The returned scene is not post-processed; it is post-processed as
+    /// part of the scene returned by the importer.
+    pub fn load<P: AsRef<Path>>(&mut self, reference: P) -> io::Result<Scene> {
+        let uri = reference.as_ref();
+        let path = self.join(uri);
+        self.source.load(&Resource {
+            path: &path,
+            uri,
+            requester: Some(self.path),
+            kind: ResourceKind::Model,
+        })
+    }
+
+    fn join(&self, reference: &Path) -> PathBuf {
+        resolver::join(
+            self.path.parent().unwrap_or_else(|| Path::new("")),
+            reference,
+        )
+    }
+}
+
+/// The source of the files referenced by the loaded file.
+pub(crate) trait Source {
+    fn read(&mut self, resource: &Resource<'_>) -> io::Result<Vec<u8>>;
+    fn load(&mut self, resource: &Resource<'_>) -> io::Result<Scene>;
+    /// Reads and parses the MTL file referenced by the loaded OBJ file.
+    #[cfg(feature = "obj")]
+    fn load_mtl(
+        &mut self,
+        resource: &Resource<'_>,
+    ) -> io::Result<Option<Arc<crate::obj::MtlLibrary>>>;
+}
+
+/// The importer of a built-in format.
+pub(crate) struct Builtin(pub(crate) Format);
+
+impl Importer for Builtin {
+    fn extensions(&self) -> &[&str] {
+        self.0.extensions()
+    }
+
+    fn sniff(&self, bytes: &[u8]) -> bool {
+        detect_format(bytes, None) == Some(self.0)
+    }
+
+    #[allow(unused_variables)]
+    fn load(&self, bytes: &[u8], path: &Path, cx: &mut Context<'_>) -> io::Result<Scene> {
+        match self.0 {
+            #[cfg(feature = "stl")]
+            Format::Stl => crate::stl::from_slice_internal(bytes, Some(path), cx.stl_parse_color),
+            #[cfg(feature = "collada")]
+            Format::Collada => crate::collada::from_slice_internal(bytes, Some(path)),
+            #[cfg(feature = "obj")]
+            Format::Obj => {
+                crate::obj::from_slice_internal(bytes, Some(path), &mut |r| cx.source.load_mtl(r))
+            }
+            #[cfg(feature = "off")]
+            Format::Off => crate::off::from_slice_internal(bytes, Some(path)),
+            #[cfg(feature = "amf")]
+            Format::Amf => crate::amf::from_slice_internal(bytes, Some(path)),
+            #[cfg(feature = "fbx")]
+            Format::Fbx => crate::fbx::from_slice_internal(bytes, Some(path)),
+            #[cfg(feature = "3ds")]
+            Format::ThreeDs => crate::three_ds::from_slice_internal(bytes, Some(path)),
+            #[cfg(feature = "vrml")]
+            Format::Vrml => crate::vrml::from_slice_internal(bytes, Some(path)),
+            #[cfg(feature = "usd")]
+            Format::Usd => crate::usd::from_slice_internal(bytes, Some(path)),
+            #[cfg(feature = "dxf")]
+            Format::Dxf => crate::dxf::from_slice_internal(bytes, Some(path)),
+            #[cfg(feature = "pcd")]
+            Format::Pcd => crate::pcd::from_slice_internal(bytes, Some(path)),
+            #[cfg(feature = "xyz")]
+            Format::Xyz => crate::xyz::from_slice_internal(bytes, Some(path)),
+            #[cfg(feature = "urdf")]
+            Format::Urdf => {
+                crate::urdf::from_slice_internal(bytes, Some(path), &HashMap::new(), &mut |r| {
+                    cx.source.load(r)
+                })
+            }
+            #[allow(unreachable_patterns)]
+            format => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "'{}' feature of mesh-loader must be enabled to parse {} file ({})",
+                    feature_name(format),
+                    format.name(),
+                    path.display()
+                ),
+            )),
+        }
+    }
+
+    fn load_from_reader(
+        &self,
+        reader: &mut dyn io::Read,
+        path: &Path,
+        cx: &mut Context<'_>,
+    ) -> io::Result<Scene> {
+        match self.0 {
+            #[cfg(feature = "stl")]
+            Format::Stl => crate::stl::from_reader_internal(reader, Some(path), cx.stl_parse_color),
+            #[cfg(feature = "obj")]
+            Format::Obj => {
+                crate::obj::from_reader_internal(reader, Some(path), &mut |r| cx.source.load_mtl(r))
+            }
+            _ => {
+                let mut bytes = vec![];
+                reader.read_to_end(&mut bytes)?;
+                self.load(&bytes, path, cx)
+            }
+        }
+    }
+
+    fn probe(&self, bytes: &[u8], path: &Path, cx: &mut Context<'_>) -> io::Result<Summary> {
+        let mut summary = match self.0 {
+            #[cfg(feature = "stl")]
+            Format::Stl => crate::stl::probe(bytes, Some(path))?,
+            #[cfg(feature = "collada")]
+            Format::Collada => crate::collada::probe(bytes)?,
+            #[cfg(feature = "obj")]
+            Format::Obj => crate::obj::probe(bytes, Some(path))?,
+            _ => Summary::from_scene(self.load(bytes, path, cx)?),
+        };
+        summary.format = Some(self.0);
+        Ok(summary)
+    }
+}
+
+fn feature_name(format: Format) -> &'static str {
+    match format {
+        Format::Stl => "stl",
+        Format::Collada => "collada",
+        Format::Obj => "obj",
+        Format::Off => "off",
+        Format::Amf => "amf",
+        Format::Fbx => "fbx",
+        Format::ThreeDs => "3ds",
+        Format::Vrml => "vrml",
+        Format::Usd => "usd",
+        Format::Dxf => "dxf",
+        Format::Pcd => "pcd",
+        Format::Xyz => "xyz",
+        Format::Urdf => "urdf",
+    }
+}
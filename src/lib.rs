@@ -6,7 +6,8 @@
         allow(dead_code, unused_variables)
     )
 ))]
-#![forbid(unsafe_code)]
+// Unsafe code is only allowed in utils::simd.
+#![deny(unsafe_code)]
 #![warn(
     // Lints that may help when writing public library.
     missing_debug_implementations,
@@ -22,29 +23,102 @@
     clippy::must_use_candidate,
 )]
 
-#[cfg(any(feature = "collada", feature = "obj", feature = "stl"))]
+#[cfg(any(
+    feature = "3ds",
+    feature = "amf",
+    feature = "archive",
+    feature = "collada",
+    feature = "dxf",
+    feature = "fbx",
+    feature = "obj",
+    feature = "off",
+    feature = "pcd",
+    feature = "stl",
+    feature = "urdf",
+    feature = "usd",
+    feature = "vrml",
+    feature = "xyz"
+))]
 #[macro_use]
 mod error;
 
 mod utils;
 
+mod format;
+pub use format::{detect_format, Format};
+pub mod importer;
 mod loader;
 pub use loader::*;
 mod common;
 pub use common::*;
 
+#[cfg(feature = "amf")]
+pub mod amf;
 #[cfg(feature = "collada")]
 pub mod collada;
+#[cfg(feature = "dxf")]
+pub mod dxf;
+#[cfg(feature = "fbx")]
+pub mod fbx;
 #[cfg(feature = "obj")]
 pub mod obj;
+#[cfg(feature = "off")]
+pub mod off;
+#[cfg(feature = "pcd")]
+pub mod pcd;
+pub mod resolver;
 #[cfg(feature = "stl")]
 pub mod stl;
+#[cfg(feature = "3ds")]
+pub mod three_ds;
+#[cfg(feature = "urdf")]
+pub mod urdf;
+#[cfg(feature = "usd")]
+pub mod usd;
+#[cfg(feature = "vrml")]
+pub mod vrml;
+#[cfg(feature = "xyz")]
+pub mod xyz;
 
-// Not public API. (exposed for benchmarks)
+// Not public API. (exposed for benchmarks and tests)
 #[doc(hidden)]
-#[cfg(any(feature = "collada", feature = "obj", feature = "stl"))]
+#[cfg(any(
+    feature = "amf",
+    feature = "collada",
+    feature = "dxf",
+    feature = "obj",
+    feature = "off",
+    feature = "pcd",
+    feature = "stl",
+    feature = "urdf",
+    feature = "usd",
+    feature = "vrml",
+    feature = "xyz"
+))]
 pub mod __private {
     pub use crate::utils::float;
-    #[cfg(any(feature = "collada", feature = "obj"))]
+    #[cfg(any(
+        feature = "amf",
+        feature = "collada",
+        feature = "dxf",
+        feature = "obj",
+        feature = "off",
+        feature = "pcd",
+        feature = "urdf",
+        feature = "vrml"
+    ))]
     pub use crate::utils::int;
+    #[cfg(any(
+        feature = "dxf",
+        feature = "obj",
+        feature = "off",
+        feature = "pcd",
+        feature = "stl",
+        feature = "usd",
+        feature = "vrml",
+        feature = "xyz"
+    ))]
+    pub use crate::utils::simd;
+    #[cfg(all(feature = "collada", feature = "rayon"))]
+    pub use crate::utils::xml::parse_float_array_parallel;
 }
@@ -1,29 +1,75 @@
-use std::{cmp, ffi::OsStr, fmt, fs, io, path::Path};
+#[cfg(feature = "obj")]
+use std::sync::Arc;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    ffi::OsStr,
+    fmt, fs,
+    io::{self, Read as _},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+#[cfg(feature = "archive")]
+use std::{path::Component, str};
 
-use crate::{utils::bytes::starts_with, Scene};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+#[cfg(feature = "archive")]
+use crate::utils::{gzip, zip};
+use crate::{
+    detect_format,
+    importer::{Builtin, Context, Importer, Source},
+    resolver::{self, resolve_path, resolve_path_lossy, Resource, ResourceKind, ResourceResolver},
+    Format, Scene, Summary,
+};
 
 type Reader<B> = fn(&Path) -> io::Result<B>;
+/// The function that reads resources, used while loading a file.
+pub(crate) type Read<'a, B> = dyn FnMut(&Resource<'_>) -> io::Result<B> + 'a;
 
 pub struct Loader<B = Vec<u8>> {
-    reader: Reader<B>,
+    resolver: LoaderResolver<B>,
+    importers: Vec<Box<dyn Importer>>,
     merge_meshes: bool,
+    sandbox: Option<PathBuf>,
     // STL config
     #[cfg(feature = "stl")]
     stl_parse_color: bool,
 }
 
+/// The resolver of a loader.
+///
+/// Readers given to [`Loader::custom_reader`] are kept as function pointers,
+/// so that they do not require `B: 'static`.
+enum LoaderResolver<B> {
+    Reader(Reader<B>),
+    Custom(Box<dyn ResourceResolver<B> + Send + Sync>),
+}
+
+impl<B> LoaderResolver<B> {
+    fn read(&self, resource: &Resource<'_>) -> io::Result<B> {
+        match self {
+            Self::Reader(reader) => reader.read(resource),
+            Self::Custom(resolver) => resolver.read(resource),
+        }
+    }
+
+    fn resolve(&self, resource: &Resource<'_>) -> Option<PathBuf> {
+        match self {
+            Self::Reader(reader) => reader.resolve(resource),
+            Self::Custom(resolver) => resolver.resolve(resource),
+        }
+    }
+}
+
 fn default_reader(path: &Path) -> io::Result<Vec<u8>> {
-    fs::read(path)
+    fs::read(resolve_path(path)?)
 }
 
 impl Default for Loader<Vec<u8>> {
     fn default() -> Self {
-        Self {
-            reader: default_reader,
-            merge_meshes: false,
-            #[cfg(feature = "stl")]
-            stl_parse_color: false,
-        }
+        Self::with_custom_reader(default_reader)
     }
 }
 
@@ -40,6 +86,32 @@ impl<B: AsRef<[u8]>> Loader<B> {
         self
     }
 
+    /// Confines the files referenced by the loaded file (MTL files, meshes
+    /// referenced by URDF, textures, etc.) to the given root directory.
+    ///
+    /// If a file references an absolute path, a URL, or a path outside of
+    /// the root (e.g., `mtllib ../../etc/passwd`), loading fails with an
+    /// error of [`io::ErrorKind::PermissionDenied`] instead of reading the
+    /// file. The file given to the loader itself is not checked.
+    ///
+    /// Paths are checked lexically, so symbolic links in the root are not
+    /// resolved.
+    ///
+    /// Default: `None`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mesh_loader::Loader;
+    ///
+    /// let loader = Loader::default().sandbox("uploads/1234");
+    /// ```
+    #[must_use]
+    pub fn sandbox<P: AsRef<Path>>(mut self, root: P) -> Self {
+        self.sandbox = Some(resolver::normalize_absolute(root.as_ref()));
+        self
+    }
+
     /// Use the given function as a file reader of this loader.
     ///
     /// Default: [`std::fs::read`]
@@ -67,7 +139,7 @@ impl<B: AsRef<[u8]>> Loader<B> {
     /// ```
     #[must_use]
     pub fn custom_reader(mut self, reader: Reader<B>) -> Self {
-        self.reader = reader;
+        self.resolver = LoaderResolver::Reader(reader);
         self
     }
 
@@ -90,74 +162,360 @@ impl<B: AsRef<[u8]>> Loader<B> {
     /// ```
     #[must_use]
     pub fn with_custom_reader(reader: Reader<B>) -> Self {
+        Self::new(LoaderResolver::Reader(reader))
+    }
+
+    /// Use the given resolver to read the file given to this loader and the
+    /// files referenced by it.
+    ///
+    /// Unlike [`custom_reader`](Self::custom_reader), the resolver can have
+    /// state (e.g., an archive or an HTTP client), and is called with the
+    /// requesting file, the reference, and the kind of the resource. See
+    /// [`ResourceResolver`] for an example.
+    #[must_use]
+    pub fn resolver<R: ResourceResolver<B> + Send + Sync + 'static>(mut self, resolver: R) -> Self {
+        self.resolver = LoaderResolver::Custom(Box::new(resolver));
+        self
+    }
+
+    /// Creates a new loader with the given resolver.
+    ///
+    /// This is similar to [`Loader::default().resolver()`](Self::resolver),
+    /// but the resolver can return a non-`Vec<u8>` type.
+    #[must_use]
+    pub fn with_resolver<R: ResourceResolver<B> + Send + Sync + 'static>(resolver: R) -> Self {
+        Self::new(LoaderResolver::Custom(Box::new(resolver)))
+    }
+
+    fn new(resolver: LoaderResolver<B>) -> Self {
         Self {
-            reader,
+            resolver,
+            importers: Format::ALL
+                .iter()
+                .map(|&format| Box::new(Builtin(format)) as Box<dyn Importer>)
+                .collect(),
             merge_meshes: false,
+            sandbox: None,
             #[cfg(feature = "stl")]
             stl_parse_color: false,
         }
     }
 
+    /// Registers an importer of a user-defined format.
+    ///
+    /// The importer is used for files with one of its
+    /// [`extensions`](Importer::extensions), and for files whose format cannot
+    /// be determined by the extension if it [sniffs](Importer::sniff) the
+    /// contents. Importers registered later take precedence, so this can also
+    /// replace the importer of a built-in format.
+    ///
+    /// The scenes loaded by the importer are post-processed in the same way as
+    /// the built-in formats. See [`Importer`] for an example.
+    #[must_use]
+    pub fn importer<I: Importer + 'static>(mut self, importer: I) -> Self {
+        self.importers.push(Box::new(importer));
+        self
+    }
+
+    /// Loads a mesh from the given path.
+    ///
+    /// `package://`, `model://`, and `file://` URIs in the path and in the
+    /// file references inside the loaded file are resolved by the
+    /// [`resolver`](crate::resolver).
     pub fn load<P: AsRef<Path>>(&self, path: P) -> io::Result<Scene> {
-        self.load_with_reader(path.as_ref(), self.reader)
+        self.load_internal(path.as_ref(), &mut |r| self.read(r))
     }
     pub fn load_with_reader<P: AsRef<Path>, F: FnMut(&Path) -> io::Result<B>>(
         &self,
         path: P,
         mut reader: F,
     ) -> io::Result<Scene> {
-        let path = path.as_ref();
-        self.load_from_slice_with_reader(reader(path)?.as_ref(), path, reader)
+        self.load_internal(path.as_ref(), &mut |r| {
+            self.check_sandbox(r)?;
+            reader(r.path)
+        })
+    }
+    fn load_internal(&self, path: &Path, read: &mut Read<'_, B>) -> io::Result<Scene> {
+        let (bytes, path) = read_model(path, read)?;
+        self.load_from_slice_internal(bytes.as_ref(), &path, read)
     }
     pub fn load_from_slice<P: AsRef<Path>>(&self, bytes: &[u8], path: P) -> io::Result<Scene> {
-        self.load_from_slice_with_reader(bytes, path.as_ref(), self.reader)
+        self.load_from_slice_internal(bytes, path.as_ref(), &mut |r| self.read(r))
     }
     pub fn load_from_slice_with_reader<P: AsRef<Path>, F: FnMut(&Path) -> io::Result<B>>(
         &self,
         bytes: &[u8],
         path: P,
-        #[allow(unused_variables)] reader: F,
+        mut reader: F,
     ) -> io::Result<Scene> {
-        let path = path.as_ref();
-        match detect_file_type(path, bytes) {
-            #[cfg(feature = "stl")]
-            FileType::Stl => self.load_stl_from_slice(bytes, path),
-            #[cfg(not(feature = "stl"))]
-            FileType::Stl => Err(io::Error::new(
-                io::ErrorKind::Unsupported,
-                "'stl' feature of mesh-loader must be enabled to parse STL file ({path:?})",
-            )),
-            #[cfg(feature = "collada")]
-            FileType::Collada => self.load_collada_from_slice(bytes, path),
-            #[cfg(not(feature = "collada"))]
-            FileType::Collada => Err(io::Error::new(
-                io::ErrorKind::Unsupported,
-                "'collada' feature of mesh-loader must be enabled to parse COLLADA file ({path:?})",
-            )),
-            #[cfg(feature = "obj")]
-            FileType::Obj => self.load_obj_from_slice_with_reader(bytes, path, reader),
-            #[cfg(not(feature = "obj"))]
-            FileType::Obj => Err(io::Error::new(
+        self.load_from_slice_internal(bytes, path.as_ref(), &mut |r| {
+            self.check_sandbox(r)?;
+            reader(r.path)
+        })
+    }
+    /// Loads a mesh from the given reader.
+    ///
+    /// `path` is used as a hint to determine the file format (if it is unknown
+    /// from the extension, it is determined from the first bytes read) and to
+    /// resolve the files referenced by the loaded file.
+    ///
+    /// STL and OBJ are parsed incrementally, so the whole file is not kept in
+    /// memory. Other formats, compressed files, and files whose format cannot
+    /// be determined from the first bytes are read into memory before parsing.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use mesh_loader::Loader;
+    ///
+    /// let scene = Loader::default().load_from_reader(std::io::stdin(), "model.obj")?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn load_from_reader<R: io::Read, P: AsRef<Path>>(
+        &self,
+        mut reader: R,
+        path: P,
+    ) -> io::Result<Scene> {
+        self.load_from_reader_internal(&mut reader, path.as_ref(), &mut |r| self.read(r))
+    }
+    fn load_from_reader_internal(
+        &self,
+        reader: &mut dyn io::Read,
+        path: &Path,
+        read: &mut Read<'_, B>,
+    ) -> io::Result<Scene> {
+        let mut head = Vec::with_capacity(HEAD_SIZE);
+        (&mut *reader)
+            .take(HEAD_SIZE as u64)
+            .read_to_end(&mut head)?;
+        let importer = if head.len() < HEAD_SIZE
+            || head.starts_with(GZIP_MAGIC)
+            || head.starts_with(ZIP_MAGIC)
+            || has_extension(path, &["gz", "zip", "zae"])
+        {
+            None
+        } else {
+            self.find_importer(&head, path)
+        };
+        let importer = match importer {
+            Some(importer) => importer,
+            None => {
+                reader.read_to_end(&mut head)?;
+                return self.load_from_slice_internal(&head, path, read);
+            }
+        };
+        let scene = importer.load_from_reader(
+            &mut head.as_slice().chain(reader),
+            path,
+            &mut Context {
+                path,
+                source: &mut LoaderSource {
+                    loader: self,
+                    read,
+                    cache: None,
+                },
+                #[cfg(feature = "stl")]
+                stl_parse_color: self.stl_parse_color,
+            },
+        )?;
+        self.post_process(scene, path)
+    }
+    fn load_from_slice_internal<R: AsRef<[u8]>>(
+        &self,
+        bytes: &[u8],
+        path: &Path,
+        read: &mut Read<'_, R>,
+    ) -> io::Result<Scene> {
+        let scene = self.import(bytes, path, read, None)?;
+        self.post_process(scene, path)
+    }
+    /// Loads meshes from the given paths.
+    ///
+    /// The result of each file is returned in the same order as `paths`, so
+    /// a file that fails to load does not prevent loading the others.
+    ///
+    /// MTL files and textures referenced by multiple files are read, parsed,
+    /// and resolved only once. With the `rayon` feature, the files are loaded
+    /// concurrently on the rayon thread pool.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let paths = ["base_link.obj", "link1.obj", "link2.obj"];
+    /// for (path, scene) in paths.iter().zip(mesh_loader::Loader::default().load_many(&paths)) {
+    ///     match scene {
+    ///         Ok(scene) => println!("{path}: {} meshes", scene.meshes.len()),
+    ///         Err(e) => eprintln!("{path}: {e}"),
+    ///     }
+    /// }
+    /// ```
+    pub fn load_many<P: AsRef<Path> + Sync>(&self, paths: &[P]) -> Vec<io::Result<Scene>> {
+        let cache = Cache::default();
+        #[cfg(feature = "rayon")]
+        let paths = paths.par_iter();
+        #[cfg(not(feature = "rayon"))]
+        let paths = paths.iter();
+        paths
+            .map(|path| self.load_cached(path.as_ref(), &cache))
+            .collect()
+    }
+    fn load_cached(&self, path: &Path, cache: &Cache) -> io::Result<Scene> {
+        let read: &mut Read<'_, B> = &mut |r| self.read(r);
+        let (bytes, path) = read_model(path, read)?;
+        let scene = self.import(bytes.as_ref(), &path, read, Some(cache))?;
+        self.post_process_internal(scene, &path, Some(cache))
+    }
+    /// Loads a scene from the given bytes using the registered importers,
+    /// without post-processing.
+    fn import<R: AsRef<[u8]>>(
+        &self,
+        bytes: &[u8],
+        path: &Path,
+        read: &mut Read<'_, R>,
+        cache: Option<&Cache>,
+    ) -> io::Result<Scene> {
+        #[cfg(feature = "archive")]
+        if gzip::is_gzip(bytes) {
+            let bytes = gzip::decompress(bytes)?;
+            // link.stl.gz -> link.stl
+            let path = if has_extension(path, &["gz"]) {
+                path.with_extension("")
+            } else {
+                path.to_owned()
+            };
+            return self.import(&bytes, &path, read, cache);
+        }
+        // Zip-compressed AMF is handled by the AMF parser.
+        #[cfg(feature = "archive")]
+        if zip::is_zip(bytes) && !has_extension(path, &["amf"]) {
+            return self.import_zip(bytes, path, read, cache);
+        }
+        let importer = self.importer_for(bytes, path)?;
+        importer.load(
+            bytes,
+            path,
+            &mut Context {
+                path,
+                source: &mut LoaderSource {
+                    loader: self,
+                    read,
+                    cache,
+                },
+                #[cfg(feature = "stl")]
+                stl_parse_color: self.stl_parse_color,
+            },
+        )
+    }
+    /// Returns a summary of the model file at the given path, without fully
+    /// loading it if possible.
+    ///
+    /// Binary STL reports the triangle count and the text in its header,
+    /// OBJ reports the counts of `v`/`f` lines and `mtllib`s, and COLLADA
+    /// reports the `<asset>` and the geometries with their `count`
+    /// attributes, without building meshes. Other formats are loaded and
+    /// summarized.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// let summary = mesh_loader::Loader::default().probe("model.stl")?;
+    /// println!("{:?}: {} meshes", summary.format, summary.meshes.len());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn probe<P: AsRef<Path>>(&self, path: P) -> io::Result<Summary> {
+        let read: &mut Read<'_, B> = &mut |r| self.read(r);
+        let (bytes, path) = read_model(path.as_ref(), read)?;
+        self.probe_internal(bytes.as_ref(), &path, read)
+    }
+    pub fn probe_from_slice<P: AsRef<Path>>(&self, bytes: &[u8], path: P) -> io::Result<Summary> {
+        self.probe_internal(bytes, path.as_ref(), &mut |r| self.read(r))
+    }
+    fn probe_internal<R: AsRef<[u8]>>(
+        &self,
+        bytes: &[u8],
+        path: &Path,
+        read: &mut Read<'_, R>,
+    ) -> io::Result<Summary> {
+        #[cfg(feature = "archive")]
+        if gzip::is_gzip(bytes) {
+            let bytes = gzip::decompress(bytes)?;
+            let path = if has_extension(path, &["gz"]) {
+                path.with_extension("")
+            } else {
+                path.to_owned()
+            };
+            return self.probe_internal(&bytes, &path, read);
+        }
+        #[cfg(feature = "archive")]
+        if zip::is_zip(bytes) && !has_extension(path, &["amf"]) {
+            return Ok(Summary::from_scene(
+                self.import_zip(bytes, path, read, None)?,
+            ));
+        }
+        let importer = self.importer_for(bytes, path)?;
+        importer.probe(
+            bytes,
+            path,
+            &mut Context {
+                path,
+                source: &mut LoaderSource {
+                    loader: self,
+                    read,
+                    cache: None,
+                },
+                #[cfg(feature = "stl")]
+                stl_parse_color: self.stl_parse_color,
+            },
+        )
+    }
+    /// Returns the importer of the given file, or an error if there is no
+    /// importer for it.
+    fn importer_for(&self, bytes: &[u8], path: &Path) -> io::Result<&dyn Importer> {
+        #[cfg(not(feature = "archive"))]
+        if bytes.starts_with(GZIP_MAGIC) || has_extension(path, &["gz", "zip", "zae"]) {
+            return Err(io::Error::new(
                 io::ErrorKind::Unsupported,
-                "'obj' feature of mesh-loader must be enabled to parse OBJ file ({path:?})",
-            )),
-            FileType::Unknown => Err(io::Error::new(
+                format!(
+                    "'archive' feature of mesh-loader must be enabled to load gzip/zip file ({})",
+                    path.display()
+                ),
+            ));
+        }
+        self.find_importer(bytes, path).ok_or_else(|| {
+            io::Error::new(
                 io::ErrorKind::Unsupported,
-                "unsupported or unrecognized file type {path:?}",
-            )),
+                format!("unsupported or unrecognized file type ({})", path.display()),
+            )
+        })
+    }
+    /// Returns the importer of the given file: the one for its extension if
+    /// any, otherwise the one that recognizes its contents.
+    fn find_importer(&self, bytes: &[u8], path: &Path) -> Option<&dyn Importer> {
+        if let Some(ext) = path.extension().and_then(OsStr::to_str) {
+            if let Some(importer) = find_by_extension(&self.importers, ext) {
+                return Some(importer);
+            }
         }
+        // The built-in formats are detected at once instead of calling
+        // `sniff` of each built-in importer.
+        let custom = &self.importers[Format::ALL.len()..];
+        if let Some(importer) = custom.iter().rev().find(|i| i.sniff(bytes)) {
+            return Some(&**importer);
+        }
+        let format = detect_format(bytes, None)?;
+        find_by_extension(&self.importers, format.extensions()[0])
     }
 
     #[cfg(feature = "stl")]
     pub fn load_stl<P: AsRef<Path>>(&self, path: P) -> io::Result<Scene> {
-        let path = path.as_ref();
-        self.load_stl_from_slice((self.reader)(path)?.as_ref(), path)
+        let (bytes, path) = read_model(path.as_ref(), &mut |r| self.read(r))?;
+        self.load_stl_from_slice(bytes.as_ref(), &path)
     }
     #[cfg(feature = "stl")]
     pub fn load_stl_from_slice<P: AsRef<Path>>(&self, bytes: &[u8], path: P) -> io::Result<Scene> {
         let scene =
             crate::stl::from_slice_internal(bytes, Some(path.as_ref()), self.stl_parse_color)?;
-        Ok(self.post_process(scene))
+        self.post_process(scene, path.as_ref())
     }
     #[cfg(feature = "stl")]
     #[must_use]
@@ -168,8 +526,8 @@ impl<B: AsRef<[u8]>> Loader<B> {
 
     #[cfg(feature = "collada")]
     pub fn load_collada<P: AsRef<Path>>(&self, path: P) -> io::Result<Scene> {
-        let path = path.as_ref();
-        self.load_collada_from_slice((self.reader)(path)?.as_ref(), path)
+        let (bytes, path) = read_model(path.as_ref(), &mut |r| self.read(r))?;
+        self.load_collada_from_slice(bytes.as_ref(), &path)
     }
     #[cfg(feature = "collada")]
     pub fn load_collada_from_slice<P: AsRef<Path>>(
@@ -178,16 +536,18 @@ impl<B: AsRef<[u8]>> Loader<B> {
         path: P,
     ) -> io::Result<Scene> {
         let scene = crate::collada::from_slice_internal(bytes, Some(path.as_ref()))?;
-        Ok(self.post_process(scene))
+        self.post_process(scene, path.as_ref())
     }
 
     #[cfg(feature = "obj")]
     pub fn load_obj<P: AsRef<Path>>(&self, path: P) -> io::Result<Scene> {
-        self.load_obj_with_reader(path.as_ref(), self.reader)
+        let read: &mut Read<'_, B> = &mut |r| self.read(r);
+        let (bytes, path) = read_model(path.as_ref(), read)?;
+        self.load_obj_internal(bytes.as_ref(), &path, read)
     }
     #[cfg(feature = "obj")]
     pub fn load_obj_from_slice<P: AsRef<Path>>(&self, bytes: &[u8], path: P) -> io::Result<Scene> {
-        self.load_obj_from_slice_with_reader(bytes, path.as_ref(), self.reader)
+        self.load_obj_internal(bytes, path.as_ref(), &mut |r| self.read(r))
     }
     #[cfg(feature = "obj")]
     pub fn load_obj_with_reader<P: AsRef<Path>, F: FnMut(&Path) -> io::Result<B>>(
@@ -195,75 +555,470 @@ impl<B: AsRef<[u8]>> Loader<B> {
         path: P,
         mut reader: F,
     ) -> io::Result<Scene> {
-        let path = path.as_ref();
-        self.load_obj_from_slice_with_reader(reader(path)?.as_ref(), path, reader)
+        let read: &mut Read<'_, B> = &mut |r| {
+            self.check_sandbox(r)?;
+            reader(r.path)
+        };
+        let (bytes, path) = read_model(path.as_ref(), read)?;
+        self.load_obj_internal(bytes.as_ref(), &path, read)
     }
     #[cfg(feature = "obj")]
     pub fn load_obj_from_slice_with_reader<P: AsRef<Path>, F: FnMut(&Path) -> io::Result<B>>(
         &self,
         bytes: &[u8],
         path: P,
-        reader: F,
+        mut reader: F,
+    ) -> io::Result<Scene> {
+        self.load_obj_internal(bytes, path.as_ref(), &mut |r| {
+            self.check_sandbox(r)?;
+            reader(r.path)
+        })
+    }
+    #[cfg(feature = "obj")]
+    fn load_obj_internal<R: AsRef<[u8]>>(
+        &self,
+        bytes: &[u8],
+        path: &Path,
+        read: &mut Read<'_, R>,
+    ) -> io::Result<Scene> {
+        let scene = crate::obj::from_slice_internal(bytes, Some(path), &mut |r| {
+            crate::obj::load_mtl(read, r)
+        })?;
+        self.post_process(scene, path)
+    }
+
+    #[cfg(feature = "off")]
+    pub fn load_off<P: AsRef<Path>>(&self, path: P) -> io::Result<Scene> {
+        let (bytes, path) = read_model(path.as_ref(), &mut |r| self.read(r))?;
+        self.load_off_from_slice(bytes.as_ref(), &path)
+    }
+    #[cfg(feature = "off")]
+    pub fn load_off_from_slice<P: AsRef<Path>>(&self, bytes: &[u8], path: P) -> io::Result<Scene> {
+        let scene = crate::off::from_slice_internal(bytes, Some(path.as_ref()))?;
+        self.post_process(scene, path.as_ref())
+    }
+
+    #[cfg(feature = "amf")]
+    pub fn load_amf<P: AsRef<Path>>(&self, path: P) -> io::Result<Scene> {
+        let (bytes, path) = read_model(path.as_ref(), &mut |r| self.read(r))?;
+        self.load_amf_from_slice(bytes.as_ref(), &path)
+    }
+    #[cfg(feature = "amf")]
+    pub fn load_amf_from_slice<P: AsRef<Path>>(&self, bytes: &[u8], path: P) -> io::Result<Scene> {
+        let scene = crate::amf::from_slice_internal(bytes, Some(path.as_ref()))?;
+        self.post_process(scene, path.as_ref())
+    }
+
+    #[cfg(feature = "fbx")]
+    pub fn load_fbx<P: AsRef<Path>>(&self, path: P) -> io::Result<Scene> {
+        let (bytes, path) = read_model(path.as_ref(), &mut |r| self.read(r))?;
+        self.load_fbx_from_slice(bytes.as_ref(), &path)
+    }
+    #[cfg(feature = "fbx")]
+    pub fn load_fbx_from_slice<P: AsRef<Path>>(&self, bytes: &[u8], path: P) -> io::Result<Scene> {
+        let scene = crate::fbx::from_slice_internal(bytes, Some(path.as_ref()))?;
+        self.post_process(scene, path.as_ref())
+    }
+
+    #[cfg(feature = "3ds")]
+    pub fn load_3ds<P: AsRef<Path>>(&self, path: P) -> io::Result<Scene> {
+        let (bytes, path) = read_model(path.as_ref(), &mut |r| self.read(r))?;
+        self.load_3ds_from_slice(bytes.as_ref(), &path)
+    }
+    #[cfg(feature = "3ds")]
+    pub fn load_3ds_from_slice<P: AsRef<Path>>(&self, bytes: &[u8], path: P) -> io::Result<Scene> {
+        let scene = crate::three_ds::from_slice_internal(bytes, Some(path.as_ref()))?;
+        self.post_process(scene, path.as_ref())
+    }
+
+    #[cfg(feature = "vrml")]
+    pub fn load_vrml<P: AsRef<Path>>(&self, path: P) -> io::Result<Scene> {
+        let (bytes, path) = read_model(path.as_ref(), &mut |r| self.read(r))?;
+        self.load_vrml_from_slice(bytes.as_ref(), &path)
+    }
+    #[cfg(feature = "vrml")]
+    pub fn load_vrml_from_slice<P: AsRef<Path>>(&self, bytes: &[u8], path: P) -> io::Result<Scene> {
+        let scene = crate::vrml::from_slice_internal(bytes, Some(path.as_ref()))?;
+        self.post_process(scene, path.as_ref())
+    }
+
+    #[cfg(feature = "usd")]
+    pub fn load_usd<P: AsRef<Path>>(&self, path: P) -> io::Result<Scene> {
+        let (bytes, path) = read_model(path.as_ref(), &mut |r| self.read(r))?;
+        self.load_usd_from_slice(bytes.as_ref(), &path)
+    }
+    #[cfg(feature = "usd")]
+    pub fn load_usd_from_slice<P: AsRef<Path>>(&self, bytes: &[u8], path: P) -> io::Result<Scene> {
+        let scene = crate::usd::from_slice_internal(bytes, Some(path.as_ref()))?;
+        self.post_process(scene, path.as_ref())
+    }
+
+    #[cfg(feature = "dxf")]
+    pub fn load_dxf<P: AsRef<Path>>(&self, path: P) -> io::Result<Scene> {
+        let (bytes, path) = read_model(path.as_ref(), &mut |r| self.read(r))?;
+        self.load_dxf_from_slice(bytes.as_ref(), &path)
+    }
+    #[cfg(feature = "dxf")]
+    pub fn load_dxf_from_slice<P: AsRef<Path>>(&self, bytes: &[u8], path: P) -> io::Result<Scene> {
+        let scene = crate::dxf::from_slice_internal(bytes, Some(path.as_ref()))?;
+        self.post_process(scene, path.as_ref())
+    }
+
+    #[cfg(feature = "pcd")]
+    pub fn load_pcd<P: AsRef<Path>>(&self, path: P) -> io::Result<Scene> {
+        let (bytes, path) = read_model(path.as_ref(), &mut |r| self.read(r))?;
+        self.load_pcd_from_slice(bytes.as_ref(), &path)
+    }
+    #[cfg(feature = "pcd")]
+    pub fn load_pcd_from_slice<P: AsRef<Path>>(&self, bytes: &[u8], path: P) -> io::Result<Scene> {
+        let scene = crate::pcd::from_slice_internal(bytes, Some(path.as_ref()))?;
+        self.post_process(scene, path.as_ref())
+    }
+
+    #[cfg(feature = "xyz")]
+    pub fn load_xyz<P: AsRef<Path>>(&self, path: P) -> io::Result<Scene> {
+        let (bytes, path) = read_model(path.as_ref(), &mut |r| self.read(r))?;
+        self.load_xyz_from_slice(bytes.as_ref(), &path)
+    }
+    #[cfg(feature = "xyz")]
+    pub fn load_xyz_from_slice<P: AsRef<Path>>(&self, bytes: &[u8], path: P) -> io::Result<Scene> {
+        let scene = crate::xyz::from_slice_internal(bytes, Some(path.as_ref()))?;
+        self.post_process(scene, path.as_ref())
+    }
+
+    #[cfg(feature = "urdf")]
+    pub fn load_urdf<P: AsRef<Path>>(
+        &self,
+        path: P,
+        joint_positions: &HashMap<String, f32>,
+    ) -> io::Result<Scene> {
+        let read: &mut Read<'_, B> = &mut |r| self.read(r);
+        let (bytes, path) = read_model(path.as_ref(), read)?;
+        self.load_urdf_internal(bytes.as_ref(), &path, joint_positions, read)
+    }
+    /// Loads all visual and collision geometries of the robot described in
+    /// the given URDF.
+    ///
+    /// The links are placed according to the given joint positions (radians
+    /// for revolute and continuous joints, meters for prismatic joints).
+    /// Joints not in `joint_positions` are at position `0`. See the
+    /// [`urdf`](crate::urdf) module for details.
+    #[cfg(feature = "urdf")]
+    pub fn load_urdf_from_slice<P: AsRef<Path>>(
+        &self,
+        bytes: &[u8],
+        path: P,
+        joint_positions: &HashMap<String, f32>,
+    ) -> io::Result<Scene> {
+        self.load_urdf_internal(bytes, path.as_ref(), joint_positions, &mut |r| self.read(r))
+    }
+    #[cfg(feature = "urdf")]
+    fn load_urdf_internal<R: AsRef<[u8]>>(
+        &self,
+        bytes: &[u8],
+        path: &Path,
+        joint_positions: &HashMap<String, f32>,
+        read: &mut Read<'_, R>,
     ) -> io::Result<Scene> {
-        let scene = crate::obj::from_slice(bytes, Some(path.as_ref()), reader)?;
-        Ok(self.post_process(scene))
+        let mut source = LoaderSource {
+            loader: self,
+            read,
+            cache: None,
+        };
+        let scene =
+            crate::urdf::from_slice_internal(bytes, Some(path), joint_positions, &mut |r| {
+                source.load(r)
+            })?;
+        self.post_process(scene, path)
     }
 
-    #[cfg(any(feature = "collada", feature = "obj", feature = "stl"))]
-    fn post_process(&self, mut scene: Scene) -> Scene {
+    /// Loads the model in the given zip archive.
+    ///
+    /// The entries of the archive are at `<path>/<name>`, and the files
+    /// referenced by the model are read from the archive if they are in it.
+    #[cfg(feature = "archive")]
+    fn import_zip<R: AsRef<[u8]>>(
+        &self,
+        bytes: &[u8],
+        path: &Path,
+        read: &mut Read<'_, R>,
+        cache: Option<&Cache>,
+    ) -> io::Result<Scene> {
+        let archive = zip::Archive::new(bytes)?;
+        let root = zip_root(&archive, &self.importers)?;
+        let bytes = archive.read(root)?;
+        let read: &mut Read<'_, Vec<u8>> = &mut |r| {
+            let entry = r
+                .path
+                .strip_prefix(path)
+                .ok()
+                .and_then(zip_entry_name)
+                .and_then(|name| archive.entries().find(|e| e.name == name));
+            match entry {
+                Some(entry) => Ok(archive.read(entry)?.into_owned()),
+                None => Ok(read(r)?.as_ref().to_vec()),
+            }
+        };
+        let mut scene = self.import(&bytes, &path.join(&root.name), read, cache)?;
+        // Normalize the paths of the textures in the archive
+        // (e.g., a.zip/models/../textures/a.png -> a.zip/textures/a.png).
+        for material in &mut scene.materials {
+            material.texture.for_each_mut(|texture| {
+                if let Some(p) = texture {
+                    if let Some(name) = p.strip_prefix(path).ok().and_then(zip_entry_name) {
+                        *p = path.join(name);
+                    }
+                }
+            });
+        }
+        Ok(scene)
+    }
+
+    /// Resolves the textures of the scene loaded from `path`, and merges the
+    /// meshes if requested.
+    fn post_process(&self, scene: Scene, path: &Path) -> io::Result<Scene> {
+        self.post_process_internal(scene, path, None)
+    }
+    fn post_process_internal(
+        &self,
+        mut scene: Scene,
+        path: &Path,
+        cache: Option<&Cache>,
+    ) -> io::Result<Scene> {
+        let mut result = Ok(());
+        for material in &mut scene.materials {
+            material.texture.for_each_mut(|texture| {
+                if let Some(p) = texture.take() {
+                    let resource = Resource {
+                        path: &p,
+                        uri: &p,
+                        requester: Some(path),
+                        kind: ResourceKind::Texture,
+                    };
+                    if let Err(e) = self.check_sandbox(&resource) {
+                        if result.is_ok() {
+                            result = Err(e);
+                        }
+                    }
+                    *texture = match cache {
+                        Some(cache) => cache
+                            .textures
+                            .lock()
+                            .unwrap()
+                            .entry(p.clone())
+                            .or_insert_with(|| self.resolver.resolve(&resource))
+                            .clone(),
+                        None => self.resolver.resolve(&resource),
+                    };
+                }
+            });
+        }
+        result?;
         if self.merge_meshes && scene.meshes.len() != 1 {
             scene.meshes = vec![crate::Mesh::merge(scene.meshes)];
             // TODO
             scene.materials = vec![crate::Material::default()];
         }
-        scene
+        Ok(scene)
+    }
+
+    fn read(&self, resource: &Resource<'_>) -> io::Result<B> {
+        self.check_sandbox(resource)?;
+        self.resolver.read(resource)
+    }
+
+    /// Checks that the given resource referenced by a loaded file is in the
+    /// sandbox.
+    fn check_sandbox(&self, resource: &Resource<'_>) -> io::Result<()> {
+        match &self.sandbox {
+            Some(root) if resource.requester.is_some() => resolver::check_sandbox(root, resource),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The source of the files referenced by the file loaded by [`Loader`].
+struct LoaderSource<'a, 'b, B, R> {
+    loader: &'a Loader<B>,
+    read: &'a mut Read<'b, R>,
+    cache: Option<&'a Cache>,
+}
+
+impl<B: AsRef<[u8]>, R: AsRef<[u8]>> Source for LoaderSource<'_, '_, B, R> {
+    fn read(&mut self, resource: &Resource<'_>) -> io::Result<Vec<u8>> {
+        Ok((self.read)(resource)?.as_ref().to_vec())
+    }
+    fn load(&mut self, resource: &Resource<'_>) -> io::Result<Scene> {
+        let bytes = (self.read)(resource)?;
+        self.loader
+            .import(bytes.as_ref(), resource.path, self.read, self.cache)
+    }
+    #[cfg(feature = "obj")]
+    fn load_mtl(
+        &mut self,
+        resource: &Resource<'_>,
+    ) -> io::Result<Option<Arc<crate::obj::MtlLibrary>>> {
+        let cache = match self.cache {
+            Some(cache) => cache,
+            None => return crate::obj::load_mtl(self.read, resource),
+        };
+        // The sandbox is checked for each reference, even if cached.
+        self.loader.check_sandbox(resource)?;
+        let entry = cache
+            .mtl
+            .lock()
+            .unwrap()
+            .entry(resource.path.to_owned())
+            .or_default()
+            .clone();
+        // Other files referencing the same MTL file wait for it to be parsed
+        // instead of parsing it again.
+        let mut entry = entry.lock().unwrap();
+        if let Some(library) = &*entry {
+            return Ok(Some(library.clone()));
+        }
+        let library = crate::obj::load_mtl(self.read, resource)?;
+        entry.clone_from(&library);
+        Ok(library)
     }
 }
 
+/// The caches shared by the files loaded by [`Loader::load_many`].
+#[derive(Default)]
+struct Cache {
+    /// The parsed MTL files by path. `None` if it has not been read yet or
+    /// cannot be read.
+    #[cfg(feature = "obj")]
+    mtl: Mutex<HashMap<PathBuf, Arc<Mutex<Option<Arc<crate::obj::MtlLibrary>>>>>>,
+    /// The resolved texture references by path.
+    textures: Mutex<HashMap<PathBuf, Option<PathBuf>>>,
+}
+
 impl fmt::Debug for Loader {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut d = f.debug_struct("Loader");
         d.field("merge_meshes", &self.merge_meshes);
+        d.field("sandbox", &self.sandbox);
         #[cfg(feature = "stl")]
         d.field("stl_parse_color", &self.stl_parse_color);
         d.finish_non_exhaustive()
     }
 }
 
-enum FileType {
-    Stl,
-    Collada,
-    Obj,
-    Unknown,
+const GZIP_MAGIC: &[u8] = b"\x1f\x8b";
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+/// The number of bytes read from a reader to determine the file format.
+const HEAD_SIZE: usize = 4096;
+
+/// Returns the last registered importer of the given extension.
+fn find_by_extension<'a>(
+    importers: &'a [Box<dyn Importer>],
+    ext: &str,
+) -> Option<&'a dyn Importer> {
+    importers
+        .iter()
+        .rev()
+        .find(|i| i.extensions().iter().any(|e| ext.eq_ignore_ascii_case(e)))
+        .map(|i| &**i)
 }
 
-fn detect_file_type(path: &Path, bytes: &[u8]) -> FileType {
-    match path.extension().and_then(OsStr::to_str) {
-        Some("stl" | "STL") => return FileType::Stl,
-        Some("dae" | "DAE") => return FileType::Collada,
-        Some("obj" | "OBJ") => return FileType::Obj,
-        _ => {}
-    }
-    // Fallback: If failed to detect file type from extension,
-    // read the first 1024 bytes to detect the file type.
-    // TODO: rewrite based on what assimp does.
-    let mut s = &bytes[..cmp::min(bytes.len(), 1024)];
-    while let Some((&c, s_next)) = s.split_first() {
-        match c {
-            b's' => {
-                if starts_with(s_next, &b"solid"[1..]) {
-                    return FileType::Stl;
-                }
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .map_or(false, |ext| {
+            extensions.iter().any(|e| ext.eq_ignore_ascii_case(e))
+        })
+}
+
+/// Returns the root document of the given zip archive: the one specified in
+/// `manifest.xml` for COLLADA ZAE, otherwise the model file at the shallowest
+/// level.
+#[cfg(feature = "archive")]
+fn zip_root<'a>(
+    archive: &'a zip::Archive<'_>,
+    importers: &[Box<dyn Importer>],
+) -> io::Result<&'a zip::Entry> {
+    let manifest = archive
+        .entries()
+        .find(|e| e.name.eq_ignore_ascii_case("manifest.xml"));
+    if let Some(manifest) = manifest {
+        let manifest = archive.read(manifest)?;
+        let manifest = String::from_utf8_lossy(&manifest);
+        if let Some(start) = manifest.find("<dae_root>") {
+            let root = &manifest[start + "<dae_root>".len()..];
+            let root = &root[..root.find('<').unwrap_or(root.len())];
+            let root = percent_decode(root.trim());
+            let name = zip_entry_name(Path::new(&root));
+            return name
+                .and_then(|name| archive.entries().find(|e| e.name == name))
+                .ok_or_else(|| {
+                    crate::error::invalid_data(format!(
+                        "root document {root:?} specified in manifest.xml not found in zip archive"
+                    ))
+                });
+        }
+    }
+    archive
+        .entries()
+        .filter(|e| {
+            Path::new(&e.name)
+                .extension()
+                .and_then(OsStr::to_str)
+                .and_then(|ext| find_by_extension(importers, ext))
+                .is_some()
+        })
+        .min_by_key(|e| e.name.matches('/').count())
+        .ok_or_else(|| crate::error::invalid_data("no model file found in zip archive"))
+}
+
+/// Returns the name of the entry at the given path relative to the root of
+/// the archive, or `None` if the path is outside of the archive.
+#[cfg(feature = "archive")]
+fn zip_entry_name(path: &Path) -> Option<String> {
+    let mut components = vec![];
+    for component in path.components() {
+        match component {
+            Component::Normal(c) => components.push(c.to_str()?),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                components.pop()?;
             }
-            b'<' => {
-                // Compare whole s instead of s_next since needle.len() == 8
-                if starts_with(s, b"<COLLADA") {
-                    return FileType::Collada;
-                }
+            // Outside of the archive.
+            Component::Prefix(_) | Component::RootDir => return None,
+        }
+    }
+    Some(components.join("/"))
+}
+
+#[cfg(feature = "archive")]
+fn percent_decode(s: &str) -> String {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut s = s.as_bytes();
+    while let Some((&c, s_next)) = s.split_first() {
+        if c == b'%' {
+            if let Some(b) = s_next
+                .get(..2)
+                .and_then(|h| u8::from_str_radix(str::from_utf8(h).ok()?, 16).ok())
+            {
+                bytes.push(b);
+                s = &s_next[2..];
+                continue;
             }
-            _ => {}
         }
+        bytes.push(c);
         s = s_next;
     }
-    FileType::Unknown
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Reads the file given to the loader, resolving URIs in the path.
+fn read_model<'a, B>(path: &'a Path, read: &mut Read<'_, B>) -> io::Result<(B, Cow<'a, Path>)> {
+    let resolved = resolve_path_lossy(path);
+    let bytes = read(&Resource {
+        path: &resolved,
+        uri: path,
+        requester: None,
+        kind: ResourceKind::Model,
+    })?;
+    Ok((bytes, resolved))
 }
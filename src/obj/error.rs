@@ -17,6 +17,19 @@ impl ErrorKind {
     #[cold]
     #[inline(never)]
     pub(super) fn into_io_error(self, start: &[u8], path: Option<&Path>) -> io::Error {
+        self.into_io_error_at(start, path, 0)
+    }
+
+    /// Converts into an I/O error, where `start` is the input after the first
+    /// `lines` lines.
+    #[cold]
+    #[inline(never)]
+    pub(super) fn into_io_error_at(
+        self,
+        start: &[u8],
+        path: Option<&Path>,
+        lines: usize,
+    ) -> io::Error {
         let remaining = match self {
             Self::Expected(.., n)
             | Self::ExpectedNewline(.., n)
@@ -30,7 +43,7 @@ impl ErrorKind {
         };
         crate::error::with_location(
             &crate::error::invalid_data(self.to_string()),
-            &crate::error::Location::find(remaining, start, path),
+            &crate::error::Location::find(remaining, start, path).offset_lines(lines),
         )
     }
 }
@@ -7,21 +7,27 @@
 mod error;
 
 use std::{
+    borrow::Cow,
     collections::HashMap,
-    io, mem,
+    io::{self, Read as _},
+    mem,
     path::{Path, PathBuf},
     str,
+    sync::Arc,
 };
 
 use self::error::ErrorKind;
 use crate::{
     common,
+    loader::Read,
+    resolver::{self, Resource, ResourceKind},
     utils::{
-        bytes::{from_utf8_lossy, memchr_naive, memchr_naive_table, path_from_bytes, starts_with},
+        bytes::{from_utf8_lossy, memchr_naive, path_from_bytes, starts_with},
         float, int,
+        simd::{bytecount, count_spaces, memchr2},
         utf16::decode_bytes,
     },
-    Color4, Mesh, Scene, ShadingModel, Vec2, Vec3,
+    Color4, Mesh, MeshSummary, PointCloud, Scene, ShadingModel, Summary, Vec2, Vec3,
 };
 
 /// Parses meshes from bytes of Wavefront OBJ text.
@@ -29,61 +35,392 @@ pub fn from_slice<B: AsRef<[u8]>, F: FnMut(&Path) -> io::Result<B>>(
     bytes: &[u8],
     path: Option<&Path>,
     mut reader: F,
+) -> io::Result<Scene> {
+    let read: &mut Read<'_, B> = &mut |r| reader(r.path);
+    from_slice_internal(bytes, path, &mut |r| load_mtl(read, r))
+}
+
+/// Parses meshes from a reader of Wavefront OBJ text.
+///
+/// Unlike [`from_slice`], the input is parsed incrementally line by line, so
+/// the whole input is not kept in memory.
+pub fn from_reader<R: io::Read, B: AsRef<[u8]>, F: FnMut(&Path) -> io::Result<B>>(
+    mut input: R,
+    path: Option<&Path>,
+    mut reader: F,
+) -> io::Result<Scene> {
+    let read: &mut Read<'_, B> = &mut |r| reader(r.path);
+    from_reader_internal(&mut input, path, &mut |r| load_mtl(read, r))
+}
+
+pub(crate) fn from_slice_internal(
+    bytes: &[u8],
+    path: Option<&Path>,
+    load_mtl: &mut MtlLoader<'_>,
 ) -> io::Result<Scene> {
     // If it is UTF-16 with BOM, it is converted to UTF-8, otherwise it is parsed as bytes.
     // We don't require UTF-8 here, as we want to support files that are partially non-UTF-8 like:
     // https://github.com/assimp/assimp/blob/v5.3.1/test/models/OBJ/regr01.mtl#L67
     let bytes = &decode_bytes(bytes)?;
-    match read_obj(bytes, path, &mut |path, materials, material_map| {
-        match reader(path) {
-            Ok(bytes) => read_mtl(bytes.as_ref(), Some(path), materials, material_map),
-            // ignore reader error for now
-            // TODO: logging?
-            Err(_e) => Ok(()),
-        }
-    }) {
-        Ok((meshes, materials)) => {
-            let materials = meshes
-                .iter()
-                .map(|m| {
-                    materials
-                        .get(m.material_index as usize)
-                        .cloned()
-                        .unwrap_or_default()
-                })
-                .collect();
-            Ok(Scene { materials, meshes })
-        }
+    match read_obj(
+        bytes,
+        path,
+        &mut |mtl_path, uri, materials, material_map| {
+            append_mtl(load_mtl, path, mtl_path, uri, materials, material_map)
+        },
+    ) {
+        Ok((meshes, materials, point_clouds)) => Ok(scene(meshes, &materials, point_clouds)),
         Err(e) => Err(e.into_io_error(bytes, path)),
     }
 }
 
+/// The size of the chunks read from the reader.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+pub(crate) fn from_reader_internal(
+    input: &mut dyn io::Read,
+    path: Option<&Path>,
+    load_mtl: &mut MtlLoader<'_>,
+) -> io::Result<Scene> {
+    let mut buf = Vec::with_capacity(CHUNK_SIZE);
+    (&mut *input)
+        .take(CHUNK_SIZE as u64)
+        .read_to_end(&mut buf)?;
+    match decode_bytes(&buf) {
+        Ok(Cow::Borrowed(bytes)) => {
+            // Strip UTF-8 BOM.
+            let bom = buf.len() - bytes.len();
+            buf.drain(..bom);
+        }
+        // UTF-16 is converted as a whole.
+        _ => {
+            input.read_to_end(&mut buf)?;
+            return from_slice_internal(&buf, path, load_mtl);
+        }
+    }
+    let mut state = ObjState::new();
+    let mtl_reader: &mut MtlReader<'_> = &mut |mtl_path, uri, materials, material_map| {
+        append_mtl(load_mtl, path, mtl_path, uri, materials, material_map)
+    };
+    // The number of lines that have been parsed.
+    let mut lines = 0;
+    let mut eof = false;
+    loop {
+        // Parse the complete lines in the buffer, and keep the rest for the
+        // next chunk.
+        let n = if eof {
+            buf.len()
+        } else {
+            complete_lines_len(&buf)
+        };
+        let s = &buf[..n];
+        if let Err(e) = state.read(s, path, mtl_reader, None) {
+            return Err(e.into_io_error_at(s, path, lines));
+        }
+        lines += bytecount(b'\n', s);
+        buf.drain(..n);
+        if eof {
+            break;
+        }
+        eof = (&mut *input)
+            .take(CHUNK_SIZE as u64)
+            .read_to_end(&mut buf)?
+            == 0;
+    }
+    match state.finish() {
+        Ok((meshes, materials, point_clouds)) => Ok(scene(meshes, &materials, point_clouds)),
+        Err(e) => Err(e.into_io_error_at(&[], path, lines)),
+    }
+}
+
+/// Returns the length of the longest prefix of `s` that ends with a line
+/// break, excluding escaped line breaks.
+fn complete_lines_len(s: &[u8]) -> usize {
+    let mut end = s.len();
+    while let Some(n) = s[..end].iter().rposition(|&b| b == b'\n') {
+        let line = &s[..n];
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if !line.ends_with(b"\\") {
+            return n + 1;
+        }
+        end = n;
+    }
+    0
+}
+
+/// The materials parsed from an MTL file.
+#[derive(Debug, Default)]
+pub(crate) struct MtlLibrary {
+    materials: Vec<common::Material>,
+    material_map: HashMap<Vec<u8>, u32>,
+}
+
+/// Reads and parses an MTL file. Returns `None` if the file cannot be read.
+pub(crate) type MtlLoader<'a> =
+    dyn FnMut(&Resource<'_>) -> io::Result<Option<Arc<MtlLibrary>>> + 'a;
+
+/// Reads the given MTL file with `read` and parses it.
+pub(crate) fn load_mtl<B: AsRef<[u8]>>(
+    read: &mut Read<'_, B>,
+    resource: &Resource<'_>,
+) -> io::Result<Option<Arc<MtlLibrary>>> {
+    match read(resource) {
+        Ok(bytes) => {
+            let mut library = MtlLibrary::default();
+            read_mtl(
+                bytes.as_ref(),
+                Some(resource.path),
+                &mut library.materials,
+                &mut library.material_map,
+            )?;
+            Ok(Some(Arc::new(library)))
+        }
+        // Sandbox violations are reported.
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => Err(e),
+        // ignore reader error for now
+        // TODO: logging?
+        Err(_e) => Ok(None),
+    }
+}
+
+/// Loads the MTL file referenced by the OBJ file and appends its materials.
+fn append_mtl(
+    load_mtl: &mut MtlLoader<'_>,
+    obj_path: Option<&Path>,
+    mtl_path: &Path,
+    uri: &Path,
+    materials: &mut Vec<common::Material>,
+    material_map: &mut HashMap<Vec<u8>, u32>,
+) -> io::Result<()> {
+    let library = load_mtl(&Resource {
+        path: mtl_path,
+        uri,
+        requester: obj_path,
+        kind: ResourceKind::Material,
+    })?;
+    if let Some(library) = library {
+        // Same as parsing the MTL file after the materials read so far.
+        #[allow(clippy::cast_possible_truncation)]
+        let offset = materials.len() as u32;
+        materials.extend_from_slice(&library.materials);
+        for (name, &index) in &library.material_map {
+            material_map.insert(name.clone(), index + offset);
+        }
+    }
+    Ok(())
+}
+
+fn scene(
+    meshes: Vec<Mesh>,
+    materials: &[common::Material],
+    point_clouds: Vec<PointCloud>,
+) -> Scene {
+    let materials = meshes
+        .iter()
+        .map(|m| {
+            materials
+                .get(m.material_index as usize)
+                .cloned()
+                .unwrap_or_default()
+        })
+        .collect();
+    Scene {
+        materials,
+        meshes,
+        point_clouds,
+    }
+}
+
+/// A triangle passed to the callback of [`for_each_triangle`].
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct Triangle<'a> {
+    /// The positions of the vertices.
+    pub vertices: [Vec3; 3],
+    /// The normals of the vertices, if the face has them.
+    pub normals: Option<[Vec3; 3]>,
+    /// The texture coordinates of the vertices, if the face has them.
+    pub texcoords: Option<[Vec2; 3]>,
+    /// The colors of the vertices, if the file has vertex colors.
+    pub colors: Option<[Color4; 3]>,
+    /// The name of the group that contains the triangle.
+    pub group: &'a str,
+    /// The name of the material of the triangle, or empty if not specified.
+    pub material: &'a str,
+}
+
+type Visitor<'a> = dyn FnMut(Triangle<'_>) + 'a;
+
+/// Calls `f` for each triangle in the given bytes of Wavefront OBJ text,
+/// without building meshes.
+///
+/// Polygons are triangulated in the same way as [`from_slice`]. Only the
+/// vertex attributes are kept while parsing. Material libraries are not read;
+/// use the name of the material to look up the material.
+///
+/// # Example
+///
+/// ```
+/// let obj = b"v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nusemtl red\nf 1 2 3 4\n";
+/// let mut area = 0.;
+/// mesh_loader::obj::for_each_triangle(obj, |triangle| {
+///     assert_eq!(triangle.material, "red");
+///     let [a, b, c] = triangle.vertices;
+///     area += ((b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1])) / 2.;
+/// })?;
+/// assert_eq!(area, 1.);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn for_each_triangle<F: FnMut(Triangle<'_>)>(bytes: &[u8], mut f: F) -> io::Result<()> {
+    let bytes = &decode_bytes(bytes)?;
+    let mut state = ObjState::new();
+    state
+        .read(bytes, None, &mut |_, _, _, _| Ok(()), Some(&mut f))
+        .map_err(|e| e.into_io_error(bytes, None))
+}
+
+/// Returns a summary of the given bytes of Wavefront OBJ text.
+///
+/// This counts `v` and `f` lines per group and collects `mtllib`s, without
+/// building meshes or reading material libraries.
+pub(crate) fn probe(bytes: &[u8], path: Option<&Path>) -> io::Result<Summary> {
+    let bytes = &decode_bytes(bytes)?;
+    let mut s = &**bytes;
+    let mut summary = Summary::default();
+    let mut num_vertices = 0;
+    let mut group: &[u8] = b"default";
+    // The index of the mesh of the current group, if it has faces.
+    let mut mesh = None;
+    while let Some((&c, s_next)) = s.split_first() {
+        match c {
+            b'v' if matches!(s_next.first(), Some(b' ' | b'\t')) => {
+                s = s_next;
+                skip_spaces(&mut s);
+                let vertex = read_float3(&mut s, "v").map_err(|e| e.into_io_error(bytes, path))?;
+                summary.add_vertex(vertex);
+                num_vertices += 1;
+            }
+            b'f' => {
+                s = s_next;
+                if skip_spaces(&mut s) {
+                    let i = *mesh.get_or_insert_with(|| {
+                        summary.meshes.push(MeshSummary {
+                            name: from_utf8_lossy(group).into_owned(),
+                            num_faces: 0,
+                        });
+                        summary.meshes.len() - 1
+                    });
+                    summary.meshes[i].num_faces += 1;
+                }
+            }
+            b'm' => {
+                s = s_next;
+                if token(&mut s, &b"mtllib"[1..]) && skip_spaces(&mut s) {
+                    let (path, s_next) = name(s);
+                    if !path.is_empty() {
+                        summary.references.push(from_utf8_lossy(path).into_owned());
+                    }
+                    s = s_next;
+                    continue;
+                }
+            }
+            b'g' => {
+                s = s_next;
+                if skip_spaces(&mut s) {
+                    let (mut name, s_next) = name(s);
+                    if name.is_empty() {
+                        name = b"default";
+                    }
+                    if name != group {
+                        group = name;
+                        mesh = None;
+                    }
+                    s = s_next;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        skip_any_until_line(&mut s);
+    }
+    summary.num_vertices = Some(num_vertices);
+    Ok(summary)
+}
+
 // -----------------------------------------------------------------------------
 // OBJ
 
+type MtlReader<'a> = dyn FnMut(&Path, &Path, &mut Vec<common::Material>, &mut HashMap<Vec<u8>, u32>) -> io::Result<()>
+    + 'a;
+
 fn read_obj(
-    mut s: &[u8],
+    s: &[u8],
     obj_path: Option<&Path>,
-    reader: &mut dyn FnMut(
-        &Path,
-        &mut Vec<common::Material>,
-        &mut HashMap<Vec<u8>, u32>,
-    ) -> io::Result<()>,
-) -> Result<(Vec<Mesh>, Vec<common::Material>), ErrorKind> {
-    let mut meshes = Vec::with_capacity(1); // TODO: right default capacity?
+    reader: &mut MtlReader<'_>,
+) -> Result<(Vec<Mesh>, Vec<common::Material>, Vec<PointCloud>), ErrorKind> {
+    let mut state = ObjState::new();
+    #[cfg(feature = "rayon")]
+    {
+        state.parsed = read_attributes_parallel(s);
+    }
+    state.read(s, obj_path, reader, None)?;
+    state.finish()
+}
 
-    // TODO: use with_capacity
-    let mut vertices = vec![];
-    let mut normals = vec![];
-    let mut texcoords = vec![];
-    let mut colors = vec![];
-    let mut face = Vec::with_capacity(3);
-    let mut faces: Vec<Face> = vec![];
-    let mut current_group: &[u8] = b"default";
-    let mut current_material: &[u8] = &[];
-    let mut materials = vec![];
-    let mut material_map = HashMap::default();
+/// The vertex attributes of OBJ, parsed in parallel.
+#[cfg(feature = "rayon")]
+#[derive(Default)]
+struct Attributes {
+    vertices: Vec<Vec3>,
+    /// The vertex colors with the indices of their vertices.
+    colors: Vec<(usize, Vec3)>,
+    /// The index of the color taken next by `ObjState::read_v`.
+    next_color: usize,
+    normals: Vec<Vec3>,
+    texcoords: Vec<Vec2>,
+}
+
+/// Parses `v`, `vn`, and `vt` lines in parallel.
+///
+/// The input is split at line boundaries, and the attributes of the chunks
+/// are concatenated. Returns `None` if the input is small or has an error in
+/// these lines; in that case, the caller parses it sequentially, which
+/// returns the same error.
+#[cfg(feature = "rayon")]
+fn read_attributes_parallel(s: &[u8]) -> Option<Attributes> {
+    use rayon::prelude::*;
+
+    // Line breaks escaped by backslash are not line boundaries.
+    let chunks = crate::utils::par::split(s, |s, i| {
+        s[i] == b'\n' && s[i - 1] != b'\\' && !(s[i - 1] == b'\r' && s[i - 2] == b'\\')
+    });
+    if chunks.len() == 1 {
+        return None;
+    }
+    let chunks = chunks
+        .into_par_iter()
+        .map(|chunk| read_attributes(chunk).ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut attributes = Attributes::default();
+    for chunk in chunks {
+        let offset = attributes.vertices.len();
+        attributes.vertices.extend_from_slice(&chunk.vertices);
+        attributes
+            .colors
+            .extend(chunk.colors.iter().map(|&(i, color)| (offset + i, color)));
+        attributes.normals.extend_from_slice(&chunk.normals);
+        attributes.texcoords.extend_from_slice(&chunk.texcoords);
+    }
+    Some(attributes)
+}
 
+/// Parses `v`, `vn`, and `vt` lines in the given lines, and skips other
+/// lines in the same way as `ObjState::read`.
+#[cfg(feature = "rayon")]
+fn read_attributes(mut s: &[u8]) -> Result<Attributes, ErrorKind> {
+    let mut attributes = Attributes::default();
+    let mut color = vec![];
     while let Some((&c, s_next)) = s.split_first() {
         match c {
             b'v' => {
@@ -91,133 +428,319 @@ fn read_obj(
                 match s.first() {
                     Some(b' ' | b'\t') => {
                         skip_spaces(&mut s);
-                        read_v(&mut s, &mut vertices, &mut colors)?;
-                        if !colors.is_empty() && colors.len() < vertices.len() {
-                            colors.resize(vertices.len(), [0.; 3]);
+                        read_v(&mut s, &mut attributes.vertices, &mut color)?;
+                        if let Some(color) = color.pop() {
+                            let i = attributes.vertices.len() - 1;
+                            attributes.colors.push((i, color));
                         }
                         continue;
                     }
                     Some(b'n') => {
                         s = &s[1..];
                         if skip_spaces(&mut s) {
-                            read_vn(&mut s, &mut normals)?;
+                            read_vn(&mut s, &mut attributes.normals)?;
                             continue;
                         }
                     }
                     Some(b't') => {
                         s = &s[1..];
                         if skip_spaces(&mut s) {
-                            read_vt(&mut s, &mut texcoords)?;
+                            read_vt(&mut s, &mut attributes.texcoords)?;
                             continue;
                         }
                     }
-                    // ignore vp or other unknown
                     _ => {}
                 }
             }
             b'f' => {
                 s = s_next;
                 if skip_spaces(&mut s) {
-                    read_f(
-                        &mut s, &mut faces, &mut face, &vertices, &texcoords, &normals,
-                    )?;
+                    // Unlike other lines, read_f does not handle escaped line breaks.
+                    s = match memchr2(b'\n', b'\r', s) {
+                        Some(n) => &s[n + 1..],
+                        None => &[],
+                    };
                     continue;
                 }
             }
-            b'u' => {
-                s = s_next;
-                if token(&mut s, &b"usemtl"[1..]) {
+            _ => {}
+        }
+        skip_any_until_line(&mut s);
+    }
+    Ok(attributes)
+}
+
+/// The state of the OBJ parser.
+///
+/// This is kept between chunks when reading from a reader, so the input given
+/// to `read` must end at the end of a line.
+struct ObjState {
+    meshes: Vec<Mesh>,
+    // TODO: use with_capacity
+    vertices: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    texcoords: Vec<Vec2>,
+    colors: Vec<Vec3>,
+    face: Vec<[u32; 3]>,
+    faces: Vec<Face>,
+    current_group: Vec<u8>,
+    current_material: Vec<u8>,
+    materials: Vec<common::Material>,
+    material_map: HashMap<Vec<u8>, u32>,
+    /// The vertex attributes parsed in advance. If this is set, `read` takes
+    /// the attributes from here instead of parsing `v`, `vn`, and `vt` lines.
+    #[cfg(feature = "rayon")]
+    parsed: Option<Attributes>,
+}
+
+impl ObjState {
+    fn new() -> Self {
+        Self {
+            meshes: Vec::with_capacity(1), // TODO: right default capacity?
+            vertices: vec![],
+            normals: vec![],
+            texcoords: vec![],
+            colors: vec![],
+            face: Vec::with_capacity(3),
+            faces: vec![],
+            current_group: b"default".to_vec(),
+            current_material: vec![],
+            materials: vec![],
+            material_map: HashMap::default(),
+            #[cfg(feature = "rayon")]
+            parsed: None,
+        }
+    }
+
+    fn read_v(&mut self, s: &mut &[u8]) -> Result<(), ErrorKind> {
+        #[cfg(feature = "rayon")]
+        if let Some(parsed) = &mut self.parsed {
+            let i = self.vertices.len();
+            self.vertices.push(parsed.vertices[i]);
+            if let Some(&(j, color)) = parsed.colors.get(parsed.next_color) {
+                if i == j {
+                    self.colors.push(color);
+                    parsed.next_color += 1;
+                }
+            }
+            skip_any_until_line(s);
+            return Ok(());
+        }
+        skip_spaces(s);
+        read_v(s, &mut self.vertices, &mut self.colors)
+    }
+
+    fn read_vn(&mut self, s: &mut &[u8]) -> Result<(), ErrorKind> {
+        #[cfg(feature = "rayon")]
+        if let Some(parsed) = &self.parsed {
+            self.normals.push(parsed.normals[self.normals.len()]);
+            skip_any_until_line(s);
+            return Ok(());
+        }
+        read_vn(s, &mut self.normals)
+    }
+
+    fn read_vt(&mut self, s: &mut &[u8]) -> Result<(), ErrorKind> {
+        #[cfg(feature = "rayon")]
+        if let Some(parsed) = &self.parsed {
+            self.texcoords.push(parsed.texcoords[self.texcoords.len()]);
+            skip_any_until_line(s);
+            return Ok(());
+        }
+        read_vt(s, &mut self.texcoords)
+    }
+
+    fn read(
+        &mut self,
+        mut s: &[u8],
+        obj_path: Option<&Path>,
+        reader: &mut MtlReader<'_>,
+        mut visitor: Option<&mut Visitor<'_>>,
+    ) -> Result<(), ErrorKind> {
+        while let Some((&c, s_next)) = s.split_first() {
+            match c {
+                b'v' => {
+                    s = s_next;
+                    match s.first() {
+                        Some(b' ' | b'\t') => {
+                            self.read_v(&mut s)?;
+                            if !self.colors.is_empty() && self.colors.len() < self.vertices.len() {
+                                self.colors.resize(self.vertices.len(), [0.; 3]);
+                            }
+                            continue;
+                        }
+                        Some(b'n') => {
+                            s = &s[1..];
+                            if skip_spaces(&mut s) {
+                                self.read_vn(&mut s)?;
+                                continue;
+                            }
+                        }
+                        Some(b't') => {
+                            s = &s[1..];
+                            if skip_spaces(&mut s) {
+                                self.read_vt(&mut s)?;
+                                continue;
+                            }
+                        }
+                        // ignore vp or other unknown
+                        _ => {}
+                    }
+                }
+                b'f' => {
+                    s = s_next;
                     if skip_spaces(&mut s) {
-                        let (name, s_next) = name(s);
-                        if name != current_material {
-                            let material_index = material_map.get(current_material).copied();
-                            push_mesh(
-                                &mut meshes,
-                                &mut faces,
-                                &vertices,
-                                &texcoords,
-                                &normals,
-                                &colors,
-                                current_group,
-                                material_index,
-                            )?;
-                            current_material = name;
+                        read_f(
+                            &mut s,
+                            &mut self.faces,
+                            &mut self.face,
+                            &self.vertices,
+                            &self.texcoords,
+                            &self.normals,
+                        )?;
+                        if let Some(visit) = &mut visitor {
+                            self.visit_faces(&mut **visit, s.len())?;
                         }
-                        s = s_next;
                         continue;
                     }
                 }
-            }
-            b'm' => {
-                s = s_next;
-                if token(&mut s, &b"mtllib"[1..]) {
-                    if skip_spaces(&mut s) {
-                        let (path, s_next) = name(s);
-                        let path = if path.is_empty() {
-                            None
-                        } else {
-                            path_from_bytes(path).ok()
-                        };
-                        if let Some(path) = path {
-                            match obj_path.and_then(Path::parent) {
-                                Some(parent) => {
-                                    reader(&parent.join(path), &mut materials, &mut material_map)
+                b'u' => {
+                    s = s_next;
+                    if token(&mut s, &b"usemtl"[1..]) {
+                        if skip_spaces(&mut s) {
+                            let (name, s_next) = name(s);
+                            if name != self.current_material {
+                                self.push_mesh()?;
+                                self.current_material.clear();
+                                self.current_material.extend_from_slice(name);
+                            }
+                            s = s_next;
+                            continue;
+                        }
+                    }
+                }
+                b'm' => {
+                    s = s_next;
+                    if token(&mut s, &b"mtllib"[1..]) {
+                        if skip_spaces(&mut s) {
+                            let (path, s_next) = name(s);
+                            let path = if path.is_empty() {
+                                None
+                            } else {
+                                path_from_bytes(path).ok()
+                            };
+                            if let Some(path) = path {
+                                match obj_path.and_then(Path::parent) {
+                                    Some(parent) => {
+                                        reader(
+                                            &resolver::join(parent, path),
+                                            path,
+                                            &mut self.materials,
+                                            &mut self.material_map,
+                                        )
                                         .map_err(ErrorKind::Io)?;
+                                    }
+                                    None => {} // ignored
                                 }
-                                None => {} // ignored
                             }
+                            s = s_next;
+                            continue;
+                        }
+                    }
+                    // ignore mg or other unknown
+                }
+                b'g' => {
+                    s = s_next;
+                    if skip_spaces(&mut s) {
+                        let (mut name, s_next) = name(s);
+                        if name.is_empty() {
+                            name = b"default";
+                        }
+                        if name != self.current_group {
+                            self.push_mesh()?;
+                            self.current_material.clear();
+                            self.current_group.clear();
+                            self.current_group.extend_from_slice(name);
                         }
                         s = s_next;
                         continue;
                     }
                 }
-                // ignore mg or other unknown
+                _ => {}
             }
-            b'g' => {
-                s = s_next;
-                if skip_spaces(&mut s) {
-                    let (mut name, s_next) = name(s);
-                    if name.is_empty() {
-                        name = b"default";
-                    }
-                    if name != current_group {
-                        let material_index = material_map.get(current_material).copied();
-                        push_mesh(
-                            &mut meshes,
-                            &mut faces,
-                            &vertices,
-                            &texcoords,
-                            &normals,
-                            &colors,
-                            current_group,
-                            material_index,
-                        )?;
-                        current_material = &[];
-                        current_group = name;
-                    }
-                    s = s_next;
-                    continue;
-                }
+            // ignore comment, p, l, s, mg, o, or other unknown
+            skip_any_until_line(&mut s);
+        }
+        Ok(())
+    }
+
+    /// Passes the faces read so far to the callback of `for_each_triangle`.
+    fn visit_faces(&mut self, visit: &mut Visitor<'_>, remaining: usize) -> Result<(), ErrorKind> {
+        let group = from_utf8_lossy(&self.current_group);
+        let material = from_utf8_lossy(&self.current_material);
+        for face in &self.faces {
+            let face = match face {
+                Face::Point(_) | Face::Line(_) => continue, // ignored
+                Face::Triangle(face) => &face[..],
+                Face::Polygon(face) => face,
+            };
+            let a = face[0];
+            for bc in face[1..].windows(2) {
+                let triangle = [a, bc[0], bc[1]];
+                let vertices = attribute(&self.vertices, triangle, 0, remaining)?
+                    .ok_or(ErrorKind::Oob(a[0] as usize, remaining))?;
+                let colors = attribute(&self.colors, triangle, 0, remaining)?;
+                visit(Triangle {
+                    vertices,
+                    normals: attribute(&self.normals, triangle, 2, remaining)?,
+                    texcoords: attribute(&self.texcoords, triangle, 1, remaining)?,
+                    // a is 1 by default: https://github.com/assimp/assimp/blob/v5.3.1/code/AssetLib/Obj/ObjFileImporter.cpp#L233
+                    colors: colors.map(|c| c.map(|[r, g, b]| [r, g, b, 1.])),
+                    group: &group,
+                    material: &material,
+                });
             }
-            _ => {}
         }
-        // ignore comment, p, l, s, mg, o, or other unknown
-        skip_any_until_line(&mut s);
+        self.faces.clear();
+        Ok(())
     }
 
-    let material_index = material_map.get(current_material).copied();
-    push_mesh(
-        &mut meshes,
-        &mut faces,
-        &vertices,
-        &texcoords,
-        &normals,
-        &colors,
-        current_group,
-        material_index,
-    )?;
+    fn push_mesh(&mut self) -> Result<(), ErrorKind> {
+        let material_index = self.material_map.get(&self.current_material).copied();
+        push_mesh(
+            &mut self.meshes,
+            &mut self.faces,
+            &self.vertices,
+            &self.texcoords,
+            &self.normals,
+            &self.colors,
+            &self.current_group,
+            material_index,
+        )
+    }
 
-    Ok((meshes, materials))
+    fn finish(mut self) -> Result<(Vec<Mesh>, Vec<common::Material>, Vec<PointCloud>), ErrorKind> {
+        self.push_mesh()?;
+
+        // Files that only have vertices (e.g., point clouds exported as OBJ) are
+        // loaded as a point cloud.
+        let mut point_clouds = vec![];
+        if self.meshes.is_empty() && !self.vertices.is_empty() {
+            point_clouds.push(PointCloud {
+                name: from_utf8_lossy(&self.current_group).into_owned(),
+                colors: self.colors.iter().map(|&[r, g, b]| [r, g, b, 1.]).collect(),
+                normals: if self.normals.len() == self.vertices.len() {
+                    self.normals
+                } else {
+                    vec![]
+                },
+                points: self.vertices,
+                ..Default::default()
+            });
+        }
+
+        Ok((self.meshes, self.materials, point_clouds))
+    }
 }
 
 #[inline(always)]
@@ -360,7 +883,7 @@ fn read_f(
 ) -> Result<(), ErrorKind> {
     // f <v1>/[vt1]/[vn1] <v2>/[vt2]/[vn2] <v3>/[vt3]/[vn3] ...
     let mut f;
-    match memchr_naive_table(LINE, &TABLE, s) {
+    match memchr2(b'\n', b'\r', s) {
         Some(n) => {
             f = &s[..n];
             *s = &s[n + 1..];
@@ -372,7 +895,7 @@ fn read_f(
     }
     while !f.is_empty() {
         let mut w;
-        let f_next = match memchr_naive_table(SPACE, &TABLE, f) {
+        let f_next = match memchr2(b' ', b'\t', f) {
             Some(n) => {
                 w = &f[..n];
                 &f[n + 1..]
@@ -567,6 +1090,29 @@ fn read_float1(s: &mut &[u8], expected: &'static str) -> Result<f32, ErrorKind>
     }
 }
 
+/// Returns the `i`-th attribute (0: vertex, 1: texcoord, 2: normal) of the
+/// vertices of the triangle, or `None` if the vertices don't have it.
+#[inline]
+fn attribute<T: Copy>(
+    values: &[T],
+    triangle: [[u32; 3]; 3],
+    i: usize,
+    remaining: usize,
+) -> Result<Option<[T; 3]>, ErrorKind> {
+    if values.is_empty() || triangle.iter().all(|vert| vert[i] == u32::MAX) {
+        return Ok(None);
+    }
+    let mut attribute = [values[0]; 3];
+    for (a, vert) in attribute.iter_mut().zip(triangle) {
+        if vert[i] == u32::MAX {
+            return Err(ErrorKind::InvalidFaceIndex(remaining));
+        }
+        let idx = vert[i] as usize;
+        *a = *values.get(idx).ok_or(ErrorKind::Oob(idx, remaining))?;
+    }
+    Ok(Some(attribute))
+}
+
 #[inline(always)]
 fn push_vertex(
     mesh: &mut Mesh,
@@ -1096,15 +1642,7 @@ fn push_material(
                 }
                 p = p.strip_prefix(b"./").unwrap_or(p);
                 let p = path_from_bytes(p).ok()?;
-                let p = mtl_dir.join(p);
-                if p.to_str().map_or(false, |s| {
-                    s.starts_with("https://") || p.starts_with("http://")
-                }) || p.exists()
-                {
-                    Some(p)
-                } else {
-                    None
-                }
+                Some(resolver::join(mtl_dir, p))
             }
             None => {
                 let p = path_from_bytes(p).ok()?.to_owned();
@@ -1297,12 +1835,13 @@ fn skip_spaces_until_line(s: &mut &[u8]) -> bool {
 #[inline]
 fn skip_spaces(s: &mut &[u8]) -> bool {
     let start = *s;
-    while let Some((&b, s_next)) = s.split_first() {
-        if TABLE[b as usize] & SPACE != 0 {
-            *s = s_next;
-            continue;
-        }
-        if b == b'\\' && matches!(s_next.first(), Some(b'\n' | b'\r')) {
+    loop {
+        *s = &s[count_spaces(s)..];
+        let s_next = match s.split_first() {
+            Some((b'\\', s_next)) => s_next,
+            _ => break,
+        };
+        if matches!(s_next.first(), Some(b'\n' | b'\r')) {
             if s_next.starts_with(b"\r\n") {
                 *s = &s_next[2..];
             } else {
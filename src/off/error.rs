@@ -0,0 +1,49 @@
+use std::{fmt, io, path::Path};
+
+#[cfg_attr(test, derive(Debug))]
+pub(super) enum ErrorKind {
+    ExpectedSpace(&'static str, usize),
+    Expected(&'static str, usize),
+    Float(usize),
+    Int(usize),
+    InvalidValueCount(&'static str, usize),
+    Oob(usize, usize),
+    Unsupported(&'static str, usize),
+}
+
+impl ErrorKind {
+    #[cold]
+    #[inline(never)]
+    pub(super) fn into_io_error(self, start: &[u8], path: Option<&Path>) -> io::Error {
+        let remaining = match self {
+            Self::Expected(.., n)
+            | Self::ExpectedSpace(.., n)
+            | Self::Float(n)
+            | Self::Int(n)
+            | Self::InvalidValueCount(.., n)
+            | Self::Oob(.., n)
+            | Self::Unsupported(.., n) => n,
+        };
+        let e = if let Self::Unsupported(..) = self {
+            io::Error::new(io::ErrorKind::Unsupported, self.to_string())
+        } else {
+            crate::error::invalid_data(self.to_string())
+        };
+        crate::error::with_location(&e, &crate::error::Location::find(remaining, start, path))
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    #[cold]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::ExpectedSpace(msg, ..) => write!(f, "expected space after {msg}"),
+            Self::Expected(msg, ..) => write!(f, "expected {msg}"),
+            Self::Float(..) => f.write_str("error while parsing a float"),
+            Self::Int(..) => f.write_str("error while parsing an integer"),
+            Self::InvalidValueCount(msg, ..) => write!(f, "invalid number of values in {msg}"),
+            Self::Oob(i, ..) => write!(f, "face index out of bounds ({i})"),
+            Self::Unsupported(msg, ..) => write!(f, "{msg} is not supported"),
+        }
+    }
+}
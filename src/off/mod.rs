@@ -0,0 +1,363 @@
+//! [OFF] (.off) parser.
+//!
+//! In addition to plain OFF, this supports the following variants that are
+//! commonly used in research datasets such as ModelNet and the Princeton Shape
+//! Benchmark:
+//!
+//! - `COFF`: per-vertex colors
+//! - `NOFF`: per-vertex normals
+//! - `STOFF`: per-vertex texture coordinates
+//! - per-face colors (color values after the vertex indices of a face)
+//!
+//! Binary OFF and higher dimensional variants (`4OFF`, `nOFF`) are not
+//! supported.
+//!
+//! [OFF]: https://en.wikipedia.org/wiki/OFF_(file_format)
+
+#![allow(clippy::many_single_char_names)]
+
+mod error;
+
+use std::{io, mem, path::Path};
+
+use self::error::ErrorKind;
+use crate::{
+    common::DEFAULT_COLOR,
+    utils::{bytes::starts_with, float, int},
+    Color4, Material, Mesh, Scene, Vec2, Vec3,
+};
+
+/// Parses meshes from bytes of OFF text.
+#[inline]
+pub fn from_slice(bytes: &[u8]) -> io::Result<Scene> {
+    from_slice_internal(bytes, None)
+}
+
+pub(crate) fn from_slice_internal(bytes: &[u8], path: Option<&Path>) -> io::Result<Scene> {
+    match read_off(bytes) {
+        Ok(mesh) => Ok(Scene {
+            materials: vec![Material::default()],
+            meshes: vec![mesh],
+            ..Default::default()
+        }),
+        Err(e) => Err(e.into_io_error(bytes, path)),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// OFF
+
+/*
+https://people.sc.fsu.edu/~jburkardt/data/off/off.html
+http://www.geomview.org/docs/html/OFF.html
+
+[ST][C][N]OFF
+<number of vertices> <number of faces> [number of edges]
+<x> <y> <z> [<nx> <ny> <nz>] [<r> <g> <b> [a]] [<s> <t>]
+...
+<n> <v1> <v2> ... <vn> [<r> <g> <b> [a]]
+...
+*/
+fn read_off(mut s: &[u8]) -> Result<Mesh, ErrorKind> {
+    skip_blank_lines(&mut s);
+    let header = read_keyword(&mut s)?;
+    skip_spaces(&mut s);
+    if starts_with(s, b"BINARY") {
+        return Err(ErrorKind::Unsupported("binary OFF", s.len()));
+    }
+
+    // <number of vertices> <number of faces> [number of edges]
+    skip_blank_lines(&mut s);
+    let num_vertices = read_int(&mut s)? as usize;
+    if !skip_spaces(&mut s) {
+        return Err(ErrorKind::ExpectedSpace("number of vertices", s.len()));
+    }
+    let num_faces = read_int(&mut s)? as usize;
+    // The number of edges is optional and can be ignored.
+    skip_any_until_line(&mut s);
+
+    // Do not trust the counts in the header for the initial capacity, as
+    // a broken file may contain a huge count.
+    let mut vertices: Vec<Vec3> = Vec::with_capacity(num_vertices.min(1 << 16));
+    let mut normals: Vec<Vec3> = vec![];
+    let mut colors: Vec<Color4> = vec![];
+    let mut texcoords: Vec<Vec2> = vec![];
+    let mut values = Vec::with_capacity(12);
+    let mut is_int = Vec::with_capacity(12);
+    for _ in 0..num_vertices {
+        skip_blank_lines(&mut s);
+        let remaining = s.len();
+        read_floats(&mut s, &mut values, &mut is_int)?;
+        let mut v = &values[..];
+        let min_len = 3 + header.normals as usize * 3 + header.texcoords as usize * 2;
+        if v.len() < min_len || !header.colors && v.len() != min_len {
+            return Err(ErrorKind::InvalidValueCount("vertex", remaining));
+        }
+        vertices.push([v[0], v[1], v[2]]);
+        v = &v[3..];
+        if header.normals {
+            normals.push([v[0], v[1], v[2]]);
+            v = &v[3..];
+        }
+        if header.colors {
+            let color_len = v.len() - header.texcoords as usize * 2;
+            let color_start = values.len() - v.len();
+            match read_color(
+                &v[..color_len],
+                &is_int[color_start..color_start + color_len],
+            ) {
+                Some(color) => colors.push(color),
+                None => return Err(ErrorKind::InvalidValueCount("vertex color", remaining)),
+            }
+            v = &v[color_len..];
+        }
+        if header.texcoords {
+            texcoords.push([v[0], v[1]]);
+        }
+    }
+
+    let mut mesh = Mesh::default();
+    let mut face = Vec::with_capacity(4);
+    let mut has_face_colors = false;
+    for _ in 0..num_faces {
+        skip_blank_lines(&mut s);
+        if s.is_empty() {
+            return Err(ErrorKind::Expected("face", s.len()));
+        }
+        // <n> <v1> <v2> ... <vn>
+        let n = read_int(&mut s)?;
+        face.clear();
+        for _ in 0..n {
+            if !skip_spaces(&mut s) {
+                return Err(ErrorKind::ExpectedSpace("face", s.len()));
+            }
+            let remaining = s.len();
+            let i = read_int(&mut s)?;
+            if i as usize >= vertices.len() {
+                return Err(ErrorKind::Oob(i as usize, remaining));
+            }
+            face.push(i);
+        }
+        // [<r> <g> <b> [a]]
+        let remaining = s.len();
+        read_floats(&mut s, &mut values, &mut is_int)?;
+        let face_color = match values.len() {
+            // A single value is an index into the colormap, which we don't support.
+            0 | 1 => None,
+            _ => match read_color(&values, &is_int) {
+                Some(color) => Some(color),
+                None => return Err(ErrorKind::InvalidValueCount("face color", remaining)),
+            },
+        };
+        if face.len() < 3 {
+            // ignore points and lines
+            continue;
+        }
+        if colors.is_empty() && face_color.is_some() && !has_face_colors {
+            // This is the first face with color. A vertex can have a different
+            // color for each face that uses it, so from now on vertices are not
+            // shared between faces. The previous faces get the default color.
+            has_face_colors = true;
+            for f in mem::take(&mut mesh.faces) {
+                push_unshared_face(&mut mesh, f, &vertices, &normals, &texcoords, DEFAULT_COLOR);
+            }
+        }
+        // Triangulate polygons in the same way as OBJ.
+        let a = face[0];
+        let mut b = face[1];
+        for &c in &face[2..] {
+            if has_face_colors {
+                let color = face_color.unwrap_or(DEFAULT_COLOR);
+                push_unshared_face(&mut mesh, [a, b, c], &vertices, &normals, &texcoords, color);
+            } else {
+                mesh.faces.push([a, b, c]);
+            }
+            b = c;
+        }
+    }
+
+    if !has_face_colors {
+        mesh.vertices = vertices;
+        mesh.normals = normals;
+        mesh.texcoords[0] = texcoords;
+        mesh.colors[0] = colors;
+    }
+    Ok(mesh)
+}
+
+/// Pushes a triangle with its own copies of the given vertices.
+fn push_unshared_face(
+    mesh: &mut Mesh,
+    face: [u32; 3],
+    vertices: &[Vec3],
+    normals: &[Vec3],
+    texcoords: &[Vec2],
+    color: Color4,
+) {
+    #[allow(clippy::cast_possible_truncation)]
+    let start = mesh.vertices.len() as u32;
+    for i in face {
+        let i = i as usize;
+        mesh.vertices.push(vertices[i]);
+        if !normals.is_empty() {
+            mesh.normals.push(normals[i]);
+        }
+        if !texcoords.is_empty() {
+            mesh.texcoords[0].push(texcoords[i]);
+        }
+        mesh.colors[0].push(color);
+    }
+    mesh.faces.push([start, start + 1, start + 2]);
+}
+
+struct Header {
+    texcoords: bool,
+    colors: bool,
+    normals: bool,
+}
+
+fn read_keyword(s: &mut &[u8]) -> Result<Header, ErrorKind> {
+    let start = *s;
+    let texcoords = token(s, b"ST");
+    let colors = token(s, b"C");
+    let normals = token(s, b"N");
+    if token(s, b"4") {
+        return Err(ErrorKind::Unsupported("4D OFF", start.len()));
+    }
+    if token(s, b"n") {
+        return Err(ErrorKind::Unsupported("n-dimensional OFF", start.len()));
+    }
+    if !token(s, b"OFF") {
+        return Err(ErrorKind::Expected("OFF", start.len()));
+    }
+    match s.first() {
+        Some(b' ' | b'\t' | b'\n' | b'\r' | b'#') | None => {}
+        // Some files (e.g., ModelNet) have no line break between OFF and the counts.
+        Some(b'0'..=b'9') => {}
+        _ => return Err(ErrorKind::Expected("OFF", start.len())),
+    }
+    Ok(Header {
+        texcoords,
+        colors,
+        normals,
+    })
+}
+
+/// Converts 3 or 4 color components to RGBA.
+///
+/// The components are either integers in the range 0-255 or floats in
+/// the range 0-1, which are distinguished by whether all of them are written
+/// as integer literals (so `1 1 1` is almost black and `1.0 1.0 1.0` is white).
+fn read_color(v: &[f32], is_int: &[bool]) -> Option<Color4> {
+    const INV_BYTE: f32 = 1. / 255.;
+    let mut color = match *v {
+        [r, g, b] => [r, g, b, 1.],
+        [r, g, b, a] => [r, g, b, a],
+        _ => return None,
+    };
+    if is_int.iter().all(|&i| i) {
+        for c in &mut color[..v.len()] {
+            *c *= INV_BYTE;
+        }
+    }
+    Some(color)
+}
+
+fn read_int(s: &mut &[u8]) -> Result<u32, ErrorKind> {
+    match int::parse_partial::<u32>(s) {
+        Some((i, n)) => {
+            *s = &s[n..];
+            Ok(i)
+        }
+        None => Err(ErrorKind::Int(s.len())),
+    }
+}
+
+/// Reads floats until the end of the line (consumes line character).
+///
+/// `is_int` is set to whether each value is written as an integer literal.
+fn read_floats(
+    s: &mut &[u8],
+    values: &mut Vec<f32>,
+    is_int: &mut Vec<bool>,
+) -> Result<(), ErrorKind> {
+    values.clear();
+    is_int.clear();
+    loop {
+        skip_spaces(s);
+        match s.first() {
+            Some(b'\n' | b'\r') => {
+                *s = &s[1..];
+                return Ok(());
+            }
+            Some(b'#') => {
+                skip_any_until_line(s);
+                return Ok(());
+            }
+            None => return Ok(()),
+            _ => {}
+        }
+        match float::parse_partial::<f32>(s) {
+            Some((f, n)) => {
+                values.push(f);
+                is_int.push(
+                    s[..n]
+                        .iter()
+                        .all(|&b| matches!(b, b'0'..=b'9' | b'+' | b'-')),
+                );
+                *s = &s[n..];
+            }
+            None => return Err(ErrorKind::Float(s.len())),
+        }
+        match s.first() {
+            Some(b' ' | b'\t' | b'\n' | b'\r' | b'#') | None => {}
+            _ => return Err(ErrorKind::ExpectedSpace("float", s.len())),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Helpers
+
+/// Skips spaces or tabs, and returns `true` if one or more spaces or tabs are
+/// present.
+#[inline]
+fn skip_spaces(s: &mut &[u8]) -> bool {
+    let start = *s;
+    while let Some((&b' ' | &b'\t', s_next)) = s.split_first() {
+        *s = s_next;
+    }
+    start.len() != s.len()
+}
+
+/// Skips whitespaces, line characters, and comments.
+fn skip_blank_lines(s: &mut &[u8]) {
+    while let Some((&b, s_next)) = s.split_first() {
+        match b {
+            b' ' | b'\t' | b'\n' | b'\r' => *s = s_next,
+            b'#' => skip_any_until_line(s),
+            _ => break,
+        }
+    }
+}
+
+/// Skips non-line (non-`[\r\n]`) characters. (consumes line character).
+#[inline]
+fn skip_any_until_line(s: &mut &[u8]) {
+    while let Some((&b, s_next)) = s.split_first() {
+        *s = s_next;
+        if let b'\n' | b'\r' = b {
+            break;
+        }
+    }
+}
+
+#[inline]
+fn token(s: &mut &[u8], token: &'static [u8]) -> bool {
+    if starts_with(s, token) {
+        *s = &s[token.len()..];
+        true
+    } else {
+        false
+    }
+}
@@ -0,0 +1,514 @@
+//! [PCD] (.pcd) parser.
+//!
+//! All data formats used by PCL (`ascii`, `binary`, and `binary_compressed`)
+//! are supported. The `x`, `y`, `z`, `normal_x`, `normal_y`, `normal_z`,
+//! `rgb`, `rgba`, and `intensity` fields are read, and other fields are
+//! ignored. Points with NaN coordinates (invalid points in organized point
+//! clouds) are skipped. `VIEWPOINT` is not applied.
+//!
+//! [PCD]: https://pointclouds.org/documentation/tutorials/pcd_file_format.html
+
+mod error;
+
+use std::{io, path::Path};
+
+use self::error::ErrorKind;
+use crate::{
+    utils::{float, int},
+    PointCloud, Scene,
+};
+
+/// Parses a point cloud from bytes of PCD.
+#[inline]
+pub fn from_slice(bytes: &[u8]) -> io::Result<Scene> {
+    from_slice_internal(bytes, None)
+}
+
+pub(crate) fn from_slice_internal(bytes: &[u8], path: Option<&Path>) -> io::Result<Scene> {
+    let mut s = bytes;
+    let header = read_header(&mut s).map_err(|e| e.into_io_error(bytes, path))?;
+    let point_cloud = match header.data {
+        Data::Ascii => read_ascii(s, &header).map_err(|e| e.into_io_error(bytes, path))?,
+        Data::Binary => read_binary(s, &header)?,
+        Data::BinaryCompressed => read_binary_compressed(s, &header)?,
+    };
+    Ok(Scene {
+        point_clouds: vec![point_cloud],
+        ..Default::default()
+    })
+}
+
+// -----------------------------------------------------------------------------
+// Header
+
+/*
+https://pointclouds.org/documentation/tutorials/pcd_file_format.html
+
+# .PCD v0.7 - Point Cloud Data file format
+VERSION 0.7
+FIELDS x y z rgb
+SIZE 4 4 4 4
+TYPE F F F F
+COUNT 1 1 1 1
+WIDTH 213
+HEIGHT 1
+VIEWPOINT 0 0 0 1 0 0 0
+POINTS 213
+DATA ascii
+0.93773 0.33763 0 4.2108e+06
+...
+*/
+#[derive(Clone, Copy)]
+enum Data {
+    Ascii,
+    Binary,
+    BinaryCompressed,
+}
+
+struct Field<'a> {
+    name: &'a [u8],
+    size: usize,
+    ty: u8,
+    count: usize,
+    /// `true` if this is a packed color field (`rgb` or `rgba`).
+    is_color: bool,
+}
+
+struct Header<'a> {
+    fields: Vec<Field<'a>>,
+    points: usize,
+    data: Data,
+    layout: Layout,
+}
+
+/// Indices of the fields that are read.
+struct Layout {
+    xyz: [usize; 3],
+    normal: Option<[usize; 3]>,
+    /// The color field and whether it has an alpha channel.
+    color: Option<(usize, bool)>,
+    intensity: Option<usize>,
+}
+
+fn read_header<'a>(s: &mut &'a [u8]) -> Result<Header<'a>, ErrorKind> {
+    let mut tokens = vec![];
+    let mut names = vec![];
+    let mut sizes = None;
+    let mut types = None;
+    let mut counts = None;
+    let mut width_height = None;
+    let mut points = None;
+    let data = loop {
+        if s.is_empty() {
+            return Err(ErrorKind::Expected("DATA", 0));
+        }
+        let remaining = s.len();
+        split_whitespace(read_line(s), remaining, &mut tokens);
+        let (keyword, values) = match tokens.split_first() {
+            Some(((keyword, _), values)) if !keyword.starts_with(b"#") => (*keyword, values),
+            _ => continue,
+        };
+        match keyword {
+            b"FIELDS" | b"COLUMNS" => names = values.iter().map(|&(v, _)| v).collect(),
+            b"SIZE" => sizes = Some((read_ints(values)?, remaining)),
+            b"TYPE" => {
+                let mut v = Vec::with_capacity(values.len());
+                for &(ty, pos) in values {
+                    match ty {
+                        [ty @ (b'F' | b'I' | b'U')] => v.push(*ty),
+                        _ => return Err(ErrorKind::Expected("F, I, or U", pos)),
+                    }
+                }
+                types = Some((v, remaining));
+            }
+            b"COUNT" => counts = Some((read_ints(values)?, remaining)),
+            b"WIDTH" | b"HEIGHT" => {
+                let [v] = read_values::<1>(values, remaining)?;
+                let (w, h) = width_height.unwrap_or((1, 1));
+                width_height = Some(if keyword == b"WIDTH" { (v, h) } else { (w, v) });
+            }
+            b"POINTS" => points = Some(read_values::<1>(values, remaining)?[0]),
+            b"DATA" => match values {
+                [(b"ascii", _)] => break Data::Ascii,
+                [(b"binary", _)] => break Data::Binary,
+                [(b"binary_compressed", _)] => break Data::BinaryCompressed,
+                [(_, pos)] => return Err(ErrorKind::Unsupported("data type", *pos)),
+                _ => return Err(ErrorKind::InvalidValueCount("DATA", remaining)),
+            },
+            // VERSION, VIEWPOINT, or unknown
+            _ => {}
+        }
+    };
+
+    let mut fields = Vec::with_capacity(names.len());
+    for (i, &name) in names.iter().enumerate() {
+        let size = field_value(sizes.as_ref(), i, "SIZE", 4)?;
+        let ty = field_value(types.as_ref(), i, "TYPE", b'F')?;
+        let count = field_value(counts.as_ref(), i, "COUNT", 1)?;
+        match (ty, size) {
+            (b'F', 4 | 8) | (b'I' | b'U', 1 | 2 | 4 | 8) => {}
+            _ => return Err(ErrorKind::Unsupported("field type", s.len())),
+        }
+        let is_color = name == b"rgb" || name == b"rgba";
+        if is_color && size != 4 {
+            return Err(ErrorKind::Unsupported("color field size", s.len()));
+        }
+        fields.push(Field {
+            name,
+            size,
+            ty,
+            count,
+            is_color,
+        });
+    }
+
+    let find = |name: &[u8]| fields.iter().position(|f| f.name == name);
+    let xyz = match (find(b"x"), find(b"y"), find(b"z")) {
+        (Some(x), Some(y), Some(z)) => [x, y, z],
+        _ => return Err(ErrorKind::Expected("x, y, and z fields", s.len())),
+    };
+    let normal = match (find(b"normal_x"), find(b"normal_y"), find(b"normal_z")) {
+        (Some(x), Some(y), Some(z)) => Some([x, y, z]),
+        _ => None,
+    };
+    let color = match (find(b"rgb"), find(b"rgba")) {
+        (_, Some(i)) => Some((i, true)),
+        (Some(i), None) => Some((i, false)),
+        (None, None) => None,
+    };
+    let layout = Layout {
+        xyz,
+        normal,
+        color,
+        intensity: find(b"intensity"),
+    };
+    let points = points
+        .or_else(|| width_height.map(|(w, h)| w * h))
+        .unwrap_or(0);
+    Ok(Header {
+        fields,
+        points,
+        data,
+        layout,
+    })
+}
+
+fn field_value<T: Copy>(
+    values: Option<&(Vec<T>, usize)>,
+    i: usize,
+    msg: &'static str,
+    default: T,
+) -> Result<T, ErrorKind> {
+    match values {
+        Some((values, pos)) => values
+            .get(i)
+            .copied()
+            .ok_or(ErrorKind::InvalidValueCount(msg, *pos)),
+        None => Ok(default),
+    }
+}
+
+fn read_ints(values: &[(&[u8], usize)]) -> Result<Vec<usize>, ErrorKind> {
+    values.iter().map(|&(v, pos)| read_int(v, pos)).collect()
+}
+
+fn read_values<const N: usize>(
+    values: &[(&[u8], usize)],
+    remaining: usize,
+) -> Result<[usize; N], ErrorKind> {
+    if values.len() != N {
+        return Err(ErrorKind::InvalidValueCount("header", remaining));
+    }
+    let mut v = [0; N];
+    for (v, &(s, pos)) in v.iter_mut().zip(values) {
+        *v = read_int(s, pos)?;
+    }
+    Ok(v)
+}
+
+fn read_int(s: &[u8], pos: usize) -> Result<usize, ErrorKind> {
+    int::parse::<u32>(s)
+        .map(|v| v as usize)
+        .ok_or(ErrorKind::Int(pos))
+}
+
+/// Reads a line (consumes line character).
+fn read_line<'a>(s: &mut &'a [u8]) -> &'a [u8] {
+    let end = s.iter().position(|&b| b == b'\n').unwrap_or(s.len());
+    let line = &s[..end];
+    *s = s.get(end + 1..).unwrap_or_default();
+    line
+}
+
+/// Splits a line into whitespace-separated tokens, with the number of
+/// remaining bytes at the start of each token.
+fn split_whitespace<'a>(line: &'a [u8], remaining: usize, tokens: &mut Vec<(&'a [u8], usize)>) {
+    tokens.clear();
+    let mut i = 0;
+    while i < line.len() {
+        if line[i].is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < line.len() && !line[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        tokens.push((&line[start..i], remaining - start));
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Data
+
+impl Layout {
+    /// Pushes a point with values returned by `get` (called with field indices).
+    fn push<E>(
+        &self,
+        cloud: &mut PointCloud,
+        mut get: impl FnMut(usize) -> Result<f64, E>,
+    ) -> Result<(), E> {
+        #[allow(clippy::cast_possible_truncation)]
+        let mut vec3 = |[x, y, z]: [usize; 3]| -> Result<_, E> {
+            Ok([get(x)? as f32, get(y)? as f32, get(z)? as f32])
+        };
+        let point = vec3(self.xyz)?;
+        if point.iter().any(|v| v.is_nan()) {
+            return Ok(());
+        }
+        cloud.points.push(point);
+        if let Some(normal) = self.normal {
+            cloud.normals.push(vec3(normal)?);
+        }
+        if let Some((i, has_alpha)) = self.color {
+            // Packed as 0xAARRGGBB.
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let [a, r, g, b] = (get(i)? as u32).to_be_bytes();
+            let a = if has_alpha { f32::from(a) / 255. } else { 1. };
+            cloud.colors.push([
+                f32::from(r) / 255.,
+                f32::from(g) / 255.,
+                f32::from(b) / 255.,
+                a,
+            ]);
+        }
+        if let Some(i) = self.intensity {
+            #[allow(clippy::cast_possible_truncation)]
+            cloud.intensities.push(get(i)? as f32);
+        }
+        Ok(())
+    }
+}
+
+fn read_ascii(mut s: &[u8], header: &Header<'_>) -> Result<PointCloud, ErrorKind> {
+    // The index of the first value of each field in a line.
+    let mut offsets = Vec::with_capacity(header.fields.len());
+    let mut num_values = 0;
+    for field in &header.fields {
+        offsets.push(num_values);
+        num_values += field.count;
+    }
+
+    let mut cloud = PointCloud::default();
+    let mut tokens = vec![];
+    let mut num_points = 0;
+    while !s.is_empty() && num_points < header.points {
+        let remaining = s.len();
+        split_whitespace(read_line(&mut s), remaining, &mut tokens);
+        if tokens.is_empty() {
+            continue;
+        }
+        if tokens.len() < num_values {
+            return Err(ErrorKind::InvalidValueCount("point", remaining));
+        }
+        header.layout.push(&mut cloud, |i| {
+            let field = &header.fields[i];
+            let (v, pos) = tokens[offsets[i]];
+            if field.ty == b'F' {
+                let f: f64 = float::parse(v).ok_or(ErrorKind::Float(pos))?;
+                #[allow(clippy::cast_possible_truncation)]
+                let f = if field.is_color {
+                    f64::from((f as f32).to_bits())
+                } else {
+                    f
+                };
+                Ok(f)
+            } else if field.ty == b'I' {
+                #[allow(clippy::cast_precision_loss)]
+                int::parse::<i64>(v)
+                    .map(|v| v as f64)
+                    .ok_or(ErrorKind::Int(pos))
+            } else {
+                #[allow(clippy::cast_precision_loss)]
+                int::parse::<u64>(v)
+                    .map(|v| v as f64)
+                    .ok_or(ErrorKind::Int(pos))
+            }
+        })?;
+        num_points += 1;
+    }
+    Ok(cloud)
+}
+
+fn read_binary(s: &[u8], header: &Header<'_>) -> io::Result<PointCloud> {
+    let mut offsets = Vec::with_capacity(header.fields.len());
+    let mut point_size = 0;
+    for field in &header.fields {
+        offsets.push(point_size);
+        point_size += field.size * field.count;
+    }
+    let data = point_size
+        .checked_mul(header.points)
+        .and_then(|len| s.get(..len))
+        .ok_or_else(|| format_err!("unexpected end of binary data"))?;
+
+    let mut cloud = PointCloud::default();
+    if point_size == 0 {
+        return Ok(cloud);
+    }
+    for point in data.chunks_exact(point_size) {
+        header.layout.push(&mut cloud, |i| {
+            let field = &header.fields[i];
+            Ok::<_, io::Error>(decode(field, &point[offsets[i]..offsets[i] + field.size]))
+        })?;
+    }
+    Ok(cloud)
+}
+
+fn read_binary_compressed(s: &[u8], header: &Header<'_>) -> io::Result<PointCloud> {
+    if s.len() < 8 {
+        bail!("unexpected end of compressed data");
+    }
+    let compressed_size = u32::from_le_bytes(s[..4].try_into().unwrap()) as usize;
+    let uncompressed_size = u32::from_le_bytes(s[4..8].try_into().unwrap()) as usize;
+    let compressed = s
+        .get(8..)
+        .and_then(|s| s.get(..compressed_size))
+        .ok_or_else(|| format_err!("unexpected end of compressed data"))?;
+
+    // The decompressed data is stored field by field: all values of the first
+    // field, then all values of the second field, and so on.
+    let mut offsets = Vec::with_capacity(header.fields.len());
+    let mut len = 0_usize;
+    for field in &header.fields {
+        offsets.push(len);
+        len = field
+            .size
+            .checked_mul(field.count)
+            .and_then(|n| n.checked_mul(header.points))
+            .and_then(|n| n.checked_add(len))
+            .ok_or_else(|| format_err!("too many points"))?;
+    }
+    if uncompressed_size != len {
+        bail!(
+            "uncompressed size ({}) does not match the size of points ({})",
+            uncompressed_size,
+            len
+        );
+    }
+    let data = lzf_decompress(compressed, uncompressed_size)?;
+
+    let mut cloud = PointCloud::default();
+    for p in 0..header.points {
+        header.layout.push(&mut cloud, |i| {
+            let field = &header.fields[i];
+            let start = offsets[i] + p * field.size * field.count;
+            Ok::<_, io::Error>(decode(field, &data[start..start + field.size]))
+        })?;
+    }
+    Ok(cloud)
+}
+
+/// Decodes a little-endian value.
+#[allow(clippy::cast_precision_loss)]
+fn decode(field: &Field<'_>, bytes: &[u8]) -> f64 {
+    macro_rules! from_le_bytes {
+        ($ty:ident) => {
+            $ty::from_le_bytes(bytes.try_into().unwrap()) as f64
+        };
+    }
+    match (field.ty, field.size) {
+        // Packed colors are read as u32 even if the type is F.
+        _ if field.is_color => from_le_bytes!(u32),
+        (b'F', 4) => from_le_bytes!(f32),
+        (b'F', _) => from_le_bytes!(f64),
+        (b'I', 1) => from_le_bytes!(i8),
+        (b'I', 2) => from_le_bytes!(i16),
+        (b'I', 4) => from_le_bytes!(i32),
+        (b'I', _) => from_le_bytes!(i64),
+        (_, 1) => from_le_bytes!(u8),
+        (_, 2) => from_le_bytes!(u16),
+        (_, 4) => from_le_bytes!(u32),
+        (_, _) => from_le_bytes!(u64),
+    }
+}
+
+/// Decompresses LZF-compressed data.
+// Based on lzf_d.c of liblzf: http://software.schmorp.de/pkg/liblzf.html
+fn lzf_decompress(mut s: &[u8], len: usize) -> io::Result<Vec<u8>> {
+    let mut out: Vec<u8> = Vec::new();
+    while let Some((&ctrl, s_next)) = s.split_first() {
+        s = s_next;
+        let ctrl = ctrl as usize;
+        if ctrl < 32 {
+            // literal run
+            let n = ctrl + 1;
+            if s.len() < n {
+                bail!("unexpected end of compressed data");
+            }
+            out.extend_from_slice(&s[..n]);
+            s = &s[n..];
+        } else {
+            // back reference
+            let mut n = ctrl >> 5;
+            if n == 7 {
+                match s.split_first() {
+                    Some((&b, s_next)) => {
+                        n += b as usize;
+                        s = s_next;
+                    }
+                    None => bail!("unexpected end of compressed data"),
+                }
+            }
+            let offset = match s.split_first() {
+                Some((&b, s_next)) => {
+                    s = s_next;
+                    ((ctrl & 0x1f) << 8) + b as usize + 1
+                }
+                None => bail!("unexpected end of compressed data"),
+            };
+            if offset > out.len() {
+                bail!("invalid back reference in compressed data");
+            }
+            // The source and destination can overlap, so copy byte by byte.
+            let start = out.len() - offset;
+            for i in start..start + n + 2 {
+                out.push(out[i]);
+            }
+        }
+        if out.len() > len {
+            bail!("decompressed data is larger than expected");
+        }
+    }
+    if out.len() != len {
+        bail!("decompressed data is smaller than expected");
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lzf() {
+        assert_eq!(
+            lzf_decompress(&[2, b'a', b'b', b'c', 0x20, 2], 6).unwrap(),
+            b"abcabc"
+        );
+        // overlapping back reference
+        assert_eq!(lzf_decompress(&[0, b'a', 0x40, 0], 5).unwrap(), b"aaaaa");
+        assert!(lzf_decompress(&[0, b'a', 0x40, 1], 5).is_err());
+        assert!(lzf_decompress(&[2, b'a'], 3).is_err());
+    }
+}
@@ -0,0 +1,486 @@
+//! Resolution and reading of the files requested by [`Loader`](crate::Loader).
+//!
+//! [`Loader`](crate::Loader) reads the given file and the files referenced by
+//! it (MTL files, meshes referenced by URDF, etc.) through a
+//! [`ResourceResolver`]. Before that, `package://`, `model://`, and `file://`
+//! URIs are resolved to file system paths:
+//!
+//! - `package://<package>/<path>`: ROS package. The package is searched in
+//!   the directories listed in `ROS_PACKAGE_PATH` (by `package.xml`) and in
+//!   the `share` directories of the prefixes listed in `AMENT_PREFIX_PATH`.
+//!   Packages are not cached, so changes to the file system and to the
+//!   environment variables are seen by the next lookup. A package whose
+//!   directory is named after it is found without crawling; other packages
+//!   are found by crawling the directories in `ROS_PACKAGE_PATH` on every
+//!   lookup.
+//! - `model://<model>/<path>`: Gazebo model. The model is searched in the
+//!   directories listed in `GAZEBO_MODEL_PATH` and `GZ_SIM_RESOURCE_PATH`.
+//! - `file://<path>`: Local file.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    env, fs, io,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+/// The kind of a resource requested by [`Loader`](crate::Loader).
+///
+/// More kinds may be added as the loader reads other kinds of references
+/// (e.g., glTF buffers or external COLLADA documents), so this enum is
+/// non-exhaustive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ResourceKind {
+    /// A model file: the file given to the loader, or a mesh referenced by
+    /// URDF.
+    Model,
+    /// A material library referenced by a model file (e.g., MTL).
+    Material,
+    /// A texture referenced by a material. Textures are not read by the
+    /// loader, but passed to [`ResourceResolver::resolve`].
+    Texture,
+}
+
+/// A resource requested by [`Loader`](crate::Loader).
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct Resource<'a> {
+    /// The path to read.
+    ///
+    /// This is [`uri`](Self::uri) resolved relative to the directory of
+    /// [`requester`](Self::requester), with `package://`, `model://`, and
+    /// `file://` URIs resolved. URIs that could not be resolved are left
+    /// as is.
+    pub path: &'a Path,
+    /// The reference as written in the requesting file, or the path given to
+    /// the loader. For textures, this is the same as [`path`](Self::path).
+    pub uri: &'a Path,
+    /// The file that references this resource, or `None` for the file given
+    /// to the loader.
+    pub requester: Option<&'a Path>,
+    pub kind: ResourceKind,
+}
+
+/// A reader of the resources requested by [`Loader`](crate::Loader).
+///
+/// This is implemented for functions and closures that take a path
+/// (`Fn(&Path) -> io::Result<B>`), which read [`Resource::path`].
+///
+/// # Example
+///
+/// A resolver that serves resources from a map, falling back to the file
+/// system:
+///
+/// ```
+/// use std::{collections::HashMap, fs, io, path::PathBuf};
+///
+/// use mesh_loader::{
+///     resolver::{Resource, ResourceResolver},
+///     Loader,
+/// };
+///
+/// struct Assets(HashMap<PathBuf, Vec<u8>>);
+///
+/// impl ResourceResolver for Assets {
+///     fn read(&self, resource: &Resource<'_>) -> io::Result<Vec<u8>> {
+///         match self.0.get(resource.path) {
+///             Some(bytes) => Ok(bytes.clone()),
+///             None => fs::read(resource.path),
+///         }
+///     }
+/// }
+///
+/// let loader = Loader::default().resolver(Assets(HashMap::new()));
+/// ```
+pub trait ResourceResolver<B = Vec<u8>> {
+    /// Reads the given resource.
+    fn read(&self, resource: &Resource<'_>) -> io::Result<B>;
+
+    /// Resolves the path of a resource that is not read by the loader (i.e.,
+    /// textures), which is stored in the loaded [`Material`](crate::Material).
+    ///
+    /// Returning `None` drops the reference.
+    ///
+    /// The default implementation keeps the path as is, even if the file does
+    /// not exist, so that the caller can decide how to fetch it.
+    fn resolve(&self, resource: &Resource<'_>) -> Option<PathBuf> {
+        Some(resource.path.to_owned())
+    }
+}
+
+impl<B, F: Fn(&Path) -> io::Result<B>> ResourceResolver<B> for F {
+    fn read(&self, resource: &Resource<'_>) -> io::Result<B> {
+        self(resource.path)
+    }
+}
+
+/// An in-memory store of files, which can be used as a resolver of
+/// [`Loader`](crate::Loader).
+///
+/// This allows loading a model and the files referenced by it (MTL files,
+/// meshes referenced by URDF, etc.) entirely from memory, e.g., in WASM.
+///
+/// Paths are normalized lexically: `.` components are removed, `..`
+/// components remove the preceding component, and all paths are relative to
+/// the root of the store (i.e., `/a.obj`, `./a.obj`, and `b/../a.obj` refer to
+/// the same file as `a.obj`).
+///
+/// Textures are not read by the loader, so the paths of the textures in the
+/// loaded materials are normalized in the same way, and the textures can be
+/// fetched by [`get`](Self::get).
+///
+/// # Example
+///
+/// ```
+/// use mesh_loader::{resolver::MemoryStore, Loader};
+///
+/// let mut store = MemoryStore::new();
+/// store.insert("models/a.obj", "mtllib ../materials/a.mtl\nusemtl a\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n");
+/// store.insert("materials/a.mtl", "newmtl a\nmap_Kd textures/a.png\n");
+/// store.insert("materials/textures/a.png", [0x89, b'P', b'N', b'G']);
+///
+/// let loader = Loader::default().resolver(store.clone());
+/// let scene = loader.load("models/a.obj")?;
+/// let texture = scene.materials[0].texture.diffuse.as_ref().unwrap();
+/// assert_eq!(store.get(texture), Some(&[0x89, b'P', b'N', b'G'][..]));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    files: HashMap<PathBuf, Arc<[u8]>>,
+}
+
+impl MemoryStore {
+    /// Creates an empty store.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a file into the store, returning the previous contents of the
+    /// file if any.
+    pub fn insert<P: AsRef<Path>, B: AsRef<[u8]>>(
+        &mut self,
+        path: P,
+        bytes: B,
+    ) -> Option<Arc<[u8]>> {
+        self.files
+            .insert(normalize(path.as_ref()), bytes.as_ref().into())
+    }
+
+    /// Returns the contents of the file at the given path.
+    #[must_use]
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&[u8]> {
+        self.files.get(&normalize(path.as_ref())).map(|b| &**b)
+    }
+
+    /// Removes a file from the store, returning its contents if any.
+    pub fn remove<P: AsRef<Path>>(&mut self, path: P) -> Option<Arc<[u8]>> {
+        self.files.remove(&normalize(path.as_ref()))
+    }
+
+    /// Returns the number of files in the store.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns `true` if the store contains no files.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    fn read_arc(&self, path: &Path) -> io::Result<Arc<[u8]>> {
+        match self.files.get(&normalize(path)) {
+            Some(bytes) => Ok(bytes.clone()),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} not found in memory store", path.display()),
+            )),
+        }
+    }
+}
+
+impl<P: AsRef<Path>, B: AsRef<[u8]>> FromIterator<(P, B)> for MemoryStore {
+    fn from_iter<I: IntoIterator<Item = (P, B)>>(iter: I) -> Self {
+        let mut store = Self::new();
+        for (path, bytes) in iter {
+            store.insert(path, bytes);
+        }
+        store
+    }
+}
+
+impl ResourceResolver<Vec<u8>> for MemoryStore {
+    fn read(&self, resource: &Resource<'_>) -> io::Result<Vec<u8>> {
+        Ok(self.read_arc(resource.path)?.to_vec())
+    }
+    fn resolve(&self, resource: &Resource<'_>) -> Option<PathBuf> {
+        Some(normalize(resource.path))
+    }
+}
+
+/// Reads files without copying, with
+/// [`Loader::with_resolver`](crate::Loader::with_resolver).
+impl ResourceResolver<Arc<[u8]>> for MemoryStore {
+    fn read(&self, resource: &Resource<'_>) -> io::Result<Arc<[u8]>> {
+        self.read_arc(resource.path)
+    }
+    fn resolve(&self, resource: &Resource<'_>) -> Option<PathBuf> {
+        Some(normalize(resource.path))
+    }
+}
+
+/// Normalizes the given path lexically, relative to the root.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::Normal(c) => normalized.push(c),
+        }
+    }
+    normalized
+}
+
+const PACKAGE_SCHEME: &str = "package://";
+const MODEL_SCHEME: &str = "model://";
+const FILE_SCHEME: &str = "file://";
+
+/// Returns `true` if the given string starts with a URI scheme supported by
+/// [`resolve_uri`].
+pub fn is_uri(s: &str) -> bool {
+    s.starts_with(PACKAGE_SCHEME) || s.starts_with(MODEL_SCHEME) || s.starts_with(FILE_SCHEME)
+}
+
+/// Resolves `package://`, `model://`, or `file://` URI to a file system path.
+///
+/// Returns an error of [`io::ErrorKind::NotFound`] if the package or model
+/// could not be found, or [`io::ErrorKind::InvalidInput`] if the given string
+/// is not a supported URI.
+///
+/// # Example
+///
+/// ```no_run
+/// let path = mesh_loader::resolver::resolve_uri("package://my_robot/meshes/base.dae")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn resolve_uri(uri: &str) -> io::Result<PathBuf> {
+    if let Some(rest) = uri.strip_prefix(PACKAGE_SCHEME) {
+        let (name, path) = split_first_component(rest);
+        match find_package(name) {
+            Some(dir) => Ok(dir.join(path)),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "package '{name}' not found in ROS_PACKAGE_PATH or AMENT_PREFIX_PATH ({uri})"
+                ),
+            )),
+        }
+    } else if let Some(rest) = uri.strip_prefix(MODEL_SCHEME) {
+        let (name, path) = split_first_component(rest);
+        match find_model(name) {
+            Some(dir) => Ok(dir.join(path)),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "model '{name}' not found in GAZEBO_MODEL_PATH or GZ_SIM_RESOURCE_PATH ({uri})"
+                ),
+            )),
+        }
+    } else if let Some(path) = uri.strip_prefix(FILE_SCHEME) {
+        // file:///C:/path on Windows
+        if cfg!(windows) && path.len() >= 3 && path.as_bytes()[2] == b':' {
+            return Ok(path[1..].into());
+        }
+        // file://localhost/path
+        let path = if path.starts_with("localhost/") {
+            &path["localhost".len()..]
+        } else {
+            path
+        };
+        Ok(path.into())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unsupported URI {uri:?}"),
+        ))
+    }
+}
+
+/// Resolves the given path if it is a URI, otherwise returns it as is.
+pub(crate) fn resolve_path(path: &Path) -> io::Result<Cow<'_, Path>> {
+    match path.to_str() {
+        Some(s) if is_uri(s) => Ok(resolve_uri(s)?.into()),
+        _ => Ok(path.into()),
+    }
+}
+
+/// Resolves the given path if it is a URI that can be resolved, otherwise
+/// returns it as is.
+pub(crate) fn resolve_path_lossy(path: &Path) -> Cow<'_, Path> {
+    resolve_path(path).unwrap_or_else(|_| path.into())
+}
+
+/// Joins a file reference in a file to the directory of that file, resolving
+/// it if it is a URI.
+///
+/// If the URI cannot be resolved, the unresolved reference is returned.
+pub(crate) fn join<P: AsRef<Path>>(dir: &Path, reference: P) -> PathBuf {
+    let reference = reference.as_ref();
+    match reference.to_str() {
+        Some(s) if is_uri(s) => resolve_uri(s).unwrap_or_else(|_| reference.into()),
+        // Other URLs (e.g., https://) are kept as is.
+        Some(s) if s.contains("://") => reference.into(),
+        _ => dir.join(reference),
+    }
+}
+
+/// Normalizes the given path lexically, making it absolute.
+pub(crate) fn normalize_absolute(path: &Path) -> PathBuf {
+    let path = match env::current_dir() {
+        Ok(dir) if !path.is_absolute() => dir.join(path).into(),
+        _ => Cow::Borrowed(path),
+    };
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if let Some(Component::Normal(_)) = normalized.components().next_back() {
+                    normalized.pop();
+                } else if !normalized.has_root() {
+                    normalized.push(component);
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Returns an error of [`io::ErrorKind::PermissionDenied`] if the given
+/// resource is an absolute path, a URL, or a path outside of `root`.
+///
+/// `package://` and other supported URIs are allowed if they are resolved to
+/// a path inside `root`.
+///
+/// `root` must be normalized by [`normalize_absolute`].
+pub(crate) fn check_sandbox(root: &Path, resource: &Resource<'_>) -> io::Result<()> {
+    // For textures, the reference has already been joined to the directory.
+    let is_absolute = resource.kind != ResourceKind::Texture && resource.uri.has_root();
+    // Supported URIs have been resolved, so this is a URL or an unresolved URI.
+    let is_url = resource.path.to_str().map_or(false, |s| s.contains("://"));
+    if is_absolute || is_url || !normalize_absolute(resource.path).starts_with(root) {
+        let msg = format!(
+            "{} is outside of the sandbox {}",
+            resource.uri.display(),
+            root.display()
+        );
+        let msg = match resource.requester {
+            Some(requester) => format!("{msg} (referenced by {})", requester.display()),
+            None => msg,
+        };
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, msg));
+    }
+    Ok(())
+}
+
+fn split_first_component(s: &str) -> (&str, &str) {
+    match s.find('/') {
+        Some(i) => (&s[..i], &s[i + 1..]),
+        None => (s, ""),
+    }
+}
+
+fn find_package(name: &str) -> Option<PathBuf> {
+    if name.is_empty() {
+        return None;
+    }
+    // ROS 2: <prefix>/share/<package>
+    if let Some(paths) = env::var_os("AMENT_PREFIX_PATH") {
+        for prefix in env::split_paths(&paths) {
+            let dir = prefix.join("share").join(name);
+            if dir.join("package.xml").is_file() {
+                return Some(dir);
+            }
+        }
+    }
+    // ROS 1: package.xml under the directories in ROS_PACKAGE_PATH
+    if let Some(paths) = env::var_os("ROS_PACKAGE_PATH") {
+        for root in env::split_paths(&paths) {
+            // Fast path: the directory name is usually the same as the package name.
+            let dir = root.join(name);
+            if package_name(&dir).as_deref() == Some(name) {
+                return Some(dir);
+            }
+            if let Some(dir) = crawl_packages(&root, name, 0) {
+                return Some(dir);
+            }
+        }
+    }
+    None
+}
+
+/// Searches the package with the given name in the given directory in the
+/// same way as rospack: the search does not descend into packages and
+/// directories containing a `CATKIN_IGNORE` file.
+fn crawl_packages(dir: &Path, name: &str, depth: usize) -> Option<PathBuf> {
+    const MAX_DEPTH: usize = 16;
+    if let Some(n) = package_name(dir) {
+        return if n == name {
+            Some(dir.to_owned())
+        } else {
+            None
+        };
+    }
+    if depth >= MAX_DEPTH || dir.join("CATKIN_IGNORE").exists() {
+        return None;
+    }
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| {
+            let e = e.ok()?;
+            if !e.file_type().ok()?.is_dir() || e.file_name().to_str()?.starts_with('.') {
+                return None;
+            }
+            Some(e.path())
+        })
+        .collect();
+    // Make the result deterministic.
+    entries.sort_unstable();
+    entries
+        .iter()
+        .find_map(|dir| crawl_packages(dir, name, depth + 1))
+}
+
+/// Returns the content of `<name>` in `package.xml` in the given directory.
+fn package_name(dir: &Path) -> Option<String> {
+    let xml = fs::read_to_string(dir.join("package.xml")).ok()?;
+    let start = xml.find("<name>")? + "<name>".len();
+    let len = xml[start..].find("</name>")?;
+    Some(xml[start..start + len].trim().to_owned())
+}
+
+fn find_model(name: &str) -> Option<PathBuf> {
+    if name.is_empty() {
+        return None;
+    }
+    for var in ["GZ_SIM_RESOURCE_PATH", "GAZEBO_MODEL_PATH"] {
+        if let Some(paths) = env::var_os(var) {
+            for root in env::split_paths(&paths) {
+                let dir = root.join(name);
+                if dir.is_dir() {
+                    return Some(dir);
+                }
+            }
+        }
+    }
+    None
+}
@@ -4,15 +4,21 @@
 
 mod error;
 
-use std::{io, path::Path, str};
+use std::{
+    io::{self, Read as _},
+    iter::FusedIterator,
+    path::Path,
+    slice, str,
+};
 
 use self::error::ErrorKind;
 use crate::{
     utils::{
-        bytes::{memchr_naive_table, starts_with},
+        bytes::starts_with,
         float,
+        simd::{count_spaces, memchr2},
     },
-    Color4, Material, Mesh, Scene, Vec3,
+    Color4, Material, Mesh, MeshSummary, Scene, Summary, Vec3,
 };
 
 /// Parses meshes from bytes of binary or ASCII STL.
@@ -21,6 +27,246 @@ pub fn from_slice(bytes: &[u8]) -> io::Result<Scene> {
     from_slice_internal(bytes, None, false)
 }
 
+/// Parses meshes from a reader of binary or ASCII STL.
+///
+/// Binary STL is parsed incrementally in triangle records, so the whole input
+/// is not kept in memory. ASCII STL is read into memory before parsing.
+#[inline]
+pub fn from_reader<R: io::Read>(mut reader: R) -> io::Result<Scene> {
+    from_reader_internal(&mut reader, None, false)
+}
+
+/// A triangle passed to the callback of [`for_each_triangle`].
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct Triangle<'a> {
+    /// The vertices of the triangle.
+    pub vertices: [Vec3; 3],
+    /// The normal of the triangle.
+    pub normal: Vec3,
+    /// The color of the triangle. This is only available in binary STL.
+    pub color: Option<Color4>,
+    /// The name of the solid that contains the triangle. This is empty in
+    /// binary STL.
+    pub solid: &'a str,
+}
+
+/// Calls `f` for each triangle in the given bytes of binary or ASCII STL,
+/// without building meshes.
+///
+/// This is useful for computing bounds, hashing, etc. of large files. The
+/// colors of binary STL are read in the same way as
+/// [`Loader::stl_parse_color(true)`](crate::Loader::stl_parse_color).
+///
+/// # Example
+///
+/// ```
+/// let stl = b"solid a
+/// facet normal 0 0 1
+/// outer loop
+/// vertex 0 0 0
+/// vertex 1 0 0
+/// vertex 0 1 0
+/// endloop
+/// endfacet
+/// endsolid a
+/// ";
+/// let mut max = [f32::MIN; 3];
+/// mesh_loader::stl::for_each_triangle(stl, |triangle| {
+///     for vertex in triangle.vertices {
+///         for (max, v) in max.iter_mut().zip(vertex) {
+///             *max = max.max(v);
+///         }
+///     }
+/// })?;
+/// assert_eq!(max, [1., 1., 0.]);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn for_each_triangle<F: FnMut(Triangle<'_>)>(bytes: &[u8], mut f: F) -> io::Result<()> {
+    if is_ascii_stl(bytes) {
+        let mut visitor = Visitor {
+            f: &mut f,
+            solid: "",
+            num_solids: 0,
+        };
+        match read_ascii_stl(bytes, &mut visitor) {
+            Ok(()) => return Ok(()),
+            Err(e) if visitor.num_solids == 0 && may_be_binary(&e) => {}
+            Err(e) => return Err(e.into_io_error(bytes, None)),
+        }
+    }
+    let header = read_binary_header(bytes, true).map_err(|e| e.into_io_error(bytes, None))?;
+    for chunk in header.triangle_bytes.chunks_exact(TRIANGLE_SIZE) {
+        let facet = read_binary_triangle(chunk);
+        f(Triangle {
+            vertices: facet.vertices,
+            normal: facet.normal,
+            color: read_binary_color(&header, facet.color),
+            solid: "",
+        });
+    }
+    Ok(())
+}
+
+/// A borrowed view of binary STL, which reads the triangles from the given
+/// bytes without copying them.
+///
+/// This is useful with memory-mapped files: unlike [`from_slice`], the
+/// vertices and normals are not copied into a [`Mesh`] unless
+/// [`to_mesh`](Self::to_mesh) is called.
+///
+/// # Example
+///
+/// ```
+/// let mut stl = vec![0; 80];
+/// stl.extend_from_slice(&1_u32.to_le_bytes());
+/// for f in [0., 0., 1., 0., 0., 0., 1., 0., 0., 0., 1., 0.] {
+///     stl.extend_from_slice(&f32::to_le_bytes(f));
+/// }
+/// stl.extend_from_slice(&0_u16.to_le_bytes());
+///
+/// let view = mesh_loader::stl::BinaryView::new(&stl)?;
+/// assert_eq!(view.num_triangles(), 1);
+/// assert_eq!(view.normals().next(), Some([0., 0., 1.]));
+/// assert_eq!(view.positions().nth(0).unwrap()[1], [1., 0., 0.]);
+/// assert_eq!(view.to_mesh().vertices.len(), 3);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct BinaryView<'a> {
+    header: &'a [u8],
+    triangle_bytes: &'a [u8],
+}
+
+impl<'a> BinaryView<'a> {
+    /// Creates a view of the given bytes of binary STL.
+    ///
+    /// This returns an error if the size of the bytes is not valid for binary
+    /// STL. Since the header of binary STL can also start with `solid`, this
+    /// does not check whether the bytes are ASCII STL; use [`from_slice`] if
+    /// the bytes may be ASCII STL.
+    pub fn new(bytes: &'a [u8]) -> io::Result<Self> {
+        let header = read_binary_header(bytes, false).map_err(|e| e.into_io_error(bytes, None))?;
+        Ok(Self {
+            header: &bytes[..HEADER_SIZE],
+            triangle_bytes: header.triangle_bytes,
+        })
+    }
+
+    /// Returns the 80-byte header.
+    #[must_use]
+    pub fn header(self) -> &'a [u8] {
+        self.header
+    }
+
+    /// Returns the number of triangles.
+    ///
+    /// This is calculated from the size of the input, since some STL files
+    /// contain a bogus count in the header.
+    #[must_use]
+    pub fn num_triangles(self) -> usize {
+        self.triangle_bytes.len() / TRIANGLE_SIZE
+    }
+
+    /// Returns an iterator over the vertices of each triangle.
+    #[must_use]
+    pub fn positions(self) -> BinaryIter<'a, [Vec3; 3]> {
+        self.iter(|chunk| {
+            [
+                read_vec3(&chunk[12..]),
+                read_vec3(&chunk[24..]),
+                read_vec3(&chunk[36..]),
+            ]
+        })
+    }
+
+    /// Returns an iterator over the normal of each triangle.
+    #[must_use]
+    pub fn normals(self) -> BinaryIter<'a, Vec3> {
+        self.iter(read_vec3)
+    }
+
+    /// Returns an iterator over the attribute byte count of each triangle,
+    /// which some software uses to store the color.
+    #[must_use]
+    pub fn attributes(self) -> BinaryIter<'a, u16> {
+        self.iter(|chunk| u16::from_le_bytes([chunk[TRIANGLE_SIZE - 2], chunk[TRIANGLE_SIZE - 1]]))
+    }
+
+    fn iter<T>(self, read: fn(&[u8]) -> T) -> BinaryIter<'a, T> {
+        BinaryIter {
+            chunks: self.triangle_bytes.chunks_exact(TRIANGLE_SIZE),
+            read,
+        }
+    }
+
+    /// Copies the triangles into a [`Mesh`], in the same way as [`from_slice`].
+    #[must_use]
+    pub fn to_mesh(self) -> Mesh {
+        read_binary_triangles(&BinaryHeader {
+            default_color: [0.; 4],
+            parse_color: false,
+            reverse_color: false,
+            triangle_bytes: self.triangle_bytes,
+        })
+    }
+}
+
+/// An iterator over the triangles of [`BinaryView`].
+///
+/// This supports random access with [`nth`](Iterator::nth) and
+/// [`nth_back`](DoubleEndedIterator::nth_back) in constant time.
+#[derive(Debug, Clone)]
+pub struct BinaryIter<'a, T> {
+    chunks: slice::ChunksExact<'a, u8>,
+    read: fn(&[u8]) -> T,
+}
+
+impl<T> Iterator for BinaryIter<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(self.read)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.chunks.len()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.chunks.nth(n).map(self.read)
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+
+impl<T> DoubleEndedIterator for BinaryIter<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chunks.next_back().map(self.read)
+    }
+
+    #[inline]
+    fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+        self.chunks.nth_back(n).map(self.read)
+    }
+}
+
+impl<T> ExactSizeIterator for BinaryIter<'_, T> {}
+
+impl<T> FusedIterator for BinaryIter<'_, T> {}
+
 pub(crate) fn from_slice_internal(
     bytes: &[u8],
     path: Option<&Path>,
@@ -31,38 +277,164 @@ pub(crate) fn from_slice_internal(
         match read_ascii_stl(bytes, &mut meshes) {
             Ok(()) => {
                 let materials = (0..meshes.len()).map(|_| Material::default()).collect();
-                return Ok(Scene { materials, meshes });
-            }
-            // If there is solid but no space or line break after solid or no
-            // facet normal, even valid ASCII text may be binary STL.
-            Err(
-                ErrorKind::NotAscii("solid", _)
-                | ErrorKind::ExpectedSpace("solid", _)
-                | ErrorKind::ExpectedNewline("solid", _)
-                | ErrorKind::Expected("facet", _),
-            ) if meshes.is_empty() => {}
+                return Ok(Scene {
+                    materials,
+                    meshes,
+                    ..Default::default()
+                });
+            }
+            Err(e) if meshes.is_empty() && may_be_binary(&e) => {}
             Err(e) => return Err(e.into_io_error(bytes, path)),
         }
     }
     match read_binary_header(bytes, parse_color) {
         Ok(header) => {
             let mesh = read_binary_triangles(&header);
-            let mut material = Material::default();
-            if header.reverse_color && mesh.colors[0].is_empty() {
-                let color = header.default_color;
-                material.color.diffuse = Some(color);
-                material.color.specular = Some(color);
-            }
-            meshes.push(mesh);
-            Ok(Scene {
-                materials: vec![material],
-                meshes,
-            })
+            Ok(binary_scene(mesh, &header))
         }
         Err(e) => Err(e.into_io_error(bytes, path)),
     }
 }
 
+/// The number of triangles read from the reader at once.
+const CHUNK_TRIANGLES: usize = 1024;
+
+pub(crate) fn from_reader_internal(
+    reader: &mut dyn io::Read,
+    path: Option<&Path>,
+    parse_color: bool,
+) -> io::Result<Scene> {
+    let mut bytes = Vec::with_capacity(TRIANGLE_START);
+    (&mut *reader)
+        .take(TRIANGLE_START as u64)
+        .read_to_end(&mut bytes)?;
+    // The bytes after the header of binary STL are not text, as it has the
+    // number of triangles. (The header of binary STL can start with "solid".)
+    let is_text = bytes
+        .iter()
+        .all(|&b| b.is_ascii_graphic() || b.is_ascii_whitespace());
+    if bytes.len() < TRIANGLE_START || is_text && is_ascii_stl(&bytes) {
+        reader.read_to_end(&mut bytes)?;
+        return from_slice_internal(&bytes, path, parse_color);
+    }
+
+    let (default_color, reverse_color) = read_header_color(&bytes[..HEADER_SIZE], parse_color);
+    let header = BinaryHeader {
+        default_color,
+        parse_color,
+        reverse_color,
+        triangle_bytes: &[],
+    };
+    // Some STL files contain bogus count, so it is only used as a hint.
+    let num_triangles = u32::from_le_bytes(bytes[HEADER_SIZE..].try_into().unwrap()) as usize;
+    let num_triangles = num_triangles.min(CHUNK_TRIANGLES * 64);
+    let mut mesh = Mesh {
+        vertices: Vec::with_capacity(num_triangles * 3),
+        normals: Vec::with_capacity(num_triangles * 3),
+        faces: Vec::with_capacity(num_triangles),
+        ..Default::default()
+    };
+
+    let mut buf = Vec::with_capacity(TRIANGLE_SIZE * CHUNK_TRIANGLES);
+    loop {
+        buf.clear();
+        (&mut *reader)
+            .take((TRIANGLE_SIZE * CHUNK_TRIANGLES) as u64)
+            .read_to_end(&mut buf)?;
+        let chunks = buf.chunks_exact(TRIANGLE_SIZE);
+        let extra_bytes = chunks.remainder();
+        if mesh.vertices.len() + chunks.len() * 3 > u32::MAX as usize {
+            // face is [u32; 3], so num_vertices must not exceed u32::MAX.
+            return Err(ErrorKind::TooManyTriangles.into_io_error(&bytes, path));
+        }
+        for chunk in chunks {
+            let facet = read_binary_triangle(chunk);
+            if let Some(color) = read_binary_color(&header, facet.color) {
+                if mesh.colors[0].is_empty() {
+                    mesh.colors[0] = vec![default_color; mesh.vertices.len()];
+                }
+                mesh.colors[0].extend_from_slice(&[color; 3]);
+            } else if !mesh.colors[0].is_empty() {
+                mesh.colors[0].extend_from_slice(&[default_color; 3]);
+            }
+            push_triangle(&mut mesh, &facet);
+        }
+        if buf.len() < TRIANGLE_SIZE * CHUNK_TRIANGLES {
+            // Some buggy STL files have a newline after triangles...
+            if !matches!(extra_bytes, b"" | b"\n" | b"\r\n") {
+                return Err(ErrorKind::InvalidSize.into_io_error(&bytes, path));
+            }
+            break;
+        }
+    }
+    Ok(binary_scene(mesh, &header))
+}
+
+/// Returns a summary of the given bytes of binary or ASCII STL.
+///
+/// For binary STL, the triangle count and the text in the header are reported
+/// without reading the triangles.
+pub(crate) fn probe(bytes: &[u8], path: Option<&Path>) -> io::Result<Summary> {
+    if is_ascii_stl(bytes) {
+        let mut summary = Summary::default();
+        match read_ascii_stl(bytes, &mut summary) {
+            Ok(()) => {
+                let num_faces: u64 = summary.meshes.iter().map(|m| m.num_faces).sum();
+                summary.num_vertices = Some(num_faces * 3);
+                return Ok(summary);
+            }
+            Err(e) if summary.meshes.is_empty() && may_be_binary(&e) => {}
+            Err(e) => return Err(e.into_io_error(bytes, path)),
+        }
+    }
+    if bytes.len() < TRIANGLE_START {
+        return Err(ErrorKind::TooSmall.into_io_error(bytes, path));
+    }
+    // Unlike read_binary_header, use the count in the file as is.
+    let num_triangles = &bytes[HEADER_SIZE..TRIANGLE_START];
+    let num_triangles = u32::from_le_bytes(num_triangles.try_into().unwrap());
+    let header = &bytes[..HEADER_SIZE];
+    let header = &header[..header.iter().position(|&b| b == 0).unwrap_or(HEADER_SIZE)];
+    Ok(Summary {
+        meshes: vec![MeshSummary {
+            name: String::new(),
+            num_faces: num_triangles.into(),
+        }],
+        num_vertices: Some(u64::from(num_triangles) * 3),
+        metadata: vec![(
+            "header".to_owned(),
+            String::from_utf8_lossy(header).trim().to_owned(),
+        )],
+        ..Default::default()
+    })
+}
+
+fn binary_scene(mesh: Mesh, header: &BinaryHeader<'_>) -> Scene {
+    let mut material = Material::default();
+    if header.reverse_color && mesh.colors[0].is_empty() {
+        let color = header.default_color;
+        material.color.diffuse = Some(color);
+        material.color.specular = Some(color);
+    }
+    Scene {
+        materials: vec![material],
+        meshes: vec![mesh],
+        ..Default::default()
+    }
+}
+
+// If there is solid but no space or line break after solid or no facet normal,
+// even valid ASCII text may be binary STL.
+fn may_be_binary(e: &ErrorKind) -> bool {
+    matches!(
+        e,
+        ErrorKind::NotAscii("solid", _)
+            | ErrorKind::ExpectedSpace("solid", _)
+            | ErrorKind::ExpectedNewline("solid", _)
+            | ErrorKind::Expected("facet", _)
+    )
+}
+
 // An ASCII STL buffer will begin with "solid NAME", where NAME is optional.
 // Note: The "solid NAME" check is necessary, but not sufficient, to determine
 // if the buffer is ASCII; a binary header could also begin with "solid NAME".
@@ -144,6 +516,19 @@ fn read_binary_header(bytes: &[u8], parse_color: bool) -> Result<BinaryHeader<'_
         return Err(ErrorKind::TooManyTriangles);
     }
 
+    let (default_color, reverse_color) = read_header_color(header, parse_color);
+
+    Ok(BinaryHeader {
+        default_color,
+        parse_color,
+        reverse_color,
+        triangle_bytes,
+    })
+}
+
+/// Returns the default color and whether the color is in RGB order (instead
+/// of BGR), specified in the header of binary STL.
+fn read_header_color(header: &[u8], parse_color: bool) -> (Color4, bool) {
     // Use the same default color (light gray) as assimp: https://github.com/assimp/assimp/blob/v5.3.1/code/AssetLib/STL/STLLoader.cpp#L183-L184
     let mut default_color = [0.6, 0.6, 0.6, 0.6];
     let mut reverse_color = false;
@@ -167,17 +552,15 @@ fn read_binary_header(bytes: &[u8], parse_color: bool) -> Result<BinaryHeader<'_
             s = &s[1..];
         }
     }
-
-    Ok(BinaryHeader {
-        default_color,
-        parse_color,
-        reverse_color,
-        triangle_bytes,
-    })
+    (default_color, reverse_color)
 }
 
 fn read_binary_triangles(header: &BinaryHeader<'_>) -> Mesh {
     let bytes = header.triangle_bytes;
+    #[cfg(feature = "rayon")]
+    if bytes.len() >= crate::utils::par::CHUNK_SIZE * 2 {
+        return read_binary_triangles_parallel(header);
+    }
 
     let chunks = bytes.chunks_exact(TRIANGLE_SIZE);
     let num_triangles = chunks.len();
@@ -198,38 +581,22 @@ fn read_binary_triangles(header: &BinaryHeader<'_>) -> Mesh {
     };
 
     let mut vertices_len = 0;
-    let has_color_mask = if header.parse_color { 1 << 15 } else { 0 };
 
     for (((chunk, vertices), normals), face) in chunks
         .zip(mesh.vertices.chunks_exact_mut(3))
         .zip(mesh.normals.chunks_exact_mut(3))
         .zip(&mut mesh.faces)
     {
-        let triangle = read_binary_triangle(chunk);
+        let facet = read_binary_triangle(chunk);
 
-        vertices.clone_from_slice(&triangle.vertices);
-        normals.clone_from_slice(&[triangle.normal; 3]);
+        vertices.clone_from_slice(&facet.vertices);
+        normals.clone_from_slice(&[facet.normal; 3]);
         *face = [vertices_len, vertices_len + 1, vertices_len + 2];
 
-        // Handling colors in STL is not standardized. We use the same way as assimp.
-        // https://github.com/assimp/assimp/blob/v5.3.1/code/AssetLib/STL/STLLoader.cpp#L502-L529
-        if triangle.color & has_color_mask != 0 {
-            const INV_VAL: f32 = 1. / 31.;
+        if let Some(color) = read_binary_color(header, facet.color) {
             if mesh.colors[0].is_empty() {
                 mesh.colors[0] = vec![header.default_color; num_vertices];
             }
-            let a = 1.;
-            let color = if header.reverse_color {
-                let r = (triangle.color & 0x1F) as f32 * INV_VAL;
-                let g = ((triangle.color & (0x1F << 5)) >> 5) as f32 * INV_VAL;
-                let b = ((triangle.color & (0x1F << 10)) >> 10) as f32 * INV_VAL;
-                [r, g, b, a]
-            } else {
-                let b = (triangle.color & 0x1F) as f32 * INV_VAL;
-                let g = ((triangle.color & (0x1F << 5)) >> 5) as f32 * INV_VAL;
-                let r = ((triangle.color & (0x1F << 10)) >> 10) as f32 * INV_VAL;
-                [r, g, b, a]
-            };
             mesh.colors[0][vertices_len as usize..vertices_len as usize + 3]
                 .copy_from_slice(&[color, color, color]);
         }
@@ -240,8 +607,96 @@ fn read_binary_triangles(header: &BinaryHeader<'_>) -> Mesh {
     mesh
 }
 
+/// The parallel version of `read_binary_triangles`, which returns the same mesh.
+#[cfg(feature = "rayon")]
+fn read_binary_triangles_parallel(header: &BinaryHeader<'_>) -> Mesh {
+    use rayon::prelude::*;
+
+    let bytes = header.triangle_bytes;
+    let num_triangles = bytes.len() / TRIANGLE_SIZE;
+    let num_vertices = num_triangles * 3;
+    let mut mesh = Mesh {
+        vertices: vec![[0., 0., 0.]; num_vertices],
+        normals: vec![[0., 0., 0.]; num_vertices],
+        faces: vec![[0, 0, 0]; num_triangles],
+        ..Default::default()
+    };
+
+    bytes
+        .par_chunks_exact(TRIANGLE_SIZE)
+        .zip(mesh.vertices.par_chunks_exact_mut(3))
+        .zip(mesh.normals.par_chunks_exact_mut(3))
+        .zip(mesh.faces.par_iter_mut())
+        .enumerate()
+        .for_each(|(i, (((chunk, vertices), normals), face))| {
+            let facet = read_binary_triangle(chunk);
+            vertices.clone_from_slice(&facet.vertices);
+            normals.clone_from_slice(&[facet.normal; 3]);
+            // num_vertices is checked in read_binary_header.
+            #[allow(clippy::cast_possible_truncation)]
+            let i = i as u32 * 3;
+            *face = [i, i + 1, i + 2];
+        });
+
+    // Allocate colors only if there is a triangle with color, as the
+    // sequential version does.
+    let color = |chunk: &[u8]| {
+        let color = u16::from_le_bytes([chunk[TRIANGLE_SIZE - 2], chunk[TRIANGLE_SIZE - 1]]);
+        read_binary_color(header, color)
+    };
+    if bytes
+        .par_chunks_exact(TRIANGLE_SIZE)
+        .any(|chunk| color(chunk).is_some())
+    {
+        mesh.colors[0] = vec![header.default_color; num_vertices];
+        bytes
+            .par_chunks_exact(TRIANGLE_SIZE)
+            .zip(mesh.colors[0].par_chunks_exact_mut(3))
+            .for_each(|(chunk, colors)| {
+                if let Some(color) = color(chunk) {
+                    colors.copy_from_slice(&[color, color, color]);
+                }
+            });
+    }
+
+    mesh
+}
+
+/// Returns the color of a triangle, or `None` if the triangle has no color.
 #[inline]
-fn read_binary_triangle(mut buf: &[u8]) -> Triangle {
+fn read_binary_color(header: &BinaryHeader<'_>, color: u16) -> Option<Color4> {
+    // Handling colors in STL is not standardized. We use the same way as assimp.
+    // https://github.com/assimp/assimp/blob/v5.3.1/code/AssetLib/STL/STLLoader.cpp#L502-L529
+    const INV_VAL: f32 = 1. / 31.;
+    if !header.parse_color || color & (1 << 15) == 0 {
+        return None;
+    }
+    let a = 1.;
+    let color = if header.reverse_color {
+        let r = (color & 0x1F) as f32 * INV_VAL;
+        let g = ((color & (0x1F << 5)) >> 5) as f32 * INV_VAL;
+        let b = ((color & (0x1F << 10)) >> 10) as f32 * INV_VAL;
+        [r, g, b, a]
+    } else {
+        let b = (color & 0x1F) as f32 * INV_VAL;
+        let g = ((color & (0x1F << 5)) >> 5) as f32 * INV_VAL;
+        let r = ((color & (0x1F << 10)) >> 10) as f32 * INV_VAL;
+        [r, g, b, a]
+    };
+    Some(color)
+}
+
+#[inline]
+fn read_vec3(buf: &[u8]) -> Vec3 {
+    [
+        f32::from_le_bytes(buf[..4].try_into().unwrap()),
+        f32::from_le_bytes(buf[4..8].try_into().unwrap()),
+        f32::from_le_bytes(buf[8..12].try_into().unwrap()),
+    ]
+}
+
+#[inline]
+fn read_binary_triangle(mut buf: &[u8]) -> Facet {
     #[inline]
     fn f32le(buf: &mut &[u8]) -> f32 {
         let f = f32::from_le_bytes(buf[..4].try_into().unwrap());
@@ -254,7 +709,7 @@ fn read_binary_triangle(mut buf: &[u8]) -> Triangle {
     let vertex2 = [f32le(&mut buf), f32le(&mut buf), f32le(&mut buf)];
     let vertex3 = [f32le(&mut buf), f32le(&mut buf), f32le(&mut buf)];
     let color = u16::from_le_bytes(buf[..2].try_into().unwrap());
-    Triangle {
+    Facet {
         normal,
         vertices: [vertex1, vertex2, vertex3],
         color,
@@ -279,16 +734,15 @@ endfacet
 
 endsolid name
 */
-fn read_ascii_stl(mut s: &[u8], meshes: &mut Vec<Mesh>) -> Result<(), ErrorKind> {
+fn read_ascii_stl<'a, T: FromStl<'a>>(mut s: &'a [u8], cx: &mut T) -> Result<(), ErrorKind> {
+    let mut num_solids = 0;
     loop {
-        let mut mesh = Mesh::default();
-
         // solid [name]
         let expected = "solid";
         if !skip_spaces_and_lines_until_token(&mut s, expected.as_bytes()) {
             if s.is_empty() {
                 // eof
-                if meshes.is_empty() {
+                if num_solids == 0 {
                     return Err(ErrorKind::Expected(expected, s.len()));
                 }
                 break;
@@ -298,7 +752,7 @@ fn read_ascii_stl(mut s: &[u8], meshes: &mut Vec<Mesh>) -> Result<(), ErrorKind>
         if !skip_spaces(&mut s) {
             return Err(ErrorKind::ExpectedSpace(expected, s.len()));
         }
-        match memchr_naive_table(LINE, &TABLE, s) {
+        match memchr2(b'\n', b'\r', s) {
             Some(n) => {
                 let mut name = &s[..n];
                 // The only strings we need to explicitly check for ASCII are the
@@ -308,7 +762,7 @@ fn read_ascii_stl(mut s: &[u8], meshes: &mut Vec<Mesh>) -> Result<(), ErrorKind>
                 if !name.is_ascii() {
                     return Err(ErrorKind::NotAscii(expected, s.len()));
                 }
-                if let Some(n) = memchr_naive_table(SPACE, &TABLE, name) {
+                if let Some(n) = memchr2(b' ', b'\t', name) {
                     // Ignore contents after the name.
                     // https://en.wikipedia.org/wiki/STL_(file_format)#ASCII
                     // > The remainder of the line is ignored and is sometimes used to
@@ -316,7 +770,8 @@ fn read_ascii_stl(mut s: &[u8], meshes: &mut Vec<Mesh>) -> Result<(), ErrorKind>
                     name = &name[..n];
                 }
                 let name = str::from_utf8(name).unwrap();
-                Mesh::set_name(&mut mesh, name);
+                cx.start_solid(name);
+                num_solids += 1;
                 s = &s[n + 1..];
             }
             None => return Err(ErrorKind::ExpectedNewline(expected, s.len())),
@@ -416,14 +871,11 @@ fn read_ascii_stl(mut s: &[u8], meshes: &mut Vec<Mesh>) -> Result<(), ErrorKind>
                 return Err(ErrorKind::ExpectedNewline(expected, s.len()));
             }
 
-            Mesh::push_triangle(
-                &mut mesh,
-                Triangle {
-                    normal,
-                    vertices,
-                    color: 0,
-                },
-            );
+            cx.push_triangle(Facet {
+                normal,
+                vertices,
+                color: 0,
+            });
         }
 
         // endsolid [name]
@@ -433,7 +885,7 @@ fn read_ascii_stl(mut s: &[u8], meshes: &mut Vec<Mesh>) -> Result<(), ErrorKind>
         }
         // Skip checking endsolid because some exporters have generated the wrong STL about endsolid.
         // https://github.com/assimp/assimp/issues/3756
-        match memchr_naive_table(LINE, &TABLE, s) {
+        match memchr2(b'\n', b'\r', s) {
             Some(n) => {
                 if !s[..n].is_ascii() {
                     return Err(ErrorKind::NotAscii(expected, s.len())); // See the comment on ASCII check for stings after solid for more.
@@ -447,8 +899,6 @@ fn read_ascii_stl(mut s: &[u8], meshes: &mut Vec<Mesh>) -> Result<(), ErrorKind>
                 s = &[];
             }
         }
-
-        meshes.push(mesh);
     }
 
     Ok(())
@@ -555,16 +1005,9 @@ fn skip_spaces_until_line(s: &mut &[u8]) -> bool {
 
 #[inline]
 fn skip_spaces(s: &mut &[u8]) -> bool {
-    let start = *s;
-    while let Some((&b, s_next)) = s.split_first() {
-        let b = TABLE[b as usize];
-        if b & SPACE != 0 {
-            *s = s_next;
-            continue;
-        }
-        break;
-    }
-    start.len() != s.len()
+    let n = count_spaces(s);
+    *s = &s[n..];
+    n != 0
 }
 
 #[inline]
@@ -603,44 +1046,89 @@ fn skip_spaces_and_lines_until_token(s: &mut &[u8], token: &'static [u8]) -> boo
     false
 }
 
-struct Triangle {
+struct Facet {
     normal: Vec3,
     vertices: [Vec3; 3],
     color: u16,
 }
 
-trait FromStl: Sized {
-    type Context;
+trait FromStl<'a> {
+    /// Starts a solid with the given name.
+    fn start_solid(&mut self, name: &'a str);
+
+    /// Appends a triangle to the current solid.
+    fn push_triangle(&mut self, facet: Facet);
+}
 
-    /// Appends a triangle.
-    fn push_triangle(cx: &mut Self::Context, triangle: Triangle);
+impl FromStl<'_> for Vec<Mesh> {
+    fn start_solid(&mut self, name: &str) {
+        self.push(Mesh {
+            name: name.to_owned(),
+            ..Default::default()
+        });
+    }
 
-    /// Sets the name.
-    fn set_name(cx: &mut Self::Context, name: &str);
+    #[inline]
+    fn push_triangle(&mut self, facet: Facet) {
+        push_triangle(self.last_mut().unwrap(), &facet);
+    }
 }
 
-impl FromStl for Mesh {
-    type Context = Self;
+impl FromStl<'_> for Summary {
+    fn start_solid(&mut self, name: &str) {
+        self.meshes.push(MeshSummary {
+            name: name.to_owned(),
+            num_faces: 0,
+        });
+    }
 
     #[inline]
-    fn push_triangle(mesh: &mut Self::Context, triangle: Triangle) {
-        // With binary STL, reserve checks that the max length of cx.vertices
-        // will not be greater than u32::MAX.
-        // With ASCII STL, the max length of cx.vertices will not be too large,
-        // since much more bytes is required per triangle than for binary STL.
-        #[allow(clippy::cast_possible_truncation)]
-        let vertices_indices = [
-            mesh.vertices.len() as u32,
-            (mesh.vertices.len() + 1) as u32,
-            (mesh.vertices.len() + 2) as u32,
-        ];
-
-        mesh.vertices.extend_from_slice(&triangle.vertices);
-        mesh.normals.resize(mesh.normals.len() + 3, triangle.normal);
-        mesh.faces.push(vertices_indices);
-    }
-
-    fn set_name(mesh: &mut Self::Context, name: &str) {
-        mesh.name = name.to_owned();
+    fn push_triangle(&mut self, facet: Facet) {
+        self.meshes.last_mut().unwrap().num_faces += 1;
+        for v in facet.vertices {
+            self.add_vertex(v);
+        }
     }
 }
+
+/// Passes the triangles to the callback of `for_each_triangle`.
+struct Visitor<'a, 'f> {
+    f: &'f mut dyn FnMut(Triangle<'a>),
+    solid: &'a str,
+    num_solids: usize,
+}
+
+impl<'a> FromStl<'a> for Visitor<'a, '_> {
+    fn start_solid(&mut self, name: &'a str) {
+        self.solid = name;
+        self.num_solids += 1;
+    }
+
+    #[inline]
+    fn push_triangle(&mut self, facet: Facet) {
+        (self.f)(Triangle {
+            vertices: facet.vertices,
+            normal: facet.normal,
+            color: None,
+            solid: self.solid,
+        });
+    }
+}
+
+#[inline]
+fn push_triangle(mesh: &mut Mesh, facet: &Facet) {
+    // With binary STL, the max length of mesh.vertices is checked before
+    // pushing triangles.
+    // With ASCII STL, the max length of mesh.vertices will not be too large,
+    // since much more bytes is required per triangle than for binary STL.
+    #[allow(clippy::cast_possible_truncation)]
+    let vertices_indices = [
+        mesh.vertices.len() as u32,
+        (mesh.vertices.len() + 1) as u32,
+        (mesh.vertices.len() + 2) as u32,
+    ];
+
+    mesh.vertices.extend_from_slice(&facet.vertices);
+    mesh.normals.resize(mesh.normals.len() + 3, facet.normal);
+    mesh.faces.push(vertices_indices);
+}
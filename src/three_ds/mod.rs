@@ -0,0 +1,445 @@
+//! [3DS] (.3ds) parser.
+//!
+//! Vertices in 3DS are stored in world space, so the keyframer (which
+//! describes the node hierarchy and animations) is ignored. The local
+//! coordinate system of each object is only used to restore the winding
+//! order of faces of mirrored objects.
+//!
+//! [3DS]: https://en.wikipedia.org/wiki/.3ds
+
+#![allow(clippy::many_single_char_names)]
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{common, Color4, ShadingModel, Vec2, Vec3};
+
+/// Parses meshes from bytes of 3DS.
+#[inline]
+pub fn from_slice(bytes: &[u8]) -> io::Result<common::Scene> {
+    from_slice_internal(bytes, None)
+}
+
+pub(crate) fn from_slice_internal(bytes: &[u8], path: Option<&Path>) -> io::Result<common::Scene> {
+    let mut doc = Document {
+        dir: path.and_then(Path::parent),
+        materials: vec![],
+        objects: vec![],
+    };
+    let mut found_main = false;
+    for_each_chunk(bytes, 0, |id, data, offset| {
+        if id == MAIN3DS {
+            found_main = true;
+            doc.read_main(data, offset)?;
+        }
+        Ok(())
+    })?;
+    if !found_main {
+        bail!("expected 3DS main chunk");
+    }
+    Ok(doc.build())
+}
+
+// Refs:
+// - https://paulbourke.net/dataformats/3ds/
+// - https://github.com/assimp/assimp/blob/v5.3.1/code/AssetLib/3DS/3DSHelper.h
+const MAIN3DS: u16 = 0x4D4D;
+const EDIT3DS: u16 = 0x3D3D;
+const OBJECT: u16 = 0x4000;
+const TRIMESH: u16 = 0x4100;
+const VERTLIST: u16 = 0x4110;
+const FACELIST: u16 = 0x4120;
+const MSHMATGROUP: u16 = 0x4130;
+const MAPLIST: u16 = 0x4140;
+const TRMATRIX: u16 = 0x4160;
+const MAT_ENTRY: u16 = 0xAFFF;
+const MAT_NAME: u16 = 0xA000;
+const MAT_AMBIENT: u16 = 0xA010;
+const MAT_DIFFUSE: u16 = 0xA020;
+const MAT_SPECULAR: u16 = 0xA030;
+const MAT_SHININESS: u16 = 0xA040;
+const MAT_TRANSPARENCY: u16 = 0xA050;
+const MAT_SHADING: u16 = 0xA100;
+const MAT_TEXMAP: u16 = 0xA200;
+const MAT_SPECMAP: u16 = 0xA204;
+const MAT_OPACMAP: u16 = 0xA210;
+const MAT_REFLMAP: u16 = 0xA220;
+const MAT_BUMPMAP: u16 = 0xA230;
+const MAT_SHINMAP: u16 = 0xA33C;
+const MAT_SELFIMAP: u16 = 0xA33D;
+const MAT_MAPNAME: u16 = 0xA300;
+const COLOR_F: u16 = 0x0010;
+const COLOR_24: u16 = 0x0011;
+const LIN_COLOR_24: u16 = 0x0012;
+const LIN_COLOR_F: u16 = 0x0013;
+const INT_PERCENTAGE: u16 = 0x0030;
+const FLOAT_PERCENTAGE: u16 = 0x0031;
+
+struct Document<'a> {
+    dir: Option<&'a Path>,
+    materials: Vec<common::Material>,
+    objects: Vec<Object>,
+}
+
+/// The `OBJECT` chunk with a `TRIMESH` chunk.
+struct Object {
+    name: String,
+    vertices: Vec<Vec3>,
+    texcoords: Vec<Vec2>,
+    faces: Vec<[u16; 3]>,
+    /// Material name and indices of faces.
+    material_groups: Vec<(String, Vec<u16>)>,
+    mirrored: bool,
+}
+
+impl Document<'_> {
+    fn read_main(&mut self, bytes: &[u8], offset: usize) -> io::Result<()> {
+        for_each_chunk(bytes, offset, |id, data, offset| {
+            if id == EDIT3DS {
+                for_each_chunk(data, offset, |id, data, offset| {
+                    match id {
+                        MAT_ENTRY => {
+                            let material = self.read_material(data, offset)?;
+                            self.materials.push(material);
+                        }
+                        OBJECT => {
+                            let mut r = Reader::new(data, offset);
+                            let name = r.read_cstr()?;
+                            for_each_chunk(r.remaining(), r.offset(), |id, data, offset| {
+                                if id == TRIMESH {
+                                    let object = read_trimesh(name.clone(), data, offset)?;
+                                    self.objects.push(object);
+                                }
+                                Ok(())
+                            })?;
+                        }
+                        _ => {}
+                    }
+                    Ok(())
+                })?;
+            }
+            // The keyframer (KEYF3DS) is ignored.
+            Ok(())
+        })
+    }
+
+    fn read_material(&self, bytes: &[u8], offset: usize) -> io::Result<common::Material> {
+        let mut material = common::Material::default();
+        for_each_chunk(bytes, offset, |id, data, offset| {
+            match id {
+                MAT_NAME => material.name = Reader::new(data, offset).read_cstr()?,
+                MAT_AMBIENT => material.color.ambient = read_color(data, offset)?,
+                MAT_DIFFUSE => material.color.diffuse = read_color(data, offset)?,
+                MAT_SPECULAR => material.color.specular = read_color(data, offset)?,
+                // Convert the percentage to something like the specular exponent.
+                MAT_SHININESS => {
+                    material.shininess = read_percentage(data, offset)?.map(|v| v * 100.);
+                }
+                MAT_TRANSPARENCY => {
+                    material.opacity = read_percentage(data, offset)?.map(|v| 1. - v);
+                }
+                MAT_SHADING => {
+                    material.shading_model = match Reader::new(data, offset).read_u16()? {
+                        1 => Some(ShadingModel::Flat),
+                        2 => Some(ShadingModel::Gouraud),
+                        3 => Some(ShadingModel::Phong),
+                        // wire frame, metal
+                        _ => None,
+                    };
+                }
+                MAT_TEXMAP => material.texture.diffuse = self.read_texture(data, offset)?,
+                MAT_SPECMAP => material.texture.specular = self.read_texture(data, offset)?,
+                MAT_OPACMAP => material.texture.opacity = self.read_texture(data, offset)?,
+                MAT_REFLMAP => material.texture.reflection = self.read_texture(data, offset)?,
+                MAT_BUMPMAP => material.texture.height = self.read_texture(data, offset)?,
+                MAT_SHINMAP => material.texture.shininess = self.read_texture(data, offset)?,
+                MAT_SELFIMAP => material.texture.emissive = self.read_texture(data, offset)?,
+                _ => {}
+            }
+            Ok(())
+        })?;
+        Ok(material)
+    }
+
+    fn read_texture(&self, bytes: &[u8], offset: usize) -> io::Result<Option<PathBuf>> {
+        let mut texture = None;
+        for_each_chunk(bytes, offset, |id, data, offset| {
+            if id == MAT_MAPNAME {
+                let name = Reader::new(data, offset).read_cstr()?;
+                if name.is_empty() {
+                    return Ok(());
+                }
+                // 3DS files are often written on Windows.
+                let name = name.replace('\\', "/");
+                match self.dir {
+                    Some(dir) => texture = Some(dir.join(name)),
+                    None => texture = Some(name.into()),
+                }
+            }
+            Ok(())
+        })?;
+        Ok(texture)
+    }
+
+    fn build(self) -> common::Scene {
+        let mut scene = common::Scene::default();
+        for object in &self.objects {
+            let mut grouped = vec![false; object.faces.len()];
+            let mut groups: Vec<(Option<&common::Material>, Vec<usize>)> = vec![];
+            for (name, faces) in &object.material_groups {
+                let material = self.materials.iter().find(|m| m.name == *name);
+                let faces = faces
+                    .iter()
+                    .map(|&f| f as usize)
+                    .filter(|&f| f < grouped.len() && !grouped[f])
+                    .collect::<Vec<_>>();
+                for &f in &faces {
+                    grouped[f] = true;
+                }
+                groups.push((material, faces));
+            }
+            // Faces that do not belong to any material group.
+            let rest: Vec<_> = (0..object.faces.len()).filter(|&f| !grouped[f]).collect();
+            groups.push((None, rest));
+
+            for (material, faces) in groups {
+                if faces.is_empty() {
+                    continue;
+                }
+                let mut mesh = common::Mesh {
+                    name: object.name.clone(),
+                    ..Default::default()
+                };
+                for f in faces {
+                    let mut face = object.faces[f];
+                    if object.mirrored {
+                        face.swap(1, 2);
+                    }
+                    #[allow(clippy::cast_possible_truncation)]
+                    let vertices_indices = [
+                        mesh.vertices.len() as u32,
+                        (mesh.vertices.len() + 1) as u32,
+                        (mesh.vertices.len() + 2) as u32,
+                    ];
+                    for i in face {
+                        mesh.vertices.push(object.vertices[i as usize]);
+                        if !object.texcoords.is_empty() {
+                            mesh.texcoords[0].push(object.texcoords[i as usize]);
+                        }
+                    }
+                    mesh.faces.push(vertices_indices);
+                }
+                scene.meshes.push(mesh);
+                scene.materials.push(material.cloned().unwrap_or_default());
+            }
+        }
+        scene
+    }
+}
+
+fn read_trimesh(name: String, bytes: &[u8], offset: usize) -> io::Result<Object> {
+    let mut object = Object {
+        name,
+        vertices: vec![],
+        texcoords: vec![],
+        faces: vec![],
+        material_groups: vec![],
+        mirrored: false,
+    };
+    for_each_chunk(bytes, offset, |id, data, offset| {
+        let mut r = Reader::new(data, offset);
+        match id {
+            VERTLIST => {
+                let n = r.read_u16()?;
+                object.vertices.reserve(n as usize);
+                for _ in 0..n {
+                    object
+                        .vertices
+                        .push([r.read_f32()?, r.read_f32()?, r.read_f32()?]);
+                }
+            }
+            MAPLIST => {
+                let n = r.read_u16()?;
+                object.texcoords.reserve(n as usize);
+                for _ in 0..n {
+                    object.texcoords.push([r.read_f32()?, r.read_f32()?]);
+                }
+            }
+            FACELIST => {
+                let n = r.read_u16()?;
+                object.faces.reserve(n as usize);
+                for _ in 0..n {
+                    let face = [r.read_u16()?, r.read_u16()?, r.read_u16()?];
+                    let _flags = r.read_u16()?;
+                    object.faces.push(face);
+                }
+                for_each_chunk(r.remaining(), r.offset(), |id, data, offset| {
+                    if id == MSHMATGROUP {
+                        let mut r = Reader::new(data, offset);
+                        let name = r.read_cstr()?;
+                        let n = r.read_u16()?;
+                        let mut faces = Vec::with_capacity(n as usize);
+                        for _ in 0..n {
+                            faces.push(r.read_u16()?);
+                        }
+                        object.material_groups.push((name, faces));
+                    }
+                    // The smoothing groups (SMOOTHGROUP) are ignored.
+                    Ok(())
+                })?;
+            }
+            TRMATRIX => {
+                // The local coordinate system: X, Y, and Z axes, and the origin.
+                let mut m = [[0.; 3]; 3];
+                for axis in &mut m {
+                    *axis = [r.read_f32()?, r.read_f32()?, r.read_f32()?];
+                }
+                let [a, b, c] = m;
+                let det = a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+                    + a[2] * (b[0] * c[1] - b[1] * c[0]);
+                object.mirrored = det < 0.;
+            }
+            _ => {}
+        }
+        Ok(())
+    })?;
+
+    let num_vertices = object.vertices.len();
+    if let Some(&i) = object
+        .faces
+        .iter()
+        .flatten()
+        .find(|&&i| i as usize >= num_vertices)
+    {
+        bail!(
+            "face index out of bounds ({}) in object {:?}",
+            i,
+            object.name
+        );
+    }
+    if !object.texcoords.is_empty() && object.texcoords.len() != num_vertices {
+        // Ignore broken mapping coordinates instead of failing to load the
+        // whole model.
+        object.texcoords.clear();
+    }
+    Ok(object)
+}
+
+/// Reads a color from `COLOR_F`, `COLOR_24`, or their linear variants.
+fn read_color(bytes: &[u8], offset: usize) -> io::Result<Option<Color4>> {
+    let mut color = None;
+    for_each_chunk(bytes, offset, |id, data, offset| {
+        let mut r = Reader::new(data, offset);
+        match id {
+            COLOR_F | LIN_COLOR_F if color.is_none() => {
+                color = Some([r.read_f32()?, r.read_f32()?, r.read_f32()?, 1.]);
+            }
+            COLOR_24 | LIN_COLOR_24 if color.is_none() => {
+                let mut c = [0., 0., 0., 1.];
+                for c in &mut c[..3] {
+                    *c = f32::from(r.read_u8()?) / 255.;
+                }
+                color = Some(c);
+            }
+            _ => {}
+        }
+        Ok(())
+    })?;
+    Ok(color)
+}
+
+/// Reads a percentage in the range 0-1 from `INT_PERCENTAGE` or `FLOAT_PERCENTAGE`.
+fn read_percentage(bytes: &[u8], offset: usize) -> io::Result<Option<f32>> {
+    let mut percentage = None;
+    for_each_chunk(bytes, offset, |id, data, offset| {
+        let mut r = Reader::new(data, offset);
+        match id {
+            INT_PERCENTAGE => percentage = Some(f32::from(r.read_u16()?) / 100.),
+            FLOAT_PERCENTAGE => percentage = Some(r.read_f32()?),
+            _ => {}
+        }
+        Ok(())
+    })?;
+    Ok(percentage)
+}
+
+/// Calls `f` with the ID, the data, and the offset of the data of each chunk.
+///
+/// A chunk consists of a 2-byte ID, a 4-byte length (including the header),
+/// and the data, which may contain sub-chunks.
+fn for_each_chunk<F>(bytes: &[u8], offset: usize, mut f: F) -> io::Result<()>
+where
+    F: FnMut(u16, &[u8], usize) -> io::Result<()>,
+{
+    let mut pos = 0;
+    while bytes.len() - pos >= 6 {
+        let id = u16::from_le_bytes(bytes[pos..pos + 2].try_into().unwrap());
+        let len = u32::from_le_bytes(bytes[pos + 2..pos + 6].try_into().unwrap()) as usize;
+        if len < 6 || len > bytes.len() - pos {
+            bail!("invalid chunk length at offset {}", offset + pos);
+        }
+        f(id, &bytes[pos + 6..pos + len], offset + pos + 6)?;
+        pos += len;
+    }
+    Ok(())
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8], offset: usize) -> Self {
+        Self {
+            bytes,
+            pos: 0,
+            offset,
+        }
+    }
+
+    fn remaining(&self) -> &'a [u8] {
+        &self.bytes[self.pos..]
+    }
+
+    fn offset(&self) -> usize {
+        self.offset + self.pos
+    }
+
+    fn read_array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        match self.bytes.get(self.pos..self.pos + N) {
+            Some(b) => {
+                self.pos += N;
+                Ok(b.try_into().unwrap())
+            }
+            None => bail!("unexpected end of chunk at offset {}", self.offset()),
+        }
+    }
+
+    fn read_u8(&mut self) -> io::Result<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    fn read_u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_f32(&mut self) -> io::Result<f32> {
+        Ok(f32::from_le_bytes(self.read_array()?))
+    }
+
+    /// Reads a null-terminated string.
+    fn read_cstr(&mut self) -> io::Result<String> {
+        let s = self.remaining();
+        match s.iter().position(|&b| b == 0) {
+            Some(len) => {
+                self.pos += len + 1;
+                Ok(String::from_utf8_lossy(&s[..len]).into_owned())
+            }
+            None => bail!("unterminated string at offset {}", self.offset()),
+        }
+    }
+}
@@ -0,0 +1,596 @@
+//! [URDF] (.urdf) loader.
+//!
+//! All `<visual>` and `<collision>` geometries of the robot are loaded and
+//! placed according to the chain of link and joint origins, with the joint
+//! positions given by the caller (`0` for joints not given). Meshes referenced
+//! by `<mesh filename>` are loaded by [`Loader`](crate::Loader), so
+//! `package://` URIs and all formats enabled by features are supported.
+//! `<box>`, `<cylinder>`, and `<sphere>` primitives are converted to meshes.
+//!
+//! The meshes are grouped by link in the order in which the links appear in
+//! the document, and each mesh is named `<link>/visual` or `<link>/collision`
+//! (followed by `/<name>` if the element has the `name` attribute).
+//!
+//! [URDF]: https://wiki.ros.org/urdf/XML
+
+#![allow(clippy::many_single_char_names)]
+
+use std::{
+    collections::{HashMap, HashSet},
+    f32::consts::PI,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    common::{self, DEFAULT_COLOR},
+    resolver::{self, Resource, ResourceKind},
+    utils::{
+        matrix::{normalize, Matrix4x4},
+        utf16::decode_string,
+        xml::{self, XmlNodeExt},
+    },
+    Color4, Mesh, Scene, Vec3,
+};
+// The number of segments around the axis of cylinders and spheres.
+const SEGMENTS: usize = 32;
+// The number of rings from the bottom to the top of spheres.
+const RINGS: usize = 16;
+
+pub(crate) fn from_slice_internal(
+    bytes: &[u8],
+    path: Option<&Path>,
+    joint_positions: &HashMap<String, f32>,
+    load: &mut dyn FnMut(&Resource<'_>) -> io::Result<Scene>,
+) -> io::Result<Scene> {
+    let s = decode_string(bytes)?;
+    let xml = xml::Document::parse(&s).map_err(crate::error::invalid_data)?;
+    let robot = Robot::parse(&xml)?;
+    robot.build(path, joint_positions, load)
+}
+
+/// The `<robot>` element.
+struct Robot<'a> {
+    materials: HashMap<&'a str, Material<'a>>,
+    links: Vec<Link<'a>>,
+    joints: Vec<Joint<'a>>,
+}
+
+/// The `<link>` element.
+struct Link<'a> {
+    name: &'a str,
+    elements: Vec<Element<'a>>,
+}
+
+/// The `<visual>` or `<collision>` element.
+struct Element<'a> {
+    kind: &'static str,
+    name: Option<&'a str>,
+    origin: Matrix4x4,
+    geometry: Geometry<'a>,
+    material: Option<Material<'a>>,
+}
+
+/// The `<geometry>` element.
+enum Geometry<'a> {
+    Mesh { filename: &'a str, scale: Vec3 },
+    Box { size: Vec3 },
+    Cylinder { radius: f32, length: f32 },
+    Sphere { radius: f32 },
+}
+
+/// The `<material>` element.
+#[derive(Clone, Default)]
+struct Material<'a> {
+    name: &'a str,
+    color: Option<Color4>,
+    texture: Option<&'a str>,
+}
+
+/// The `<joint>` element.
+struct Joint<'a> {
+    name: &'a str,
+    ty: &'a str,
+    parent: &'a str,
+    child: &'a str,
+    origin: Matrix4x4,
+    axis: Vec3,
+    /// (joint, multiplier, offset)
+    mimic: Option<(&'a str, f32, f32)>,
+}
+
+impl<'a> Robot<'a> {
+    fn parse(xml: &'a xml::Document<'_>) -> io::Result<Self> {
+        let node = xml.root_element();
+        if node.tag_name().name() != "robot" {
+            bail!(
+                "expected <robot> element, but found <{}> element at {}",
+                node.tag_name().name(),
+                node.node_location()
+            );
+        }
+
+        let mut robot = Robot {
+            materials: HashMap::new(),
+            links: vec![],
+            joints: vec![],
+        };
+        for child in node.element_children() {
+            match child.tag_name().name() {
+                "material" => {
+                    let material = parse_material(child)?;
+                    robot.materials.insert(material.name, material);
+                }
+                "link" => robot.links.push(robot.parse_link(child)?),
+                "joint" => robot.joints.push(parse_joint(child)?),
+                _ => {}
+            }
+        }
+        Ok(robot)
+    }
+
+    fn parse_link(&self, node: xml::Node<'a, '_>) -> io::Result<Link<'a>> {
+        let mut link = Link {
+            name: node.required_attribute("name")?,
+            elements: vec![],
+        };
+        for child in node.element_children() {
+            let kind = match child.tag_name().name() {
+                "visual" => "visual",
+                "collision" => "collision",
+                _ => continue,
+            };
+            let geometry = match child.child("geometry") {
+                Some(geometry) => geometry,
+                None => bail!(
+                    "expected <geometry> element in <{}> element at {}",
+                    kind,
+                    child.node_location()
+                ),
+            };
+            let material = match child.child("material") {
+                Some(material) => {
+                    let mut material = parse_material(material)?;
+                    // A material that only has a name refers to the material
+                    // defined in the <robot> element.
+                    if let Some(m) = self.materials.get(material.name) {
+                        material.color = material.color.or(m.color);
+                        material.texture = material.texture.or(m.texture);
+                    }
+                    Some(material)
+                }
+                None => None,
+            };
+            link.elements.push(Element {
+                kind,
+                name: child.attribute("name"),
+                origin: parse_origin(child)?,
+                geometry: parse_geometry(geometry)?,
+                material,
+            });
+        }
+        Ok(link)
+    }
+
+    fn build(
+        &self,
+        path: Option<&Path>,
+        joint_positions: &HashMap<String, f32>,
+        load: &mut dyn FnMut(&Resource<'_>) -> io::Result<Scene>,
+    ) -> io::Result<Scene> {
+        let dir = path.and_then(Path::parent);
+        let transforms = self.link_transforms(joint_positions);
+        let mut scene = Scene::default();
+        for link in &self.links {
+            let link_transform = transforms.get(link.name).copied().unwrap_or_default();
+            for element in &link.elements {
+                let name = match element.name {
+                    Some(name) => format!("{}/{}/{name}", link.name, element.kind),
+                    None => format!("{}/{}", link.name, element.kind),
+                };
+                let mut transform = link_transform;
+                transform *= element.origin;
+                match element.geometry {
+                    Geometry::Mesh { filename, scale } => {
+                        let mesh_path = match dir {
+                            Some(dir) => resolver::join(dir, filename),
+                            None => resolver::resolve_path_lossy(Path::new(filename)).into_owned(),
+                        };
+                        let loaded = load(&Resource {
+                            path: &mesh_path,
+                            uri: Path::new(filename),
+                            requester: path,
+                            kind: ResourceKind::Model,
+                        })?;
+                        transform *= Matrix4x4::scaling(scale);
+                        let flip = scale[0] * scale[1] * scale[2] < 0.;
+                        for (mut mesh, mut material) in
+                            loaded.meshes.into_iter().zip(loaded.materials)
+                        {
+                            mesh.name.clone_from(&name);
+                            transform_mesh(&mut mesh, transform, flip);
+                            // The material in URDF fills in what the mesh file
+                            // does not have (e.g., STL).
+                            if let Some(m) = &element.material {
+                                material.color.diffuse = material.color.diffuse.or(m.color);
+                                if material.texture.diffuse.is_none() {
+                                    material.texture.diffuse = m.texture_path(dir);
+                                }
+                            }
+                            scene.meshes.push(mesh);
+                            scene.materials.push(material);
+                        }
+                    }
+                    _ => {
+                        let mut mesh = element.geometry.primitive();
+                        mesh.name = name;
+                        transform_mesh(&mut mesh, transform, false);
+                        let m = element.material.clone().unwrap_or_default();
+                        let mut material = common::Material {
+                            name: m.name.to_owned(),
+                            ..Default::default()
+                        };
+                        material.color.diffuse = Some(m.color.unwrap_or(DEFAULT_COLOR));
+                        material.texture.diffuse = m.texture_path(dir);
+                        scene.meshes.push(mesh);
+                        scene.materials.push(material);
+                    }
+                }
+            }
+        }
+        Ok(scene)
+    }
+
+    /// Computes the transforms of links from the root links.
+    fn link_transforms(
+        &self,
+        joint_positions: &HashMap<String, f32>,
+    ) -> HashMap<&'a str, Matrix4x4> {
+        let children: HashSet<_> = self.joints.iter().map(|j| j.child).collect();
+        let mut transforms = HashMap::new();
+        let mut stack: Vec<_> = self
+            .links
+            .iter()
+            .filter(|l| !children.contains(l.name))
+            .map(|l| (l.name, Matrix4x4::default()))
+            .collect();
+        while let Some((link, transform)) = stack.pop() {
+            // Guard against cyclic references.
+            if transforms.insert(link, transform).is_some() {
+                continue;
+            }
+            for joint in self.joints.iter().filter(|j| j.parent == link) {
+                let position = self.joint_position(joint, joint_positions);
+                let mut transform = transform;
+                transform *= joint.origin;
+                match joint.ty {
+                    "revolute" | "continuous" => {
+                        transform *= Matrix4x4::rotation(position, joint.axis);
+                    }
+                    "prismatic" => {
+                        let [x, y, z] = joint.axis;
+                        transform *=
+                            Matrix4x4::translation([x * position, y * position, z * position]);
+                    }
+                    // fixed, floating, planar
+                    _ => {}
+                }
+                stack.push((joint.child, transform));
+            }
+        }
+        transforms
+    }
+
+    fn joint_position(&self, joint: &Joint<'_>, joint_positions: &HashMap<String, f32>) -> f32 {
+        let mut joint = joint;
+        let mut multiplier = 1.;
+        let mut offset = 0.;
+        // Follow the chain of mimic joints (with a limit to guard against
+        // cyclic references).
+        for _ in 0..self.joints.len() {
+            if let Some(&position) = joint_positions.get(joint.name) {
+                return multiplier * position + offset;
+            }
+            match joint
+                .mimic
+                .and_then(|(name, m, o)| Some((self.joints.iter().find(|j| j.name == name)?, m, o)))
+            {
+                Some((mimic, m, o)) => {
+                    offset += multiplier * o;
+                    multiplier *= m;
+                    joint = mimic;
+                }
+                None => break,
+            }
+        }
+        offset
+    }
+}
+
+impl Material<'_> {
+    fn texture_path(&self, dir: Option<&Path>) -> Option<PathBuf> {
+        let texture = self.texture?;
+        match dir {
+            Some(dir) => Some(resolver::join(dir, texture)),
+            None => Some(texture.into()),
+        }
+    }
+}
+
+/*
+<material name="...">
+  <color rgba="..."/>
+  <texture filename="..."/>
+</material>
+*/
+fn parse_material<'a>(node: xml::Node<'a, '_>) -> io::Result<Material<'a>> {
+    let mut material = Material {
+        name: node.attribute("name").unwrap_or_default(),
+        ..Default::default()
+    };
+    if let Some(color) = node.child("color") {
+        material.color = parse_floats(color, "rgba")?;
+    }
+    if let Some(texture) = node.child("texture") {
+        material.texture = texture.attribute("filename");
+    }
+    Ok(material)
+}
+
+/*
+<joint name="..." type="...">
+  <origin xyz="..." rpy="..."/>
+  <parent link="..."/>
+  <child link="..."/>
+  <axis xyz="..."/>
+  <mimic joint="..." multiplier="..." offset="..."/>
+</joint>
+*/
+fn parse_joint<'a>(node: xml::Node<'a, '_>) -> io::Result<Joint<'a>> {
+    let link = |name| match node.child(name) {
+        Some(child) => child.required_attribute("link"),
+        None => bail!(
+            "expected <{}> element in <joint> element at {}",
+            name,
+            node.node_location()
+        ),
+    };
+    let axis = match node.child("axis") {
+        Some(axis) => normalize(parse_floats(axis, "xyz")?.unwrap_or([1., 0., 0.])),
+        None => [1., 0., 0.],
+    };
+    let mimic = match node.child("mimic") {
+        Some(mimic) => Some((
+            mimic.required_attribute("joint")?,
+            parse_floats(mimic, "multiplier")?.map_or(1., |[v]| v),
+            parse_floats(mimic, "offset")?.map_or(0., |[v]| v),
+        )),
+        None => None,
+    };
+    Ok(Joint {
+        name: node.required_attribute("name")?,
+        ty: node.required_attribute("type")?,
+        parent: link("parent")?,
+        child: link("child")?,
+        origin: parse_origin(node)?,
+        axis,
+        mimic,
+    })
+}
+
+/*
+<geometry>
+  <mesh filename="..." scale="..."/>
+  <box size="..."/>
+  <cylinder radius="..." length="..."/>
+  <sphere radius="..."/>
+</geometry>
+*/
+fn parse_geometry<'a>(node: xml::Node<'a, '_>) -> io::Result<Geometry<'a>> {
+    for child in node.element_children() {
+        let required = |name| match parse_floats(child, name)? {
+            Some([v]) => Ok(v),
+            None => Err(format_err!(
+                "expected {} attribute in <{}> element at {}",
+                name,
+                child.tag_name().name(),
+                child.node_location()
+            )),
+        };
+        match child.tag_name().name() {
+            "mesh" => {
+                return Ok(Geometry::Mesh {
+                    filename: child.required_attribute("filename")?,
+                    scale: parse_floats(child, "scale")?.unwrap_or([1.; 3]),
+                })
+            }
+            "box" => {
+                return match parse_floats(child, "size")? {
+                    Some(size) => Ok(Geometry::Box { size }),
+                    None => bail!(
+                        "expected size attribute in <box> element at {}",
+                        child.node_location()
+                    ),
+                }
+            }
+            "cylinder" => {
+                return Ok(Geometry::Cylinder {
+                    radius: required("radius")?,
+                    length: required("length")?,
+                })
+            }
+            "sphere" => {
+                return Ok(Geometry::Sphere {
+                    radius: required("radius")?,
+                })
+            }
+            _ => {}
+        }
+    }
+    bail!(
+        "expected <mesh>, <box>, <cylinder>, or <sphere> element in <geometry> element at {}",
+        node.node_location()
+    )
+}
+
+/*
+<origin xyz="..." rpy="..."/>
+*/
+fn parse_origin(node: xml::Node<'_, '_>) -> io::Result<Matrix4x4> {
+    let mut transform = Matrix4x4::default();
+    if let Some(origin) = node.child("origin") {
+        if let Some(xyz) = parse_floats(origin, "xyz")? {
+            transform *= Matrix4x4::translation(xyz);
+        }
+        if let Some([r, p, y]) = parse_floats(origin, "rpy")? {
+            transform *= Matrix4x4::rotation(y, [0., 0., 1.]);
+            transform *= Matrix4x4::rotation(p, [0., 1., 0.]);
+            transform *= Matrix4x4::rotation(r, [1., 0., 0.]);
+        }
+    }
+    Ok(transform)
+}
+
+fn parse_floats<const N: usize>(
+    node: xml::Node<'_, '_>,
+    name: &str,
+) -> io::Result<Option<[f32; N]>> {
+    let text = match node.attribute(name) {
+        Some(text) => xml::trim(text),
+        None => return Ok(None),
+    };
+    let map_err = |e| {
+        format_err!(
+            "{e} in <{}> element at {}: {:?}",
+            node.tag_name().name(),
+            node.attr_value_location(name),
+            text
+        )
+    };
+    let mut values = [0.; N];
+    let mut iter = xml::parse_float_array_exact(text, N);
+    for v in &mut values {
+        *v = iter.next().unwrap().map_err(map_err)?;
+    }
+    if let Some(res) = iter.next() {
+        res.map_err(map_err)?;
+    }
+    Ok(Some(values))
+}
+
+impl Geometry<'_> {
+    fn primitive(&self) -> Mesh {
+        let mut mesh = Mesh::default();
+        match *self {
+            Geometry::Mesh { .. } => unreachable!(),
+            Geometry::Box { size } => {
+                let h = [size[0] / 2., size[1] / 2., size[2] / 2.];
+                for axis in 0..3 {
+                    for sign in [1., -1.] {
+                        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+                        let corner = |a: f32, b: f32| {
+                            let mut p = [0.; 3];
+                            p[axis] = sign * h[axis];
+                            p[u] = a * h[u];
+                            p[v] = b * h[v];
+                            p
+                        };
+                        let mut normal = [0.; 3];
+                        normal[axis] = sign;
+                        let mut quad = [
+                            corner(-1., -1.),
+                            corner(1., -1.),
+                            corner(1., 1.),
+                            corner(-1., 1.),
+                        ];
+                        if sign < 0. {
+                            quad.reverse();
+                        }
+                        push_triangle(&mut mesh, [quad[0], quad[1], quad[2]], [normal; 3]);
+                        push_triangle(&mut mesh, [quad[0], quad[2], quad[3]], [normal; 3]);
+                    }
+                }
+            }
+            Geometry::Cylinder { radius, length } => {
+                let h = length / 2.;
+                let point = |i: usize, z: f32| {
+                    #[allow(clippy::cast_precision_loss)]
+                    let a = 2. * PI * i as f32 / SEGMENTS as f32;
+                    (
+                        [radius * a.cos(), radius * a.sin(), z],
+                        [a.cos(), a.sin(), 0.],
+                    )
+                };
+                for i in 0..SEGMENTS {
+                    let (p0, n0) = point(i, -h);
+                    let (p1, n1) = point(i + 1, -h);
+                    let (p2, _) = point(i + 1, h);
+                    let (p3, _) = point(i, h);
+                    push_triangle(&mut mesh, [p0, p1, p2], [n0, n1, n1]);
+                    push_triangle(&mut mesh, [p0, p2, p3], [n0, n1, n0]);
+                    push_triangle(&mut mesh, [[0., 0., h], p3, p2], [[0., 0., 1.]; 3]);
+                    push_triangle(&mut mesh, [[0., 0., -h], p1, p0], [[0., 0., -1.]; 3]);
+                }
+            }
+            Geometry::Sphere { radius } => {
+                let normal = |i: usize, j: usize| {
+                    #[allow(clippy::cast_precision_loss)]
+                    let (lon, lat) = (
+                        2. * PI * i as f32 / SEGMENTS as f32,
+                        PI * j as f32 / RINGS as f32 - PI / 2.,
+                    );
+                    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+                };
+                let point = |n: Vec3| [radius * n[0], radius * n[1], radius * n[2]];
+                for j in 0..RINGS {
+                    for i in 0..SEGMENTS {
+                        let n = [
+                            normal(i, j),
+                            normal(i + 1, j),
+                            normal(i + 1, j + 1),
+                            normal(i, j + 1),
+                        ];
+                        let p = n.map(point);
+                        // Skip degenerate triangles at the poles.
+                        if j != 0 {
+                            push_triangle(&mut mesh, [p[0], p[1], p[2]], [n[0], n[1], n[2]]);
+                        }
+                        if j != RINGS - 1 {
+                            push_triangle(&mut mesh, [p[0], p[2], p[3]], [n[0], n[2], n[3]]);
+                        }
+                    }
+                }
+            }
+        }
+        mesh
+    }
+}
+
+fn push_triangle(mesh: &mut Mesh, vertices: [Vec3; 3], normals: [Vec3; 3]) {
+    #[allow(clippy::cast_possible_truncation)]
+    let vertices_indices = [
+        mesh.vertices.len() as u32,
+        (mesh.vertices.len() + 1) as u32,
+        (mesh.vertices.len() + 2) as u32,
+    ];
+    mesh.vertices.extend_from_slice(&vertices);
+    mesh.normals.extend_from_slice(&normals);
+    mesh.faces.push(vertices_indices);
+}
+
+fn transform_mesh(mesh: &mut Mesh, transform: Matrix4x4, flip: bool) {
+    for v in &mut mesh.vertices {
+        *v *= transform;
+    }
+    let normal_transform = transform.normal_matrix();
+    for n in &mut mesh.normals {
+        *n *= normal_transform;
+        *n = normalize(*n);
+    }
+    // Mirroring reverses the winding order.
+    if flip {
+        for face in &mut mesh.faces {
+            face.swap(1, 2);
+        }
+    }
+}
@@ -0,0 +1,720 @@
+//! [USD] text format (.usda) parser.
+//!
+//! Mesh prims are flattened according to the `xformOp`s of their ancestors,
+//! and coordinates are converted to meters according to the `metersPerUnit`
+//! layer metadata. Like the `up_axis` of COLLADA, `upAxis` is validated but
+//! coordinates are not rotated.
+//!
+//! Only a single layer is read: composition arcs (references, payloads,
+//! sublayers, variants, etc.), `over` prims, and `class` prims are ignored.
+//! Binary USD (.usdc) and USDZ are not supported.
+//!
+//! [USD]: https://openusd.org
+
+#![allow(clippy::many_single_char_names)]
+
+mod error;
+mod parse;
+
+use std::{
+    collections::HashMap,
+    f32::consts::PI,
+    io,
+    path::{Path, PathBuf},
+};
+
+use self::parse::{Prim, Property, Specifier, Value};
+use crate::{
+    common::{self, DEFAULT_COLOR},
+    resolver,
+    utils::matrix::{normalize, Matrix4x4},
+    Vec3,
+};
+
+/// Parses meshes from bytes of USD text format.
+#[inline]
+pub fn from_slice(bytes: &[u8]) -> io::Result<common::Scene> {
+    from_slice_internal(bytes, None)
+}
+
+pub(crate) fn from_slice_internal(bytes: &[u8], path: Option<&Path>) -> io::Result<common::Scene> {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let layer = parse::parse(bytes).map_err(|e| e.into_io_error(bytes, path))?;
+
+    // The fallback value of metersPerUnit is 0.01 (centimeters).
+    let mut meters_per_unit = 0.01;
+    for (key, value) in &layer.metadata {
+        match &**key {
+            "metersPerUnit" => match value {
+                #[allow(clippy::cast_possible_truncation)]
+                Value::Number(v) => meters_per_unit = *v as f32,
+                _ => bail!("expected number for metersPerUnit layer metadata"),
+            },
+            "upAxis" => match value {
+                Value::String(v) if v == "Y" || v == "Z" => {}
+                _ => bail!("expected \"Y\" or \"Z\" for upAxis layer metadata"),
+            },
+            _ => {}
+        }
+    }
+
+    let mut prims = HashMap::new();
+    for prim in &layer.prims {
+        collect_prims(prim, String::new(), &mut prims);
+    }
+    let mut builder = Builder {
+        prims,
+        dir: path.and_then(Path::parent),
+        scene: common::Scene::default(),
+    };
+    let transform = Matrix4x4::scaling([meters_per_unit; 3]);
+    for prim in &layer.prims {
+        builder.visit(prim, "", transform, transform, None)?;
+    }
+    Ok(builder.scene)
+}
+
+/// Collects `def` prims by their absolute paths.
+fn collect_prims<'a, 'b>(
+    prim: &'b Prim<'a>,
+    parent: String,
+    prims: &mut HashMap<String, &'b Prim<'a>>,
+) {
+    if prim.specifier != Specifier::Def {
+        return;
+    }
+    let path = parent + "/" + &prim.name;
+    for child in &prim.children {
+        collect_prims(child, path.clone(), prims);
+    }
+    prims.entry(path).or_insert(prim);
+}
+
+struct Builder<'a, 'b> {
+    prims: HashMap<String, &'b Prim<'a>>,
+    dir: Option<&'b Path>,
+    scene: common::Scene,
+}
+
+impl<'a, 'b> Builder<'a, 'b> {
+    fn visit(
+        &mut self,
+        prim: &'b Prim<'a>,
+        parent: &str,
+        root_transform: Matrix4x4,
+        mut transform: Matrix4x4,
+        mut binding: Option<&'b str>,
+    ) -> io::Result<()> {
+        if prim.specifier != Specifier::Def {
+            return Ok(());
+        }
+        let path = format!("{parent}/{}", prim.name);
+        if let Some(Value::String(v)) = attribute(prim, "visibility") {
+            if v == "invisible" {
+                return Ok(());
+            }
+        }
+        if let Some(order) = attribute(prim, "xformOpOrder") {
+            let (m, reset) = local_transform(prim, order, &path)?;
+            if reset {
+                transform = root_transform;
+            }
+            transform *= m;
+        }
+        if let Some(target) = relationship(prim, "material:binding") {
+            binding = Some(target);
+        }
+        if prim.ty == Some("Mesh") {
+            self.push_mesh(prim, &path, transform, binding)?;
+        }
+        for child in &prim.children {
+            self.visit(child, &path, root_transform, transform, binding)?;
+        }
+        Ok(())
+    }
+
+    /*
+    def Mesh "Name"
+    {
+        int[] faceVertexCounts = [...]
+        int[] faceVertexIndices = [...]
+        point3f[] points = [(x, y, z), ...]
+        normal3f[] normals = [(x, y, z), ...] (interpolation = "...")
+        texCoord2f[] primvars:st = [(u, v), ...] (interpolation = "...")
+        int[] primvars:st:indices = [...]
+        color3f[] primvars:displayColor = [(r, g, b), ...] (interpolation = "...")
+        uniform token orientation = "rightHanded"
+        rel material:binding = </Path/To/Material>
+
+        def GeomSubset "Name"
+        {
+            uniform token elementType = "face"
+            uniform token familyName = "materialBind"
+            int[] indices = [...]
+            rel material:binding = </Path/To/Material>
+        }
+    }
+    */
+    fn push_mesh(
+        &mut self,
+        prim: &Prim<'_>,
+        path: &str,
+        transform: Matrix4x4,
+        binding: Option<&str>,
+    ) -> io::Result<()> {
+        let points: Vec<Vec3> = chunks(&numbers(prim, "points", path)?);
+        let counts = numbers(prim, "faceVertexCounts", path)?;
+        let indices = numbers(prim, "faceVertexIndices", path)?;
+        let normals = if attribute(prim, "primvars:normals").is_some() {
+            Primvar::<3>::new(prim, "primvars:normals", Interpolation::Vertex, path)?
+        } else {
+            Primvar::new(prim, "normals", Interpolation::Vertex, path)?
+        };
+        let normal_transform = transform.normal_matrix();
+        // primvars:st is the conventional name of texture coordinates, but
+        // other texCoord2f primvars are also used if it is not present.
+        let st_name = prim
+            .properties
+            .iter()
+            .find(|p| p.name == "primvars:st")
+            .or_else(|| {
+                prim.properties
+                    .iter()
+                    .find(|p| p.ty.map_or(false, |ty| ty.starts_with("texCoord2")))
+            })
+            .map_or("primvars:st", |p| p.name);
+        let texcoords = Primvar::<2>::new(prim, st_name, Interpolation::Constant, path)?;
+        let colors =
+            Primvar::<3>::new(prim, "primvars:displayColor", Interpolation::Constant, path)?;
+        let left_handed = matches!(
+            attribute(prim, "orientation"),
+            Some(Value::String(v)) if v == "leftHanded"
+        );
+
+        // Assign faces to GeomSubsets with material bindings.
+        let num_faces = counts.len();
+        let mut groups = vec![(binding, vec![])];
+        let mut face_group = vec![0; num_faces];
+        for subset in &prim.children {
+            if subset.specifier != Specifier::Def || subset.ty != Some("GeomSubset") {
+                continue;
+            }
+            let subset_binding = match relationship(subset, "material:binding") {
+                Some(b) => Some(b),
+                None => continue,
+            };
+            let subset_path = format!("{path}/{}", subset.name);
+            let group = groups.len();
+            groups.push((subset_binding, vec![]));
+            for &face in &*numbers(subset, "indices", &subset_path)? {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                if let Some(g) = face_group.get_mut(face as usize) {
+                    *g = group;
+                }
+            }
+        }
+        let mut start = 0_usize;
+        for (face, &count) in counts.iter().enumerate() {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let count = count.max(0.) as usize;
+            let end = match start.checked_add(count) {
+                Some(end) if end <= indices.len() => end,
+                _ => bail!(
+                    "faceVertexCounts and faceVertexIndices mismatch in {}",
+                    path
+                ),
+            };
+            groups[face_group[face]].1.push((face, start..end));
+            start = end;
+        }
+
+        let constant_color = match colors.interpolation {
+            Interpolation::Constant => colors.values.first().map(|&[r, g, b]| [r, g, b, 1.]),
+            _ => None,
+        };
+        for (binding, faces) in groups {
+            if faces.is_empty() {
+                continue;
+            }
+            let mut mesh = common::Mesh {
+                name: prim.name.clone().into_owned(),
+                ..Default::default()
+            };
+            for (face, corners) in faces {
+                if corners.len() < 3 {
+                    continue;
+                }
+                for i in 1..corners.len() - 1 {
+                    #[allow(clippy::cast_possible_truncation)]
+                    let vertices_indices = [
+                        mesh.vertices.len() as u32,
+                        (mesh.vertices.len() + 1) as u32,
+                        (mesh.vertices.len() + 2) as u32,
+                    ];
+                    let triangle = if left_handed {
+                        [0, i + 1, i]
+                    } else {
+                        [0, i, i + 1]
+                    };
+                    for pos in triangle.map(|i| corners.start + i) {
+                        let point = indices[pos];
+                        let mut v = get(&points, point, "faceVertexIndices", path)?;
+                        v *= transform;
+                        mesh.vertices.push(v);
+                        if !normals.values.is_empty() {
+                            let mut n = normals.get(face, pos, point, path)?;
+                            n *= normal_transform;
+                            mesh.normals.push(normalize(n));
+                        }
+                        if !texcoords.values.is_empty() {
+                            mesh.texcoords[0].push(texcoords.get(face, pos, point, path)?);
+                        }
+                        if constant_color.is_none() && !colors.values.is_empty() {
+                            let [r, g, b] = colors.get(face, pos, point, path)?;
+                            mesh.colors[0].push([r, g, b, 1.]);
+                        }
+                    }
+                    mesh.faces.push(vertices_indices);
+                }
+            }
+            let material = match binding.and_then(|b| self.prims.get(b)) {
+                Some(material) => self.material(material, binding.unwrap())?,
+                None => {
+                    let mut mat = common::Material::default();
+                    mat.color.diffuse = Some(constant_color.unwrap_or(DEFAULT_COLOR));
+                    mat
+                }
+            };
+            self.scene.meshes.push(mesh);
+            self.scene.materials.push(material);
+        }
+        Ok(())
+    }
+
+    /*
+    def Material "Name"
+    {
+        token outputs:surface.connect = </Path/To/Material/Shader.outputs:surface>
+
+        def Shader "Shader"
+        {
+            uniform token info:id = "UsdPreviewSurface"
+            color3f inputs:diffuseColor = (r, g, b)
+            color3f inputs:diffuseColor.connect = </Path/To/Material/Texture.outputs:rgb>
+            color3f inputs:emissiveColor = (r, g, b)
+            color3f inputs:specularColor = (r, g, b)
+            float inputs:opacity = 1
+            float inputs:ior = 1.5
+            float inputs:roughness = 0.5
+        }
+
+        def Shader "Texture"
+        {
+            uniform token info:id = "UsdUVTexture"
+            asset inputs:file = @path@
+        }
+    }
+    */
+    #[allow(clippy::cast_possible_truncation)]
+    fn material(&self, material: &Prim<'_>, path: &str) -> io::Result<common::Material> {
+        let mut mat = common::Material {
+            name: material.name.clone().into_owned(),
+            ..Default::default()
+        };
+        let shader = match relationship(material, "outputs:surface.connect")
+            .and_then(|target| self.connected_prim(target))
+        {
+            Some(shader) => shader,
+            // Fall back to the UsdPreviewSurface shader in the material.
+            None => match material
+                .children
+                .iter()
+                .find(|c| shader_id(c) == Some("UsdPreviewSurface"))
+            {
+                Some(shader) => shader,
+                None => return Ok(mat),
+            },
+        };
+        if shader_id(shader) != Some("UsdPreviewSurface") {
+            return Ok(mat);
+        }
+        let path = format!("{path}/{}", shader.name);
+        let color = |name: &str, default: Vec3| -> io::Result<Vec3> {
+            Ok(match *numbers(shader, name, &path)? {
+                [r, g, b, ..] => [r as f32, g as f32, b as f32],
+                _ => default,
+            })
+        };
+        let float = |name: &str, default: f32| -> io::Result<f32> {
+            Ok(match *numbers(shader, name, &path)? {
+                [v, ..] => v as f32,
+                _ => default,
+            })
+        };
+        let opacity = float("inputs:opacity", 1.)?;
+        let [r, g, b] = color("inputs:diffuseColor", [0.18, 0.18, 0.18])?;
+        mat.color.diffuse = Some([r, g, b, opacity]);
+        let [r, g, b] = color("inputs:emissiveColor", [0., 0., 0.])?;
+        mat.color.emissive = Some([r, g, b, 1.]);
+        if float("inputs:useSpecularWorkflow", 0.)? != 0. {
+            let [r, g, b] = color("inputs:specularColor", [0., 0., 0.])?;
+            mat.color.specular = Some([r, g, b, 1.]);
+        }
+        mat.opacity = Some(opacity);
+        mat.index_of_refraction = Some(float("inputs:ior", 1.5)?);
+
+        mat.texture.diffuse = self.texture(shader, "inputs:diffuseColor.connect");
+        mat.texture.emissive = self.texture(shader, "inputs:emissiveColor.connect");
+        mat.texture.specular = self.texture(shader, "inputs:specularColor.connect");
+        mat.texture.opacity = self.texture(shader, "inputs:opacity.connect");
+        mat.texture.normal = self.texture(shader, "inputs:normal.connect");
+        mat.texture.displacement = self.texture(shader, "inputs:displacement.connect");
+        Ok(mat)
+    }
+
+    fn texture(&self, shader: &Prim<'_>, connect: &str) -> Option<PathBuf> {
+        let texture = self.connected_prim(relationship(shader, connect)?)?;
+        if shader_id(texture) != Some("UsdUVTexture") {
+            return None;
+        }
+        let file = match attribute(texture, "inputs:file")? {
+            Value::Asset(file) => file,
+            _ => return None,
+        };
+        match self.dir {
+            Some(dir) => Some(resolver::join(dir, &**file)),
+            None => Some(file.clone().into_owned().into()),
+        }
+    }
+
+    /// Returns the prim that owns the property referred by the given path
+    /// (`/Path/To/Prim.outputs:name`).
+    fn connected_prim(&self, target: &str) -> Option<&'b Prim<'a>> {
+        let prim_path = target.rsplit_once('.').map_or(target, |(prim, _)| prim);
+        self.prims.get(prim_path).copied()
+    }
+}
+
+fn shader_id<'a>(prim: &'a Prim<'_>) -> Option<&'a str> {
+    match attribute(prim, "info:id")? {
+        Value::String(id) if prim.ty == Some("Shader") => Some(id),
+        _ => None,
+    }
+}
+
+fn property<'a, 'b>(prim: &'b Prim<'a>, name: &str) -> Option<&'b Property<'a>> {
+    // The last one takes precedence if a property is specified more than once.
+    prim.properties.iter().rev().find(|p| p.name == name)
+}
+
+/// Returns the default value of the attribute, or the first time sample if
+/// the attribute has no default value.
+fn attribute<'a, 'b>(prim: &'b Prim<'a>, name: &str) -> Option<&'b Value<'a>> {
+    if let Some(value) = property(prim, name).and_then(|p| p.value.as_ref()) {
+        return Some(value);
+    }
+    let samples = prim
+        .properties
+        .iter()
+        .rev()
+        .find(|p| p.name.strip_suffix(".timeSamples") == Some(name))?;
+    match samples.value.as_ref()? {
+        Value::Dict(samples) => samples.first().map(|(_, v)| v),
+        _ => None,
+    }
+}
+
+/// Returns the first target of the relationship (or connection).
+fn relationship<'a>(prim: &Prim<'a>, name: &str) -> Option<&'a str> {
+    match property(prim, name)?.value.as_ref()? {
+        Value::Path(path) => Some(path),
+        Value::List(paths) => match paths.first()? {
+            Value::Path(path) => Some(path),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns numbers in the attribute value, flattening arrays and tuples.
+fn numbers(prim: &Prim<'_>, name: &str, path: &str) -> io::Result<Vec<f64>> {
+    fn flatten(value: &Value<'_>, out: &mut Vec<f64>) -> bool {
+        match value {
+            Value::Number(v) => out.push(*v),
+            Value::Ident("true") => out.push(1.),
+            Value::Ident("false") => out.push(0.),
+            Value::List(values) => return values.iter().all(|v| flatten(v, out)),
+            _ => return false,
+        }
+        true
+    }
+    let mut out = vec![];
+    match attribute(prim, name) {
+        Some(Value::Ident("None")) | None => {}
+        Some(value) => {
+            if !flatten(value, &mut out) {
+                bail!("expected numbers in {} attribute of {}", name, path);
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[derive(Clone, Copy)]
+enum Interpolation {
+    Constant,
+    Uniform,
+    Vertex,
+    FaceVarying,
+}
+
+struct Primvar<const N: usize> {
+    name: String,
+    values: Vec<[f32; N]>,
+    indices: Option<Vec<f64>>,
+    interpolation: Interpolation,
+}
+
+impl<const N: usize> Primvar<N> {
+    fn new(prim: &Prim<'_>, name: &str, default: Interpolation, path: &str) -> io::Result<Self> {
+        let values = chunks(&numbers(prim, name, path)?);
+        let indices_name = format!("{name}:indices");
+        let indices = match property(prim, &indices_name) {
+            Some(_) => Some(numbers(prim, &indices_name, path)?).filter(|i| !i.is_empty()),
+            None => None,
+        };
+        let interpolation = property(prim, name)
+            .and_then(|p| p.metadata.iter().find(|(k, _)| k == "interpolation"))
+            .map_or(Ok(default), |(_, v)| match v {
+                Value::String(v) => match &**v {
+                    "constant" => Ok(Interpolation::Constant),
+                    "uniform" => Ok(Interpolation::Uniform),
+                    "vertex" | "varying" => Ok(Interpolation::Vertex),
+                    "faceVarying" => Ok(Interpolation::FaceVarying),
+                    _ => bail!(
+                        "unknown interpolation {:?} of {} attribute of {}",
+                        v,
+                        name,
+                        path
+                    ),
+                },
+                _ => bail!(
+                    "expected string for interpolation of {} attribute of {}",
+                    name,
+                    path
+                ),
+            })?;
+        Ok(Self {
+            name: name.to_owned(),
+            values,
+            indices,
+            interpolation,
+        })
+    }
+
+    /// Returns the value for the `pos`-th face-vertex, which is the
+    /// `point`-th point in the `face`-th face.
+    fn get(&self, face: usize, pos: usize, point: f64, path: &str) -> io::Result<[f32; N]> {
+        #[allow(clippy::cast_precision_loss)]
+        let index = match self.interpolation {
+            Interpolation::Constant => 0.,
+            Interpolation::Uniform => face as f64,
+            Interpolation::Vertex => point,
+            Interpolation::FaceVarying => pos as f64,
+        };
+        let index = match &self.indices {
+            Some(indices) => {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let i = index as usize;
+                match indices.get(i) {
+                    Some(&i) => i,
+                    None => bail!(
+                        "index out of bounds ({}) in {}:indices of {}",
+                        i,
+                        self.name,
+                        path
+                    ),
+                }
+            }
+            None => index,
+        };
+        get(&self.values, index, &self.name, path)
+    }
+}
+
+/// Computes the local transform from `xformOpOrder`, and returns whether
+/// `!resetXformStack!` is present.
+fn local_transform(
+    prim: &Prim<'_>,
+    order: &Value<'_>,
+    path: &str,
+) -> io::Result<(Matrix4x4, bool)> {
+    let ops = match order {
+        Value::List(ops) => ops,
+        _ => bail!("expected array for xformOpOrder attribute of {}", path),
+    };
+    let mut m = Matrix4x4::default();
+    let mut reset = false;
+    for op in ops {
+        let op = match op {
+            Value::String(op) => &**op,
+            _ => bail!("expected token in xformOpOrder attribute of {}", path),
+        };
+        if op == "!resetXformStack!" {
+            m = Matrix4x4::default();
+            reset = true;
+            continue;
+        }
+        let (invert, name) = match op.strip_prefix("!invert!") {
+            Some(name) => (true, name),
+            None => (false, op),
+        };
+        let v = numbers(prim, name, path)?;
+        let ty = name
+            .strip_prefix("xformOp:")
+            .and_then(|s| s.split(':').next())
+            .unwrap_or_default();
+        m *= xform_op(ty, &v, invert).ok_or_else(|| {
+            format_err!(
+                "unsupported or invalid xformOp {} in xformOpOrder attribute of {}",
+                op,
+                path
+            )
+        })?;
+    }
+    Ok((m, reset))
+}
+
+// Refs: https://openusd.org/release/api/class_usd_geom_xform_op.html
+#[allow(clippy::cast_possible_truncation)]
+fn xform_op(ty: &str, v: &[f64], invert: bool) -> Option<Matrix4x4> {
+    let sign: f32 = if invert { -1. } else { 1. };
+    let rotation = |axis: u8, angle: f64| {
+        let mut a = [0., 0., 0.];
+        a[(axis - b'X') as usize] = 1.;
+        Matrix4x4::rotation(sign * angle as f32 * PI / 180., a)
+    };
+    match (ty, v) {
+        ("translate", &[x, y, z]) => Some(Matrix4x4::translation(
+            [x as f32, y as f32, z as f32].map(|v| sign * v),
+        )),
+        ("scale", &[x, y, z]) => Some(Matrix4x4::scaling(
+            [x as f32, y as f32, z as f32].map(|v| if invert { 1. / v } else { v }),
+        )),
+        ("rotateX" | "rotateY" | "rotateZ", &[angle]) => Some(rotation(ty.as_bytes()[6], angle)),
+        // rotateXYZ rotates around the X axis first, then Y, then Z.
+        (
+            "rotateXYZ" | "rotateXZY" | "rotateYXZ" | "rotateYZX" | "rotateZXY" | "rotateZYX",
+            &[..],
+        ) => {
+            let axes = &ty.as_bytes()[6..];
+            if v.len() != 3 {
+                return None;
+            }
+            let mut m = Matrix4x4::default();
+            if invert {
+                for i in 0..3 {
+                    m *= rotation(axes[i], v[usize::from(axes[i] - b'X')]);
+                }
+            } else {
+                for i in (0..3).rev() {
+                    m *= rotation(axes[i], v[usize::from(axes[i] - b'X')]);
+                }
+            }
+            Some(m)
+        }
+        // Quaternion in (real, i, j, k) order.
+        ("orient", &[w, x, y, z]) => {
+            let len = (w * w + x * x + y * y + z * z).sqrt();
+            if len == 0. {
+                return None;
+            }
+            let [w, x, y, z] = [w / len, x / len, y / len, z / len].map(|v| v as f32);
+            let [x, y, z] = [x, y, z].map(|v| sign * v);
+            Some(Matrix4x4::new(
+                1. - 2. * (y * y + z * z),
+                2. * (x * y - w * z),
+                2. * (x * z + w * y),
+                0.,
+                2. * (x * y + w * z),
+                1. - 2. * (x * x + z * z),
+                2. * (y * z - w * x),
+                0.,
+                2. * (x * z - w * y),
+                2. * (y * z + w * x),
+                1. - 2. * (x * x + y * y),
+                0.,
+                0.,
+                0.,
+                0.,
+                1.,
+            ))
+        }
+        // USD matrices are row-major and multiply row vectors, so transpose.
+        ("transform", m) if m.len() == 16 && !invert => {
+            let m: Vec<f32> = m.iter().map(|&v| v as f32).collect();
+            Some(Matrix4x4::new(
+                m[0], m[4], m[8], m[12], m[1], m[5], m[9], m[13], m[2], m[6], m[10], m[14], m[3],
+                m[7], m[11], m[15],
+            ))
+        }
+        _ => None,
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn chunks<const N: usize>(v: &[f64]) -> Vec<[f32; N]> {
+    v.chunks_exact(N)
+        .map(|c| {
+            let mut out = [0.; N];
+            for (out, &c) in out.iter_mut().zip(c) {
+                *out = c as f32;
+            }
+            out
+        })
+        .collect()
+}
+
+fn get<T: Copy>(values: &[T], index: f64, name: &str, path: &str) -> io::Result<T> {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    if index >= 0. {
+        if let Some(&v) = values.get(index as usize) {
+            return Ok(v);
+        }
+    }
+    bail!("index out of bounds ({}) in {} of {}", index, name, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate_xyz() {
+        // rotateXYZ = (90, 0, 90): X first, then Z.
+        let m = xform_op("rotateXYZ", &[90., 0., 90.], false).unwrap();
+        let mut v: Vec3 = [0., 1., 0.];
+        v *= m;
+        assert!((v[0] - 0.).abs() < 1e-6 && (v[1] - 0.).abs() < 1e-6 && (v[2] - 1.).abs() < 1e-6);
+        let mut v: Vec3 = [0., 0., 1.];
+        v *= m;
+        assert!((v[0] - 1.).abs() < 1e-6 && v[1].abs() < 1e-6 && v[2].abs() < 1e-6);
+
+        // Inverted op cancels the original op.
+        let mut m2 = m;
+        m2 *= xform_op("rotateXYZ", &[90., 0., 90.], true).unwrap();
+        let mut v: Vec3 = [1., 2., 3.];
+        v *= m2;
+        assert!((v[0] - 1.).abs() < 1e-5 && (v[1] - 2.).abs() < 1e-5 && (v[2] - 3.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn orient() {
+        // 90 degrees around the Z axis.
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        let m = xform_op("orient", &[h, 0., 0., h], false).unwrap();
+        let mut v: Vec3 = [1., 0., 0.];
+        v *= m;
+        assert!((v[0] - 0.).abs() < 1e-6 && (v[1] - 1.).abs() < 1e-6 && v[2].abs() < 1e-6);
+    }
+}
@@ -0,0 +1,585 @@
+// Parser for the text format of USD layers (.usda).
+//
+// Values are parsed based on their syntax, so type names of attributes are
+// only kept as strings. Variant sets and reorder statements are skipped.
+//
+// Refs: https://openusd.org/release/api/sdf_page_front.html
+
+use std::{borrow::Cow, str};
+
+use super::error::ErrorKind;
+use crate::utils::{bytes::starts_with, float};
+
+// Deeply nested files are almost certainly broken, so limit the depth to
+// avoid stack overflow.
+const MAX_DEPTH: usize = 128;
+
+pub(super) struct Layer<'a> {
+    pub(super) metadata: Vec<(Cow<'a, str>, Value<'a>)>,
+    pub(super) prims: Vec<Prim<'a>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(super) enum Specifier {
+    Def,
+    Over,
+    Class,
+}
+
+pub(super) struct Prim<'a> {
+    pub(super) specifier: Specifier,
+    pub(super) ty: Option<&'a str>,
+    pub(super) name: Cow<'a, str>,
+    pub(super) properties: Vec<Property<'a>>,
+    pub(super) children: Vec<Prim<'a>>,
+}
+
+pub(super) struct Property<'a> {
+    /// The type name of the attribute without `[]` (e.g., `point3f`), or
+    /// `None` for relationships.
+    pub(super) ty: Option<&'a str>,
+    /// The namespaced name of the property, including `.connect` or
+    /// `.timeSamples` suffix if present.
+    pub(super) name: &'a str,
+    pub(super) value: Option<Value<'a>>,
+    pub(super) metadata: Vec<(Cow<'a, str>, Value<'a>)>,
+}
+
+pub(super) enum Value<'a> {
+    Number(f64),
+    /// String or token.
+    String(Cow<'a, str>),
+    /// Keywords such as `true`, `false`, and `None`.
+    Ident(&'a str),
+    /// Asset path (`@path@`).
+    Asset(Cow<'a, str>),
+    /// Path (`</path>`).
+    Path(&'a str),
+    /// Array (`[...]`) or tuple (`(...)`).
+    List(Vec<Value<'a>>),
+    /// Dictionary or time samples.
+    Dict(Vec<(Cow<'a, str>, Value<'a>)>),
+}
+
+pub(super) fn parse(s: &[u8]) -> Result<Layer<'_>, ErrorKind> {
+    if starts_with(s, b"PXR-USDC") {
+        return Err(ErrorKind::Unsupported("binary USD (usdc)", s.len()));
+    }
+    if !starts_with(s, b"#usda ") {
+        return Err(ErrorKind::Expected("#usda header", s.len()));
+    }
+    let mut p = Parser { s };
+    // Skip the header line.
+    p.skip_spaces();
+    let mut layer = Layer {
+        metadata: vec![],
+        prims: vec![],
+    };
+    if p.token(b'(') {
+        layer.metadata = p.read_metadata(0)?;
+    }
+    loop {
+        p.skip_spaces();
+        if p.s.is_empty() {
+            break;
+        }
+        let remaining = p.s.len();
+        let ident = p.read_ident()?;
+        layer.prims.push(p.read_prim(ident, remaining, 0)?);
+    }
+    Ok(layer)
+}
+
+struct Parser<'a> {
+    s: &'a [u8],
+}
+
+impl<'a> Parser<'a> {
+    /*
+    def Xform "Name" (
+        <metadata>
+    )
+    {
+        <properties and children>
+    }
+    */
+    fn read_prim(
+        &mut self,
+        specifier: &str,
+        remaining: usize,
+        depth: usize,
+    ) -> Result<Prim<'a>, ErrorKind> {
+        if depth > MAX_DEPTH {
+            return Err(ErrorKind::TooDeep(remaining));
+        }
+        let specifier = match specifier {
+            "def" => Specifier::Def,
+            "over" => Specifier::Over,
+            "class" => Specifier::Class,
+            _ => return Err(ErrorKind::Expected("def, over, or class", remaining)),
+        };
+        self.skip_spaces();
+        let ty = if self.s.first().map_or(false, |&c| is_ident_start(c)) {
+            Some(self.read_ident()?)
+        } else {
+            None
+        };
+        self.skip_spaces();
+        let name = self.read_string()?;
+        self.skip_spaces();
+        if self.token(b'(') {
+            // Prim metadata (references, apiSchemas, kind, etc.) are not used.
+            self.read_metadata(depth)?;
+            self.skip_spaces();
+        }
+        if !self.token(b'{') {
+            return Err(ErrorKind::Expected("'{'", self.s.len()));
+        }
+
+        let mut prim = Prim {
+            specifier,
+            ty,
+            name,
+            properties: vec![],
+            children: vec![],
+        };
+        loop {
+            self.skip_spaces_and_separators();
+            if self.token(b'}') {
+                break;
+            }
+            let remaining = self.s.len();
+            let mut ident = self.read_ident()?;
+            match ident {
+                "def" | "over" | "class" => {
+                    prim.children
+                        .push(self.read_prim(ident, remaining, depth + 1)?);
+                    continue;
+                }
+                // variantSet "name" = { "variant" { ... } ... }
+                "variantSet" => {
+                    self.skip_spaces();
+                    self.read_string()?;
+                    self.expect(b'=', "'='")?;
+                    self.expect(b'{', "'{'")?;
+                    self.skip_block()?;
+                    continue;
+                }
+                // reorder nameChildren = [...]
+                "reorder" => {
+                    self.read_ident()?;
+                    self.expect(b'=', "'='")?;
+                    self.read_value(depth + 1)?;
+                    continue;
+                }
+                _ => {}
+            }
+            // List operations and variability.
+            let mut delete = false;
+            while let "add" | "append" | "prepend" | "delete" | "custom" | "uniform" | "varying"
+            | "config" = ident
+            {
+                delete |= ident == "delete";
+                ident = self.read_ident()?;
+            }
+            let ty = if ident == "rel" {
+                None
+            } else {
+                // Array types (e.g., `point3f[]`) are not distinguished.
+                if self.s.starts_with(b"[]") {
+                    self.s = &self.s[2..];
+                }
+                Some(ident)
+            };
+            let name = self.read_ident()?;
+            self.skip_spaces_inline();
+            let value = if self.token(b'=') {
+                self.skip_spaces();
+                Some(self.read_value(depth + 1)?)
+            } else {
+                None
+            };
+            self.skip_spaces_inline();
+            let metadata = if self.token(b'(') {
+                self.read_metadata(depth)?
+            } else {
+                vec![]
+            };
+            if !delete {
+                prim.properties.push(Property {
+                    ty,
+                    name,
+                    value,
+                    metadata,
+                });
+            }
+        }
+        Ok(prim)
+    }
+
+    /// Reads `key = value` pairs until `)`.
+    fn read_metadata(&mut self, depth: usize) -> Result<Vec<(Cow<'a, str>, Value<'a>)>, ErrorKind> {
+        let mut metadata = vec![];
+        loop {
+            self.skip_spaces_and_separators();
+            match self.s.first() {
+                Some(b')') => {
+                    self.s = &self.s[1..];
+                    break;
+                }
+                // Documentation string.
+                Some(b'"' | b'\'') => {
+                    self.read_string()?;
+                }
+                Some(_) => {
+                    let mut key = self.read_ident()?;
+                    while let "add" | "append" | "prepend" | "delete" | "reorder" = key {
+                        key = self.read_ident()?;
+                    }
+                    self.expect(b'=', "'='")?;
+                    self.skip_spaces();
+                    let value = self.read_value(depth + 1)?;
+                    metadata.push((Cow::Borrowed(key), value));
+                }
+                None => return Err(ErrorKind::Expected("')'", self.s.len())),
+            }
+        }
+        Ok(metadata)
+    }
+
+    fn read_value(&mut self, depth: usize) -> Result<Value<'a>, ErrorKind> {
+        if depth > MAX_DEPTH {
+            return Err(ErrorKind::TooDeep(self.s.len()));
+        }
+        match self.s.first() {
+            Some(&open @ (b'[' | b'(')) => {
+                let close = if open == b'[' { b']' } else { b')' };
+                self.s = &self.s[1..];
+                let mut values = vec![];
+                loop {
+                    self.skip_spaces();
+                    if self.token(close) {
+                        break;
+                    }
+                    values.push(self.read_value(depth + 1)?);
+                    self.skip_spaces();
+                    if !self.token(b',') {
+                        if self.token(close) {
+                            break;
+                        }
+                        return Err(ErrorKind::Expected(
+                            if close == b']' {
+                                "',' or ']'"
+                            } else {
+                                "',' or ')'"
+                            },
+                            self.s.len(),
+                        ));
+                    }
+                }
+                Ok(Value::List(values))
+            }
+            Some(b'{') => {
+                self.s = &self.s[1..];
+                self.read_dict(depth)
+            }
+            Some(b'"' | b'\'') => Ok(Value::String(self.read_string()?)),
+            Some(b'@') => {
+                let asset = self.read_asset()?;
+                // References and payloads can be followed by a prim path.
+                self.skip_spaces_inline();
+                if self.s.first() == Some(&b'<') {
+                    self.read_path()?;
+                }
+                Ok(Value::Asset(asset))
+            }
+            Some(b'<') => Ok(Value::Path(self.read_path()?)),
+            Some(&c) if is_number_start(c) => Ok(Value::Number(self.read_number()?)),
+            Some(_) => {
+                let ident = self.read_ident()?;
+                Ok(match ident {
+                    "inf" => Value::Number(f64::INFINITY),
+                    "nan" => Value::Number(f64::NAN),
+                    _ => Value::Ident(ident),
+                })
+            }
+            None => Err(ErrorKind::Expected("value", self.s.len())),
+        }
+    }
+
+    /*
+    {
+        string key = "value"
+    }
+    or
+    {
+        0: value,
+        1: value,
+    }
+    */
+    fn read_dict(&mut self, depth: usize) -> Result<Value<'a>, ErrorKind> {
+        let mut entries = vec![];
+        loop {
+            self.skip_spaces_and_separators();
+            if self.token(b'}') {
+                break;
+            }
+            let mut key = self.read_key()?;
+            self.skip_spaces();
+            if !(self.token(b':') || self.token(b'=')) {
+                // The previous token was the type name of the value.
+                if self.s.starts_with(b"[]") {
+                    self.s = &self.s[2..];
+                    self.skip_spaces();
+                }
+                key = self.read_key()?;
+                self.expect(b'=', "'='")?;
+            }
+            self.skip_spaces();
+            let value = self.read_value(depth + 1)?;
+            entries.push((key, value));
+        }
+        Ok(Value::Dict(entries))
+    }
+
+    fn read_key(&mut self) -> Result<Cow<'a, str>, ErrorKind> {
+        match self.s.first() {
+            Some(b'"' | b'\'') => self.read_string(),
+            Some(&c) if is_number_start(c) => {
+                let start = self.s;
+                self.read_number()?;
+                let len = start.len() - self.s.len();
+                Ok(Cow::Borrowed(str::from_utf8(&start[..len]).unwrap()))
+            }
+            _ => self.read_ident().map(Cow::Borrowed),
+        }
+    }
+
+    fn read_ident(&mut self) -> Result<&'a str, ErrorKind> {
+        self.skip_spaces();
+        let mut len = 0;
+        if self.s.first().map_or(false, |&c| is_ident_start(c)) {
+            len = self
+                .s
+                .iter()
+                .position(|&c| !(c.is_ascii_alphanumeric() || matches!(c, b'_' | b':' | b'.')))
+                .unwrap_or(self.s.len());
+        }
+        if len == 0 {
+            return Err(ErrorKind::Expected("identifier", self.s.len()));
+        }
+        // Identifiers are ASCII-only.
+        let ident = str::from_utf8(&self.s[..len]).unwrap();
+        self.s = &self.s[len..];
+        Ok(ident)
+    }
+
+    fn read_number(&mut self) -> Result<f64, ErrorKind> {
+        match float::parse_partial::<f64>(self.s) {
+            Some((v, n)) => {
+                self.s = &self.s[n..];
+                Ok(v)
+            }
+            None => {
+                let remaining = self.s.len();
+                // -inf
+                if let Some(s) = self.s.strip_prefix(b"-inf") {
+                    self.s = s;
+                    return Ok(f64::NEG_INFINITY);
+                }
+                Err(ErrorKind::Float(remaining))
+            }
+        }
+    }
+
+    fn read_string(&mut self) -> Result<Cow<'a, str>, ErrorKind> {
+        let start = self.s.len();
+        let quote = match self.s.first() {
+            Some(&q @ (b'"' | b'\'')) => q,
+            _ => return Err(ErrorKind::Expected("string", start)),
+        };
+        let triple = [quote; 3];
+        let (close, s): (&[u8], _) = if self.s.starts_with(&triple) {
+            (&triple, &self.s[3..])
+        } else {
+            (&triple[..1], &self.s[1..])
+        };
+        let mut i = 0;
+        let mut escaped = false;
+        while i < s.len() {
+            if s[i] == b'\\' {
+                escaped = true;
+                i += 2;
+                continue;
+            }
+            if s[i..].starts_with(close) {
+                let bytes = &s[..i];
+                self.s = &s[i + close.len()..];
+                return Ok(if escaped {
+                    Cow::Owned(unescape(bytes))
+                } else {
+                    String::from_utf8_lossy(bytes)
+                });
+            }
+            if close.len() == 1 && s[i] == b'\n' {
+                break;
+            }
+            i += 1;
+        }
+        Err(ErrorKind::UnclosedString(start))
+    }
+
+    fn read_asset(&mut self) -> Result<Cow<'a, str>, ErrorKind> {
+        let start = self.s.len();
+        let (close, s): (&[u8], _) = if self.s.starts_with(b"@@@") {
+            (b"@@@", &self.s[3..])
+        } else {
+            (b"@", &self.s[1..])
+        };
+        for i in 0..s.len() {
+            if s[i..].starts_with(close) {
+                self.s = &s[i + close.len()..];
+                return Ok(String::from_utf8_lossy(&s[..i]));
+            }
+            if s[i] == b'\n' {
+                break;
+            }
+        }
+        Err(ErrorKind::UnclosedString(start))
+    }
+
+    fn read_path(&mut self) -> Result<&'a str, ErrorKind> {
+        let start = self.s.len();
+        let s = &self.s[1..];
+        match s.iter().position(|&c| c == b'>' || c == b'\n') {
+            Some(i) if s[i] == b'>' => {
+                self.s = &s[i + 1..];
+                str::from_utf8(&s[..i]).map_err(|_| ErrorKind::Expected("path", start))
+            }
+            _ => Err(ErrorKind::Expected("'>'", start)),
+        }
+    }
+
+    /// Skips a block until the `}` that matches the already consumed `{`.
+    fn skip_block(&mut self) -> Result<(), ErrorKind> {
+        let mut level = 1;
+        while level != 0 {
+            self.skip_spaces();
+            match self.s.first() {
+                Some(b'"' | b'\'') => {
+                    self.read_string()?;
+                }
+                Some(b'@') => {
+                    self.read_asset()?;
+                }
+                Some(&c) => {
+                    if c == b'{' {
+                        level += 1;
+                    } else if c == b'}' {
+                        level -= 1;
+                    }
+                    self.s = &self.s[1..];
+                }
+                None => return Err(ErrorKind::Expected("'}'", self.s.len())),
+            }
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, c: u8, msg: &'static str) -> Result<(), ErrorKind> {
+        self.skip_spaces();
+        if self.token(c) {
+            Ok(())
+        } else {
+            Err(ErrorKind::Expected(msg, self.s.len()))
+        }
+    }
+
+    fn token(&mut self, c: u8) -> bool {
+        if self.s.first() == Some(&c) {
+            self.s = &self.s[1..];
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Skips whitespaces (including newlines) and comments.
+    fn skip_spaces(&mut self) {
+        loop {
+            self.skip_spaces_inline();
+            match self.s.first() {
+                Some(b'\n' | b'\r') => self.s = &self.s[1..],
+                _ => break,
+            }
+        }
+    }
+
+    /// Skips whitespaces (including newlines), comments, and separators
+    /// between statements.
+    fn skip_spaces_and_separators(&mut self) {
+        loop {
+            self.skip_spaces();
+            match self.s.first() {
+                Some(b';' | b',') => self.s = &self.s[1..],
+                _ => break,
+            }
+        }
+    }
+
+    /// Skips whitespaces (excluding newlines) and comments.
+    fn skip_spaces_inline(&mut self) {
+        loop {
+            match self.s.first() {
+                Some(b' ' | b'\t') => self.s = &self.s[1..],
+                Some(b'#') => self.skip_line(),
+                Some(b'/') if self.s.get(1) == Some(&b'/') => self.skip_line(),
+                Some(b'/') if self.s.get(1) == Some(&b'*') => {
+                    let len = self.s[2..]
+                        .windows(2)
+                        .position(|w| w == b"*/")
+                        .map_or(self.s.len(), |i| i + 4);
+                    self.s = &self.s[len..];
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn skip_line(&mut self) {
+        let len = self
+            .s
+            .iter()
+            .position(|&c| c == b'\n' || c == b'\r')
+            .unwrap_or(self.s.len());
+        self.s = &self.s[len..];
+    }
+}
+
+#[cold]
+fn unescape(bytes: &[u8]) -> String {
+    let mut buf = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter();
+    while let Some(&c) = iter.next() {
+        if c != b'\\' {
+            buf.push(c);
+            continue;
+        }
+        match iter.next() {
+            Some(b'n') => buf.push(b'\n'),
+            Some(b't') => buf.push(b'\t'),
+            Some(b'r') => buf.push(b'\r'),
+            Some(&c) => buf.push(c),
+            None => {}
+        }
+    }
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+fn is_ident_start(c: u8) -> bool {
+    c.is_ascii_alphabetic() || c == b'_'
+}
+
+fn is_number_start(c: u8) -> bool {
+    matches!(c, b'0'..=b'9' | b'+' | b'-' | b'.')
+}
@@ -44,7 +44,7 @@ pub(crate) fn starts_with(mut s: &[u8], mut needle: &'static [u8]) -> bool {
     s.starts_with(needle)
 }
 
-#[cfg(any(feature = "collada", feature = "obj"))]
+#[cfg(any(feature = "amf", feature = "obj", feature = "urdf", feature = "vrml"))]
 #[inline]
 pub(crate) const fn memchr_naive(needle: u8, mut s: &[u8]) -> Option<usize> {
     let start = s;
@@ -57,24 +57,16 @@ pub(crate) const fn memchr_naive(needle: u8, mut s: &[u8]) -> Option<usize> {
     None
 }
 
-#[cfg(any(feature = "obj", feature = "stl"))]
-#[inline]
-pub(crate) const fn memchr_naive_table(
-    needle_mask: u8,
-    table: &[u8; 256],
-    mut s: &[u8],
-) -> Option<usize> {
-    let start = s;
-    while let Some((&b, s_next)) = s.split_first() {
-        if table[b as usize] & needle_mask != 0 {
-            return Some(start.len() - s.len());
-        }
-        s = s_next;
-    }
-    None
-}
-
-#[cfg(any(feature = "obj", feature = "stl"))]
+#[cfg(any(
+    feature = "dxf",
+    feature = "obj",
+    feature = "off",
+    feature = "pcd",
+    feature = "stl",
+    feature = "usd",
+    feature = "vrml",
+    feature = "xyz"
+))]
 #[inline]
 pub(crate) const fn memrchr_naive(needle: u8, mut s: &[u8]) -> Option<usize> {
     let start = s;
@@ -87,17 +79,6 @@ pub(crate) const fn memrchr_naive(needle: u8, mut s: &[u8]) -> Option<usize> {
     None
 }
 
-#[cfg(any(feature = "obj", feature = "stl"))]
-#[inline]
-pub(crate) const fn bytecount_naive(needle: u8, mut s: &[u8]) -> usize {
-    let mut n = 0;
-    while let Some((&b, s_next)) = s.split_first() {
-        n += (b == needle) as usize;
-        s = s_next;
-    }
-    n
-}
-
 #[cfg(feature = "obj")]
 #[allow(clippy::unnecessary_wraps)] // clippy bug: this lint doesn't consider cfg attribute
 pub(crate) fn os_str_from_bytes(bytes: &[u8]) -> Result<&OsStr, std::str::Utf8Error> {
@@ -0,0 +1,70 @@
+// A minimal decoder for gzip-compressed files.
+//
+// This supports single-member files, which is enough for gzip-compressed
+// assets. Files with multiple concatenated members are not supported.
+//
+// Refs: https://www.rfc-editor.org/rfc/rfc1952
+
+use std::io;
+
+use crate::error;
+
+const MAGIC: &[u8] = b"\x1f\x8b";
+const HEADER_SIZE: usize = 10;
+const TRAILER_SIZE: usize = 8;
+
+const DEFLATED: u8 = 8;
+
+const FHCRC: u8 = 1 << 1;
+const FEXTRA: u8 = 1 << 2;
+const FNAME: u8 = 1 << 3;
+const FCOMMENT: u8 = 1 << 4;
+
+/// Returns `true` if the given bytes start with the magic number of gzip.
+pub(crate) fn is_gzip(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+pub(crate) fn decompress(bytes: &[u8]) -> io::Result<Vec<u8>> {
+    let eof = || error::invalid_data("gzip: unexpected end of file");
+    if bytes.len() < HEADER_SIZE + TRAILER_SIZE || !is_gzip(bytes) {
+        return Err(error::invalid_data("gzip: invalid header"));
+    }
+    if bytes[2] != DEFLATED {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("gzip: compression method {} is not supported", bytes[2]),
+        ));
+    }
+    let flags = bytes[3];
+    let mut pos = HEADER_SIZE;
+    if flags & FEXTRA != 0 {
+        let len = bytes.get(pos..pos + 2).ok_or_else(eof)?;
+        pos += 2 + u16::from_le_bytes(len.try_into().unwrap()) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            // Zero-terminated string
+            let len = bytes
+                .get(pos..)
+                .and_then(|s| s.iter().position(|&b| b == 0))
+                .ok_or_else(eof)?;
+            pos += len + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+    let data = bytes.get(pos..bytes.len() - TRAILER_SIZE).ok_or_else(eof)?;
+    let data = miniz_oxide::inflate::decompress_to_vec(data)
+        .map_err(|e| error::invalid_data(format!("gzip: {e}")))?;
+    // The trailer has CRC-32 and the size (modulo 2^32) of the uncompressed
+    // data. Only the size is checked.
+    let size = &bytes[bytes.len() - 4..];
+    #[allow(clippy::cast_possible_truncation)] // modulo 2^32
+    let len = data.len() as u32;
+    if len != u32::from_le_bytes(size.try_into().unwrap()) {
+        return Err(error::invalid_data("gzip: size mismatch"));
+    }
+    Ok(data)
+}
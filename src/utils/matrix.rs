@@ -0,0 +1,282 @@
+// A 4x4 matrix used to represent transforms of nodes.
+
+#![allow(clippy::many_single_char_names)]
+
+use std::ops;
+
+// Based on https://github.com/assimp/assimp/blob/v5.3.1/include/assimp/matrix4x4.inl
+#[derive(Clone, Copy)]
+pub(crate) struct Matrix4x4 {
+    a1: f32,
+    a2: f32,
+    a3: f32,
+    a4: f32,
+    b1: f32,
+    b2: f32,
+    b3: f32,
+    b4: f32,
+    c1: f32,
+    c2: f32,
+    c3: f32,
+    c4: f32,
+    d1: f32,
+    d2: f32,
+    d3: f32,
+    d4: f32,
+}
+impl Matrix4x4 {
+    pub(crate) const fn new(
+        a1: f32,
+        a2: f32,
+        a3: f32,
+        a4: f32,
+        b1: f32,
+        b2: f32,
+        b3: f32,
+        b4: f32,
+        c1: f32,
+        c2: f32,
+        c3: f32,
+        c4: f32,
+        d1: f32,
+        d2: f32,
+        d3: f32,
+        d4: f32,
+    ) -> Self {
+        Self {
+            a1,
+            a2,
+            a3,
+            a4,
+            b1,
+            b2,
+            b3,
+            b4,
+            c1,
+            c2,
+            c3,
+            c4,
+            d1,
+            d2,
+            d3,
+            d4,
+        }
+    }
+    pub(crate) fn rotation(a: f32, axis: [f32; 3]) -> Self {
+        let c = a.cos();
+        let s = a.sin();
+        let t = 1. - c;
+        let [x, y, z] = axis;
+        Self::new(
+            t * x * x + c,
+            t * x * y - s * z,
+            t * x * z + s * y,
+            0.,
+            t * x * y + s * z,
+            t * y * y + c,
+            t * y * z - s * x,
+            0.,
+            t * x * z - s * y,
+            t * y * z + s * x,
+            t * z * z + c,
+            0.,
+            0.,
+            0.,
+            0.,
+            1.,
+        )
+    }
+    pub(crate) fn translation(v: [f32; 3]) -> Self {
+        Self {
+            a4: v[0],
+            b4: v[1],
+            c4: v[2],
+            ..Default::default()
+        }
+    }
+    #[cfg(any(
+        feature = "amf",
+        feature = "dxf",
+        feature = "fbx",
+        feature = "urdf",
+        feature = "usd",
+        feature = "vrml"
+    ))]
+    pub(crate) fn scaling(v: [f32; 3]) -> Self {
+        Self {
+            a1: v[0],
+            b2: v[1],
+            c3: v[2],
+            ..Default::default()
+        }
+    }
+    /// Returns the matrix that transforms normals, which is the inverse
+    /// transpose of the upper-left 3x3 part of this matrix.
+    ///
+    /// Unlike this matrix, it keeps normals perpendicular to the surface under
+    /// non-uniform scaling and shear. The transformed normals need to be
+    /// normalized.
+    #[cfg(any(feature = "fbx", feature = "urdf", feature = "usd", feature = "vrml"))]
+    pub(crate) fn normal_matrix(&self) -> Self {
+        // The cofactor matrix, which is the inverse transpose multiplied by
+        // the determinant.
+        let mut m = Self::new(
+            self.b2 * self.c3 - self.b3 * self.c2,
+            self.b3 * self.c1 - self.b1 * self.c3,
+            self.b1 * self.c2 - self.b2 * self.c1,
+            0.,
+            self.a3 * self.c2 - self.a2 * self.c3,
+            self.a1 * self.c3 - self.a3 * self.c1,
+            self.a2 * self.c1 - self.a1 * self.c2,
+            0.,
+            self.a2 * self.b3 - self.a3 * self.b2,
+            self.a3 * self.b1 - self.a1 * self.b3,
+            self.a1 * self.b2 - self.a2 * self.b1,
+            0.,
+            0.,
+            0.,
+            0.,
+            1.,
+        );
+        let det = self.a1 * m.a1 + self.a2 * m.a2 + self.a3 * m.a3;
+        // If the matrix is singular, the cofactor matrix is used as is.
+        if det != 0. {
+            let inv = 1. / det;
+            for v in [
+                &mut m.a1, &mut m.a2, &mut m.a3, &mut m.b1, &mut m.b2, &mut m.b3, &mut m.c1,
+                &mut m.c2, &mut m.c3,
+            ] {
+                *v *= inv;
+            }
+        }
+        m
+    }
+    #[cfg(feature = "collada")]
+    pub(crate) fn is_identity(&self) -> bool {
+        // TODO: use f32::EPSILON?
+        const EPSILON: f32 = 10e-3;
+        self.a2 <= EPSILON
+            && self.a2 >= -EPSILON
+            && self.a3 <= EPSILON
+            && self.a3 >= -EPSILON
+            && self.a4 <= EPSILON
+            && self.a4 >= -EPSILON
+            && self.b1 <= EPSILON
+            && self.b1 >= -EPSILON
+            && self.b3 <= EPSILON
+            && self.b3 >= -EPSILON
+            && self.b4 <= EPSILON
+            && self.b4 >= -EPSILON
+            && self.c1 <= EPSILON
+            && self.c1 >= -EPSILON
+            && self.c2 <= EPSILON
+            && self.c2 >= -EPSILON
+            && self.c4 <= EPSILON
+            && self.c4 >= -EPSILON
+            && self.d1 <= EPSILON
+            && self.d1 >= -EPSILON
+            && self.d2 <= EPSILON
+            && self.d2 >= -EPSILON
+            && self.d3 <= EPSILON
+            && self.d3 >= -EPSILON
+            && self.a1 <= 1. + EPSILON
+            && self.a1 >= 1. - EPSILON
+            && self.b2 <= 1. + EPSILON
+            && self.b2 >= 1. - EPSILON
+            && self.c3 <= 1. + EPSILON
+            && self.c3 >= 1. - EPSILON
+            && self.d4 <= 1. + EPSILON
+            && self.d4 >= 1. - EPSILON
+    }
+}
+impl Default for Matrix4x4 {
+    fn default() -> Self {
+        Self::new(
+            1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1., 0., 0., 0., 0., 1.,
+        )
+    }
+}
+impl ops::MulAssign<Matrix4x4> for [f32; 4] {
+    fn mul_assign(&mut self, m: Matrix4x4) {
+        let [x, y, z, w] = *self;
+        self[0] = m.a1 * x + m.a2 * y + m.a3 * z + m.a4 * w;
+        self[1] = m.b1 * x + m.b2 * y + m.b3 * z + m.b4 * w;
+        self[2] = m.c1 * x + m.c2 * y + m.c3 * z + m.c4 * w;
+        self[3] = m.d1 * x + m.d2 * y + m.d3 * z + m.d4 * w;
+    }
+}
+impl ops::MulAssign<Matrix4x4> for [f32; 3] {
+    fn mul_assign(&mut self, m: Matrix4x4) {
+        let mut result = [self[0], self[1], self[2], 1.];
+        result *= m;
+        *self = [result[0], result[1], result[2]];
+    }
+}
+impl ops::MulAssign for Matrix4x4 {
+    fn mul_assign(&mut self, m: Self) {
+        let t = *self;
+        self.a1 = m.a1 * t.a1 + m.b1 * t.a2 + m.c1 * t.a3 + m.d1 * t.a4;
+        self.a2 = m.a2 * t.a1 + m.b2 * t.a2 + m.c2 * t.a3 + m.d2 * t.a4;
+        self.a3 = m.a3 * t.a1 + m.b3 * t.a2 + m.c3 * t.a3 + m.d3 * t.a4;
+        self.a4 = m.a4 * t.a1 + m.b4 * t.a2 + m.c4 * t.a3 + m.d4 * t.a4;
+        self.b1 = m.a1 * t.b1 + m.b1 * t.b2 + m.c1 * t.b3 + m.d1 * t.b4;
+        self.b2 = m.a2 * t.b1 + m.b2 * t.b2 + m.c2 * t.b3 + m.d2 * t.b4;
+        self.b3 = m.a3 * t.b1 + m.b3 * t.b2 + m.c3 * t.b3 + m.d3 * t.b4;
+        self.b4 = m.a4 * t.b1 + m.b4 * t.b2 + m.c4 * t.b3 + m.d4 * t.b4;
+        self.c1 = m.a1 * t.c1 + m.b1 * t.c2 + m.c1 * t.c3 + m.d1 * t.c4;
+        self.c2 = m.a2 * t.c1 + m.b2 * t.c2 + m.c2 * t.c3 + m.d2 * t.c4;
+        self.c3 = m.a3 * t.c1 + m.b3 * t.c2 + m.c3 * t.c3 + m.d3 * t.c4;
+        self.c4 = m.a4 * t.c1 + m.b4 * t.c2 + m.c4 * t.c3 + m.d4 * t.c4;
+        self.d1 = m.a1 * t.d1 + m.b1 * t.d2 + m.c1 * t.d3 + m.d1 * t.d4;
+        self.d2 = m.a2 * t.d1 + m.b2 * t.d2 + m.c2 * t.d3 + m.d2 * t.d4;
+        self.d3 = m.a3 * t.d1 + m.b3 * t.d2 + m.c3 * t.d3 + m.d3 * t.d4;
+        self.d4 = m.a4 * t.d1 + m.b4 * t.d2 + m.c4 * t.d3 + m.d4 * t.d4;
+    }
+}
+
+/// Returns the vector scaled to unit length, or the vector itself if its
+/// length is zero.
+#[cfg(any(
+    feature = "amf",
+    feature = "dxf",
+    feature = "fbx",
+    feature = "urdf",
+    feature = "usd",
+    feature = "vrml"
+))]
+pub(crate) fn normalize([x, y, z]: [f32; 3]) -> [f32; 3] {
+    let len = (x * x + y * y + z * z).sqrt();
+    if len == 0. {
+        [x, y, z]
+    } else {
+        [x / len, y / len, z / len]
+    }
+}
+
+#[cfg(test)]
+#[cfg(any(feature = "fbx", feature = "urdf", feature = "usd", feature = "vrml"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normal_matrix() {
+        // The normal of the plane x + y = 1 scaled by (2, 1, 1) is (1, 2, 0).
+        let mut m = Matrix4x4::translation([1., 2., 3.]);
+        m *= Matrix4x4::scaling([2., 1., 1.]);
+        let mut n = [1., 1., 0.];
+        n *= m.normal_matrix();
+        assert_eq!(n, [0.5, 1., 0.]);
+        // Mirroring flips normals in the same way as the surface.
+        let mut n = [1., 0., 0.];
+        n *= Matrix4x4::scaling([-2., 1., 1.]).normal_matrix();
+        assert_eq!(n, [-0.5, 0., 0.]);
+        // Rotations are kept as is.
+        let m = Matrix4x4::rotation(std::f32::consts::FRAC_PI_2, [0., 0., 1.]);
+        let (mut a, mut b) = ([1., 0., 0.], [1., 0., 0.]);
+        a *= m;
+        b *= m.normal_matrix();
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+}
@@ -1,14 +1,76 @@
 pub(crate) mod bytes;
-#[cfg(any(feature = "collada", feature = "obj", feature = "stl"))]
+#[cfg(any(
+    feature = "amf",
+    feature = "collada",
+    feature = "dxf",
+    feature = "obj",
+    feature = "off",
+    feature = "pcd",
+    feature = "stl",
+    feature = "urdf",
+    feature = "usd",
+    feature = "vrml",
+    feature = "xyz"
+))]
 pub mod float;
+#[cfg(feature = "archive")]
+pub(crate) mod gzip;
 #[cfg(feature = "collada")]
 pub(crate) mod hex;
-#[cfg(any(feature = "collada", feature = "obj"))]
+#[cfg(any(
+    feature = "amf",
+    feature = "collada",
+    feature = "dxf",
+    feature = "obj",
+    feature = "off",
+    feature = "pcd",
+    feature = "urdf",
+    feature = "vrml"
+))]
 pub mod int;
-#[cfg(feature = "collada")]
+#[cfg(any(
+    feature = "amf",
+    feature = "collada",
+    feature = "dxf",
+    feature = "fbx",
+    feature = "urdf",
+    feature = "usd",
+    feature = "vrml"
+))]
+pub(crate) mod matrix;
+#[cfg(all(
+    feature = "rayon",
+    any(feature = "collada", feature = "obj", feature = "stl")
+))]
+pub(crate) mod par;
+#[cfg(any(
+    feature = "dxf",
+    feature = "obj",
+    feature = "off",
+    feature = "pcd",
+    feature = "stl",
+    feature = "usd",
+    feature = "vrml",
+    feature = "xyz"
+))]
+pub mod simd;
+#[cfg(any(
+    feature = "amf",
+    feature = "collada",
+    feature = "urdf",
+    feature = "vrml"
+))]
 pub(crate) mod xml;
+#[cfg(any(feature = "amf", feature = "archive"))]
+pub(crate) mod zip;
 
-#[cfg(any(feature = "collada", feature = "obj"))]
+#[cfg(any(
+    feature = "amf",
+    feature = "collada",
+    feature = "obj",
+    feature = "urdf",
+    feature = "vrml"
+))]
 pub(crate) mod utf16 {
     use std::{borrow::Cow, io};
 
@@ -22,7 +84,12 @@ pub(crate) mod utf16 {
     const UTF32LE_BOM: &[u8] = &[0xFF, 0xFE, 0x00, 0x00];
 
     /// Converts bytes to a string. Converts to UTF-8 if bytes are UTF-16 and have BOM.
-    #[cfg(feature = "collada")]
+    #[cfg(any(
+        feature = "amf",
+        feature = "collada",
+        feature = "urdf",
+        feature = "vrml"
+    ))]
     pub(crate) fn decode_string(bytes: &[u8]) -> io::Result<Cow<'_, str>> {
         if bytes.starts_with(UTF8_BOM) {
             std::str::from_utf8(&bytes[UTF8_BOM.len()..])
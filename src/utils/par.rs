@@ -0,0 +1,32 @@
+// Helpers for parsing in parallel with rayon.
+
+/// The minimum size of the chunks parsed in parallel.
+///
+/// Inputs smaller than two chunks are parsed sequentially.
+pub(crate) const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Splits the given bytes into chunks of at least `CHUNK_SIZE` bytes.
+///
+/// Each chunk except the last ends with the byte at the index for which
+/// `is_end(s, i)` returns `true`. Returns a single chunk if `s` is smaller
+/// than two chunks.
+#[cfg(any(feature = "collada", feature = "obj"))]
+pub(crate) fn split<F>(mut s: &[u8], is_end: F) -> Vec<&[u8]>
+where
+    F: Fn(&[u8], usize) -> bool,
+{
+    let mut chunks = vec![];
+    if s.len() >= CHUNK_SIZE * 2 {
+        while s.len() > CHUNK_SIZE {
+            match (CHUNK_SIZE..s.len()).find(|&i| is_end(s, i)) {
+                Some(i) => {
+                    chunks.push(&s[..=i]);
+                    s = &s[i + 1..];
+                }
+                None => break,
+            }
+        }
+    }
+    chunks.push(s);
+    chunks
+}
@@ -0,0 +1,364 @@
+// Byte scanning accelerated with SIMD.
+//
+// This is the only module that contains unsafe code. The SIMD implementations
+// are selected at runtime on x86_64 (SSE2 is always available, AVX2 is
+// detected) and at compile time on aarch64 (NEON is always available).
+// Other targets and inputs shorter than a vector use the scalar implementations.
+//
+// All SIMD implementations share the same structure: they only read full
+// vectors within the bounds of the input (`i + WIDTH <= s.len()`), and pass
+// the remaining bytes to the scalar implementation.
+
+#![allow(unsafe_code)]
+#![warn(unsafe_op_in_unsafe_fn)]
+
+/// Returns the index of the first byte in `s` that is `needle1` or `needle2`.
+#[cfg(any(feature = "obj", feature = "stl"))]
+#[inline]
+pub fn memchr2(needle1: u8, needle2: u8, s: &[u8]) -> Option<usize> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if s.len() >= x86_64::AVX2_WIDTH && x86_64::has_avx2() {
+            // SAFETY: AVX2 is available.
+            return unsafe { x86_64::memchr2_avx2(needle1, needle2, s) };
+        }
+        if s.len() >= x86_64::SSE2_WIDTH {
+            return x86_64::memchr2_sse2(needle1, needle2, s);
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+    {
+        if s.len() >= aarch64::WIDTH {
+            return aarch64::memchr2_neon(needle1, needle2, s);
+        }
+    }
+    memchr2_naive(needle1, needle2, s)
+}
+
+/// Returns the number of spaces and tabs at the start of `s`.
+#[cfg(any(feature = "obj", feature = "stl"))]
+#[inline]
+pub fn count_spaces(s: &[u8]) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if s.len() >= x86_64::AVX2_WIDTH && x86_64::has_avx2() {
+            // SAFETY: AVX2 is available.
+            return unsafe { x86_64::count_spaces_avx2(s) };
+        }
+        if s.len() >= x86_64::SSE2_WIDTH {
+            return x86_64::count_spaces_sse2(s);
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+    {
+        if s.len() >= aarch64::WIDTH {
+            return aarch64::count_spaces_neon(s);
+        }
+    }
+    count_spaces_naive(s)
+}
+
+/// Returns the number of `needle` in `s`.
+#[inline]
+pub fn bytecount(needle: u8, s: &[u8]) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if s.len() >= x86_64::AVX2_WIDTH && x86_64::has_avx2() {
+            // SAFETY: AVX2 is available.
+            return unsafe { x86_64::bytecount_avx2(needle, s) };
+        }
+        if s.len() >= x86_64::SSE2_WIDTH {
+            return x86_64::bytecount_sse2(needle, s);
+        }
+    }
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+    {
+        if s.len() >= aarch64::WIDTH {
+            return aarch64::bytecount_neon(needle, s);
+        }
+    }
+    bytecount_naive(needle, s)
+}
+
+/// The scalar implementation of [`memchr2`].
+#[cfg(any(feature = "obj", feature = "stl"))]
+#[inline]
+pub const fn memchr2_naive(needle1: u8, needle2: u8, mut s: &[u8]) -> Option<usize> {
+    let start = s;
+    while let Some((&b, s_next)) = s.split_first() {
+        if b == needle1 || b == needle2 {
+            return Some(start.len() - s.len());
+        }
+        s = s_next;
+    }
+    None
+}
+
+/// The scalar implementation of [`count_spaces`].
+#[cfg(any(feature = "obj", feature = "stl"))]
+#[inline]
+pub const fn count_spaces_naive(mut s: &[u8]) -> usize {
+    let mut n = 0;
+    while let Some((&(b' ' | b'\t'), s_next)) = s.split_first() {
+        n += 1;
+        s = s_next;
+    }
+    n
+}
+
+/// The scalar implementation of [`bytecount`].
+#[inline]
+pub const fn bytecount_naive(needle: u8, mut s: &[u8]) -> usize {
+    let mut n = 0;
+    while let Some((&b, s_next)) = s.split_first() {
+        n += (b == needle) as usize;
+        s = s_next;
+    }
+    n
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86_64 {
+    // The loads below are unaligned loads.
+    #![allow(clippy::cast_ptr_alignment)]
+
+    use std::arch::x86_64::{
+        __m128i, __m256i, _mm256_cmpeq_epi8, _mm256_loadu_si256, _mm256_movemask_epi8,
+        _mm256_set1_epi8, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_movemask_epi8, _mm_set1_epi8,
+    };
+    #[cfg(any(feature = "obj", feature = "stl"))]
+    use std::arch::x86_64::{_mm256_or_si256, _mm_or_si128};
+
+    pub(super) const SSE2_WIDTH: usize = 16;
+    pub(super) const AVX2_WIDTH: usize = 32;
+
+    #[inline]
+    pub(super) fn has_avx2() -> bool {
+        // The result is cached by std.
+        is_x86_feature_detected!("avx2")
+    }
+
+    // SSE2 is enabled on all x86_64 targets, so the SSE2 implementations are
+    // safe functions.
+
+    #[cfg(any(feature = "obj", feature = "stl"))]
+    #[inline]
+    pub(super) fn memchr2_sse2(needle1: u8, needle2: u8, s: &[u8]) -> Option<usize> {
+        // SAFETY: SSE2 is available on x86_64, and only the bytes in `s` are
+        // read.
+        unsafe {
+            let va = _mm_set1_epi8(i8::from_ne_bytes([needle1]));
+            let vb = _mm_set1_epi8(i8::from_ne_bytes([needle2]));
+            let mut i = 0;
+            while i + SSE2_WIDTH <= s.len() {
+                let v = _mm_loadu_si128(s.as_ptr().add(i).cast::<__m128i>());
+                let eq = _mm_or_si128(_mm_cmpeq_epi8(v, va), _mm_cmpeq_epi8(v, vb));
+                let mask = _mm_movemask_epi8(eq);
+                if mask != 0 {
+                    return Some(i + mask.trailing_zeros() as usize);
+                }
+                i += SSE2_WIDTH;
+            }
+            super::memchr2_naive(needle1, needle2, &s[i..]).map(|n| i + n)
+        }
+    }
+
+    #[cfg(any(feature = "obj", feature = "stl"))]
+    #[inline]
+    pub(super) fn count_spaces_sse2(s: &[u8]) -> usize {
+        // SAFETY: SSE2 is available on x86_64, and only the bytes in `s` are
+        // read.
+        unsafe {
+            let vs = _mm_set1_epi8(i8::from_ne_bytes([b' ']));
+            let vt = _mm_set1_epi8(i8::from_ne_bytes([b'\t']));
+            let mut i = 0;
+            while i + SSE2_WIDTH <= s.len() {
+                let v = _mm_loadu_si128(s.as_ptr().add(i).cast::<__m128i>());
+                let eq = _mm_or_si128(_mm_cmpeq_epi8(v, vs), _mm_cmpeq_epi8(v, vt));
+                // The bits of the bytes that are not spaces.
+                let mask = !_mm_movemask_epi8(eq) & 0xFFFF;
+                if mask != 0 {
+                    return i + mask.trailing_zeros() as usize;
+                }
+                i += SSE2_WIDTH;
+            }
+            i + super::count_spaces_naive(&s[i..])
+        }
+    }
+
+    /// # Safety
+    ///
+    /// AVX2 must be available.
+    #[cfg(any(feature = "obj", feature = "stl"))]
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn memchr2_avx2(needle1: u8, needle2: u8, s: &[u8]) -> Option<usize> {
+        // SAFETY: the caller guarantees that AVX2 is available, and only the
+        // bytes in `s` are read.
+        unsafe {
+            let va = _mm256_set1_epi8(i8::from_ne_bytes([needle1]));
+            let vb = _mm256_set1_epi8(i8::from_ne_bytes([needle2]));
+            let mut i = 0;
+            while i + AVX2_WIDTH <= s.len() {
+                let v = _mm256_loadu_si256(s.as_ptr().add(i).cast::<__m256i>());
+                let eq = _mm256_or_si256(_mm256_cmpeq_epi8(v, va), _mm256_cmpeq_epi8(v, vb));
+                let mask = _mm256_movemask_epi8(eq);
+                if mask != 0 {
+                    return Some(i + mask.trailing_zeros() as usize);
+                }
+                i += AVX2_WIDTH;
+            }
+            // The remaining bytes are shorter than an AVX2 vector.
+            super::memchr2(needle1, needle2, &s[i..]).map(|n| i + n)
+        }
+    }
+
+    /// # Safety
+    ///
+    /// AVX2 must be available.
+    #[cfg(any(feature = "obj", feature = "stl"))]
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn count_spaces_avx2(s: &[u8]) -> usize {
+        // SAFETY: the caller guarantees that AVX2 is available, and only the
+        // bytes in `s` are read.
+        unsafe {
+            let vs = _mm256_set1_epi8(i8::from_ne_bytes([b' ']));
+            let vt = _mm256_set1_epi8(i8::from_ne_bytes([b'\t']));
+            let mut i = 0;
+            while i + AVX2_WIDTH <= s.len() {
+                let v = _mm256_loadu_si256(s.as_ptr().add(i).cast::<__m256i>());
+                let eq = _mm256_or_si256(_mm256_cmpeq_epi8(v, vs), _mm256_cmpeq_epi8(v, vt));
+                // The bits of the bytes that are not spaces.
+                let mask = !_mm256_movemask_epi8(eq);
+                if mask != 0 {
+                    return i + mask.trailing_zeros() as usize;
+                }
+                i += AVX2_WIDTH;
+            }
+            // The remaining bytes are shorter than an AVX2 vector.
+            i + super::count_spaces(&s[i..])
+        }
+    }
+
+    #[inline]
+    pub(super) fn bytecount_sse2(needle: u8, s: &[u8]) -> usize {
+        // SAFETY: SSE2 is available on x86_64, and only the bytes in `s` are
+        // read.
+        unsafe {
+            let vn = _mm_set1_epi8(i8::from_ne_bytes([needle]));
+            let mut n = 0;
+            let mut i = 0;
+            while i + SSE2_WIDTH <= s.len() {
+                let v = _mm_loadu_si128(s.as_ptr().add(i).cast::<__m128i>());
+                n += _mm_movemask_epi8(_mm_cmpeq_epi8(v, vn)).count_ones() as usize;
+                i += SSE2_WIDTH;
+            }
+            n + super::bytecount_naive(needle, &s[i..])
+        }
+    }
+
+    /// # Safety
+    ///
+    /// AVX2 must be available.
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn bytecount_avx2(needle: u8, s: &[u8]) -> usize {
+        // SAFETY: the caller guarantees that AVX2 is available, and only the
+        // bytes in `s` are read.
+        unsafe {
+            let vn = _mm256_set1_epi8(i8::from_ne_bytes([needle]));
+            let mut n = 0;
+            let mut i = 0;
+            while i + AVX2_WIDTH <= s.len() {
+                let v = _mm256_loadu_si256(s.as_ptr().add(i).cast::<__m256i>());
+                n += _mm256_movemask_epi8(_mm256_cmpeq_epi8(v, vn)).count_ones() as usize;
+                i += AVX2_WIDTH;
+            }
+            // The remaining bytes are shorter than an AVX2 vector.
+            n + super::bytecount(needle, &s[i..])
+        }
+    }
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+mod aarch64 {
+    use std::arch::aarch64::{vaddvq_u8, vandq_u8, vceqq_u8, vdupq_n_u8, vld1q_u8};
+    #[cfg(any(feature = "obj", feature = "stl"))]
+    use std::arch::aarch64::{
+        vget_lane_u64, vorrq_u8, vreinterpret_u64_u8, vreinterpretq_u16_u8, vshrn_n_u16,
+    };
+
+    pub(super) const WIDTH: usize = 16;
+
+    // NEON is enabled (checked by cfg), so the NEON implementations are safe
+    // functions.
+
+    #[cfg(any(feature = "obj", feature = "stl"))]
+    #[inline]
+    pub(super) fn memchr2_neon(needle1: u8, needle2: u8, s: &[u8]) -> Option<usize> {
+        // SAFETY: NEON is available, and only the bytes in `s` are read.
+        unsafe {
+            let va = vdupq_n_u8(needle1);
+            let vb = vdupq_n_u8(needle2);
+            let mut i = 0;
+            while i + WIDTH <= s.len() {
+                let v = vld1q_u8(s.as_ptr().add(i));
+                let eq = vorrq_u8(vceqq_u8(v, va), vceqq_u8(v, vb));
+                // Narrow each byte of the comparison result to 4 bits.
+                let mask = vget_lane_u64::<0>(vreinterpret_u64_u8(vshrn_n_u16::<4>(
+                    vreinterpretq_u16_u8(eq),
+                )));
+                if mask != 0 {
+                    return Some(i + mask.trailing_zeros() as usize / 4);
+                }
+                i += WIDTH;
+            }
+            super::memchr2_naive(needle1, needle2, &s[i..]).map(|n| i + n)
+        }
+    }
+
+    #[cfg(any(feature = "obj", feature = "stl"))]
+    #[inline]
+    pub(super) fn count_spaces_neon(s: &[u8]) -> usize {
+        // SAFETY: NEON is available, and only the bytes in `s` are read.
+        unsafe {
+            let vs = vdupq_n_u8(b' ');
+            let vt = vdupq_n_u8(b'\t');
+            let mut i = 0;
+            while i + WIDTH <= s.len() {
+                let v = vld1q_u8(s.as_ptr().add(i));
+                let eq = vorrq_u8(vceqq_u8(v, vs), vceqq_u8(v, vt));
+                // Narrow each byte of the comparison result to 4 bits, and
+                // invert it to get the bits of the bytes that are not spaces.
+                let mask = !vget_lane_u64::<0>(vreinterpret_u64_u8(vshrn_n_u16::<4>(
+                    vreinterpretq_u16_u8(eq),
+                )));
+                if mask != 0 {
+                    return i + mask.trailing_zeros() as usize / 4;
+                }
+                i += WIDTH;
+            }
+            i + super::count_spaces_naive(&s[i..])
+        }
+    }
+
+    #[inline]
+    pub(super) fn bytecount_neon(needle: u8, s: &[u8]) -> usize {
+        // SAFETY: NEON is available, and only the bytes in `s` are read.
+        unsafe {
+            let vn = vdupq_n_u8(needle);
+            let one = vdupq_n_u8(1);
+            let mut n = 0;
+            let mut i = 0;
+            while i + WIDTH <= s.len() {
+                let v = vld1q_u8(s.as_ptr().add(i));
+                // At most 16, so the sum does not overflow.
+                n += vaddvq_u8(vandq_u8(vceqq_u8(v, vn), one)) as usize;
+                i += WIDTH;
+            }
+            n + super::bytecount_naive(needle, &s[i..])
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "tests/simd.rs"]
+mod tests;
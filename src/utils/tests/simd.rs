@@ -0,0 +1,96 @@
+use super::*;
+
+fn input(len: usize) -> Vec<u8> {
+    // Mostly non-matching bytes, to test all positions in a vector.
+    (0..len)
+        .map(|_| match fastrand::u8(..32) {
+            0 => b'\n',
+            1 => b'\r',
+            2 => b' ',
+            _ => fastrand::u8(b'0'..=b'9'),
+        })
+        .collect()
+}
+
+#[test]
+fn memchr2() {
+    for len in (0..100).chain([1000, 10000]) {
+        for _ in 0..10 {
+            let s = input(len);
+            for start in [0, 1, 7, 15, 31].into_iter().filter(|&i| i <= len) {
+                let s = &s[start..];
+                let expected = memchr2_naive(b'\n', b'\r', s);
+                assert_eq!(super::memchr2(b'\n', b'\r', s), expected);
+                #[cfg(target_arch = "x86_64")]
+                {
+                    assert_eq!(x86_64::memchr2_sse2(b'\n', b'\r', s), expected);
+                    if x86_64::has_avx2() {
+                        // SAFETY: AVX2 is available.
+                        let actual = unsafe { x86_64::memchr2_avx2(b'\n', b'\r', s) };
+                        assert_eq!(actual, expected);
+                    }
+                }
+            }
+        }
+    }
+    assert_eq!(super::memchr2(b' ', b'\t', &[0xA0; 64]), None);
+    assert_eq!(
+        super::memchr2(b' ', b'\t', b"0123456789abcdefghij\t"),
+        Some(20)
+    );
+}
+
+#[test]
+fn count_spaces() {
+    for len in (0..100).chain([1000, 10000]) {
+        for _ in 0..10 {
+            // Runs of spaces and tabs of random length.
+            let run = fastrand::usize(..=len);
+            let mut s: Vec<u8> = (0..len)
+                .map(|i| match (i < run, fastrand::bool()) {
+                    (true, true) => b' ',
+                    (true, false) => b'\t',
+                    (false, _) => fastrand::u8(b'0'..=b'9'),
+                })
+                .collect();
+            s.extend_from_slice(b"  ");
+            for start in [0, 1, 7, 15, 31].into_iter().filter(|&i| i <= s.len()) {
+                let s = &s[start..];
+                let expected = count_spaces_naive(s);
+                assert_eq!(super::count_spaces(s), expected);
+                #[cfg(target_arch = "x86_64")]
+                {
+                    assert_eq!(x86_64::count_spaces_sse2(s), expected);
+                    if x86_64::has_avx2() {
+                        // SAFETY: AVX2 is available.
+                        let actual = unsafe { x86_64::count_spaces_avx2(s) };
+                        assert_eq!(actual, expected);
+                    }
+                }
+            }
+        }
+    }
+    assert_eq!(super::count_spaces(&[b' '; 64]), 64);
+    assert_eq!(super::count_spaces(b"\t \t \t \t \t \t \t \t \t \t \n"), 20);
+}
+
+#[test]
+fn bytecount() {
+    for len in (0..100).chain([1000, 10000]) {
+        for _ in 0..10 {
+            let s = input(len);
+            let expected = bytecount_naive(b'\n', &s);
+            assert_eq!(super::bytecount(b'\n', &s), expected);
+            #[cfg(target_arch = "x86_64")]
+            {
+                assert_eq!(x86_64::bytecount_sse2(b'\n', &s), expected);
+                if x86_64::has_avx2() {
+                    // SAFETY: AVX2 is available.
+                    let actual = unsafe { x86_64::bytecount_avx2(b'\n', &s) };
+                    assert_eq!(actual, expected);
+                }
+            }
+        }
+    }
+    assert_eq!(super::bytecount(0xFF, &[0xFF; 1000]), 1000);
+}
@@ -1,10 +1,16 @@
 // A module that provides utilities for parsing and visiting XML nodes.
 
-use std::{borrow::Cow, fmt, io, iter, marker::PhantomData, str::FromStr};
+// Some helpers are only used by the COLLADA parser.
+#![cfg_attr(not(feature = "collada"), allow(dead_code))]
 
+use std::{borrow::Cow, io, marker::PhantomData};
+
+#[cfg(any(feature = "amf", feature = "urdf", feature = "vrml"))]
 pub(crate) use roxmltree::*;
 
-use super::{bytes::memchr_naive, float, int};
+#[cfg(any(feature = "amf", feature = "urdf", feature = "vrml"))]
+use super::bytes::memchr_naive;
+use super::{float, int};
 
 #[inline]
 #[must_use]
@@ -72,8 +78,8 @@ where
     }
 }
 
-/*
 /// Parses float array "<float> <float> <float>..."
+#[cfg(feature = "rayon")]
 pub(crate) fn parse_float_array<T>(text: &str) -> ParseFloatArray<'_, T>
 where
     T: float::Float,
@@ -84,11 +90,13 @@ where
     }
 }
 
+#[cfg(feature = "rayon")]
 pub(crate) struct ParseFloatArray<'a, T> {
     text: &'a str,
     _marker: PhantomData<fn() -> T>,
 }
 
+#[cfg(feature = "rayon")]
 impl<T> Iterator for ParseFloatArray<'_, T>
 where
     T: float::Float,
@@ -108,7 +116,6 @@ where
         }
     }
 }
-*/
 
 /// Parses float array "<float> <float> <float>..."
 pub(crate) fn parse_float_array_exact<T>(text: &str, num: usize) -> ParseFloatArrayExact<'_, T>
@@ -158,27 +165,67 @@ where
     }
 }
 
+/// Parses array "<value> <value> <value>..." in parallel with the given
+/// sequential parser.
+///
+/// Returns `None` if the text is small or has an error; in that case, the
+/// caller parses it sequentially, which returns the same error.
+#[cfg(feature = "rayon")]
+pub(crate) fn parse_array_parallel<'a, T, I, F>(text: &'a str, parse: F) -> Option<Vec<T>>
+where
+    T: Copy + Send,
+    I: Iterator<Item = io::Result<T>>,
+    F: Fn(&'a str) -> I + Sync,
+{
+    use rayon::prelude::*;
+
+    let chunks = super::par::split(text.as_bytes(), |s, i| is_whitespace(s[i] as char));
+    if chunks.len() == 1 {
+        return None;
+    }
+    let mut start = 0;
+    let chunks: Vec<_> = chunks
+        .iter()
+        .map(|chunk| {
+            // Chunks end with ASCII whitespace, so this is at a char boundary.
+            let end = start + chunk.len();
+            let chunk = trim_start(&text[start..end]);
+            start = end;
+            chunk
+        })
+        .collect();
+    let values = chunks
+        .into_par_iter()
+        .map(|chunk| parse(chunk).collect::<io::Result<Vec<T>>>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    Some(values.concat())
+}
+
+/// Parses the contents of a `<float_array>` element in parallel.
+///
+/// Returns `None` if [`parse_array_parallel`] returns `None` or if the number
+/// of values is not `count`; in that case, the caller parses the contents
+/// with [`parse_float_array_exact`], which returns the error.
+#[cfg(feature = "rayon")]
+pub fn parse_float_array_parallel(text: &str, count: usize) -> Option<Vec<f32>> {
+    parse_array_parallel(text, parse_float_array::<f32>).filter(|values| values.len() == count)
+}
+
 // -----------------------------------------------------------------------------
 // XmlNodeExt
 
+#[cfg(any(feature = "amf", feature = "urdf", feature = "vrml"))]
 pub(crate) trait XmlNodeExt<'a, 'input> {
     fn element_children(&self) -> ElementChildren<'a, 'input>;
     fn child(&self, name: &str) -> Option<Node<'a, 'input>>;
     fn required_attribute(&self, name: &str) -> io::Result<&'a str>;
-    fn parse_attribute<T>(&self, name: &str) -> io::Result<Option<T>>
-    where
-        T: FromStr,
-        T::Err: fmt::Display;
-    fn parse_required_attribute<T>(&self, name: &str) -> io::Result<T>
-    where
-        T: FromStr,
-        T::Err: fmt::Display;
     fn trimmed_text(&self) -> &'a str;
     fn node_location(&self) -> TextPos;
     fn text_location(&self) -> TextPos;
     fn attr_value_location(&self, name: &str) -> TextPos;
 }
 
+#[cfg(any(feature = "amf", feature = "urdf", feature = "vrml"))]
 impl<'a, 'input> XmlNodeExt<'a, 'input> for Node<'a, 'input> {
     fn element_children(&self) -> ElementChildren<'a, 'input> {
         self.children()
@@ -208,42 +255,6 @@ impl<'a, 'input> XmlNodeExt<'a, 'input> for Node<'a, 'input> {
         }
     }
 
-    fn parse_attribute<T>(&self, name: &str) -> io::Result<Option<T>>
-    where
-        T: FromStr,
-        T::Err: fmt::Display,
-    {
-        match self.attribute(name) {
-            Some(v) => Ok(Some(v.parse::<T>().map_err(|e| {
-                format_err!(
-                    "{} in <{}> element at {}: {:?}",
-                    e,
-                    self.tag_name().name(),
-                    self.attr_value_location(name),
-                    v
-                )
-            })?)),
-            None => Ok(None),
-        }
-    }
-
-    fn parse_required_attribute<T>(&self, name: &str) -> io::Result<T>
-    where
-        T: FromStr,
-        T::Err: fmt::Display,
-    {
-        let v = self.required_attribute(name)?;
-        v.parse::<T>().map_err(|e| {
-            format_err!(
-                "{} in <{}> element at {}: {:?}",
-                e,
-                self.tag_name().name(),
-                self.attr_value_location(name),
-                v
-            )
-        })
-    }
-
     fn trimmed_text(&self) -> &'a str {
         trim(self.text().unwrap_or_default())
     }
@@ -267,5 +278,6 @@ impl<'a, 'input> XmlNodeExt<'a, 'input> for Node<'a, 'input> {
     }
 }
 
+#[cfg(any(feature = "amf", feature = "urdf", feature = "vrml"))]
 pub(crate) type ElementChildren<'a, 'input> =
-    iter::Filter<Children<'a, 'input>, fn(&Node<'a, 'input>) -> bool>;
+    std::iter::Filter<Children<'a, 'input>, fn(&Node<'a, 'input>) -> bool>;
@@ -0,0 +1,143 @@
+// A minimal reader for ZIP archives.
+//
+// This supports stored and deflate-compressed entries, which is enough for
+// zip-compressed assets. ZIP64 and encrypted archives are not supported.
+//
+// Refs: https://pkware.cachefly.net/webdocs/casestudies/APPNOTE.TXT
+
+use std::{borrow::Cow, io};
+
+use crate::error;
+
+const LOCAL_FILE_HEADER_SIGNATURE: &[u8] = b"PK\x03\x04";
+const CENTRAL_DIRECTORY_SIGNATURE: &[u8] = b"PK\x01\x02";
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: &[u8] = b"PK\x05\x06";
+const LOCAL_FILE_HEADER_SIZE: usize = 30;
+const CENTRAL_DIRECTORY_HEADER_SIZE: usize = 46;
+const END_OF_CENTRAL_DIRECTORY_SIZE: usize = 22;
+
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+/// Returns `true` if the given bytes start with the signature of ZIP archive.
+pub(crate) fn is_zip(bytes: &[u8]) -> bool {
+    bytes.starts_with(LOCAL_FILE_HEADER_SIGNATURE)
+}
+
+pub(crate) struct Archive<'a> {
+    bytes: &'a [u8],
+    entries: Vec<Entry>,
+}
+
+pub(crate) struct Entry {
+    pub(crate) name: String,
+    flags: u16,
+    method: u16,
+    compressed_size: usize,
+    uncompressed_size: usize,
+    local_header_offset: usize,
+}
+
+impl<'a> Archive<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> io::Result<Self> {
+        // The end of central directory record is at the end of the archive,
+        // followed by a comment of variable length (up to u16::MAX).
+        let eocd = (0..=bytes.len().saturating_sub(END_OF_CENTRAL_DIRECTORY_SIZE))
+            .rev()
+            .take(u16::MAX as usize + 1)
+            .find(|&i| bytes[i..].starts_with(END_OF_CENTRAL_DIRECTORY_SIGNATURE))
+            .ok_or_else(|| error::invalid_data("zip: end of central directory not found"))?;
+        let eocd = &bytes[eocd..];
+        let num_entries = u16le(eocd, 10)?;
+        let cd_offset = u32le(eocd, 16)?;
+        if num_entries == u16::MAX || cd_offset == u32::MAX {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "zip: ZIP64 archive is not supported",
+            ));
+        }
+
+        let mut entries = Vec::with_capacity(num_entries as usize);
+        let mut pos = cd_offset as usize;
+        for _ in 0..num_entries {
+            let header = bytes
+                .get(pos..)
+                .filter(|h| h.starts_with(CENTRAL_DIRECTORY_SIGNATURE))
+                .ok_or_else(|| error::invalid_data("zip: invalid central directory header"))?;
+            let name_len = u16le(header, 28)? as usize;
+            let extra_len = u16le(header, 30)? as usize;
+            let comment_len = u16le(header, 32)? as usize;
+            let name = header
+                .get(CENTRAL_DIRECTORY_HEADER_SIZE..CENTRAL_DIRECTORY_HEADER_SIZE + name_len)
+                .ok_or_else(|| error::invalid_data("zip: unexpected end of file"))?;
+            entries.push(Entry {
+                name: String::from_utf8_lossy(name).into_owned(),
+                flags: u16le(header, 8)?,
+                method: u16le(header, 10)?,
+                compressed_size: u32le(header, 20)? as usize,
+                uncompressed_size: u32le(header, 24)? as usize,
+                local_header_offset: u32le(header, 42)? as usize,
+            });
+            pos = pos
+                .checked_add(CENTRAL_DIRECTORY_HEADER_SIZE + name_len + extra_len + comment_len)
+                .ok_or_else(|| error::invalid_data("zip: invalid central directory header"))?;
+        }
+
+        Ok(Self { bytes, entries })
+    }
+
+    /// Returns file entries (directories are excluded) in the archive.
+    pub(crate) fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter().filter(|e| !e.name.ends_with('/'))
+    }
+
+    pub(crate) fn read(&self, entry: &Entry) -> io::Result<Cow<'a, [u8]>> {
+        if entry.flags & 1 != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("zip: encrypted entry {:?} is not supported", entry.name),
+            ));
+        }
+        let header = self
+            .bytes
+            .get(entry.local_header_offset..)
+            .filter(|h| h.starts_with(LOCAL_FILE_HEADER_SIGNATURE))
+            .ok_or_else(|| error::invalid_data("zip: invalid local file header"))?;
+        let name_len = u16le(header, 26)? as usize;
+        let extra_len = u16le(header, 28)? as usize;
+        let start = LOCAL_FILE_HEADER_SIZE + name_len + extra_len;
+        let data = start
+            .checked_add(entry.compressed_size)
+            .and_then(|end| header.get(start..end))
+            .ok_or_else(|| error::invalid_data("zip: unexpected end of file"))?;
+        match entry.method {
+            STORED => Ok(Cow::Borrowed(data)),
+            DEFLATED => {
+                let data = miniz_oxide::inflate::decompress_to_vec_with_limit(
+                    data,
+                    entry.uncompressed_size,
+                )
+                .map_err(|e| error::invalid_data(format!("zip: {e}")))?;
+                Ok(Cow::Owned(data))
+            }
+            method => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("zip: compression method {method} is not supported"),
+            )),
+        }
+    }
+}
+
+fn u16le(bytes: &[u8], pos: usize) -> io::Result<u16> {
+    match bytes.get(pos..pos + 2) {
+        Some(b) => Ok(u16::from_le_bytes(b.try_into().unwrap())),
+        None => Err(error::invalid_data("zip: unexpected end of file")),
+    }
+}
+
+fn u32le(bytes: &[u8], pos: usize) -> io::Result<u32> {
+    match bytes.get(pos..pos + 4) {
+        Some(b) => Ok(u32::from_le_bytes(b.try_into().unwrap())),
+        None => Err(error::invalid_data("zip: unexpected end of file")),
+    }
+}
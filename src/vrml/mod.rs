@@ -0,0 +1,517 @@
+//! [VRML] (.wrl) and [X3D] (.x3d, .x3dv) parser.
+//!
+//! VRML97 and both the classic and XML encodings of X3D are supported.
+//! The node hierarchy (Transform, Group, Switch, LOD, etc.) is flattened, and
+//! IndexedFaceSet geometries in Shape nodes are converted into triangle
+//! meshes.
+//!
+//! VRML 1.0, primitive geometries (Box, Sphere, etc.), Inline, and PROTO
+//! instances are not supported.
+//!
+//! [VRML]: https://en.wikipedia.org/wiki/VRML
+//! [X3D]: https://en.wikipedia.org/wiki/X3D
+
+#![allow(clippy::many_single_char_names)]
+
+mod error;
+mod parse;
+mod x3d;
+
+use std::{
+    borrow::Cow,
+    io,
+    path::{Path, PathBuf},
+    slice,
+};
+
+use crate::{
+    common::{self, DEFAULT_COLOR},
+    resolver,
+    utils::{
+        bytes::starts_with,
+        float,
+        matrix::{normalize, Matrix4x4},
+        utf16::decode_string,
+        xml,
+    },
+    Color4, Vec2, Vec3,
+};
+
+/// Parses meshes from bytes of VRML or X3D (classic or XML encoding).
+#[inline]
+pub fn from_slice(bytes: &[u8]) -> io::Result<common::Scene> {
+    from_slice_internal(bytes, None)
+}
+
+pub(crate) fn from_slice_internal(bytes: &[u8], path: Option<&Path>) -> io::Result<common::Scene> {
+    let mut nodes = vec![];
+    if is_xml(bytes) {
+        let s = decode_string(bytes)?;
+        let xml = xml::Document::parse(&s).map_err(crate::error::invalid_data)?;
+        let roots = x3d::parse(&xml, &mut nodes)?;
+        return build(&nodes, &roots, path);
+    }
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    let roots = parse::parse(bytes, &mut nodes).map_err(|e| e.into_io_error(bytes, path))?;
+    build(&nodes, &roots, path)
+}
+
+fn is_xml(bytes: &[u8]) -> bool {
+    // UTF-16 XML files are also handled by decode_string.
+    if starts_with(bytes, b"\xFE\xFF") || starts_with(bytes, b"\xFF\xFE") {
+        return true;
+    }
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    bytes
+        .iter()
+        .find(|c| !c.is_ascii_whitespace())
+        .map_or(false, |&c| c == b'<')
+}
+
+/// A node in either the classic or XML encoding.
+struct Node<'a> {
+    ty: &'a str,
+    /// The name given by DEF.
+    name: Option<&'a str>,
+    fields: Vec<(&'a str, Value<'a>)>,
+}
+
+enum Value<'a> {
+    /// SFNode
+    Node(Option<usize>),
+    /// MFNode
+    Nodes(Vec<usize>),
+    /// Numeric fields (SFFloat, MFVec3f, MFInt32, etc.)
+    Numbers(Vec<f64>),
+    /// SFString and MFString
+    Strings(Vec<String>),
+    /// SFBool
+    Bool(bool),
+    /// An attribute value of the XML encoding, which is parsed on access.
+    Text(&'a str),
+}
+
+impl<'a> Node<'a> {
+    fn field(&self, name: &str) -> Option<&Value<'a>> {
+        // The last one takes precedence if a field is specified more than once.
+        self.fields
+            .iter()
+            .rev()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v)
+    }
+
+    fn numbers(&self, name: &str) -> io::Result<Cow<'_, [f64]>> {
+        match self.field(name) {
+            Some(Value::Numbers(v)) => Ok(Cow::Borrowed(v)),
+            Some(Value::Text(s)) => s
+                .split(|c: char| xml::is_whitespace(c) || c == ',')
+                .filter(|s| !s.is_empty())
+                .map(|s| {
+                    float::parse(s.as_bytes()).ok_or_else(|| {
+                        format_err!(
+                            "error while parsing a float in {name} attribute of <{}>",
+                            self.ty
+                        )
+                    })
+                })
+                .collect::<io::Result<Vec<_>>>()
+                .map(Cow::Owned),
+            _ => Ok(Cow::Borrowed(&[])),
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn vec3(&self, name: &str, default: Vec3) -> io::Result<Vec3> {
+        Ok(match *self.numbers(name)? {
+            [x, y, z, ..] => [x as f32, y as f32, z as f32],
+            _ => default,
+        })
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn float(&self, name: &str, default: f32) -> io::Result<f32> {
+        Ok(match *self.numbers(name)? {
+            [v, ..] => v as f32,
+            _ => default,
+        })
+    }
+
+    fn bool(&self, name: &str, default: bool) -> bool {
+        match self.field(name) {
+            Some(Value::Bool(v)) => *v,
+            Some(Value::Text(s)) => match xml::trim(s) {
+                "true" | "TRUE" => true,
+                "false" | "FALSE" => false,
+                _ => default,
+            },
+            _ => default,
+        }
+    }
+
+    fn node(&self, name: &str) -> Option<usize> {
+        match self.field(name) {
+            Some(Value::Node(v)) => *v,
+            Some(Value::Nodes(v)) => v.first().copied(),
+            _ => None,
+        }
+    }
+
+    fn nodes(&self, name: &str) -> &[usize] {
+        match self.field(name) {
+            Some(Value::Node(Some(v))) => slice::from_ref(v),
+            Some(Value::Nodes(v)) => v,
+            _ => &[],
+        }
+    }
+
+    fn strings(&self, name: &str) -> Vec<Cow<'_, str>> {
+        match self.field(name) {
+            Some(Value::Strings(v)) => v.iter().map(|s| Cow::Borrowed(&**s)).collect(),
+            Some(Value::Text(s)) => x3d::parse_mfstring(s),
+            _ => vec![],
+        }
+    }
+}
+
+fn build(nodes: &[Node<'_>], roots: &[usize], path: Option<&Path>) -> io::Result<common::Scene> {
+    let mut builder = Builder {
+        nodes,
+        dir: path.and_then(Path::parent),
+        scene: common::Scene::default(),
+    };
+    for &node in roots {
+        builder.visit(node, Matrix4x4::default(), None)?;
+    }
+    Ok(builder.scene)
+}
+
+struct Builder<'a, 'b> {
+    nodes: &'b [Node<'a>],
+    dir: Option<&'b Path>,
+    scene: common::Scene,
+}
+
+impl Builder<'_, '_> {
+    fn visit(
+        &mut self,
+        node: usize,
+        mut transform: Matrix4x4,
+        name: Option<&str>,
+    ) -> io::Result<()> {
+        let n = &self.nodes[node];
+        let name = n.name.or(name);
+        match n.ty {
+            "Transform" => {
+                transform *= transform_matrix(n)?;
+                for &child in n.nodes("children") {
+                    self.visit(child, transform, name)?;
+                }
+            }
+            "Shape" => self.push_shape(n, transform, name)?,
+            "Switch" => {
+                let choice = n.float("whichChoice", -1.)?;
+                let children = match n.field("choice") {
+                    Some(_) => n.nodes("choice"),
+                    None => n.nodes("children"),
+                };
+                if choice >= 0. {
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    if let Some(&child) = children.get(choice as usize) {
+                        self.visit(child, transform, name)?;
+                    }
+                }
+            }
+            "LOD" => {
+                // Use the most detailed level.
+                let children = match n.field("level") {
+                    Some(_) => n.nodes("level"),
+                    None => n.nodes("children"),
+                };
+                if let Some(&child) = children.first() {
+                    self.visit(child, transform, name)?;
+                }
+            }
+            // Group, Anchor, Billboard, Collision, StaticGroup, etc.
+            _ => {
+                for &child in n.nodes("children") {
+                    self.visit(child, transform, name)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn push_shape(
+        &mut self,
+        shape: &Node<'_>,
+        transform: Matrix4x4,
+        name: Option<&str>,
+    ) -> io::Result<()> {
+        let geometry = match shape.node("geometry") {
+            Some(geometry) if self.nodes[geometry].ty == "IndexedFaceSet" => &self.nodes[geometry],
+            _ => return Ok(()),
+        };
+        let mut mesh = self.indexed_face_set(geometry, transform)?;
+        mesh.name = name.unwrap_or_default().to_owned();
+        let material = self.material(shape.node("appearance"))?;
+        self.scene.meshes.push(mesh);
+        self.scene.materials.push(material);
+        Ok(())
+    }
+
+    /*
+    IndexedFaceSet {
+      coord Coordinate { point [ ... ] }
+      coordIndex [ 0 1 2 -1 ... ]
+      normal Normal { vector [ ... ] }
+      normalIndex [ ... ]
+      normalPerVertex TRUE
+      color Color { color [ ... ] }
+      colorIndex [ ... ]
+      colorPerVertex TRUE
+      texCoord TextureCoordinate { point [ ... ] }
+      texCoordIndex [ ... ]
+      ccw TRUE
+    }
+    */
+    fn indexed_face_set(&self, node: &Node<'_>, transform: Matrix4x4) -> io::Result<common::Mesh> {
+        let vertices: Vec<Vec3> = match node.node("coord") {
+            Some(coord) => chunks(&self.nodes[coord].numbers("point")?),
+            None => vec![],
+        };
+        let normals: Vec<Vec3> = match node.node("normal") {
+            Some(normal) => chunks(&self.nodes[normal].numbers("vector")?),
+            None => vec![],
+        };
+        let normal_transform = transform.normal_matrix();
+        let texcoords: Vec<Vec2> = match node.node("texCoord") {
+            Some(texcoord) => chunks(&self.nodes[texcoord].numbers("point")?),
+            None => vec![],
+        };
+        let colors: Vec<Color4> = match node.node("color") {
+            Some(color) if self.nodes[color].ty == "ColorRGBA" => {
+                chunks(&self.nodes[color].numbers("color")?)
+            }
+            Some(color) => chunks::<3>(&self.nodes[color].numbers("color")?)
+                .into_iter()
+                .map(|[r, g, b]| [r, g, b, 1.])
+                .collect(),
+            None => vec![],
+        };
+        let coord_index = node.numbers("coordIndex")?;
+        let normal_index = node.numbers("normalIndex")?;
+        let color_index = node.numbers("colorIndex")?;
+        let texcoord_index = node.numbers("texCoordIndex")?;
+        let normal_per_vertex = node.bool("normalPerVertex", true);
+        let color_per_vertex = node.bool("colorPerVertex", true);
+        let ccw = node.bool("ccw", true);
+
+        let mut mesh = common::Mesh::default();
+        let mut face = 0;
+        let mut start = 0;
+        for end in 0..=coord_index.len() {
+            // A face is terminated by -1 or the end of the array.
+            if end < coord_index.len() && coord_index[end] >= 0. {
+                continue;
+            }
+            let corners = start..end;
+            start = end + 1;
+            if corners.len() < 3 {
+                if !corners.is_empty() {
+                    face += 1;
+                }
+                continue;
+            }
+
+            // Triangulate polygons with a triangle fan. This is correct for
+            // convex polygons, which IndexedFaceSet requires by default.
+            for i in 1..corners.len() - 1 {
+                #[allow(clippy::cast_possible_truncation)]
+                let vertices_indices = [
+                    mesh.vertices.len() as u32,
+                    (mesh.vertices.len() + 1) as u32,
+                    (mesh.vertices.len() + 2) as u32,
+                ];
+                let triangle = if ccw { [0, i, i + 1] } else { [0, i + 1, i] };
+                for pos in triangle.map(|i| corners.start + i) {
+                    let mut v = get(&vertices, coord_index[pos], "coordIndex")?;
+                    v *= transform;
+                    mesh.vertices.push(v);
+                    if !normals.is_empty() {
+                        let index = attribute_index(
+                            &normal_index,
+                            &coord_index,
+                            normal_per_vertex,
+                            pos,
+                            face,
+                        );
+                        let mut n = get(&normals, index, "normalIndex")?;
+                        n *= normal_transform;
+                        mesh.normals.push(normalize(n));
+                    }
+                    if !colors.is_empty() {
+                        let index = attribute_index(
+                            &color_index,
+                            &coord_index,
+                            color_per_vertex,
+                            pos,
+                            face,
+                        );
+                        mesh.colors[0].push(get(&colors, index, "colorIndex")?);
+                    }
+                    if !texcoords.is_empty() {
+                        let index = attribute_index(&texcoord_index, &coord_index, true, pos, face);
+                        mesh.texcoords[0].push(get(&texcoords, index, "texCoordIndex")?);
+                    }
+                }
+                mesh.faces.push(vertices_indices);
+            }
+            face += 1;
+        }
+        Ok(mesh)
+    }
+
+    /*
+    Appearance {
+      material Material {
+        ambientIntensity 0.2
+        diffuseColor 0.8 0.8 0.8
+        emissiveColor 0 0 0
+        shininess 0.2
+        specularColor 0 0 0
+        transparency 0
+      }
+      texture ImageTexture { url [ "..." ] }
+    }
+    */
+    fn material(&self, appearance: Option<usize>) -> io::Result<common::Material> {
+        let mut mat = common::Material::default();
+        let appearance = match appearance {
+            Some(appearance) => &self.nodes[appearance],
+            None => {
+                mat.color.diffuse = Some(DEFAULT_COLOR);
+                return Ok(mat);
+            }
+        };
+        match appearance.node("material") {
+            Some(material) => {
+                let material = &self.nodes[material];
+                mat.name = material.name.unwrap_or_default().to_owned();
+                let [r, g, b] = material.vec3("diffuseColor", [0.8, 0.8, 0.8])?;
+                let ambient = material.float("ambientIntensity", 0.2)?;
+                let opacity = 1. - material.float("transparency", 0.)?;
+                mat.color.diffuse = Some([r, g, b, opacity]);
+                mat.color.ambient = Some([r * ambient, g * ambient, b * ambient, 1.]);
+                let [r, g, b] = material.vec3("specularColor", [0., 0., 0.])?;
+                mat.color.specular = Some([r, g, b, 1.]);
+                let [r, g, b] = material.vec3("emissiveColor", [0., 0., 0.])?;
+                mat.color.emissive = Some([r, g, b, 1.]);
+                mat.shininess = Some(material.float("shininess", 0.2)? * 128.);
+                mat.opacity = Some(opacity);
+            }
+            None => mat.color.diffuse = Some(DEFAULT_COLOR),
+        }
+        if let Some(texture) = appearance.node("texture") {
+            let texture = &self.nodes[texture];
+            if texture.ty == "ImageTexture" {
+                mat.texture.diffuse = self.texture_path(texture);
+            }
+        }
+        Ok(mat)
+    }
+
+    fn texture_path(&self, texture: &Node<'_>) -> Option<PathBuf> {
+        // url is a list of candidates in order of preference. Whether the
+        // file is available is decided by the loader's resolver, so the
+        // most preferred one is used.
+        let url = texture.strings("url").into_iter().next()?;
+        match self.dir {
+            Some(dir) => Some(resolver::join(dir, &*url)),
+            None => Some(url.into_owned().into()),
+        }
+    }
+}
+
+/// Computes the transform of a Transform node:
+/// `T * C * R * SR * S * -SR * -C`
+fn transform_matrix(node: &Node<'_>) -> io::Result<Matrix4x4> {
+    let translation = node.vec3("translation", [0., 0., 0.])?;
+    let center = node.vec3("center", [0., 0., 0.])?;
+    let scale = node.vec3("scale", [1., 1., 1.])?;
+    let rotation = axis_angle(&node.numbers("rotation")?);
+    let scale_orientation = axis_angle(&node.numbers("scaleOrientation")?);
+    let mut m = Matrix4x4::translation(translation);
+    m *= Matrix4x4::translation(center);
+    if let Some((axis, angle)) = rotation {
+        m *= Matrix4x4::rotation(angle, axis);
+    }
+    if let Some((axis, angle)) = scale_orientation {
+        m *= Matrix4x4::rotation(angle, axis);
+    }
+    m *= Matrix4x4::scaling(scale);
+    if let Some((axis, angle)) = scale_orientation {
+        m *= Matrix4x4::rotation(-angle, axis);
+    }
+    m *= Matrix4x4::translation([-center[0], -center[1], -center[2]]);
+    Ok(m)
+}
+
+/// Converts SFRotation (axis and angle) to a normalized axis and an angle.
+#[allow(clippy::cast_possible_truncation)]
+fn axis_angle(v: &[f64]) -> Option<(Vec3, f32)> {
+    match *v {
+        [x, y, z, angle, ..] if angle != 0. => {
+            let axis = [x as f32, y as f32, z as f32];
+            if axis == [0., 0., 0.] {
+                return None;
+            }
+            Some((normalize(axis), angle as f32))
+        }
+        _ => None,
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn chunks<const N: usize>(v: &[f64]) -> Vec<[f32; N]> {
+    v.chunks_exact(N)
+        .map(|c| {
+            let mut out = [0.; N];
+            for (out, &c) in out.iter_mut().zip(c) {
+                *out = c as f32;
+            }
+            out
+        })
+        .collect()
+}
+
+/// Returns the index of a per-vertex or per-face attribute.
+fn attribute_index(
+    index: &[f64],
+    coord_index: &[f64],
+    per_vertex: bool,
+    pos: usize,
+    face: usize,
+) -> f64 {
+    if per_vertex {
+        // Use coordIndex if the index is empty.
+        let index = if index.is_empty() { coord_index } else { index };
+        index.get(pos).copied().unwrap_or(-1.)
+    } else if index.is_empty() {
+        #[allow(clippy::cast_precision_loss)]
+        let face = face as f64;
+        face
+    } else {
+        index.get(face).copied().unwrap_or(-1.)
+    }
+}
+
+fn get<T: Copy>(values: &[T], index: f64, name: &str) -> io::Result<T> {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    if index >= 0. {
+        if let Some(&v) = values.get(index as usize) {
+            return Ok(v);
+        }
+    }
+    bail!("index out of bounds ({}) in {}", index, name)
+}
@@ -0,0 +1,371 @@
+// Parser for the classic encoding of VRML97 and X3D.
+//
+// Field types are not known without the node definitions, so field values
+// are parsed based on their syntax. PROTO and EXTERNPROTO definitions, ROUTE
+// statements, and X3D header statements are skipped.
+//
+// Refs:
+// - https://www.web3d.org/documents/specifications/14772/V2.0/part1/grammar.html
+// - https://www.web3d.org/documents/specifications/19776-2/V3.3/Part02/grammar.html
+
+use std::str;
+
+use super::{error::ErrorKind, Node, Value};
+use crate::utils::{bytes::starts_with, float};
+
+// Deeply nested files are almost certainly broken, so limit the depth to
+// avoid stack overflow.
+const MAX_DEPTH: usize = 128;
+
+pub(super) fn parse<'a>(s: &'a [u8], nodes: &mut Vec<Node<'a>>) -> Result<Vec<usize>, ErrorKind> {
+    let mut p = Parser {
+        s,
+        nodes,
+        defs: vec![],
+    };
+    if starts_with(s, b"#VRML V1.0") {
+        return Err(ErrorKind::Unsupported("VRML 1.0", s.len()));
+    }
+    let mut roots = vec![];
+    loop {
+        p.skip_spaces();
+        if p.s.is_empty() {
+            break;
+        }
+        let remaining = p.s.len();
+        let ident = p.read_ident()?;
+        if p.skip_statement(ident)? {
+            continue;
+        }
+        if let Some(node) = p.read_node(ident, remaining, 0)? {
+            roots.push(node);
+        }
+    }
+    Ok(roots)
+}
+
+struct Parser<'a, 'b> {
+    s: &'a [u8],
+    nodes: &'b mut Vec<Node<'a>>,
+    defs: Vec<(&'a str, usize)>,
+}
+
+impl<'a> Parser<'a, '_> {
+    /// Skips statements other than node statements, and returns `true` if
+    /// skipped.
+    fn skip_statement(&mut self, ident: &str) -> Result<bool, ErrorKind> {
+        match ident {
+            // PROTO <name> [ <interface> ] { <body> }
+            "PROTO" => {
+                self.read_ident()?;
+                self.skip_block(b'[', b']')?;
+                self.skip_block(b'{', b'}')?;
+            }
+            // EXTERNPROTO <name> [ <interface> ] <url>
+            "EXTERNPROTO" => {
+                self.read_ident()?;
+                self.skip_block(b'[', b']')?;
+                self.read_value(0)?;
+            }
+            // ROUTE <node>.<field> TO <node>.<field>
+            // IMPORT <inline>.<node> AS <name>
+            // EXPORT <node> AS <name>
+            "ROUTE" | "IMPORT" | "EXPORT" => {
+                for _ in 0..3 {
+                    self.read_ident()?;
+                }
+            }
+            // PROFILE <name>
+            // COMPONENT <name>:<level>
+            "PROFILE" | "COMPONENT" => {
+                self.read_ident()?;
+            }
+            // META <key> <value>
+            "META" => {
+                self.read_value(0)?;
+                self.read_value(0)?;
+            }
+            // UNIT <category> <name> <conversion factor>
+            "UNIT" => {
+                self.read_ident()?;
+                self.read_ident()?;
+                self.read_value(0)?;
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Reads a node that starts with `ident` (`DEF`, `USE`, `NULL`, or the
+    /// type of the node).
+    fn read_node(
+        &mut self,
+        ident: &'a str,
+        remaining: usize,
+        depth: usize,
+    ) -> Result<Option<usize>, ErrorKind> {
+        if depth > MAX_DEPTH {
+            return Err(ErrorKind::TooDeep(remaining));
+        }
+        let (name, ty) = match ident {
+            "NULL" => return Ok(None),
+            "USE" => {
+                self.skip_spaces();
+                let remaining = self.s.len();
+                let name = self.read_ident()?;
+                // Nodes defined later take precedence.
+                return match self.defs.iter().rev().find(|(n, _)| *n == name) {
+                    Some(&(_, node)) => Ok(Some(node)),
+                    None => Err(ErrorKind::UnknownUse(remaining)),
+                };
+            }
+            "DEF" => {
+                let name = self.read_ident()?;
+                (Some(name), self.read_ident()?)
+            }
+            _ => (None, ident),
+        };
+
+        self.skip_spaces();
+        if !self.token(b'{') {
+            return Err(ErrorKind::Expected("'{'", self.s.len()));
+        }
+        let mut fields = vec![];
+        loop {
+            self.skip_spaces();
+            if self.token(b'}') {
+                break;
+            }
+            let field = self.read_ident()?;
+            if self.skip_statement(field)? {
+                continue;
+            }
+            match field {
+                // Field declarations in Script and shader nodes.
+                "field" | "exposedField" | "initializeOnly" | "inputOutput" => {
+                    self.read_ident()?;
+                    self.read_ident()?;
+                    self.read_value(depth)?;
+                }
+                "eventIn" | "eventOut" | "inputOnly" | "outputOnly" => {
+                    self.read_ident()?;
+                    self.read_ident()?;
+                }
+                _ => {
+                    let value = self.read_value(depth)?;
+                    fields.push((field, value));
+                }
+            }
+        }
+
+        let node = self.nodes.len();
+        self.nodes.push(Node { ty, name, fields });
+        if let Some(name) = name {
+            self.defs.push((name, node));
+        }
+        Ok(Some(node))
+    }
+
+    fn read_value(&mut self, depth: usize) -> Result<Value<'a>, ErrorKind> {
+        self.skip_spaces();
+        match self.s.first() {
+            Some(b'[') => {
+                self.s = &self.s[1..];
+                let mut numbers = vec![];
+                let mut strings = vec![];
+                let mut nodes = vec![];
+                loop {
+                    self.skip_spaces();
+                    match self.s.first() {
+                        Some(b']') => {
+                            self.s = &self.s[1..];
+                            break;
+                        }
+                        Some(b'"') => strings.push(self.read_string()?),
+                        Some(&c) if is_number_start(c) => numbers.push(self.read_number()?),
+                        Some(_) => {
+                            let remaining = self.s.len();
+                            match self.read_ident()? {
+                                "TRUE" => numbers.push(1.),
+                                "FALSE" => numbers.push(0.),
+                                ident => {
+                                    if let Some(node) =
+                                        self.read_node(ident, remaining, depth + 1)?
+                                    {
+                                        nodes.push(node);
+                                    }
+                                }
+                            }
+                        }
+                        None => return Err(ErrorKind::Expected("']'", self.s.len())),
+                    }
+                }
+                Ok(if !nodes.is_empty() {
+                    Value::Nodes(nodes)
+                } else if !strings.is_empty() {
+                    Value::Strings(strings)
+                } else {
+                    Value::Numbers(numbers)
+                })
+            }
+            Some(b'"') => Ok(Value::Strings(vec![self.read_string()?])),
+            Some(&c) if is_number_start(c) => {
+                // Values of single-value fields with multiple components
+                // (e.g., SFVec3f) are not enclosed in brackets.
+                let mut numbers = vec![self.read_number()?];
+                loop {
+                    self.skip_spaces();
+                    match self.s.first() {
+                        Some(&c) if is_number_start(c) => numbers.push(self.read_number()?),
+                        _ => break,
+                    }
+                }
+                Ok(Value::Numbers(numbers))
+            }
+            Some(_) => {
+                let remaining = self.s.len();
+                match self.read_ident()? {
+                    "TRUE" => Ok(Value::Bool(true)),
+                    "FALSE" => Ok(Value::Bool(false)),
+                    ident => Ok(Value::Node(self.read_node(ident, remaining, depth + 1)?)),
+                }
+            }
+            None => Err(ErrorKind::Expected("field value", self.s.len())),
+        }
+    }
+
+    fn read_ident(&mut self) -> Result<&'a str, ErrorKind> {
+        self.skip_spaces();
+        let len = self
+            .s
+            .iter()
+            .position(|&c| is_whitespace(c) || matches!(c, b'{' | b'}' | b'[' | b']' | b'"' | b'#'))
+            .unwrap_or(self.s.len());
+        if len == 0 {
+            return Err(ErrorKind::Expected("identifier", self.s.len()));
+        }
+        let ident = str::from_utf8(&self.s[..len])
+            .map_err(|_| ErrorKind::Expected("identifier", self.s.len()))?;
+        self.s = &self.s[len..];
+        Ok(ident)
+    }
+
+    fn read_number(&mut self) -> Result<f64, ErrorKind> {
+        let len = self
+            .s
+            .iter()
+            .position(|&c| is_whitespace(c) || matches!(c, b'{' | b'}' | b'[' | b']' | b'"' | b'#'))
+            .unwrap_or(self.s.len());
+        let token = &self.s[..len];
+        let v = match float::parse::<f64>(token) {
+            Some(v) => v,
+            None => {
+                // Hexadecimal integers are allowed in SFInt32 and SFImage.
+                let hex = token
+                    .strip_prefix(b"0x")
+                    .or_else(|| token.strip_prefix(b"0X"))
+                    .and_then(|h| str::from_utf8(h).ok())
+                    .and_then(|h| u32::from_str_radix(h, 16).ok());
+                match hex {
+                    Some(v) => f64::from(v),
+                    None => return Err(ErrorKind::Float(self.s.len())),
+                }
+            }
+        };
+        self.s = &self.s[len..];
+        Ok(v)
+    }
+
+    fn read_string(&mut self) -> Result<String, ErrorKind> {
+        let start = self.s.len();
+        self.s = &self.s[1..];
+        let mut buf = vec![];
+        loop {
+            match self.s.split_first() {
+                Some((b'"', s)) => {
+                    self.s = s;
+                    return Ok(String::from_utf8_lossy(&buf).into_owned());
+                }
+                Some((b'\\', s)) if !s.is_empty() => {
+                    buf.push(s[0]);
+                    self.s = &s[1..];
+                }
+                Some((&c, s)) => {
+                    buf.push(c);
+                    self.s = s;
+                }
+                None => return Err(ErrorKind::UnclosedString(start)),
+            }
+        }
+    }
+
+    /// Skips a block enclosed in `open` and `close`.
+    fn skip_block(&mut self, open: u8, close: u8) -> Result<(), ErrorKind> {
+        self.skip_spaces();
+        if !self.token(open) {
+            return Err(ErrorKind::Expected(
+                if open == b'[' { "'['" } else { "'{'" },
+                self.s.len(),
+            ));
+        }
+        let mut level = 1;
+        while level != 0 {
+            self.skip_spaces();
+            match self.s.first() {
+                Some(b'"') => {
+                    self.read_string()?;
+                }
+                Some(&c) => {
+                    if c == open {
+                        level += 1;
+                    } else if c == close {
+                        level -= 1;
+                    }
+                    self.s = &self.s[1..];
+                }
+                None => {
+                    return Err(ErrorKind::Expected(
+                        if close == b']' { "']'" } else { "'}'" },
+                        self.s.len(),
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Skips whitespaces, commas, and comments.
+    fn skip_spaces(&mut self) {
+        while let Some((&c, s)) = self.s.split_first() {
+            if is_whitespace(c) {
+                self.s = s;
+            } else if c == b'#' {
+                let len = s
+                    .iter()
+                    .position(|&c| c == b'\n' || c == b'\r')
+                    .unwrap_or(s.len());
+                self.s = &s[len..];
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn token(&mut self, c: u8) -> bool {
+        if self.s.first() == Some(&c) {
+            self.s = &self.s[1..];
+            true
+        } else {
+            false
+        }
+    }
+}
+
+// Commas are treated as whitespace in the classic encoding.
+fn is_whitespace(c: u8) -> bool {
+    matches!(c, b' ' | b'\t' | b'\n' | b'\r' | b',')
+}
+
+fn is_number_start(c: u8) -> bool {
+    matches!(c, b'0'..=b'9' | b'+' | b'-' | b'.')
+}
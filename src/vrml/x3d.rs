@@ -0,0 +1,158 @@
+// Parser for the XML encoding of X3D.
+//
+// Elements are converted into the same node representation as the classic
+// encoding. Attribute values are kept as text and parsed on access.
+//
+// Refs: https://www.web3d.org/documents/specifications/19776-1/V3.3/Part01/EncodingOfNodes.html
+
+use std::{borrow::Cow, collections::HashMap, io};
+
+use super::{Node, Value};
+use crate::utils::xml::{self, XmlNodeExt};
+
+// Deeply nested files are almost certainly broken, so limit the depth to
+// avoid stack overflow.
+const MAX_DEPTH: usize = 128;
+
+pub(super) fn parse<'a>(
+    doc: &'a xml::Document<'_>,
+    nodes: &mut Vec<Node<'a>>,
+) -> io::Result<Vec<usize>> {
+    let root = doc.root_element();
+    if root.tag_name().name() != "X3D" {
+        bail!(
+            "expected <X3D> element, but found <{}> element at {}",
+            root.tag_name().name(),
+            root.node_location()
+        );
+    }
+    let mut p = Parser {
+        nodes,
+        defs: HashMap::new(),
+    };
+    let mut roots = vec![];
+    if let Some(scene) = root.child("Scene") {
+        for child in scene.element_children() {
+            if let Some((_, node)) = p.read_node(child, 0)? {
+                roots.push(node);
+            }
+        }
+    }
+    Ok(roots)
+}
+
+struct Parser<'a, 'b> {
+    nodes: &'b mut Vec<Node<'a>>,
+    defs: HashMap<&'a str, usize>,
+}
+
+impl<'a> Parser<'a, '_> {
+    /// Reads a node element, and returns the name of the field that contains
+    /// the node and the node.
+    fn read_node(
+        &mut self,
+        node: xml::Node<'a, '_>,
+        depth: usize,
+    ) -> io::Result<Option<(&'a str, usize)>> {
+        let ty = node.tag_name().name();
+        match ty {
+            // Statements and prototypes are not nodes.
+            "ROUTE" | "IMPORT" | "EXPORT" | "ProtoDeclare" | "ExternProtoDeclare"
+            | "ProtoInstance" | "IS" | "field" | "fieldValue" => return Ok(None),
+            _ => {}
+        }
+        if depth > MAX_DEPTH {
+            bail!(
+                "too deeply nested node in <{ty}> element at {}",
+                node.node_location()
+            );
+        }
+        let container = node
+            .attribute("containerField")
+            .unwrap_or_else(|| default_container(ty));
+
+        if let Some(name) = node.attribute("USE") {
+            return match self.defs.get(name) {
+                Some(&n) => Ok(Some((container, n))),
+                None => bail!(
+                    "USE of undefined node '{name}' in <{ty}> element at {}",
+                    node.node_location()
+                ),
+            };
+        }
+
+        let mut fields = vec![];
+        for attr in node.attributes() {
+            match attr.name() {
+                "DEF" | "USE" | "containerField" | "class" | "id" | "style" => {}
+                name => fields.push((name, Value::Text(attr.value()))),
+            }
+        }
+        let mut children: Vec<(&'a str, Vec<usize>)> = vec![];
+        for child in node.element_children() {
+            if let Some((field, n)) = self.read_node(child, depth + 1)? {
+                match children.iter_mut().find(|(f, _)| *f == field) {
+                    Some((_, nodes)) => nodes.push(n),
+                    None => children.push((field, vec![n])),
+                }
+            }
+        }
+        fields.extend(children.into_iter().map(|(f, n)| (f, Value::Nodes(n))));
+
+        let name = node.attribute("DEF");
+        let n = self.nodes.len();
+        self.nodes.push(Node { ty, name, fields });
+        if let Some(name) = name {
+            self.defs.insert(name, n);
+        }
+        Ok(Some((container, n)))
+    }
+}
+
+/// Returns the default value of the containerField attribute.
+fn default_container(ty: &str) -> &'static str {
+    match ty {
+        "Appearance" => "appearance",
+        "Material" | "TwoSidedMaterial" => "material",
+        "ImageTexture" | "PixelTexture" | "MovieTexture" | "MultiTexture" => "texture",
+        "IndexedFaceSet" | "IndexedTriangleSet" | "IndexedLineSet" | "Box" | "Sphere" | "Cone"
+        | "Cylinder" | "ElevationGrid" | "Extrusion" | "PointSet" | "Text" => "geometry",
+        "Coordinate" | "CoordinateDouble" => "coord",
+        "Normal" => "normal",
+        "Color" | "ColorRGBA" => "color",
+        "TextureCoordinate" | "MultiTextureCoordinate" => "texCoord",
+        _ => "children",
+    }
+}
+
+/// Parses a value of MFString in the XML encoding (`'"a" "b"'`).
+pub(super) fn parse_mfstring(s: &str) -> Vec<Cow<'_, str>> {
+    let mut s = xml::trim(s);
+    if !s.starts_with('"') {
+        // Single-value field (SFString).
+        return if s.is_empty() { vec![] } else { vec![s.into()] };
+    }
+    let mut strings = vec![];
+    while let Some(rest) = s.strip_prefix('"') {
+        let mut buf = String::new();
+        let mut chars = rest.char_indices();
+        let mut end = rest.len();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    end = i + 1;
+                    break;
+                }
+                '\\' => {
+                    if let Some((_, c)) = chars.next() {
+                        buf.push(c);
+                    }
+                }
+                _ => buf.push(c),
+            }
+        }
+        strings.push(buf.into());
+        s = xml::trim_start(&rest[end..]);
+    }
+    strings
+}
@@ -0,0 +1,148 @@
+//! XYZ (.xyz) and PTS (.pts) point cloud parser.
+//!
+//! Each line has the values of a point, separated by spaces, tabs, commas, or
+//! semicolons. The meaning of the values is determined by the number of
+//! values in the first point:
+//!
+//! - 3: `x y z`
+//! - 4: `x y z intensity`
+//! - 6: `x y z r g b` if `r g b` are integers, otherwise `x y z nx ny nz`
+//! - 7: `x y z intensity r g b` (Leica PTS)
+//! - 9: `x y z r g b nx ny nz`
+//!
+//! Colors are in the range 0-255. A line with a single value before the first
+//! point (the number of points in PTS) and comment lines starting with `#` or
+//! `//` are ignored.
+
+mod error;
+
+use std::{io, path::Path};
+
+use self::error::ErrorKind;
+use crate::{utils::float, PointCloud, Scene};
+
+/// Parses a point cloud from bytes of XYZ or PTS text.
+#[inline]
+pub fn from_slice(bytes: &[u8]) -> io::Result<Scene> {
+    from_slice_internal(bytes, None)
+}
+
+pub(crate) fn from_slice_internal(bytes: &[u8], path: Option<&Path>) -> io::Result<Scene> {
+    match read_xyz(bytes) {
+        Ok(point_cloud) => Ok(Scene {
+            point_clouds: vec![point_cloud],
+            ..Default::default()
+        }),
+        Err(e) => Err(e.into_io_error(bytes, path)),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Columns {
+    Xyz,
+    XyzI,
+    XyzRgb,
+    XyzNormal,
+    XyzIRgb,
+    XyzRgbNormal,
+}
+
+fn read_xyz(mut s: &[u8]) -> Result<PointCloud, ErrorKind> {
+    let mut cloud = PointCloud::default();
+    let mut tokens = vec![];
+    let mut values = vec![];
+    let mut columns = None;
+    while !s.is_empty() {
+        let remaining = s.len();
+        let end = s.iter().position(|&b| b == b'\n').unwrap_or(s.len());
+        let line = &s[..end];
+        s = s.get(end + 1..).unwrap_or_default();
+        split_values(line, remaining, &mut tokens);
+        match tokens.first() {
+            None => continue,
+            Some((t, _)) if t.starts_with(b"#") || t.starts_with(b"//") => continue,
+            Some(_) => {}
+        }
+
+        let columns = match columns {
+            Some(columns) => columns,
+            None => {
+                let c = match tokens.len() {
+                    // The number of points in PTS.
+                    1 if cloud.points.is_empty() => continue,
+                    3 => Columns::Xyz,
+                    4 => Columns::XyzI,
+                    6 if tokens[3..].iter().all(|(t, _)| is_integer(t)) => Columns::XyzRgb,
+                    6 => Columns::XyzNormal,
+                    7 => Columns::XyzIRgb,
+                    9 => Columns::XyzRgbNormal,
+                    _ => return Err(ErrorKind::Expected("3, 4, 6, 7, or 9 values", remaining)),
+                };
+                *columns.insert(c)
+            }
+        };
+        let len = match columns {
+            Columns::Xyz => 3,
+            Columns::XyzI => 4,
+            Columns::XyzRgb | Columns::XyzNormal => 6,
+            Columns::XyzIRgb => 7,
+            Columns::XyzRgbNormal => 9,
+        };
+        if tokens.len() != len {
+            return Err(ErrorKind::InvalidValueCount("point", remaining));
+        }
+        values.clear();
+        for &(t, pos) in &tokens {
+            values.push(float::parse::<f32>(t).ok_or(ErrorKind::Float(pos))?);
+        }
+
+        cloud.points.push([values[0], values[1], values[2]]);
+        let rgb = |i: usize| {
+            [
+                values[i] / 255.,
+                values[i + 1] / 255.,
+                values[i + 2] / 255.,
+                1.,
+            ]
+        };
+        match columns {
+            Columns::Xyz => {}
+            Columns::XyzI => cloud.intensities.push(values[3]),
+            Columns::XyzRgb => cloud.colors.push(rgb(3)),
+            Columns::XyzNormal => cloud.normals.push([values[3], values[4], values[5]]),
+            Columns::XyzIRgb => {
+                cloud.intensities.push(values[3]);
+                cloud.colors.push(rgb(4));
+            }
+            Columns::XyzRgbNormal => {
+                cloud.colors.push(rgb(3));
+                cloud.normals.push([values[6], values[7], values[8]]);
+            }
+        }
+    }
+    Ok(cloud)
+}
+
+fn is_integer(s: &[u8]) -> bool {
+    let s = s.strip_prefix(b"-").unwrap_or(s);
+    !s.is_empty() && s.iter().all(u8::is_ascii_digit)
+}
+
+/// Splits a line into values, with the number of remaining bytes at the
+/// start of each value.
+fn split_values<'a>(line: &'a [u8], remaining: usize, tokens: &mut Vec<(&'a [u8], usize)>) {
+    let is_separator = |b: u8| b.is_ascii_whitespace() || b == b',' || b == b';';
+    tokens.clear();
+    let mut i = 0;
+    while i < line.len() {
+        if is_separator(line[i]) {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < line.len() && !is_separator(line[i]) {
+            i += 1;
+        }
+        tokens.push((&line[start..i], remaining - start));
+    }
+}
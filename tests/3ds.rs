@@ -0,0 +1,203 @@
+#![cfg(feature = "3ds")]
+
+#[path = "shared/three_ds.rs"]
+mod three_ds_helper;
+
+use std::path::Path;
+
+use mesh_loader::{three_ds::from_slice, Loader, ShadingModel};
+use three_ds_helper::{chunk, f32s, main_3ds, u16s};
+
+#[test]
+fn material_groups() {
+    let scene = from_slice(&quad_3ds(1.)).unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+    assert_eq!(scene.materials.len(), 2);
+
+    // Faces in the material group
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name, "Quad");
+    assert_eq!(mesh.faces, vec![[0, 1, 2]]);
+    assert_eq!(
+        mesh.vertices,
+        vec![[0., 0., 0.], [1., 0., 0.], [1., 1., 0.]]
+    );
+    assert_eq!(mesh.texcoords[0], vec![[0., 0.], [1., 0.], [1., 1.]]);
+    let material = &scene.materials[0];
+    assert_eq!(material.name, "Red");
+    assert_eq!(material.color.diffuse, Some([1., 0., 0., 1.]));
+    assert_eq!(material.opacity, Some(0.75));
+    assert_eq!(
+        material.texture.diffuse.as_deref(),
+        Some(Path::new("textures/red.png"))
+    );
+
+    // Faces that do not belong to any material group
+    let mesh = &scene.meshes[1];
+    assert_eq!(
+        mesh.vertices,
+        vec![[0., 0., 0.], [1., 1., 0.], [0., 1., 0.]]
+    );
+    assert_eq!(scene.materials[1].name, "");
+
+    // A face belongs to the first group that contains it, and groups of
+    // unknown materials use the default material.
+    let bytes = main_3ds(&[
+        red_material(),
+        quad_object(1., &[("Red", &[1]), ("Missing", &[1, 0, 5])]),
+    ]);
+    let scene = from_slice(&bytes).unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+    assert_eq!(
+        scene.meshes[0].vertices,
+        vec![[0., 0., 0.], [1., 1., 0.], [0., 1., 0.]]
+    );
+    assert_eq!(scene.materials[0].name, "Red");
+    assert_eq!(
+        scene.meshes[1].vertices,
+        vec![[0., 0., 0.], [1., 0., 0.], [1., 1., 0.]]
+    );
+    assert_eq!(scene.materials[1].name, "");
+}
+
+#[test]
+fn mirrored() {
+    // The determinant of the local coordinate system is negative, so the
+    // winding order of faces is flipped.
+    let scene = from_slice(&quad_3ds(-1.)).unwrap();
+    assert_eq!(
+        scene.meshes[0].vertices,
+        vec![[0., 0., 0.], [1., 1., 0.], [1., 0., 0.]]
+    );
+    assert_eq!(
+        scene.meshes[0].texcoords[0],
+        vec![[0., 0.], [1., 1.], [1., 0.]]
+    );
+}
+
+#[test]
+fn materials() {
+    let material = chunk(
+        0xAFFF,
+        &[
+            chunk(0xA000, &[b"Metal\0".to_vec()]),
+            // The first color chunk is used.
+            chunk(
+                0xA020,
+                &[
+                    chunk(0x0010, &[f32s(&[0.5, 0.25, 1.])]),
+                    chunk(0x0011, &[vec![255, 0, 0]]),
+                ],
+            ),
+            chunk(0xA030, &[chunk(0x0012, &[vec![0, 255, 0]])]),
+            chunk(0xA040, &[chunk(0x0031, &[f32s(&[0.5])])]),
+            chunk(0xA100, &[u16s(&[3])]),
+            chunk(0xA204, &[chunk(0xA300, &[b"maps\\metal.png\0".to_vec()])]),
+            // An empty map name is ignored.
+            chunk(0xA230, &[chunk(0xA300, &[b"\0".to_vec()])]),
+        ],
+    );
+    let bytes = main_3ds(&[material, quad_object(1., &[("Metal", &[0, 1])])]);
+    // Texture paths are relative to the directory of the file.
+    let scene = Loader::default()
+        .load_from_slice(&bytes, "models/a.3ds")
+        .unwrap();
+    assert_eq!(scene.meshes.len(), 1);
+    let material = &scene.materials[0];
+    assert_eq!(material.name, "Metal");
+    assert_eq!(material.color.diffuse, Some([0.5, 0.25, 1., 1.]));
+    assert_eq!(material.color.specular, Some([0., 1., 0., 1.]));
+    assert_eq!(material.shininess, Some(50.));
+    assert!(matches!(material.shading_model, Some(ShadingModel::Phong)));
+    assert_eq!(
+        material.texture.specular.as_deref(),
+        Some(Path::new("models/maps/metal.png"))
+    );
+    assert_eq!(material.texture.height, None);
+}
+
+#[test]
+fn error() {
+    let mut bytes = quad_3ds(1.);
+    bytes.truncate(bytes.len() - 1);
+    assert_eq!(
+        from_slice(&bytes).unwrap_err().to_string(),
+        "invalid chunk length at offset 0"
+    );
+    let bytes = main_3ds(&[chunk(
+        0x4000,
+        &[
+            b"Broken\0".to_vec(),
+            chunk(
+                0x4100,
+                &[
+                    chunk(0x4110, &[u16s(&[1]), f32s(&[0., 0., 0.])]),
+                    chunk(0x4120, &[u16s(&[1, 0, 0, 1, 0])]),
+                ],
+            ),
+        ],
+    )]);
+    assert_eq!(
+        from_slice(&bytes).unwrap_err().to_string(),
+        "face index out of bounds (1) in object \"Broken\""
+    );
+    assert_eq!(
+        from_slice(&chunk(0x3D3D, &[])).unwrap_err().to_string(),
+        "expected 3DS main chunk"
+    );
+}
+
+fn quad_3ds(x_axis: f32) -> Vec<u8> {
+    main_3ds(&[red_material(), quad_object(x_axis, &[("Red", &[0])])])
+}
+
+fn red_material() -> Vec<u8> {
+    chunk(
+        0xAFFF,
+        &[
+            chunk(0xA000, &[b"Red\0".to_vec()]),
+            chunk(0xA020, &[chunk(0x0011, &[vec![255, 0, 0]])]),
+            chunk(0xA050, &[chunk(0x0030, &[u16s(&[25])])]),
+            chunk(0xA200, &[chunk(0xA300, &[b"textures\\red.png\0".to_vec()])]),
+        ],
+    )
+}
+
+/// Creates an object of a unit square on the xy-plane, whose local x-axis is
+/// `(x_axis, 0, 0)`.
+#[allow(clippy::cast_possible_truncation)]
+fn quad_object(x_axis: f32, groups: &[(&str, &[u16])]) -> Vec<u8> {
+    let mut face_list = vec![u16s(&[2, 0, 1, 2, 0, 0, 2, 3, 0])];
+    for (name, faces) in groups {
+        face_list.push(chunk(
+            0x4130,
+            &[
+                format!("{name}\0").into_bytes(),
+                u16s(&[faces.len() as u16]),
+                u16s(faces),
+            ],
+        ));
+    }
+    let trimesh = chunk(
+        0x4100,
+        &[
+            chunk(
+                0x4110,
+                &[
+                    u16s(&[4]),
+                    f32s(&[0., 0., 0., 1., 0., 0., 1., 1., 0., 0., 1., 0.]),
+                ],
+            ),
+            chunk(0x4120, &face_list),
+            chunk(
+                0x4140,
+                &[u16s(&[4]), f32s(&[0., 0., 1., 0., 1., 1., 0., 1.])],
+            ),
+            chunk(
+                0x4160,
+                &[f32s(&[x_axis, 0., 0., 0., 1., 0., 0., 0., 1., 0., 0., 0.])],
+            ),
+        ],
+    );
+    chunk(0x4000, &[b"Quad\0".to_vec(), trimesh])
+}
@@ -0,0 +1,213 @@
+#![cfg(feature = "amf")]
+
+#[path = "shared/zip.rs"]
+mod zip_helper;
+
+use mesh_loader::{amf::from_slice, Loader};
+use zip_helper::stored_zip;
+
+const TRIANGLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<amf unit="millimeter">
+  <material id="1">
+    <metadata type="name">red</metadata>
+    <color><r>1</r><g>0</g><b>0</b></color>
+  </material>
+  <object id="0">
+    <metadata type="name">triangle</metadata>
+    <mesh>
+      <vertices>
+        <vertex><coordinates><x>0</x><y>0</y><z>0</z></coordinates></vertex>
+        <vertex><coordinates><x>1000</x><y>0</y><z>0</z></coordinates></vertex>
+        <vertex><coordinates><x>0</x><y>1000</y><z>0</z></coordinates></vertex>
+      </vertices>
+      <volume materialid="1">
+        <triangle><v1>0</v1><v2>1</v2><v3>2</v3></triangle>
+      </volume>
+    </mesh>
+  </object>
+</amf>
+"#;
+
+#[test]
+fn triangle() {
+    let scene = from_slice(TRIANGLE.as_bytes()).unwrap();
+    assert_eq!(scene.meshes.len(), 1);
+    assert_eq!(scene.materials.len(), 1);
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name, "triangle");
+    assert_eq!(mesh.faces, vec![[0, 1, 2]]);
+    assert_eq!(
+        mesh.vertices,
+        vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]
+    );
+    assert!(mesh.colors[0].is_empty());
+    let material = &scene.materials[0];
+    assert_eq!(material.name, "red");
+    assert_eq!(material.color.diffuse, Some([1., 0., 0., 1.]));
+}
+
+#[test]
+fn constellation() {
+    let s = TRIANGLE.replace(
+        "</amf>",
+        r#"<constellation id="2">
+    <instance objectid="0"><deltax>1000</deltax></instance>
+    <instance objectid="0"><rz>90</rz></instance>
+  </constellation>
+</amf>"#,
+    );
+    let scene = from_slice(s.as_bytes()).unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+    assert_eq!(
+        scene.meshes[0].vertices,
+        vec![[1., 0., 0.], [2., 0., 0.], [1., 1., 0.]]
+    );
+    let v = scene.meshes[1].vertices[1];
+    assert!(v[0].abs() < 1e-6 && (v[1] - 1.).abs() < 1e-6);
+}
+
+#[test]
+fn constellation_cycle() {
+    // Constellation 2 and 3 refer to each other, and 4 is the root.
+    let s = TRIANGLE.replace(
+        "</amf>",
+        r#"<constellation id="2">
+    <instance objectid="0"><deltax>1000</deltax></instance>
+    <instance objectid="3"><deltay>1000</deltay></instance>
+  </constellation>
+  <constellation id="3">
+    <instance objectid="0"></instance>
+    <instance objectid="2"><deltaz>1000</deltaz></instance>
+  </constellation>
+  <constellation id="4">
+    <instance objectid="2"></instance>
+  </constellation>
+</amf>"#,
+    );
+    let scene = from_slice(s.as_bytes()).unwrap();
+    // The reference back to constellation 2 is skipped.
+    assert_eq!(scene.meshes.len(), 2);
+    assert_eq!(
+        scene.meshes[0].vertices,
+        vec![[1., 0., 0.], [2., 0., 0.], [1., 1., 0.]]
+    );
+    assert_eq!(
+        scene.meshes[1].vertices,
+        vec![[0., 1., 0.], [1., 1., 0.], [0., 2., 0.]]
+    );
+}
+
+#[test]
+fn units() {
+    for (unit, scale) in [
+        ("meter", 1.),
+        ("inch", 0.0254),
+        ("feet", 0.3048),
+        ("micron", 0.000_001),
+    ] {
+        let s = TRIANGLE.replace("millimeter", unit);
+        let scene = from_slice(s.as_bytes()).unwrap();
+        assert_eq!(scene.meshes[0].vertices[1], [1000. * scale, 0., 0.]);
+    }
+    // The default unit is millimeter.
+    let s = TRIANGLE.replace(r#" unit="millimeter""#, "");
+    let scene = from_slice(s.as_bytes()).unwrap();
+    assert_eq!(scene.meshes[0].vertices[1], [1., 0., 0.]);
+
+    let s = TRIANGLE.replace("millimeter", "furlong");
+    let e = from_slice(s.as_bytes()).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "unknown unit \"furlong\" in <amf> element at 2:12"
+    );
+}
+
+#[test]
+fn colors() {
+    let s = r#"<amf unit="meter">
+  <material id="1">
+    <color><r>0</r><g>0</g><b>1</b></color>
+  </material>
+  <object id="0">
+    <color><r>0</r><g>1</g><b>0</b></color>
+    <mesh>
+      <vertices>
+        <vertex>
+          <coordinates><x>0</x><y>0</y><z>0</z></coordinates>
+          <color><r>1</r><g>1</g><b>1</b><a>0.5</a></color>
+        </vertex>
+        <vertex><coordinates><x>1</x><y>0</y><z>0</z></coordinates></vertex>
+        <vertex><coordinates><x>0</x><y>1</y><z>0</z></coordinates></vertex>
+        <vertex><coordinates><x>1</x><y>1</y><z>0</z></coordinates></vertex>
+      </vertices>
+      <volume materialid="1">
+        <triangle><v1>0</v1><v2>1</v2><v3>2</v3></triangle>
+        <triangle>
+          <v1>1</v1><v2>3</v2><v3>2</v3>
+          <color><r>1</r><g>0</g><b>0</b></color>
+        </triangle>
+      </volume>
+      <volume materialid="1">
+        <color><r>1</r><g>1</g><b>0</b></color>
+        <triangle><v1>0</v1><v2>1</v2><v3>2</v3></triangle>
+      </volume>
+    </mesh>
+  </object>
+  <object id="1">
+    <mesh>
+      <vertices>
+        <vertex><coordinates><x>0</x><y>0</y><z>0</z></coordinates></vertex>
+        <vertex><coordinates><x>1</x><y>0</y><z>0</z></coordinates></vertex>
+        <vertex><coordinates><x>0</x><y>1</y><z>0</z></coordinates></vertex>
+      </vertices>
+      <volume materialid="1">
+        <triangle><v1>0</v1><v2>1</v2><v3>2</v3></triangle>
+      </volume>
+    </mesh>
+  </object>
+</amf>
+"#;
+    let scene = from_slice(s.as_bytes()).unwrap();
+    assert_eq!(scene.meshes.len(), 3);
+    let white = [1., 1., 1., 0.5];
+    let red = [1., 0., 0., 1.];
+    let green = [0., 1., 0., 1.];
+    let yellow = [1., 1., 0., 1.];
+    // Triangle colors take precedence over vertex colors, which take
+    // precedence over the color of the volume, the object, and the material.
+    assert_eq!(
+        scene.meshes[0].colors[0],
+        vec![white, green, green, red, red, red]
+    );
+    assert_eq!(scene.materials[0].color.diffuse, Some(green));
+    assert_eq!(scene.meshes[1].colors[0], vec![white, yellow, yellow]);
+    assert_eq!(scene.materials[1].color.diffuse, Some(yellow));
+    // Without vertex or triangle colors, only the material has the color.
+    assert!(scene.meshes[2].colors[0].is_empty());
+    assert_eq!(scene.materials[2].color.diffuse, Some([0., 0., 1., 1.]));
+}
+
+#[test]
+fn zip() {
+    let scene = from_slice(&stored_zip(&[("triangle.amf", TRIANGLE.as_bytes())])).unwrap();
+    assert_eq!(scene.meshes.len(), 1);
+    assert_eq!(scene.meshes[0].faces.len(), 1);
+}
+
+#[test]
+fn error() {
+    let s = TRIANGLE.replace("<v3>2</v3>", "<v3>3</v3>");
+    let e = from_slice(s.as_bytes()).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "vertex index out of bounds (3) in <v3> element at 16:43"
+    );
+    // The loader adds the path of the file.
+    let e = Loader::default()
+        .load_from_slice(s.as_bytes(), "a.amf")
+        .unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "vertex index out of bounds (3) in <v3> element at 16:43 (a.amf)"
+    );
+}
@@ -0,0 +1,165 @@
+#![cfg(all(
+    feature = "archive",
+    feature = "collada",
+    feature = "obj",
+    feature = "stl"
+))]
+
+#[path = "shared/zip.rs"]
+mod zip_helper;
+
+use std::path::Path;
+
+use mesh_loader::{resolver::MemoryStore, Loader};
+use zip_helper::stored_zip;
+
+const STL: &str = "solid a
+facet normal 0 0 1
+outer loop
+vertex 0 0 0
+vertex 1 0 0
+vertex 0 1 0
+endloop
+endfacet
+endsolid a
+";
+
+const DAE: &str = r##"<?xml version="1.0" encoding="utf-8"?>
+<COLLADA xmlns="http://www.collada.org/2005/11/COLLADASchema" version="1.4.1">
+  <library_geometries>
+    <geometry id="tri-mesh" name="tri">
+      <mesh>
+        <source id="tri-positions">
+          <float_array id="tri-positions-array" count="9">0 0 0 1 0 0 0 1 0</float_array>
+          <technique_common>
+            <accessor source="#tri-positions-array" count="3" stride="3">
+              <param name="X" type="float"/>
+              <param name="Y" type="float"/>
+              <param name="Z" type="float"/>
+            </accessor>
+          </technique_common>
+        </source>
+        <vertices id="tri-vertices">
+          <input semantic="POSITION" source="#tri-positions"/>
+        </vertices>
+        <triangles count="1">
+          <input semantic="VERTEX" source="#tri-vertices" offset="0"/>
+          <p>0 1 2</p>
+        </triangles>
+      </mesh>
+    </geometry>
+  </library_geometries>
+  <library_visual_scenes>
+    <visual_scene id="Scene" name="Scene">
+      <node id="tri" name="tri" type="NODE">
+        <instance_geometry url="#tri-mesh" name="tri"/>
+      </node>
+    </visual_scene>
+  </library_visual_scenes>
+  <scene>
+    <instance_visual_scene url="#Scene"/>
+  </scene>
+</COLLADA>
+"##;
+
+#[test]
+fn gzip() {
+    let gz = stored_gzip(STL.as_bytes());
+    let loader = Loader::default();
+    let scene = loader.load_from_slice(&gz, "link.stl.gz").unwrap();
+    assert_eq!(scene.meshes[0].faces, [[0, 1, 2]]);
+    // The format of the decompressed file is detected from the contents.
+    let scene = loader.load_from_slice(&gz, "0123456789abcdef").unwrap();
+    assert_eq!(scene.meshes[0].faces, [[0, 1, 2]]);
+
+    let mut store = MemoryStore::new();
+    store.insert("meshes/link.stl.gz", &gz);
+    let scene = Loader::default()
+        .resolver(store)
+        .load("meshes/link.stl.gz")
+        .unwrap();
+    assert_eq!(scene.meshes[0].faces, [[0, 1, 2]]);
+
+    let mut broken = gz.clone();
+    broken.truncate(gz.len() - 10);
+    let e = loader.load_from_slice(&broken, "link.stl.gz").unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn zip() {
+    let zip = stored_zip(&[
+        ("b/c/d.stl", STL.as_bytes()),
+        (
+            "models/a.obj",
+            b"mtllib ../materials/a.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl a\nf 1 2 3\n",
+        ),
+        (
+            "materials/a.mtl",
+            b"newmtl a\nKd 1 0 0\nmap_Kd ../textures/a.png\n",
+        ),
+        ("textures/a.png", b"png"),
+    ]);
+    let scene = Loader::default()
+        .load_from_slice(&zip, "assets/model.zip")
+        .unwrap();
+    // The model file at the shallowest level is loaded, and the referenced
+    // files are read from the archive.
+    assert_eq!(scene.meshes.len(), 1);
+    assert_eq!(scene.materials[0].color.diffuse, Some([1., 0., 0., 1.]));
+    assert_eq!(
+        scene.materials[0].texture.diffuse.as_deref(),
+        Some(Path::new("assets/model.zip/textures/a.png"))
+    );
+
+    let e = Loader::default()
+        .load_from_slice(&stored_zip(&[("a.png", b"png")]), "a.zip")
+        .unwrap_err();
+    assert_eq!(e.to_string(), "no model file found in zip archive");
+
+    // The compressed size in the central directory is larger than the archive.
+    let mut zip = stored_zip(&[("a.stl", STL.as_bytes())]);
+    let cd = zip.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+    zip[cd + 20..cd + 24].copy_from_slice(&u32::MAX.to_le_bytes());
+    let e = Loader::default()
+        .load_from_slice(&zip, "a.zip")
+        .unwrap_err();
+    assert_eq!(e.to_string(), "zip: unexpected end of file");
+}
+
+#[test]
+fn zae() {
+    let manifest = b"<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<dae_root>./scenes/my%20scene.dae</dae_root>\n";
+    let zae = stored_zip(&[
+        ("broken.dae", b"<COLLADA>"),
+        ("manifest.xml", manifest),
+        ("scenes/my scene.dae", DAE.as_bytes()),
+    ]);
+    let scene = Loader::default().load_from_slice(&zae, "a.zae").unwrap();
+    assert_eq!(scene.meshes.len(), 1);
+    assert_eq!(scene.meshes[0].name, "tri-mesh");
+    assert_eq!(scene.meshes[0].faces, [[0, 1, 2]]);
+
+    let zae = stored_zip(&[("manifest.xml", b"<dae_root>a.dae</dae_root>")]);
+    let e = Loader::default()
+        .load_from_slice(&zae, "a.zae")
+        .unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "root document \"a.dae\" specified in manifest.xml not found in zip archive"
+    );
+}
+
+/// Creates a gzip file containing the given data in a stored (uncompressed)
+/// deflate block.
+#[allow(clippy::cast_possible_truncation)]
+fn stored_gzip(data: &[u8]) -> Vec<u8> {
+    let mut bytes = b"\x1f\x8b\x08\0\0\0\0\0\0\xff".to_vec();
+    bytes.push(1); // BFINAL = 1, BTYPE = 00
+    bytes.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(&(!(data.len() as u16)).to_le_bytes());
+    bytes.extend_from_slice(data);
+    bytes.extend_from_slice(&0_u32.to_le_bytes()); // CRC-32 (not checked)
+    bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    bytes
+}
@@ -286,3 +286,134 @@ fn issue62() {
     assert_eq!(scene.materials[0].name, "Material");
     assert_eq!(scene.materials[1].name, "Material_002");
 }
+
+const TRIANGLE: &str = r##"<?xml version="1.0" encoding="utf-8"?>
+<COLLADA xmlns="http://www.collada.org/2005/11/COLLADASchema" version="1.4.1">
+  <library_geometries>
+    <geometry id="mesh">
+      <mesh>
+        <source id="positions">
+          <float_array id="positions-array" count="9">0 0 0
+            1 0 0
+            0 1 0</float_array>
+          <technique_common>
+            <accessor source="#positions-array" count="3" stride="3">
+              <param name="X" type="float"/>
+              <param name="Y" type="float"/>
+              <param name="Z" type="float"/>
+            </accessor>
+          </technique_common>
+        </source>
+        <vertices id="vertices">
+          <input semantic="POSITION" source="#positions"/>
+        </vertices>
+        <polylist count="1">
+          <input semantic="VERTEX" source="#vertices" offset="0"/>
+          <vcount>3</vcount>
+          <p>0 1 2</p>
+        </polylist>
+      </mesh>
+    </geometry>
+  </library_geometries>
+  <library_visual_scenes>
+    <visual_scene id="scene">
+      <node id="node">
+        <instance_geometry url="#mesh"/>
+      </node>
+    </visual_scene>
+  </library_visual_scenes>
+  <scene>
+    <instance_visual_scene url="#scene"/>
+  </scene>
+</COLLADA>
+"##;
+
+// The contents of arrays are parsed while the document is read, and the
+// locations in errors point to the original text.
+#[test]
+fn arrays() {
+    let expected = [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]];
+    let scene = from_str(TRIANGLE).unwrap();
+    assert_eq!(scene.meshes[0].vertices, expected);
+    assert_eq!(scene.meshes[0].faces, [[0, 1, 2]]);
+    // Contents with markup.
+    let dae = TRIANGLE
+        .replace("0 1 0<", "0 1 0<!-- x --><")
+        .replace(">0 1 2", ">0 1 <![CDATA[2]]>");
+    let scene = from_str(&dae).unwrap();
+    assert_eq!(scene.meshes[0].vertices, expected);
+    assert_eq!(scene.meshes[0].faces, [[0, 1, 2]]);
+    // Contents with entity references.
+    let scene = from_str(&TRIANGLE.replace("0 1 0<", "0 &#49; 0<")).unwrap();
+    assert_eq!(scene.meshes[0].vertices, expected);
+
+    let location = |dae: &str, pos: usize| {
+        let line = dae[..pos].matches('\n').count() + 1;
+        let column = pos - dae[..pos].rfind('\n').map_or(0, |n| n + 1) + 1;
+        format!("{line}:{column}")
+    };
+    // Also test single-line documents, in which other nodes follow the arrays
+    // on the same line.
+    for dae in [TRIANGLE.to_owned(), TRIANGLE.replace('\n', " ")] {
+        let e = from_str(&dae.replacen("1 0 0", "1 x 0", 1)).unwrap_err();
+        let pos = dae.find("0 0 0").unwrap();
+        assert_eq!(
+            e.to_string(),
+            format!(
+                "error while parsing a float in <float_array> element ({})",
+                location(&dae, pos)
+            )
+        );
+        let e = from_str(&dae.replace("0 1 2<", "0 1 x<")).unwrap_err();
+        let pos = dae.find("0 1 2<").unwrap();
+        assert_eq!(
+            e.to_string(),
+            format!(
+                "error while parsing an integer in <p> element ({})",
+                location(&dae, pos)
+            )
+        );
+        let e = from_str(&dae.replace("<vcount>3", "<vcount>0")).unwrap_err();
+        let pos = dae.find("<vcount>").unwrap();
+        assert_eq!(
+            e.to_string(),
+            format!(
+                "incorrect number of indices in <p> element ({})",
+                location(&dae, pos)
+            )
+        );
+        let e = from_str(&dae.replace(" count=\"3\" stride", " stride")).unwrap_err();
+        let pos = dae.find("<accessor").unwrap();
+        assert_eq!(
+            e.to_string(),
+            format!(
+                "expected count attribute in <accessor> element at {}",
+                location(&dae, pos)
+            )
+        );
+    }
+}
+
+#[test]
+fn xml() {
+    let scene = from_str(TRIANGLE).unwrap();
+    let expected = &scene.meshes[0].vertices;
+    // Document type declarations, references and quotes in attributes, and
+    // prefixed names.
+    let dae = TRIANGLE
+        .replacen('\n', "\n<!DOCTYPE COLLADA [<!ENTITY x \"<x>\">]>\n", 1)
+        .replace("url=\"#mesh\"", "url='&#35;mesh'")
+        .replace("<technique_common>", "<dae:technique_common>")
+        .replace("</technique_common>", "</dae:technique_common>");
+    let scene = from_str(&dae).unwrap();
+    assert_eq!(&scene.meshes[0].vertices, expected);
+
+    let e = from_str(&TRIANGLE.replace("</mesh>", "</mash>")).unwrap_err();
+    assert_eq!(e.to_string(), "expected </mesh> but found </mash> at 26:7");
+    let e = from_str(&TRIANGLE.replace("</COLLADA>", "")).unwrap_err();
+    assert_eq!(e.to_string(), "<COLLADA> element at 2:1 is not closed");
+    let e = from_str(&TRIANGLE.replace("0 1 0<", "0 &one; 0<")).unwrap_err();
+    assert_eq!(e.to_string(), "invalid reference at 9:15");
+    let e = from_str(&TRIANGLE.replace("count=\"1\"", "count=\"1\"count=\"1\"")).unwrap_err();
+    assert_eq!(e.to_string(), "expected whitespace at 21:28");
+}
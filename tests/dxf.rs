@@ -0,0 +1,403 @@
+#![cfg(feature = "dxf")]
+
+use std::fmt::Write as _;
+
+use mesh_loader::{dxf::from_slice, Loader};
+
+const FACES_DXF: &str = "999
+Two faces on the layer \"Red\"
+  0
+SECTION
+  2
+HEADER
+  9
+$ACADVER
+  1
+AC1015
+  9
+$INSUNITS
+ 70
+4
+  0
+ENDSEC
+  0
+SECTION
+  2
+TABLES
+  0
+TABLE
+  2
+LAYER
+  0
+LAYER
+  2
+Red
+ 70
+0
+ 62
+1
+  0
+ENDTAB
+  0
+ENDSEC
+  0
+SECTION
+  2
+ENTITIES
+  0
+3DFACE
+  8
+Red
+ 10
+0.0
+ 20
+0.0
+ 30
+0.0
+ 11
+1000.0
+ 21
+0.0
+ 31
+0.0
+ 12
+1000.0
+ 22
+1000.0
+ 32
+0.0
+ 13
+1000.0
+ 23
+1000.0
+ 33
+0.0
+  0
+3DFACE
+  8
+Red
+ 62
+3
+ 10
+0.0
+ 20
+0.0
+ 30
+1000.0
+ 11
+1000.0
+ 21
+0.0
+ 31
+1000.0
+ 12
+1000.0
+ 22
+1000.0
+ 32
+1000.0
+ 13
+0.0
+ 23
+1000.0
+ 33
+1000.0
+  0
+ENDSEC
+  0
+EOF
+";
+
+#[test]
+fn faces() {
+    let scene = from_slice(FACES_DXF.as_bytes()).unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+    assert_eq!(scene.materials.len(), 2);
+
+    // The triangle uses the color of the layer (BYLAYER), and millimeters are
+    // converted to meters.
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name, "Red");
+    assert_eq!(mesh.faces, vec![[0, 1, 2]]);
+    assert_eq!(
+        mesh.vertices,
+        vec![[0., 0., 0.], [1., 0., 0.], [1., 1., 0.]]
+    );
+    assert_eq!(scene.materials[0].name, "Red");
+    assert_eq!(scene.materials[0].color.diffuse, Some([1., 0., 0., 1.]));
+
+    // The quad has its own color.
+    let mesh = &scene.meshes[1];
+    assert_eq!(mesh.name, "Red");
+    assert_eq!(mesh.faces, vec![[0, 1, 2], [3, 4, 5]]);
+    assert_eq!(mesh.vertices[5], [0., 1., 1.]);
+    assert_eq!(scene.materials[1].color.diffuse, Some([0., 1., 0., 1.]));
+}
+
+fn dxf(entities: &[(i32, &str)]) -> String {
+    let mut s = String::from("  0\nSECTION\n  2\nENTITIES\n");
+    for (code, value) in entities {
+        writeln!(s, "{code:>3}\n{value}").unwrap();
+    }
+    s + "  0\nENDSEC\n  0\nEOF\n"
+}
+
+#[test]
+fn polyface() {
+    let mut groups = vec![(0, "POLYLINE"), (8, "Mesh"), (66, "1"), (70, "64")];
+    for (x, y) in [("0", "0"), ("1", "0"), ("1", "1"), ("0", "1")] {
+        groups.extend([(0, "VERTEX"), (10, x), (20, y), (30, "0"), (70, "192")]);
+    }
+    groups.extend([
+        (0, "VERTEX"),
+        (70, "128"),
+        (71, "1"),
+        (72, "2"),
+        (73, "-3"),
+        (74, "4"),
+        (0, "SEQEND"),
+    ]);
+    let scene = from_slice(dxf(&groups).as_bytes()).unwrap();
+    assert_eq!(scene.meshes.len(), 1);
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name, "Mesh");
+    assert_eq!(
+        mesh.vertices,
+        vec![
+            [0., 0., 0.],
+            [1., 0., 0.],
+            [1., 1., 0.],
+            [0., 0., 0.],
+            [1., 1., 0.],
+            [0., 1., 0.],
+        ]
+    );
+    // No layer table: the default color is used.
+    assert_eq!(scene.materials[0].color.diffuse, Some([0.6, 0.6, 0.6, 1.]));
+}
+
+#[test]
+fn polygon_mesh() {
+    // 2 x 3 vertices, closed in the N direction.
+    let mut groups = vec![(0, "POLYLINE"), (70, "48"), (71, "2"), (72, "3")];
+    for z in ["0", "1"] {
+        for (x, y) in [("0", "0"), ("1", "0"), ("0", "1")] {
+            groups.extend([(0, "VERTEX"), (10, x), (20, y), (30, z), (70, "64")]);
+        }
+    }
+    groups.push((0, "SEQEND"));
+    let scene = from_slice(dxf(&groups).as_bytes()).unwrap();
+    assert_eq!(scene.meshes[0].faces.len(), 6);
+}
+
+#[test]
+fn mesh() {
+    let scene = from_slice(
+        dxf(&[
+            (0, "MESH"),
+            (8, "0"),
+            (420, "255"),
+            (100, "AcDbSubDMesh"),
+            (71, "2"),
+            (72, "0"),
+            (91, "0"),
+            (92, "4"),
+            (10, "0"),
+            (20, "0"),
+            (30, "0"),
+            (10, "1"),
+            (20, "0"),
+            (30, "0"),
+            (10, "1"),
+            (20, "1"),
+            (30, "0"),
+            (10, "0"),
+            (20, "1"),
+            (30, "0"),
+            (93, "5"),
+            (90, "4"),
+            (90, "0"),
+            (90, "1"),
+            (90, "2"),
+            (90, "3"),
+            (94, "0"),
+            (95, "0"),
+            (90, "0"),
+        ])
+        .as_bytes(),
+    )
+    .unwrap();
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name, "0");
+    assert_eq!(mesh.faces.len(), 2);
+    assert_eq!(mesh.vertices[5], [0., 1., 0.]);
+    assert_eq!(scene.materials[0].color.diffuse, Some([0., 0., 1., 1.]));
+}
+
+#[test]
+fn insert() {
+    let s = "  0
+SECTION
+  2
+BLOCKS
+  0
+BLOCK
+  8
+0
+  2
+Fixture
+ 10
+1.0
+ 20
+0.0
+ 30
+0.0
+  0
+3DFACE
+  8
+0
+ 62
+0
+ 10
+1.0
+ 20
+0.0
+ 30
+0.0
+ 11
+2.0
+ 21
+0.0
+ 31
+0.0
+ 12
+1.0
+ 22
+1.0
+ 32
+0.0
+ 13
+1.0
+ 23
+1.0
+ 33
+0.0
+  0
+ENDBLK
+  0
+ENDSEC
+  0
+SECTION
+  2
+ENTITIES
+  0
+INSERT
+  8
+Fixtures
+ 62
+5
+  2
+Fixture
+ 10
+10.0
+ 20
+0.0
+ 30
+0.0
+ 41
+2.0
+ 42
+2.0
+ 43
+2.0
+ 50
+90.0
+ 70
+2
+ 44
+5.0
+  0
+ENDSEC
+  0
+EOF
+";
+    let scene = from_slice(s.as_bytes()).unwrap();
+    assert_eq!(scene.meshes.len(), 1);
+    // The block is placed twice (2 columns), and its entities inherit the
+    // layer and the color of the INSERT.
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name, "Fixtures");
+    assert_eq!(mesh.faces.len(), 2);
+    assert_eq!(scene.materials[0].color.diffuse, Some([0., 0., 1., 1.]));
+    // (2, 0, 0) -> translated by -base -> scaled -> rotated 90 degrees ->
+    // translated to the insertion point.
+    let expected = [[10., 0., 0.], [10., 2., 0.], [8., 0., 0.]];
+    for (v, e) in mesh.vertices[..3].iter().zip(expected) {
+        assert!(v.iter().zip(e).all(|(v, e)| (v - e).abs() < 1e-5), "{v:?}");
+    }
+    // The second column is placed 5 units away along the rotated x-axis.
+    let [x, y, _] = mesh.vertices[3];
+    assert!((x - 10.).abs() < 1e-5 && (y - 5.).abs() < 1e-5, "{x} {y}");
+}
+
+#[test]
+fn insert_limits() {
+    fn dxf_with_block(block: &[(i32, &str)], entities: &[(i32, &str)]) -> String {
+        let mut s = String::from("  0\nSECTION\n  2\nBLOCKS\n  0\nBLOCK\n  2\nA\n");
+        for (code, value) in block {
+            writeln!(s, "{code:>3}\n{value}").unwrap();
+        }
+        s += "  0\nENDBLK\n  0\nENDSEC\n";
+        s + &dxf(entities)
+    }
+    let face = [(0, "3DFACE"), (11, "1.0"), (22, "1.0")];
+
+    // Large arrays are rejected before they are expanded.
+    let s = dxf_with_block(
+        &face,
+        &[(0, "INSERT"), (2, "A"), (70, "100000"), (71, "100000")],
+    );
+    let e = from_slice(s.as_bytes()).unwrap_err();
+    assert_eq!(e.to_string(), "too many block instances (24:1)");
+    // Self-referencing blocks stop at the depth limit, before the number of
+    // instances grows exponentially.
+    let mut block = face.to_vec();
+    block.extend([(0, "INSERT"), (2, "A"), (70, "4")]);
+    let s = dxf_with_block(&block, &[(0, "INSERT"), (2, "A")]);
+    let e = from_slice(s.as_bytes()).unwrap_err();
+    assert_eq!(e.to_string(), "too deeply nested block references (16:1)");
+}
+
+#[test]
+fn error() {
+    let e = from_slice(dxf(&[(0, "3DFACE"), (10, "abc")]).as_bytes()).unwrap_err();
+    assert_eq!(e.to_string(), "error while parsing a float (8:1)");
+    let e = from_slice(b"  0\nSECTION\n  x\n").unwrap_err();
+    assert_eq!(e.to_string(), "error while parsing an integer (3:1)");
+    let e = from_slice(
+        dxf(&[
+            (0, "POLYLINE"),
+            (70, "64"),
+            (0, "VERTEX"),
+            (70, "192"),
+            (0, "VERTEX"),
+            (70, "128"),
+            (71, "1"),
+            (72, "1"),
+            (73, "2"),
+        ])
+        .as_bytes(),
+    )
+    .unwrap_err();
+    assert_eq!(e.to_string(), "vertex index out of bounds (2) (22:1)");
+    let e = from_slice(b"AutoCAD Binary DXF\r\n\x1a\0").unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::Unsupported);
+}
+
+#[test]
+fn detect() {
+    let scene = Loader::default()
+        .load_from_slice(FACES_DXF.as_bytes(), "model")
+        .unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+}
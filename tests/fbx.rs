@@ -0,0 +1,354 @@
+#![cfg(feature = "fbx")]
+
+#[path = "shared/fbx.rs"]
+mod fbx_helper;
+
+use fbx_helper::{binary_fbx, node, p, Node, Property::*};
+use mesh_loader::{fbx::from_slice, Loader};
+
+#[test]
+fn quad() {
+    let bytes = quad_fbx(7500);
+    let scene = from_slice(&bytes).unwrap();
+    assert_eq!(scene.meshes.len(), 1);
+    assert_eq!(scene.materials.len(), 1);
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name, "Quad");
+    assert_eq!(mesh.faces, vec![[0, 1, 2], [3, 4, 5]]);
+    // UnitScaleFactor is 100 (1 unit = 1 meter), and the parent model is
+    // translated by (0, 0, 1).
+    assert_eq!(
+        mesh.vertices,
+        vec![
+            [0., 0., 1.],
+            [1., 0., 1.],
+            [1., 1., 1.],
+            [0., 0., 1.],
+            [1., 1., 1.],
+            [0., 1., 1.],
+        ]
+    );
+    assert_eq!(mesh.normals, vec![[0., 0., 1.]; 6]);
+    assert_eq!(
+        mesh.texcoords[0],
+        vec![[0., 0.], [1., 0.], [1., 1.], [0., 0.], [1., 1.], [0., 1.]]
+    );
+    let material = &scene.materials[0];
+    assert_eq!(material.name, "Red");
+    assert_eq!(material.color.diffuse, Some([1., 0., 0., 1.]));
+
+    // FBX 7.4 and earlier use 32-bit offsets.
+    let scene = from_slice(&quad_fbx(7400)).unwrap();
+    assert_eq!(scene.meshes[0].faces.len(), 2);
+}
+
+#[test]
+fn transform_chain() {
+    let bytes = binary_fbx(
+        7500,
+        &[
+            global_settings(),
+            node(
+                "Objects",
+                vec![],
+                vec![
+                    quad_geometry(1, vec![0, 1, !2]),
+                    model(
+                        10,
+                        b"Root\x00\x01Model",
+                        vec![
+                            p(
+                                b"Lcl Translation",
+                                b"Lcl Translation",
+                                vec![F64(10.), F64(0.), F64(0.)],
+                            ),
+                            p(
+                                b"Lcl Scaling",
+                                b"Lcl Scaling",
+                                vec![F64(2.), F64(2.), F64(2.)],
+                            ),
+                        ],
+                    ),
+                    model(
+                        11,
+                        b"Parent\x00\x01Model",
+                        vec![
+                            p(
+                                b"Lcl Rotation",
+                                b"Lcl Rotation",
+                                vec![F64(0.), F64(0.), F64(90.)],
+                            ),
+                            p(
+                                b"GeometricTranslation",
+                                b"Vector3D",
+                                vec![F64(0.), F64(0.), F64(5.)],
+                            ),
+                        ],
+                    ),
+                    model(
+                        12,
+                        b"Child\x00\x01Model",
+                        vec![p(
+                            b"Lcl Translation",
+                            b"Lcl Translation",
+                            vec![F64(1.), F64(0.), F64(0.)],
+                        )],
+                    ),
+                ],
+            ),
+            connections(&[(10, 0), (11, 10), (12, 11), (1, 11), (1, 12)]),
+        ],
+    );
+    let scene = from_slice(&bytes).unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+
+    // Root * Parent * GeometricTransform(Parent): the triangle is moved by
+    // (0, 0, 5), rotated 90 degrees around the z-axis, scaled by 2, and then
+    // moved by (10, 0, 0).
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name, "Parent");
+    assert_vertices(
+        &mesh.vertices,
+        &[[10., 0., 10.], [10., 2., 10.], [8., 2., 10.]],
+    );
+    // Root * Parent * Child: the geometric transform of the parent is not
+    // inherited.
+    let mesh = &scene.meshes[1];
+    assert_eq!(mesh.name, "Child");
+    assert_vertices(
+        &mesh.vertices,
+        &[[10., 2., 0.], [10., 4., 0.], [8., 4., 0.]],
+    );
+    assert_vertices(&mesh.normals, &[[0., 0., 1.]; 3]);
+
+    // Cyclic hierarchy
+    let bytes = binary_fbx(
+        7500,
+        &[
+            node(
+                "Objects",
+                vec![],
+                vec![
+                    quad_geometry(1, vec![0, 1, !2]),
+                    model(2, b"A\x00\x01Model", vec![]),
+                    model(3, b"B\x00\x01Model", vec![]),
+                ],
+            ),
+            connections(&[(2, 3), (3, 2), (1, 3)]),
+        ],
+    );
+    let e = from_slice(&bytes).unwrap_err();
+    assert_eq!(e.to_string(), "cyclic model hierarchy in model \"B\"");
+}
+
+#[test]
+fn material_slots() {
+    let mut geometry = quad_geometry(1, vec![0, 1, !2, 0, 2, !3]);
+    geometry.children.push(node(
+        "LayerElementMaterial",
+        vec![I32(0)],
+        vec![
+            node("MappingInformationType", vec![String(b"ByPolygon")], vec![]),
+            node(
+                "ReferenceInformationType",
+                vec![String(b"IndexToDirect")],
+                vec![],
+            ),
+            node("Materials", vec![I32Array(vec![1, 0])], vec![]),
+        ],
+    ));
+    let bytes = binary_fbx(
+        7500,
+        &[
+            global_settings(),
+            node(
+                "Objects",
+                vec![],
+                vec![
+                    geometry,
+                    model(2, b"Quad\x00\x01Model", vec![]),
+                    material(3, b"Red\x00\x01Material", [1., 0., 0.]),
+                    material(4, b"Green\x00\x01Material", [0., 1., 0.]),
+                ],
+            ),
+            connections(&[(2, 0), (1, 2), (3, 2), (4, 2)]),
+        ],
+    );
+    let scene = from_slice(&bytes).unwrap();
+    // One mesh per material slot, in the order of the slots.
+    assert_eq!(scene.meshes.len(), 2);
+    assert_eq!(
+        scene.meshes[0].vertices,
+        vec![[0., 0., 0.], [1., 1., 0.], [0., 1., 0.]]
+    );
+    assert_eq!(scene.materials[0].name, "Red");
+    assert_eq!(
+        scene.meshes[1].vertices,
+        vec![[0., 0., 0.], [1., 0., 0.], [1., 1., 0.]]
+    );
+    assert_eq!(scene.materials[1].name, "Green");
+    assert_eq!(scene.materials[1].color.diffuse, Some([0., 1., 0., 1.]));
+}
+
+#[test]
+fn error() {
+    let e = from_slice(b"; FBX 7.4.0 project file\n").unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::Unsupported);
+    let mut bytes = quad_fbx(7500);
+    bytes.truncate(100);
+    assert!(from_slice(&bytes).is_err());
+    // The last polygon is not terminated by a negative index.
+    let bytes = quad_fbx_with_indices(7500, vec![0, 1, 2, 3]);
+    let e = from_slice(&bytes).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "last polygon in PolygonVertexIndex does not end with a negative index"
+    );
+    // The loader adds the path of the file.
+    let e = Loader::default()
+        .load_from_slice(&bytes, "a.fbx")
+        .unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "last polygon in PolygonVertexIndex does not end with a negative index (a.fbx)"
+    );
+}
+
+#[track_caller]
+fn assert_vertices(vertices: &[[f32; 3]], expected: &[[f32; 3]]) {
+    assert_eq!(vertices.len(), expected.len());
+    for (v, e) in vertices.iter().zip(expected) {
+        assert!(v.iter().zip(e).all(|(v, e)| (v - e).abs() < 1e-5), "{v:?}");
+    }
+}
+
+fn quad_fbx(version: u32) -> Vec<u8> {
+    quad_fbx_with_indices(version, vec![0, 1, 2, !3])
+}
+
+fn quad_fbx_with_indices(version: u32, polygon_vertex_index: Vec<i32>) -> Vec<u8> {
+    binary_fbx(
+        version,
+        &[
+            global_settings(),
+            node(
+                "Objects",
+                vec![],
+                vec![
+                    quad_geometry(1, polygon_vertex_index),
+                    model(
+                        2,
+                        b"Root\x00\x01Model",
+                        vec![p(
+                            b"Lcl Translation",
+                            b"Lcl Translation",
+                            vec![F64(0.), F64(0.), F64(1.)],
+                        )],
+                    ),
+                    model(3, b"Quad\x00\x01Model", vec![]),
+                    material(4, b"Red\x00\x01Material", [1., 0., 0.]),
+                ],
+            ),
+            connections(&[(2, 0), (3, 2), (1, 3), (4, 3)]),
+        ],
+    )
+}
+
+/// Creates a `GlobalSettings` node where 1 unit is 1 meter.
+fn global_settings() -> Node {
+    node(
+        "GlobalSettings",
+        vec![],
+        vec![node(
+            "Properties70",
+            vec![],
+            vec![p(b"UnitScaleFactor", b"double", vec![F64(100.)])],
+        )],
+    )
+}
+
+/// Creates a `Geometry` node of a unit square on the xy-plane with normals
+/// and UVs.
+fn quad_geometry(id: i64, polygon_vertex_index: Vec<i32>) -> Node {
+    node(
+        "Geometry",
+        vec![I64(id), String(b"Quad\x00\x01Geometry"), String(b"Mesh")],
+        vec![
+            node(
+                "Vertices",
+                vec![CompressedF64Array(vec![
+                    0., 0., 0., 1., 0., 0., 1., 1., 0., 0., 1., 0.,
+                ])],
+                vec![],
+            ),
+            node(
+                "PolygonVertexIndex",
+                vec![I32Array(polygon_vertex_index)],
+                vec![],
+            ),
+            node(
+                "LayerElementNormal",
+                vec![I32(0)],
+                vec![
+                    node("MappingInformationType", vec![String(b"ByVertice")], vec![]),
+                    node("ReferenceInformationType", vec![String(b"Direct")], vec![]),
+                    node("Normals", vec![F64Array([0., 0., 1.].repeat(4))], vec![]),
+                ],
+            ),
+            node(
+                "LayerElementUV",
+                vec![I32(0)],
+                vec![
+                    node(
+                        "MappingInformationType",
+                        vec![String(b"ByPolygonVertex")],
+                        vec![],
+                    ),
+                    node(
+                        "ReferenceInformationType",
+                        vec![String(b"IndexToDirect")],
+                        vec![],
+                    ),
+                    node(
+                        "UV",
+                        vec![F64Array(vec![0., 0., 1., 0., 1., 1., 0., 1.])],
+                        vec![],
+                    ),
+                    node("UVIndex", vec![I32Array(vec![0, 1, 2, 3])], vec![]),
+                ],
+            ),
+        ],
+    )
+}
+
+fn model(id: i64, name: &'static [u8], properties: Vec<Node>) -> Node {
+    node(
+        "Model",
+        vec![I64(id), String(name), String(b"Mesh")],
+        vec![node("Properties70", vec![], properties)],
+    )
+}
+
+fn material(id: i64, name: &'static [u8], [r, g, b]: [f64; 3]) -> Node {
+    node(
+        "Material",
+        vec![I64(id), String(name), String(b"")],
+        vec![node(
+            "Properties70",
+            vec![],
+            vec![p(b"DiffuseColor", b"Color", vec![F64(r), F64(g), F64(b)])],
+        )],
+    )
+}
+
+/// Creates a `Connections` node with object-object connections from the
+/// child to the parent.
+fn connections(c: &[(i64, i64)]) -> Node {
+    node(
+        "Connections",
+        vec![],
+        c.iter()
+            .map(|&(child, parent)| node("C", vec![String(b"OO"), I64(child), I64(parent)], vec![]))
+            .collect(),
+    )
+}
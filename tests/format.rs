@@ -0,0 +1,131 @@
+use std::path::Path;
+
+use mesh_loader::{detect_format, Format};
+
+fn detect(bytes: &[u8]) -> Option<Format> {
+    detect_format(bytes, None)
+}
+
+/// Creates a binary STL file with the given header and number of triangles.
+fn binary_stl(header: &[u8], num_triangles: u32) -> Vec<u8> {
+    let mut bytes = header.to_vec();
+    bytes.resize(80, b' ');
+    bytes.extend_from_slice(&num_triangles.to_le_bytes());
+    bytes.resize(84 + num_triangles as usize * 50, 0);
+    bytes
+}
+
+#[test]
+fn extension() {
+    let detect = |path: &str| detect_format(b"", Some(Path::new(path)));
+    assert_eq!(detect("a.stl"), Some(Format::Stl));
+    assert_eq!(detect("a.Dae"), Some(Format::Collada));
+    assert_eq!(detect("dir.obj/a.X3DV"), Some(Format::Vrml));
+    assert_eq!(detect("a.pts"), Some(Format::Xyz));
+    assert_eq!(detect("a.urdf"), Some(Format::Urdf));
+    assert_eq!(detect("a.png"), None);
+    assert_eq!(detect("obj"), None);
+    // The extension takes precedence over the contents.
+    assert_eq!(
+        detect_format(b"solid a\n", Some(Path::new("a.obj"))),
+        Some(Format::Obj)
+    );
+
+    assert_eq!(Format::from_extension("USDA"), Some(Format::Usd));
+    assert_eq!(Format::from_extension("gz"), None);
+    assert_eq!(Format::Xyz.extensions(), ["xyz", "pts"]);
+    assert_eq!(Format::ThreeDs.name(), "3DS");
+}
+
+#[test]
+fn binary() {
+    // Binary STL is detected by its size, even if the header starts with "solid".
+    assert_eq!(detect(&binary_stl(b"solid exported", 2)), Some(Format::Stl));
+    assert_eq!(detect(&binary_stl(b"", 0)), Some(Format::Stl));
+    let mut broken = binary_stl(b"", 2);
+    broken.pop();
+    assert_eq!(detect(&broken), None);
+
+    assert_eq!(detect(b"Kaydara FBX Binary  \0\x1a\0"), Some(Format::Fbx));
+    assert_eq!(detect(b"PXR-USDC"), Some(Format::Usd));
+    assert_eq!(detect(b"AutoCAD Binary DXF\r\n\x1a\0"), Some(Format::Dxf));
+    assert_eq!(detect(b"MM\x06\0\0\0"), Some(Format::ThreeDs));
+    assert_eq!(detect(b"MM\x07\0\0\0"), None);
+}
+
+// Binary USD and DXF are detected, but only their text variants are parsed,
+// so loading them fails with an error saying that they are not supported.
+#[cfg(all(feature = "dxf", feature = "usd"))]
+#[test]
+fn unsupported_binary() {
+    let loader = mesh_loader::Loader::default();
+    for (bytes, path, msg) in [
+        (&b"PXR-USDC\0\x07\0\0"[..], "a", "binary USD (usdc)"),
+        (b"PXR-USDC\0\x07\0\0", "a.usd", "binary USD (usdc)"),
+        (b"AutoCAD Binary DXF\r\n\x1a\0", "a", "binary DXF"),
+        (b"AutoCAD Binary DXF\r\n\x1a\0", "a.dxf", "binary DXF"),
+    ] {
+        let e = loader.load_from_slice(bytes, path).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::Unsupported);
+        assert_eq!(
+            e.to_string(),
+            format!("{msg} is not supported ({path}:1:1)")
+        );
+    }
+}
+
+#[test]
+fn xml() {
+    assert_eq!(
+        detect(
+            b"\xEF\xBB\xBF<?xml version=\"1.0\"?>\n<!-- <amf> -->\n<COLLADA xmlns=\"http://www.collada.org/2005/11/COLLADASchema\">"
+        ),
+        Some(Format::Collada)
+    );
+    assert_eq!(detect(b"  <amf unit=\"mm\">"), Some(Format::Amf));
+    assert_eq!(
+        detect(b"<?xml version=\"1.0\"?>\n<!DOCTYPE X3D PUBLIC \"ISO//Web3D//DTD X3D 3.3//EN\" \"x3d-3.3.dtd\">\n<X3D>"),
+        Some(Format::Vrml)
+    );
+    assert_eq!(detect(b"<robot name=\"a\">"), Some(Format::Urdf));
+    assert_eq!(detect(b"<x:robot xmlns:x=\"\"/>"), Some(Format::Urdf));
+    assert_eq!(detect(b"<html>"), None);
+}
+
+#[test]
+fn text() {
+    assert_eq!(detect(b"#VRML V2.0 utf8\n"), Some(Format::Vrml));
+    assert_eq!(detect(b"#usda 1.0\n"), Some(Format::Usd));
+    assert_eq!(detect(b"# .PCD v0.7\nVERSION 0.7\n"), Some(Format::Pcd));
+    assert_eq!(
+        detect(b"999\ncomment\n  0\nSECTION\n  2\nENTITIES\n"),
+        Some(Format::Dxf)
+    );
+    assert_eq!(detect(b"# comment\nCOFF\n3 1 0\n"), Some(Format::Off));
+    assert_eq!(detect(b"OFF3 1 0\n"), Some(Format::Off));
+    assert_eq!(detect(b"OFFSET 1\n"), None);
+
+    let stl = b"solid a\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nvertex 0 1 0\nendloop\nendfacet\nendsolid a\n";
+    assert_eq!(detect(stl), Some(Format::Stl));
+    assert_eq!(detect(b"solid\n"), Some(Format::Stl));
+
+    let obj = b"# Exported\n\nmtllib a.mtl\no a\nv 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nusemtl a\nf 1//1 2//1 3//1\n";
+    assert_eq!(detect(obj), Some(Format::Obj));
+    // Points only
+    assert_eq!(detect(b"v 0 0 0\nv 1 0 0\n"), Some(Format::Obj));
+
+    assert_eq!(detect(b"0 0 0\n1 0 0\n0 1 0\n"), Some(Format::Xyz));
+    assert_eq!(
+        detect(b"2\n0 0 0 10 255 0 0\n1.5 -1e3 0 10 255 0 0\n"),
+        Some(Format::Xyz)
+    );
+
+    // A large OBJ file whose head is cut in the middle of a line
+    let mut obj = b"v 0 0 0\n".repeat(1000);
+    obj.extend_from_slice(b"f 1 2 3\n");
+    assert_eq!(detect(&obj), Some(Format::Obj));
+
+    assert_eq!(detect(b""), None);
+    assert_eq!(detect(b"hello world\n"), None);
+    assert_eq!(detect(b"0 0\n"), None);
+}
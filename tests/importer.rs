@@ -0,0 +1,149 @@
+#![cfg(all(feature = "obj", feature = "stl"))]
+
+use std::{io, path::Path, str};
+
+use mesh_loader::{
+    importer::{Context, Importer},
+    resolver::{MemoryStore, ResourceKind},
+    Loader, Material, Mesh, Scene,
+};
+
+/// A format that has a triangle per line, and `include <path>` lines that
+/// include the triangles of other files.
+struct TriImporter;
+
+impl Importer for TriImporter {
+    fn extensions(&self) -> &[&str] {
+        &["tri"]
+    }
+    fn sniff(&self, bytes: &[u8]) -> bool {
+        bytes.starts_with(b"TRI\n")
+    }
+    fn load(&self, bytes: &[u8], _path: &Path, cx: &mut Context<'_>) -> io::Result<Scene> {
+        let s = str::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let mut scene = Scene::default();
+        for line in s.lines() {
+            if let Some(reference) = line.strip_prefix("include ") {
+                let bytes = cx.read(reference, ResourceKind::Model)?;
+                scene
+                    .meshes
+                    .extend(self.load(&bytes, Path::new(reference), cx)?.meshes);
+                continue;
+            }
+            let v: Vec<f32> = line
+                .split_whitespace()
+                .filter_map(|v| v.parse().ok())
+                .collect();
+            if let [a0, a1, a2, b0, b1, b2, c0, c1, c2] = v[..] {
+                let mut mesh = Mesh::default();
+                mesh.vertices = vec![[a0, a1, a2], [b0, b1, b2], [c0, c1, c2]];
+                mesh.faces = vec![[0, 1, 2]];
+                scene.meshes.push(mesh);
+            }
+        }
+        scene.materials = vec![Material::default(); scene.meshes.len()];
+        Ok(scene)
+    }
+}
+
+/// An importer of the files that reference a model file of another format.
+struct RefImporter;
+
+impl Importer for RefImporter {
+    fn extensions(&self) -> &[&str] {
+        &["ref"]
+    }
+    fn load(&self, bytes: &[u8], _path: &Path, cx: &mut Context<'_>) -> io::Result<Scene> {
+        let reference = str::from_utf8(bytes).unwrap().trim();
+        cx.load(reference)
+    }
+}
+
+/// An importer that replaces the built-in STL importer.
+struct StlImporter;
+
+impl Importer for StlImporter {
+    fn extensions(&self) -> &[&str] {
+        &["STL"]
+    }
+    fn load(&self, _bytes: &[u8], _path: &Path, _cx: &mut Context<'_>) -> io::Result<Scene> {
+        Ok(Scene::default())
+    }
+}
+
+const TRI: &str = "TRI\n0 0 0 1 0 0 0 1 0\n0 0 1 1 0 1 0 1 1\n";
+const STL: &str = "solid a
+facet normal 0 0 1
+outer loop
+vertex 0 0 0
+vertex 1 0 0
+vertex 0 1 0
+endloop
+endfacet
+endsolid a
+";
+
+#[test]
+fn dispatch() {
+    let loader = Loader::default().importer(TriImporter);
+    let scene = loader.load_from_slice(TRI.as_bytes(), "a.Tri").unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+    // Detected from the contents.
+    let scene = loader.load_from_slice(TRI.as_bytes(), "a").unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+    // Built-in formats are still available.
+    let scene = loader.load_from_slice(STL.as_bytes(), "a").unwrap();
+    assert_eq!(scene.meshes[0].faces, [[0, 1, 2]]);
+
+    let e = Loader::default()
+        .load_from_slice(b"TRI\n", "a.tri")
+        .unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::Unsupported);
+}
+
+#[test]
+fn override_builtin() {
+    let loader = Loader::default().importer(StlImporter);
+    let scene = loader.load_from_slice(STL.as_bytes(), "a.stl").unwrap();
+    assert!(scene.meshes.is_empty());
+    // The format detected from the contents is also handled by the
+    // registered importer.
+    let scene = loader.load_from_slice(STL.as_bytes(), "a").unwrap();
+    assert!(scene.meshes.is_empty());
+    // Format-specific methods always use the built-in importer.
+    let scene = loader.load_stl_from_slice(STL.as_bytes(), "a.stl").unwrap();
+    assert_eq!(scene.meshes.len(), 1);
+}
+
+#[test]
+fn post_process() {
+    let loader = Loader::default().importer(TriImporter).merge_meshes(true);
+    let scene = loader.load_from_slice(TRI.as_bytes(), "a.tri").unwrap();
+    assert_eq!(scene.meshes.len(), 1);
+    assert_eq!(scene.meshes[0].faces, [[0, 1, 2], [3, 4, 5]]);
+}
+
+#[test]
+fn references() {
+    let mut store = MemoryStore::new();
+    store.insert("models/a.tri", "include parts/b.tri\n0 0 0 1 0 0 0 1 0\n");
+    store.insert("models/parts/b.tri", TRI);
+    store.insert("models/c.ref", "parts/d.obj");
+    store.insert("models/parts/d.obj", "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n");
+    store.insert("models/e.ref", "../f.stl");
+    store.insert("f.stl", STL);
+    let loader = Loader::default()
+        .resolver(store)
+        .importer(TriImporter)
+        .importer(RefImporter);
+
+    let scene = loader.load("models/a.tri").unwrap();
+    assert_eq!(scene.meshes.len(), 3);
+    let scene = loader.load("models/c.ref").unwrap();
+    assert_eq!(scene.meshes[0].faces, [[0, 1, 2]]);
+
+    // The references are confined to the sandbox.
+    assert!(loader.load("models/e.ref").is_ok());
+    let e = loader.sandbox("models").load("models/e.ref").unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::PermissionDenied);
+}
@@ -0,0 +1,29 @@
+#![cfg(feature = "obj")]
+
+use mesh_loader::Loader;
+
+#[test]
+fn point_cloud() {
+    // OBJ files that only have vertices are loaded as a point cloud.
+    let obj = b"\
+# point cloud
+v 0 0 0 1 0 0
+v 1 0 0 0 1 0
+v 0 1 0 0 0 1
+";
+    let scene = Loader::default()
+        .load_from_slice(obj, "points.obj")
+        .unwrap();
+    assert!(scene.meshes.is_empty());
+    assert_eq!(scene.point_clouds.len(), 1);
+    let cloud = &scene.point_clouds[0];
+    assert_eq!(cloud.points, vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]);
+    assert_eq!(cloud.colors[2], [0., 0., 1., 1.]);
+    assert!(cloud.normals.is_empty());
+
+    // Files that have faces are loaded as meshes.
+    let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+    let scene = Loader::default().load_from_slice(obj, "mesh.obj").unwrap();
+    assert_eq!(scene.meshes.len(), 1);
+    assert!(scene.point_clouds.is_empty());
+}
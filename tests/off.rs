@@ -0,0 +1,110 @@
+#![cfg(feature = "off")]
+
+use mesh_loader::off::from_slice;
+
+#[test]
+fn cube() {
+    let cube = b"\
+OFF
+# cube
+8 6 12
+-0.5 -0.5 -0.5
+0.5 -0.5 -0.5
+0.5 0.5 -0.5
+-0.5 0.5 -0.5
+-0.5 -0.5 0.5
+0.5 -0.5 0.5
+0.5 0.5 0.5
+-0.5 0.5 0.5
+4 0 3 2 1
+4 4 5 6 7
+4 0 1 5 4
+4 1 2 6 5
+4 2 3 7 6
+4 3 0 4 7
+";
+    let scene = from_slice(cube).unwrap();
+    assert_eq!(scene.meshes.len(), 1);
+    assert_eq!(scene.materials.len(), 1);
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.faces.len(), 12);
+    // Vertices are shared between faces when there are no per-face colors.
+    assert_eq!(mesh.vertices.len(), 8);
+    assert_eq!(mesh.faces[0], [0, 3, 2]);
+    assert_eq!(mesh.faces[1], [0, 2, 1]);
+    assert_eq!(mesh.vertices[3], [-0.5, 0.5, -0.5]);
+    assert!(mesh.normals.is_empty());
+    assert!(mesh.colors[0].is_empty());
+}
+
+#[test]
+fn variants() {
+    // COFF with 0-255 integer colors and NOFF
+    let s = b"CNOFF\n3 1 0\n0 0 0 0 0 1 255 0 0 255\n1 0 0 0 0 1 0 255 0 255\n0 1 0 0 0 1 0 0 255 255\n3 0 1 2\n";
+    let scene = from_slice(s).unwrap();
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.faces.len(), 1);
+    assert_eq!(mesh.normals, vec![[0., 0., 1.]; 3]);
+    assert_eq!(
+        mesh.colors[0],
+        vec![[1., 0., 0., 1.], [0., 1., 0., 1.], [0., 0., 1., 1.]]
+    );
+
+    // Per-face colors and no line break after OFF (ModelNet)
+    let s = b"OFF4 2 0\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n3 0 1 2\n3 0 2 3 0.0 0.5 1.0\n";
+    let scene = from_slice(s).unwrap();
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.faces, vec![[0, 1, 2], [3, 4, 5]]);
+    assert_eq!(mesh.vertices.len(), 6);
+    assert_eq!(mesh.vertices[4], [1., 1., 0.]);
+    assert_eq!(mesh.colors[0].len(), 6);
+    assert_eq!(mesh.colors[0][0], [0.6, 0.6, 0.6, 1.]);
+    assert_eq!(mesh.colors[0][3], [0., 0.5, 1., 1.]);
+
+    // The color scale depends on whether the values are integer literals,
+    // not on their magnitude.
+    let s = b"COFF\n3 1\n0 0 0 1 1 1\n1 0 0 1.0 1.0 1.0\n0 1 0 0.5 1 0\n3 0 1 2 0 0 51\n";
+    let scene = from_slice(s).unwrap();
+    let mesh = &scene.meshes[0];
+    let b = 1. / 255.;
+    assert_eq!(
+        mesh.colors[0],
+        vec![[b, b, b, 1.], [1., 1., 1., 1.], [0.5, 1., 0., 1.]]
+    );
+    assert_eq!(mesh.faces, vec![[0, 1, 2]]);
+    let s = b"OFF\n3 1\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2 0 0 255\n";
+    let scene = from_slice(s).unwrap();
+    assert_eq!(scene.meshes[0].colors[0], vec![[0., 0., 1., 1.]; 3]);
+
+    // STOFF
+    let s = b"STOFF\n3 1 0\n0 0 0 0 0\n1 0 0 1 0\n0 1 0 0 1\n3 0 1 2\n";
+    let scene = from_slice(s).unwrap();
+    assert_eq!(
+        scene.meshes[0].texcoords[0],
+        vec![[0., 0.], [1., 0.], [0., 1.]]
+    );
+}
+
+#[test]
+fn error() {
+    let e = from_slice(b"OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 3\n").unwrap_err();
+    assert_eq!(e.to_string(), "face index out of bounds (3) (6:7)");
+    let e = from_slice(b"OFF\n3 1 0\n0 0 0\n1 0\n0 1 0\n3 0 1 2\n").unwrap_err();
+    assert_eq!(e.to_string(), "invalid number of values in vertex (4:1)");
+    for s in [&b"OFF BINARY\n"[..], b"4OFF\n", b"nOFF\n4\n"] {
+        let e = from_slice(s).unwrap_err();
+        assert_eq!(e.kind(), std::io::ErrorKind::Unsupported);
+    }
+}
+
+#[test]
+fn points_and_lines() {
+    // Points and lines are skipped, and a single value after the indices (an
+    // index into the colormap) is ignored.
+    let s = b"OFF\n\n# comment\n4 3\n0 0 0\n1 0 0\n0 1 0\n0 0 1\n1 3\n2 0 3 # line\n3 0 1 2 7\n";
+    let scene = from_slice(s).unwrap();
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.faces, vec![[0, 1, 2]]);
+    assert_eq!(mesh.vertices.len(), 4);
+    assert!(mesh.colors[0].is_empty());
+}
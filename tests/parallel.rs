@@ -0,0 +1,196 @@
+#![cfg(all(
+    feature = "rayon",
+    feature = "collada",
+    feature = "obj",
+    feature = "stl"
+))]
+
+// Inputs larger than the chunks parsed in parallel are parsed in parallel.
+// The results are compared with the incremental parsers, which are always
+// sequential.
+
+use std::fmt::Write as _;
+
+use mesh_loader::Scene;
+
+#[track_caller]
+fn assert_scene_eq(a: &Scene, b: &Scene) {
+    assert_eq!(a.meshes.len(), b.meshes.len());
+    for (a, b) in a.meshes.iter().zip(&b.meshes) {
+        assert_eq!(a.name, b.name);
+        assert_eq!(a.vertices, b.vertices);
+        assert_eq!(a.normals, b.normals);
+        assert_eq!(a.texcoords, b.texcoords);
+        assert_eq!(a.colors, b.colors);
+        assert_eq!(a.faces, b.faces);
+    }
+    assert_eq!(format!("{:?}", a.materials), format!("{:?}", b.materials));
+    assert_eq!(a.point_clouds.len(), b.point_clouds.len());
+    for (a, b) in a.point_clouds.iter().zip(&b.point_clouds) {
+        assert_eq!(a.points, b.points);
+        assert_eq!(a.colors, b.colors);
+    }
+}
+
+#[test]
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn stl() {
+    for colored in [None, Some(30000)] {
+        let mut stl = b"COLOR=\x01\x02\x03\x04".to_vec();
+        stl.resize(80, b' ');
+        let num_triangles = 50000_u32;
+        stl.extend_from_slice(&num_triangles.to_le_bytes());
+        for i in 0..num_triangles {
+            for f in [
+                0., 0., 1., i as f32, 0., 0., 1., i as f32, 0., 0., 1., i as f32,
+            ] {
+                stl.extend_from_slice(&f32::to_le_bytes(f));
+            }
+            let color: u16 = if Some(i) == colored { 1 << 15 | 31 } else { 0 };
+            stl.extend_from_slice(&color.to_le_bytes());
+        }
+        let loader = mesh_loader::Loader::default().stl_parse_color(true);
+        let scene = loader.load_from_slice(&stl, "a.stl").unwrap();
+        assert_eq!(scene.meshes[0].faces[49999], [149997, 149998, 149999]);
+        assert_eq!(scene.meshes[0].colors[0].is_empty(), colored.is_none());
+        assert_scene_eq(&scene, &loader.load_from_reader(&stl[..], "a.stl").unwrap());
+    }
+}
+
+#[test]
+fn obj() {
+    let mut obj = String::new();
+    for i in 0..100000 {
+        let _ = write!(obj, "v {i} 0.5 -1e-3");
+        // Vertex colors start in the middle of the file.
+        if i >= 70000 && i % 2 == 0 {
+            obj.push_str(" 1 0 0");
+        }
+        obj.push_str(if i % 3 == 0 { "\r\n" } else { "\n" });
+        let _ = writeln!(obj, "vn 0 0 \\\n1\nvt 0.{i} 1");
+        if i % 20000 == 0 {
+            let _ = writeln!(obj, "g group{i}");
+        }
+        if i % 2 == 1 {
+            let _ = writeln!(obj, "f -2/-2/-2 -1/-1/-1 1/1/1");
+        }
+    }
+    let scene = mesh_loader::obj::from_slice(obj.as_bytes(), None, |_| Ok(vec![])).unwrap();
+    assert_eq!(scene.meshes.len(), 5);
+    assert_scene_eq(
+        &scene,
+        &mesh_loader::obj::from_reader(obj.as_bytes(), None, |_| Ok(vec![])).unwrap(),
+    );
+
+    // Point clouds
+    let points: String =
+        obj.lines()
+            .filter(|l| l.starts_with("v "))
+            .fold(String::new(), |mut s, l| {
+                let _ = writeln!(s, "{l}");
+                s
+            });
+    let scene = mesh_loader::obj::from_slice(points.as_bytes(), None, |_| Ok(vec![])).unwrap();
+    assert_eq!(scene.point_clouds[0].points.len(), 100000);
+    assert_scene_eq(
+        &scene,
+        &mesh_loader::obj::from_reader(points.as_bytes(), None, |_| Ok(vec![])).unwrap(),
+    );
+
+    // Errors are the same as the sequential parser.
+    for error in ["v 1 x 3\n", "vn 1 2\n", "f 1 2 100001\n"] {
+        let mut obj = obj.clone();
+        obj.insert_str(
+            obj.len() / 2 + obj[obj.len() / 2..].find('\n').unwrap() + 1,
+            error,
+        );
+        let e = mesh_loader::obj::from_slice(obj.as_bytes(), None, |_| Ok(vec![])).unwrap_err();
+        let expected =
+            mesh_loader::obj::from_reader(obj.as_bytes(), None, |_| Ok(vec![])).unwrap_err();
+        if error.starts_with('f') {
+            // Face indices are checked when the mesh is finished, and the
+            // incremental parser reports that location relative to its buffer.
+            let strip = |e: std::io::Error| e.to_string().rsplit_once(" (").unwrap().0.to_owned();
+            assert_eq!(strip(e), strip(expected));
+        } else {
+            assert_eq!(e.to_string(), expected.to_string());
+        }
+    }
+}
+
+#[test]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn collada() {
+    // The float array is larger than two chunks.
+    let n = 200000;
+    let mut positions = String::new();
+    let mut indices = String::new();
+    for i in 0..n {
+        let _ = write!(positions, "{i} 0.5\n-1e-3\t");
+        let _ = write!(indices, "{}  {} \n{} ", i, (i + 1) % n, (i + 2) % n);
+    }
+    let dae = format!(
+        r##"<?xml version="1.0" encoding="utf-8"?>
+<COLLADA xmlns="http://www.collada.org/2005/11/COLLADASchema" version="1.4.1">
+  <library_geometries>
+    <geometry id="mesh">
+      <mesh>
+        <source id="positions">
+          <float_array id="positions-array" count="{count}">{positions}</float_array>
+          <technique_common>
+            <accessor source="#positions-array" count="{n}" stride="3">
+              <param name="X" type="float"/>
+              <param name="Y" type="float"/>
+              <param name="Z" type="float"/>
+            </accessor>
+          </technique_common>
+        </source>
+        <vertices id="vertices">
+          <input semantic="POSITION" source="#positions"/>
+        </vertices>
+        <triangles count="{n}">
+          <input semantic="VERTEX" source="#vertices" offset="0"/>
+          <p>{indices}</p>
+        </triangles>
+      </mesh>
+    </geometry>
+  </library_geometries>
+  <library_visual_scenes>
+    <visual_scene id="scene">
+      <node id="node">
+        <instance_geometry url="#mesh"/>
+      </node>
+    </visual_scene>
+  </library_visual_scenes>
+  <scene>
+    <instance_visual_scene url="#scene"/>
+  </scene>
+</COLLADA>
+"##,
+        count = n * 3,
+    );
+    let scene = mesh_loader::collada::from_str(&dae).unwrap();
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.faces.len(), n);
+    for (i, face) in mesh.faces.iter().enumerate() {
+        let v = face.map(|j| mesh.vertices[j as usize][0] as usize);
+        assert_eq!(v, [i, (i + 1) % n, (i + 2) % n]);
+    }
+    assert!(mesh.vertices.iter().all(|v| v[1] == 0.5 && v[2] == -1e-3));
+
+    // The float array is parsed in parallel, without falling back to the
+    // sequential parser.
+    let values = mesh_loader::__private::parse_float_array_parallel(&positions, n * 3).unwrap();
+    assert_eq!(values.len(), n * 3);
+    assert_eq!(values[..3], [0., 0.5, -1e-3]);
+    // The number of values must match the count exactly.
+    assert!(mesh_loader::__private::parse_float_array_parallel(&positions, n * 3 + 1).is_none());
+    assert!(mesh_loader::__private::parse_float_array_parallel(&positions, n * 3 - 1).is_none());
+
+    // Errors are the same as the sequential parser.
+    let e = mesh_loader::collada::from_str(&dae.replace("99999 0.5", "99999 x"));
+    assert!(e
+        .unwrap_err()
+        .to_string()
+        .contains("error while parsing a float"));
+}
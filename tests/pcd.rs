@@ -0,0 +1,180 @@
+#![cfg(feature = "pcd")]
+
+use std::fmt::Write as _;
+
+use mesh_loader::pcd::from_slice;
+
+const ASCII_PCD: &str = "\
+# .PCD v0.7 - Point Cloud Data file format
+VERSION 0.7
+FIELDS x y z rgb normal_x normal_y normal_z curvature
+SIZE 4 4 4 4 4 4 4 4
+TYPE F F F F F F F F
+COUNT 1 1 1 1 1 1 1 1
+WIDTH 3
+HEIGHT 1
+VIEWPOINT 0 0 0 1 0 0 0
+POINTS 3
+DATA ascii
+0.5 1 -2 5.90052031e-39 0 0 1 0.1
+nan nan nan 5.90052031e-39 0 0 1 0.1
+1 2 3 2.34180515e-38 0 1 0 0
+";
+
+#[test]
+fn ascii() {
+    let scene = from_slice(ASCII_PCD.as_bytes()).unwrap();
+    assert!(scene.meshes.is_empty());
+    assert_eq!(scene.point_clouds.len(), 1);
+    let cloud = &scene.point_clouds[0];
+    // The point with NaN coordinates is skipped.
+    assert_eq!(cloud.points, vec![[0.5, 1., -2.], [1., 2., 3.]]);
+    assert_eq!(cloud.normals, vec![[0., 0., 1.], [0., 1., 0.]]);
+    assert!(cloud.intensities.is_empty());
+    // Packed colors: the bits of 5.90052031e-39 and 2.34180515e-38 are
+    // 0x00404040 and 0x00FF0000.
+    assert_eq!(cloud.colors[0], [64. / 255., 64. / 255., 64. / 255., 1.]);
+    assert_eq!(cloud.colors[1], [1., 0., 0., 1.]);
+}
+
+fn binary_points() -> Vec<u8> {
+    let mut data = vec![];
+    for (x, rgba, intensity) in [(1_f32, 0x80FF0000_u32, 7_u16), (2., 0xFF00FF00, 9)] {
+        data.extend_from_slice(&x.to_le_bytes());
+        data.extend_from_slice(&0_f32.to_le_bytes());
+        data.extend_from_slice(&(-x).to_le_bytes());
+        data.extend_from_slice(&rgba.to_le_bytes());
+        data.extend_from_slice(&intensity.to_le_bytes());
+    }
+    data
+}
+
+#[test]
+fn binary() {
+    let mut pcd = b"\
+VERSION .7
+FIELDS x y z rgba intensity
+SIZE 4 4 4 4 2
+TYPE F F F U U
+WIDTH 2
+HEIGHT 1
+DATA binary
+"
+    .to_vec();
+    pcd.extend_from_slice(&binary_points());
+    let scene = from_slice(&pcd).unwrap();
+    let cloud = &scene.point_clouds[0];
+    assert_eq!(cloud.points, vec![[1., 0., -1.], [2., 0., -2.]]);
+    assert_eq!(cloud.colors[0], [1., 0., 0., 128. / 255.]);
+    assert_eq!(cloud.colors[1], [0., 1., 0., 1.]);
+    assert_eq!(cloud.intensities, vec![7., 9.]);
+    assert!(cloud.normals.is_empty());
+}
+
+#[allow(clippy::cast_possible_truncation)]
+#[test]
+fn binary_compressed() {
+    // Rearrange the points field by field.
+    let points = binary_points();
+    let mut data = vec![];
+    for (offset, size) in [(0, 4), (4, 4), (8, 4), (12, 4), (16, 2)] {
+        for point in points.chunks(18) {
+            data.extend_from_slice(&point[offset..offset + size]);
+        }
+    }
+    // LZF stream with literal runs only.
+    let mut compressed = vec![];
+    for chunk in data.chunks(32) {
+        compressed.push(chunk.len() as u8 - 1);
+        compressed.extend_from_slice(chunk);
+    }
+    let mut pcd = b"\
+VERSION .7
+FIELDS x y z rgba intensity
+SIZE 4 4 4 4 2
+TYPE F F F U U
+COUNT 1 1 1 1 1
+POINTS 2
+DATA binary_compressed
+"
+    .to_vec();
+    pcd.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+    pcd.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    pcd.extend_from_slice(&compressed);
+    let scene = from_slice(&pcd).unwrap();
+    let cloud = &scene.point_clouds[0];
+    assert_eq!(cloud.points, vec![[1., 0., -1.], [2., 0., -2.]]);
+    assert_eq!(cloud.colors[1], [0., 1., 0., 1.]);
+    assert_eq!(cloud.intensities, vec![7., 9.]);
+
+    // Broken size.
+    let len = pcd.len();
+    pcd[len - compressed.len() - 4] += 1;
+    assert!(from_slice(&pcd).is_err());
+}
+
+#[test]
+fn binary_matches_ascii() {
+    // An organized point cloud with an invalid point, 64-bit coordinates,
+    // an ignored field with multiple values, and signed intensities.
+    let header = "\
+VERSION 0.7
+FIELDS x y z _ normal_x normal_y normal_z intensity
+SIZE 8 8 8 1 4 4 4 2
+TYPE F F F U F F F I
+COUNT 1 1 1 3 1 1 1 1
+WIDTH 2
+HEIGHT 2
+VIEWPOINT 0 0 0 1 0 0 0
+POINTS 4
+";
+    let points: [([f64; 3], [f32; 3], i16); 4] = [
+        ([0.5, 1., -2.], [0., 0., 1.], -7),
+        ([f64::NAN; 3], [0.; 3], 0),
+        ([1., 2., 3.], [0., 1., 0.], 300),
+        ([-1.25, 0., 1e3], [1., 0., 0.], 0),
+    ];
+    let mut ascii = format!("{header}DATA ascii\n");
+    let mut binary = format!("{header}DATA binary\n").into_bytes();
+    for ([x, y, z], [nx, ny, nz], intensity) in points {
+        writeln!(ascii, "{x} {y} {z} 1 2 3 {nx} {ny} {nz} {intensity}").unwrap();
+        for v in [x, y, z] {
+            binary.extend_from_slice(&v.to_le_bytes());
+        }
+        binary.extend_from_slice(&[1, 2, 3]);
+        for v in [nx, ny, nz] {
+            binary.extend_from_slice(&v.to_le_bytes());
+        }
+        binary.extend_from_slice(&intensity.to_le_bytes());
+    }
+    let ascii = from_slice(ascii.as_bytes()).unwrap();
+    let binary = from_slice(&binary).unwrap();
+    for cloud in [&ascii.point_clouds[0], &binary.point_clouds[0]] {
+        assert_eq!(
+            cloud.points,
+            vec![[0.5, 1., -2.], [1., 2., 3.], [-1.25, 0., 1e3]]
+        );
+        assert_eq!(
+            cloud.normals,
+            vec![[0., 0., 1.], [0., 1., 0.], [1., 0., 0.]]
+        );
+        assert_eq!(cloud.intensities, vec![-7., 300., 0.]);
+        assert!(cloud.colors.is_empty());
+    }
+}
+
+#[test]
+fn error() {
+    let e = from_slice(b"VERSION 0.7\nFIELDS x y z\nSIZE 4 4\nDATA ascii\n").unwrap_err();
+    assert_eq!(e.to_string(), "invalid number of values in SIZE (3:1)");
+    let e = from_slice(b"FIELDS x y z\nPOINTS 1\nDATA ascii\n1 2 a\n").unwrap_err();
+    assert_eq!(e.to_string(), "error while parsing a float (4:5)");
+    let e = from_slice(b"FIELDS x y z\nPOINTS 1\nDATA ascii\n1 2\n").unwrap_err();
+    assert_eq!(e.to_string(), "invalid number of values in point (4:1)");
+    let e = from_slice(b"FIELDS x y\nDATA ascii\n").unwrap_err();
+    assert_eq!(e.to_string(), "expected x, y, and z fields (3:1)");
+    let e = from_slice(b"FIELDS x y z\nDATA binary_gzip\n").unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::Unsupported);
+    let e = from_slice(b"FIELDS x y z\nPOINTS 1\nDATA binary\n\0\0\0\0").unwrap_err();
+    assert_eq!(e.to_string(), "unexpected end of binary data");
+}
@@ -0,0 +1,133 @@
+#![cfg(all(feature = "collada", feature = "obj", feature = "stl"))]
+
+use mesh_loader::{resolver::MemoryStore, Format, Loader};
+
+#[test]
+fn stl() {
+    let loader = Loader::default();
+
+    let mut binary = b"solid exported by foo\0\0\0".to_vec();
+    binary.resize(80, 0);
+    binary.extend_from_slice(&3_u32.to_le_bytes());
+    // Only the header is read.
+    let summary = loader.probe_from_slice(&binary, "a.stl").unwrap();
+    assert_eq!(summary.format, Some(Format::Stl));
+    assert_eq!(summary.meshes[0].num_faces, 3);
+    assert_eq!(summary.num_vertices, Some(9));
+    assert_eq!(
+        summary.metadata,
+        [("header".to_owned(), "solid exported by foo".to_owned())]
+    );
+
+    let ascii = "solid a
+facet normal 0 0 1
+outer loop
+vertex 0 0 0
+vertex 1 0 0
+vertex 0 1 -1
+endloop
+endfacet
+endsolid a
+";
+    let summary = loader.probe_from_slice(ascii.as_bytes(), "a").unwrap();
+    assert_eq!(summary.format, Some(Format::Stl));
+    assert_eq!(summary.meshes[0].name, "a");
+    assert_eq!(summary.meshes[0].num_faces, 1);
+    assert_eq!(summary.bounds, Some([[0., 0., -1.], [1., 1., 0.]]));
+}
+
+#[test]
+fn obj() {
+    let obj = "mtllib a b.mtl
+v 0 0 0
+v 1 0 0
+v 1 2 3
+v 0 1 0
+vn 0 0 1
+f 1 2 3
+f 1 3 4
+g g1
+f 1 2 4
+g g2
+l 1 2
+";
+    let mut store = MemoryStore::new();
+    store.insert("models/a.obj", obj);
+    // The MTL file is not read.
+    let summary = Loader::default()
+        .resolver(store)
+        .probe("models/a.obj")
+        .unwrap();
+    assert_eq!(summary.format, Some(Format::Obj));
+    assert_eq!(summary.num_vertices, Some(4));
+    assert_eq!(summary.bounds, Some([[0., 0., 0.], [1., 2., 3.]]));
+    assert_eq!(summary.references, ["a b.mtl"]);
+    let meshes: Vec<_> = summary
+        .meshes
+        .iter()
+        .map(|m| (&*m.name, m.num_faces))
+        .collect();
+    assert_eq!(meshes, [("default", 2), ("g1", 1)]);
+}
+
+#[test]
+fn collada() {
+    let dae = r#"<?xml version="1.0" encoding="utf-8"?>
+<COLLADA xmlns="http://www.collada.org/2005/11/COLLADASchema" version="1.4.1">
+  <asset>
+    <contributor>
+      <authoring_tool>Blender 2.79.0</authoring_tool>
+    </contributor>
+    <unit name="millimeter" meter="0.001"/>
+    <up_axis>Z_UP</up_axis>
+  </asset>
+  <library_images>
+    <image id="tex"><init_from>textures/a.png</init_from></image>
+  </library_images>
+  <library_geometries>
+    <geometry id="Cube-mesh" name="Cube">
+      <mesh>
+        <triangles count="12"/>
+        <polylist count="6"/>
+      </mesh>
+    </geometry>
+  </library_geometries>
+</COLLADA>
+"#;
+    let summary = Loader::default()
+        .probe_from_slice(dae.as_bytes(), "a.dae")
+        .unwrap();
+    assert_eq!(summary.format, Some(Format::Collada));
+    assert_eq!(summary.unit, Some(0.001));
+    assert_eq!(
+        summary.metadata,
+        [
+            ("authoring_tool".to_owned(), "Blender 2.79.0".to_owned()),
+            ("up_axis".to_owned(), "Z_UP".to_owned()),
+        ]
+    );
+    assert_eq!(summary.meshes[0].name, "Cube-mesh");
+    assert_eq!(summary.meshes[0].num_faces, 18);
+    assert_eq!(summary.references, ["textures/a.png"]);
+
+    // The number of faces saturates instead of overflowing.
+    let dae = dae.replace(r#"count="6""#, &format!(r#"count="{}""#, u64::MAX));
+    let summary = Loader::default()
+        .probe_from_slice(dae.as_bytes(), "a.dae")
+        .unwrap();
+    assert_eq!(summary.meshes[0].num_faces, u64::MAX);
+}
+
+#[test]
+fn fallback() {
+    // Formats without a dedicated probe are loaded and summarized.
+    let off = "OFF\n4 2 0\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n3 0 1 2\n3 0 2 3\n";
+    let loader = Loader::default();
+    if cfg!(feature = "off") {
+        let summary = loader.probe_from_slice(off.as_bytes(), "a.off").unwrap();
+        assert_eq!(summary.format, Some(Format::Off));
+        assert_eq!(summary.meshes[0].num_faces, 2);
+        assert_eq!(summary.bounds, Some([[0., 0., 0.], [1., 1., 0.]]));
+    }
+    assert!(loader.probe_from_slice(b"?", "a.unknown").is_err());
+}
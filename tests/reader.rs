@@ -0,0 +1,139 @@
+#![cfg(all(feature = "obj", feature = "stl"))]
+
+use std::{cmp, fmt::Write as _, io};
+
+use mesh_loader::{resolver::MemoryStore, Loader, Scene};
+
+/// A reader that returns at most `max` bytes per read.
+struct SlowReader<'a> {
+    bytes: &'a [u8],
+    max: usize,
+}
+
+impl io::Read for SlowReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = cmp::min(cmp::min(buf.len(), self.max), self.bytes.len());
+        buf[..n].copy_from_slice(&self.bytes[..n]);
+        self.bytes = &self.bytes[n..];
+        Ok(n)
+    }
+}
+
+fn slow(bytes: &[u8]) -> SlowReader<'_> {
+    SlowReader { bytes, max: 1000 }
+}
+
+#[track_caller]
+fn assert_scene_eq(a: &Scene, b: &Scene) {
+    assert_eq!(a.meshes.len(), b.meshes.len());
+    for (a, b) in a.meshes.iter().zip(&b.meshes) {
+        assert_eq!(a.name, b.name);
+        assert_eq!(a.vertices, b.vertices);
+        assert_eq!(a.normals, b.normals);
+        assert_eq!(a.texcoords, b.texcoords);
+        assert_eq!(a.colors, b.colors);
+        assert_eq!(a.faces, b.faces);
+    }
+    assert_eq!(format!("{:?}", a.materials), format!("{:?}", b.materials));
+    assert_eq!(a.point_clouds.len(), b.point_clouds.len());
+}
+
+/// Creates a binary STL file with the given header and number of triangles.
+#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+fn binary_stl(header: &[u8], num_triangles: u32) -> Vec<u8> {
+    let mut bytes = header.to_vec();
+    bytes.resize(80, b' ');
+    bytes.extend_from_slice(&num_triangles.to_le_bytes());
+    for i in 0..num_triangles {
+        for f in [
+            0., 0., 1., 0., 0., i as f32, 1., 0., i as f32, 0., 1., i as f32,
+        ] {
+            bytes.extend_from_slice(&f32::to_le_bytes(f));
+        }
+        // Every third triangle has a color.
+        let color: u16 = if i % 3 == 0 {
+            1 << 15 | (i as u16 & 0x7FFF)
+        } else {
+            0
+        };
+        bytes.extend_from_slice(&color.to_le_bytes());
+    }
+    bytes
+}
+
+#[test]
+fn stl() {
+    let loader = Loader::default().stl_parse_color(true);
+    for header in [&b"binary"[..], b"solid exported COLOR=\x01\x02\x03\x04"] {
+        let stl = &binary_stl(header, 3000);
+        let expected = loader.load_from_slice(stl, "a.stl").unwrap();
+        assert_eq!(expected.meshes[0].faces.len(), 3000);
+        assert_scene_eq(
+            &loader.load_from_reader(slow(stl), "a.stl").unwrap(),
+            &expected,
+        );
+        // The format is detected from the contents.
+        assert_scene_eq(&loader.load_from_reader(slow(stl), "a").unwrap(), &expected);
+        assert_scene_eq(
+            &mesh_loader::stl::from_reader(slow(stl)).unwrap(),
+            &mesh_loader::stl::from_slice(stl).unwrap(),
+        );
+    }
+
+    // Some STL files contain bogus count or a newline after triangles.
+    let mut stl = binary_stl(b"", 2);
+    stl[80..84].copy_from_slice(&u32::MAX.to_le_bytes());
+    stl.extend_from_slice(b"\r\n");
+    let scene = mesh_loader::stl::from_reader(slow(&stl)).unwrap();
+    assert_eq!(scene.meshes[0].faces, [[0, 1, 2], [3, 4, 5]]);
+    stl.push(b'\n');
+    let e = mesh_loader::stl::from_reader(slow(&stl)).unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+
+    let ascii = b"solid a\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nvertex 0 1 0\nendloop\nendfacet\nendsolid a\n";
+    let scene = mesh_loader::stl::from_reader(slow(ascii)).unwrap();
+    assert_eq!(scene.meshes[0].name, "a");
+    assert_eq!(scene.meshes[0].faces, [[0, 1, 2]]);
+}
+
+#[test]
+fn obj() {
+    // Larger than the chunks read from the reader.
+    let mut obj = String::from("\u{feff}mtllib a.mtl\n");
+    for i in 0..5000 {
+        let _ = write!(obj, "v {i} 0 0\nv {i} 1 0\nv \\\r\n{i} 0 1\nvn 0 0 1\n");
+        if i % 1000 == 0 {
+            let _ = writeln!(
+                obj,
+                "g group{i}\nusemtl {}",
+                if i % 2000 == 0 { "a" } else { "b" }
+            );
+        }
+        let _ = writeln!(obj, "f -3//{0} -2//{0} -1//{0}", i + 1);
+    }
+    let mtl = "newmtl a\nKd 1 0 0\nnewmtl b\nKd 0 1 0\n";
+    let mut store = MemoryStore::new();
+    store.insert("models/a.obj", &obj);
+    store.insert("models/a.mtl", mtl);
+    let loader = Loader::default().resolver(store);
+
+    let expected = loader.load("models/a.obj").unwrap();
+    assert_eq!(expected.meshes.len(), 5);
+    assert_eq!(expected.materials[1].color.diffuse, Some([0., 1., 0., 1.]));
+    assert_scene_eq(
+        &loader
+            .load_from_reader(slow(obj.as_bytes()), "models/a.obj")
+            .unwrap(),
+        &expected,
+    );
+    assert_scene_eq(
+        &mesh_loader::obj::from_reader(slow(obj.as_bytes()), None, |_| Ok(mtl)).unwrap(),
+        &mesh_loader::obj::from_slice(obj.as_bytes(), None, |_| Ok(mtl)).unwrap(),
+    );
+
+    // Errors have the location in the whole file.
+    obj.push_str("f 1 x 3\n");
+    let e = mesh_loader::obj::from_reader(slow(obj.as_bytes()), None, |_| Ok(mtl)).unwrap_err();
+    let expected = mesh_loader::obj::from_slice(obj.as_bytes(), None, |_| Ok(mtl)).unwrap_err();
+    assert_eq!(e.to_string(), expected.to_string());
+}
@@ -0,0 +1,369 @@
+#![cfg(feature = "obj")]
+
+use std::{
+    collections::HashMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use mesh_loader::{
+    resolver::{resolve_uri, MemoryStore, Resource, ResourceKind, ResourceResolver},
+    Loader,
+};
+
+const OBJ: &str =
+    "mtllib package://my_robot/materials/a.mtl\nusemtl a\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+const MTL: &str = "newmtl a\nmap_Kd model://wood/textures/wood.png\n";
+
+// All URI cases are in one test because they modify environment variables.
+#[test]
+fn resolve() {
+    let tmp = env::temp_dir().join(format!("mesh-loader-resolver-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&tmp);
+    let write = |path: &str, contents: &str| {
+        let path = tmp.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    };
+    // ROS 1 workspace, the directory name differs from the package name.
+    write(
+        "ros/src/robot/package.xml",
+        "<package><name>my_robot</name></package>",
+    );
+    write("ros/src/robot/meshes/a.obj", OBJ);
+    write("ros/src/robot/materials/a.mtl", MTL);
+    write("ros/src/ignored/CATKIN_IGNORE", "");
+    write(
+        "ros/src/ignored/other/package.xml",
+        "<package><name>other</name></package>",
+    );
+    // ROS 2 install prefix
+    write(
+        "ament/share/ros2_robot/package.xml",
+        "<package><name>ros2_robot</name></package>",
+    );
+    // Gazebo model
+    write("models/wood/textures/wood.png", "");
+    #[allow(unused_unsafe)] // set_var and remove_var are safe functions on MSRV
+    // SAFETY: this is the only test in this binary, so no other thread reads
+    // or writes environment variables.
+    unsafe {
+        env::set_var("ROS_PACKAGE_PATH", tmp.join("ros/src"));
+        env::set_var("AMENT_PREFIX_PATH", tmp.join("ament"));
+        env::set_var("GAZEBO_MODEL_PATH", tmp.join("models"));
+        env::remove_var("GZ_SIM_RESOURCE_PATH");
+    }
+
+    assert_eq!(
+        resolve_uri("package://my_robot/meshes/a.obj").unwrap(),
+        tmp.join("ros/src/robot/meshes/a.obj")
+    );
+    assert_eq!(
+        resolve_uri("package://ros2_robot/urdf/a.urdf").unwrap(),
+        tmp.join("ament/share/ros2_robot/urdf/a.urdf")
+    );
+    assert_eq!(
+        resolve_uri("model://wood").unwrap(),
+        tmp.join("models/wood/")
+    );
+    assert_eq!(
+        resolve_uri("file:///a/b.stl").unwrap(),
+        Path::new("/a/b.stl")
+    );
+    assert_eq!(
+        resolve_uri("file://localhost/a/b.stl").unwrap(),
+        Path::new("/a/b.stl")
+    );
+    let e = resolve_uri("package://other/a.stl").unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::NotFound);
+    // Packages are not cached, so packages added after a lookup are found.
+    write(
+        "ros/src/new/package.xml",
+        "<package><name>new_robot</name></package>",
+    );
+    assert_eq!(
+        resolve_uri("package://new_robot/a.stl").unwrap(),
+        tmp.join("ros/src/new/a.stl")
+    );
+    let e = resolve_uri("model://metal/a.stl").unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::NotFound);
+    let e = resolve_uri("https://example.com/a.stl").unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+
+    // Both the given path and the references inside the loaded files are resolved.
+    let scene = Loader::default()
+        .load("package://my_robot/meshes/a.obj")
+        .unwrap();
+    assert_eq!(scene.meshes.len(), 1);
+    assert_eq!(
+        scene.materials[0].texture.diffuse.as_deref(),
+        Some(&*tmp.join("models/wood/textures/wood.png"))
+    );
+    let e = Loader::default().load("package://other/a.obj").unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::NotFound);
+
+    fs::remove_dir_all(&tmp).unwrap();
+}
+
+#[derive(Default)]
+struct Assets {
+    files: HashMap<PathBuf, &'static str>,
+    requests: Arc<Mutex<Vec<(PathBuf, PathBuf, Option<PathBuf>, ResourceKind)>>>,
+}
+
+impl ResourceResolver for Assets {
+    fn read(&self, resource: &Resource<'_>) -> io::Result<Vec<u8>> {
+        self.requests.lock().unwrap().push((
+            resource.path.to_owned(),
+            resource.uri.to_owned(),
+            resource.requester.map(Path::to_owned),
+            resource.kind,
+        ));
+        match self.files.get(resource.path) {
+            Some(s) => Ok(s.as_bytes().to_vec()),
+            None => Err(io::ErrorKind::NotFound.into()),
+        }
+    }
+}
+
+#[test]
+fn resource_resolver() {
+    let mut assets = Assets::default();
+    assets.files.insert(
+        "assets/a.obj".into(),
+        "mtllib mtl/a.mtl\nusemtl a\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n",
+    );
+    assets
+        .files
+        .insert("assets/mtl/a.mtl".into(), "newmtl a\nKd 1 0 0\n");
+    let requests = assets.requests.clone();
+    let loader = Loader::default().resolver(assets);
+    let scene = loader.load("assets/a.obj").unwrap();
+    assert_eq!(scene.materials[0].color.diffuse, Some([1., 0., 0., 1.]));
+    assert_eq!(
+        *requests.lock().unwrap(),
+        [
+            (
+                "assets/a.obj".into(),
+                "assets/a.obj".into(),
+                None,
+                ResourceKind::Model
+            ),
+            (
+                "assets/mtl/a.mtl".into(),
+                "mtl/a.mtl".into(),
+                Some("assets/a.obj".into()),
+                ResourceKind::Material
+            ),
+        ]
+    );
+    let scene = loader.load_obj("assets/a.obj").unwrap();
+    assert_eq!(scene.materials[0].color.diffuse, Some([1., 0., 0., 1.]));
+    let e = loader.load("assets/b.obj").unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn custom_reader_non_static() {
+    // Readers can return buffers that borrow data.
+    fn loader<'a>() -> Loader<&'a [u8]> {
+        Loader::with_custom_reader(|_| Ok(&b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n"[..]))
+    }
+    let scene = loader().load("a.obj").unwrap();
+    assert_eq!(scene.meshes[0].faces, [[0, 1, 2]]);
+}
+
+fn read_textures(path: &Path) -> io::Result<Vec<u8>> {
+    match path.to_str() {
+        Some("assets/a.obj") => Ok(b"mtllib a.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl a\nf 1 2 3\nusemtl b\nf 1 2 3\nusemtl c\nf 1 2 3\n".to_vec()),
+        Some("assets/a.mtl") => Ok(b"newmtl a\nmap_Kd a.png\nnewmtl b\nmap_Kd https://example.com/b.png\nnewmtl c\nmap_Kd c.png\n".to_vec()),
+        _ => Err(io::ErrorKind::NotFound.into()),
+    }
+}
+
+struct Textures;
+
+impl ResourceResolver for Textures {
+    fn read(&self, resource: &Resource<'_>) -> io::Result<Vec<u8>> {
+        read_textures(resource.path)
+    }
+    fn resolve(&self, resource: &Resource<'_>) -> Option<PathBuf> {
+        assert_eq!(resource.kind, ResourceKind::Texture);
+        assert_eq!(resource.requester, Some(Path::new("assets/a.obj")));
+        if resource.path == Path::new("assets/c.png") {
+            None
+        } else {
+            Some(Path::new("cache").join(resource.path.file_name()?))
+        }
+    }
+}
+
+#[test]
+fn texture() {
+    // Textures that do not exist on the file system are kept by default.
+    let scene = Loader::default()
+        .custom_reader(read_textures)
+        .load("assets/a.obj")
+        .unwrap();
+    let textures: Vec<_> = scene
+        .materials
+        .iter()
+        .map(|m| m.texture.diffuse.clone())
+        .collect();
+    assert_eq!(
+        textures,
+        [
+            Some("assets/a.png".into()),
+            Some("https://example.com/b.png".into()),
+            Some("assets/c.png".into())
+        ]
+    );
+
+    let scene = Loader::default()
+        .resolver(Textures)
+        .load("assets/a.obj")
+        .unwrap();
+    let textures: Vec<_> = scene
+        .materials
+        .iter()
+        .map(|m| m.texture.diffuse.clone())
+        .collect();
+    assert_eq!(
+        textures,
+        [Some("cache/a.png".into()), Some("cache/b.png".into()), None]
+    );
+}
+
+#[test]
+fn memory_store() {
+    let mut store: MemoryStore = [
+        (
+            "/models/robot/a.obj",
+            "mtllib ./../materials/a.mtl\nmtllib ../../b.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl a\nf 1 2 3\nusemtl b\nf 1 2 3\n",
+        ),
+        ("models/materials/a.mtl", "newmtl a\nmap_Kd ./textures/../a.png\n"),
+        ("b.mtl", "newmtl b\nKd 1 0 0\n"),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(store.len(), 3);
+    assert!(store.insert("./models/materials/a.png", b"png").is_none());
+    assert!(store.insert("x", b"").is_none());
+    assert_eq!(&*store.remove("models/../x").unwrap(), b"");
+    assert_eq!(store.len(), 4);
+
+    let scene = Loader::default()
+        .resolver(store.clone())
+        .load("models/robot/a.obj")
+        .unwrap();
+    let texture = scene.materials[0].texture.diffuse.as_ref().unwrap();
+    assert_eq!(texture, Path::new("models/materials/a.png"));
+    assert_eq!(store.get(texture), Some(&b"png"[..]));
+    assert_eq!(scene.materials[1].color.diffuse, Some([1., 0., 0., 1.]));
+
+    // Without copying
+    let loader = Loader::<Arc<[u8]>>::with_resolver(store.clone());
+    let scene = loader.load("models/robot/a.obj").unwrap();
+    assert_eq!(scene.materials[1].color.diffuse, Some([1., 0., 0., 1.]));
+    let e = loader.load("a.obj").unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn sandbox() {
+    let obj =
+        |mtllib: &str| format!("mtllib {mtllib}\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl a\nf 1 2 3\n");
+    let store: MemoryStore = [
+        ("assets/a.mtl", "newmtl a\nmap_Kd textures/a.png\n"),
+        ("assets/b.mtl", "newmtl a\nmap_Kd /../a.png\n"),
+        (
+            "assets/c.mtl",
+            "newmtl a\nmap_Kd https://example.com/a.png\n",
+        ),
+        ("secret.mtl", "newmtl a\nKd 1 0 0\n"),
+    ]
+    .into_iter()
+    .collect();
+    let loader = Loader::default().resolver(store.clone());
+    let sandboxed = Loader::default().resolver(store).sandbox("assets");
+    let load = |loader: &Loader, mtllib: &str| {
+        loader.load_obj_from_slice(obj(mtllib).as_bytes(), "assets/models/a.obj")
+    };
+
+    let scene = load(&sandboxed, "../a.mtl").unwrap();
+    assert_eq!(
+        scene.materials[0].texture.diffuse.as_deref(),
+        Some(Path::new("assets/textures/a.png"))
+    );
+    // The file given to the loader is not checked.
+    sandboxed.load_obj_from_slice(b"", "a.obj").unwrap();
+
+    for mtllib in ["../../secret.mtl", "/secret.mtl", "../b.mtl", "../c.mtl"] {
+        load(&loader, mtllib).unwrap();
+        let e = load(&sandboxed, mtllib).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::PermissionDenied, "{mtllib}");
+    }
+    assert_eq!(
+        load(&sandboxed, "../../secret.mtl")
+            .unwrap_err()
+            .to_string(),
+        format!(
+            "../../secret.mtl is outside of the sandbox {} (referenced by assets/models/a.obj)",
+            env::current_dir().unwrap().join("assets").display()
+        )
+    );
+}
+
+#[test]
+fn load_many() {
+    let mut assets = Assets::default();
+    let obj = "mtllib m.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl a\nf 1 2 3\n";
+    assets.files.insert("assets/a.obj".into(), obj);
+    assets.files.insert("assets/b.obj".into(), obj);
+    assets.files.insert("assets/c.obj".into(), obj);
+    // Face index out of bounds
+    assets.files.insert("assets/d.obj".into(), "f 1 2 3\n");
+    assets
+        .files
+        .insert("assets/m.mtl".into(), "newmtl a\nKd 1 0 0\nmap_Kd a.png\n");
+    let requests = assets.requests.clone();
+    let loader = Loader::default().resolver(assets);
+    let paths = [
+        "assets/a.obj",
+        "assets/x.obj",
+        "assets/b.obj",
+        "assets/d.obj",
+        "assets/c.obj",
+    ];
+    let scenes = loader.load_many(&paths);
+    assert_eq!(scenes.len(), paths.len());
+    // A file that fails to load does not affect the others.
+    assert_eq!(
+        scenes[1].as_ref().unwrap_err().kind(),
+        io::ErrorKind::NotFound
+    );
+    assert!(scenes[3].is_err());
+    for i in [0, 2, 4] {
+        let scene = scenes[i].as_ref().unwrap();
+        let expected = loader.load(paths[i]).unwrap();
+        assert_eq!(scene.materials.len(), 1);
+        assert_eq!(scene.materials[0].color.diffuse, Some([1., 0., 0., 1.]));
+        assert_eq!(
+            scene.materials[0].texture.diffuse,
+            expected.materials[0].texture.diffuse
+        );
+        assert_eq!(
+            scene.materials[0].texture.diffuse.as_deref(),
+            Some(Path::new("assets/a.png"))
+        );
+    }
+    // The MTL file was read once by `load_many` and three times by `load`.
+    let mtl_requests = requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|r| r.3 == ResourceKind::Material)
+        .count();
+    assert_eq!(mtl_requests, 4);
+}
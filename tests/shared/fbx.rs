@@ -0,0 +1,145 @@
+pub(crate) enum Property {
+    I32(i32),
+    I64(i64),
+    F64(f64),
+    String(&'static [u8]),
+    F64Array(Vec<f64>),
+    I32Array(Vec<i32>),
+    CompressedF64Array(Vec<f64>),
+}
+
+pub(crate) struct Node {
+    pub(crate) name: &'static str,
+    pub(crate) properties: Vec<Property>,
+    pub(crate) children: Vec<Node>,
+}
+
+pub(crate) fn node(name: &'static str, properties: Vec<Property>, children: Vec<Node>) -> Node {
+    Node {
+        name,
+        properties,
+        children,
+    }
+}
+
+/// Creates a `P` record of a `Properties70` node.
+pub(crate) fn p(name: &'static [u8], ty: &'static [u8], values: Vec<Property>) -> Node {
+    let mut properties = vec![
+        Property::String(name),
+        Property::String(ty),
+        Property::String(b""),
+        Property::String(b"A"),
+    ];
+    properties.extend(values);
+    node("P", properties, vec![])
+}
+
+/// Creates a binary FBX file with the given top-level nodes.
+pub(crate) fn binary_fbx(version: u32, nodes: &[Node]) -> Vec<u8> {
+    let mut out = b"Kaydara FBX Binary  \0\x1a\0".to_vec();
+    out.extend_from_slice(&version.to_le_bytes());
+    // FBX 7.5 and later use 64-bit offsets.
+    let wide = version >= 7500;
+    for node in nodes {
+        write_node(&mut out, node, wide);
+    }
+    write_null_record(&mut out, wide);
+    out
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn write_node(out: &mut Vec<u8>, node: &Node, wide: bool) {
+    let start = out.len();
+    let offset_size = if wide { 8 } else { 4 };
+    out.resize(start + offset_size * 3, 0);
+    out.push(node.name.len() as u8);
+    out.extend_from_slice(node.name.as_bytes());
+    let properties_start = out.len();
+    for property in &node.properties {
+        write_property(out, property);
+    }
+    let properties_len = out.len() - properties_start;
+    if !node.children.is_empty() {
+        for child in &node.children {
+            write_node(out, child, wide);
+        }
+        write_null_record(out, wide);
+    }
+    let end = out.len();
+    for (i, v) in [end, node.properties.len(), properties_len]
+        .into_iter()
+        .enumerate()
+    {
+        let pos = start + i * offset_size;
+        if wide {
+            out[pos..pos + 8].copy_from_slice(&(v as u64).to_le_bytes());
+        } else {
+            out[pos..pos + 4].copy_from_slice(&(v as u32).to_le_bytes());
+        }
+    }
+}
+
+fn write_null_record(out: &mut Vec<u8>, wide: bool) {
+    out.resize(out.len() + if wide { 25 } else { 13 }, 0);
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn write_property(out: &mut Vec<u8>, property: &Property) {
+    match property {
+        Property::I32(v) => {
+            out.push(b'I');
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Property::I64(v) => {
+            out.push(b'L');
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Property::F64(v) => {
+            out.push(b'D');
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Property::String(v) => {
+            out.push(b'S');
+            out.extend_from_slice(&(v.len() as u32).to_le_bytes());
+            out.extend_from_slice(v);
+        }
+        Property::F64Array(v) => {
+            let data: Vec<u8> = v.iter().flat_map(|v| v.to_le_bytes()).collect();
+            write_array(out, b'd', v.len(), 0, &data);
+        }
+        Property::I32Array(v) => {
+            let data: Vec<u8> = v.iter().flat_map(|v| v.to_le_bytes()).collect();
+            write_array(out, b'i', v.len(), 0, &data);
+        }
+        Property::CompressedF64Array(v) => {
+            let data: Vec<u8> = v.iter().flat_map(|v| v.to_le_bytes()).collect();
+            write_array(out, b'd', v.len(), 1, &zlib_stored(&data));
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn write_array(out: &mut Vec<u8>, ty: u8, len: usize, encoding: u32, data: &[u8]) {
+    out.push(ty);
+    out.extend_from_slice(&(len as u32).to_le_bytes());
+    out.extend_from_slice(&encoding.to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(data);
+}
+
+/// Creates a zlib stream containing a single uncompressed deflate block.
+#[allow(clippy::cast_possible_truncation)]
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01, 0x01];
+    out.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(!(data.len() as u16)).to_le_bytes());
+    out.extend_from_slice(data);
+    // Adler-32 checksum
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for &byte in data {
+        a = (a + u32::from(byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+    out.extend_from_slice(&((b << 16) | a).to_be_bytes());
+    out
+}
@@ -0,0 +1,23 @@
+/// Creates a 3DS chunk with the given ID and data (including sub-chunks).
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn chunk(id: u16, data: &[Vec<u8>]) -> Vec<u8> {
+    let data = data.concat();
+    let mut out = id.to_le_bytes().to_vec();
+    out.extend_from_slice(&(data.len() as u32 + 6).to_le_bytes());
+    out.extend_from_slice(&data);
+    out
+}
+
+pub(crate) fn f32s(v: &[f32]) -> Vec<u8> {
+    v.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub(crate) fn u16s(v: &[u16]) -> Vec<u8> {
+    v.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Creates a 3DS file with the given materials (`MAT_ENTRY` chunks) and
+/// objects (`OBJECT` chunks).
+pub(crate) fn main_3ds(chunks: &[Vec<u8>]) -> Vec<u8> {
+    chunk(0x4D4D, &[chunk(0x3D3D, chunks)])
+}
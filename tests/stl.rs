@@ -0,0 +1,48 @@
+#![cfg(feature = "stl")]
+
+use mesh_loader::stl::BinaryView;
+
+#[test]
+#[allow(clippy::cast_precision_loss)]
+fn binary_view() {
+    let mut stl = b"solid header".to_vec();
+    stl.resize(80, b' ');
+    // Some STL files contain bogus count.
+    stl.extend_from_slice(&1_u32.to_le_bytes());
+    for i in 0..5_u16 {
+        let f = f32::from(i);
+        for v in [0., 0., f, f, 0., 0., 0., f, 0., 0., 0., f] {
+            stl.extend_from_slice(&f32::to_le_bytes(v));
+        }
+        stl.extend_from_slice(&i.to_le_bytes());
+    }
+    // Some buggy STL files have a newline after triangles.
+    stl.push(b'\n');
+
+    let view = BinaryView::new(&stl).unwrap();
+    assert!(view.header().starts_with(b"solid header"));
+    assert_eq!(view.num_triangles(), 5);
+    assert_eq!(view.attributes().collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+    let mut normals = view.normals();
+    assert_eq!(normals.len(), 5);
+    assert_eq!(normals.nth(3), Some([0., 0., 3.]));
+    assert_eq!(normals.len(), 1);
+    assert_eq!(normals.next_back(), Some([0., 0., 4.]));
+    assert_eq!(normals.next(), None);
+    assert_eq!(
+        view.positions().nth_back(3),
+        Some([[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]])
+    );
+
+    let mesh = view.to_mesh();
+    let scene = mesh_loader::stl::from_slice(&stl).unwrap();
+    assert_eq!(mesh.vertices, scene.meshes[0].vertices);
+    assert_eq!(mesh.normals, scene.meshes[0].normals);
+    assert_eq!(mesh.faces, scene.meshes[0].faces);
+    let vertices: Vec<_> = view.positions().flatten().collect();
+    assert_eq!(vertices, mesh.vertices);
+
+    assert!(BinaryView::new(&stl[..stl.len() - 2]).is_err());
+    assert!(BinaryView::new(&stl[..83]).is_err());
+    assert_eq!(BinaryView::new(&stl[..84]).unwrap().num_triangles(), 0);
+}
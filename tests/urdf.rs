@@ -0,0 +1,208 @@
+#![cfg(all(feature = "urdf", feature = "obj"))]
+
+use std::{
+    collections::HashMap,
+    f32::consts::FRAC_PI_2,
+    io,
+    path::{Path, PathBuf},
+};
+
+use mesh_loader::Loader;
+
+type Vec3 = [f32; 3];
+
+const ROBOT: &str = r#"<?xml version="1.0"?>
+<robot name="arm">
+  <material name="red">
+    <color rgba="1 0 0 1"/>
+  </material>
+  <link name="base">
+    <visual>
+      <origin xyz="0 0 0.5"/>
+      <geometry><box size="1 2 1"/></geometry>
+      <material name="red"/>
+    </visual>
+  </link>
+  <link name="upper">
+    <visual name="tri">
+      <origin xyz="1 0 0"/>
+      <geometry><mesh filename="meshes/tri.obj" scale="2 2 2"/></geometry>
+      <material name="red"/>
+    </visual>
+    <collision>
+      <geometry><cylinder radius="0.5" length="2"/></geometry>
+    </collision>
+  </link>
+  <link name="finger">
+    <collision>
+      <geometry><sphere radius="0.25"/></geometry>
+    </collision>
+  </link>
+  <joint name="shoulder" type="revolute">
+    <parent link="base"/>
+    <child link="upper"/>
+    <origin xyz="0 0 1"/>
+    <axis xyz="0 0 1"/>
+  </joint>
+  <joint name="slide" type="prismatic">
+    <parent link="upper"/>
+    <child link="finger"/>
+    <axis xyz="1 0 0"/>
+    <mimic joint="shoulder" multiplier="2" offset="1"/>
+  </joint>
+</robot>
+"#;
+
+fn reader(path: &Path) -> io::Result<Vec<u8>> {
+    if path == Path::new("robot/meshes/tri.obj") {
+        Ok(b"v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n".to_vec())
+    } else if path == Path::new("robot/meshes/slope.obj") {
+        // A triangle on the plane x + y = 1.
+        Ok(b"v 1 0 0\nv 0 1 0\nv 0 1 1\nvn 1 1 0\nf 1//1 2//1 3//1\n".to_vec())
+    } else if path == Path::new("robot/arm.urdf") {
+        Ok(ROBOT.as_bytes().to_vec())
+    } else {
+        Err(io::Error::from(io::ErrorKind::NotFound))
+    }
+}
+
+fn assert_near(a: Vec3, b: Vec3) {
+    assert!(
+        a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5),
+        "{a:?} != {b:?}"
+    );
+}
+
+fn center(vertices: &[Vec3]) -> Vec3 {
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for v in vertices {
+        for i in 0..3 {
+            min[i] = min[i].min(v[i]);
+            max[i] = max[i].max(v[i]);
+        }
+    }
+    [
+        (min[0] + max[0]) / 2.,
+        (min[1] + max[1]) / 2.,
+        (min[2] + max[2]) / 2.,
+    ]
+}
+
+#[test]
+fn robot() {
+    let loader = Loader::default().custom_reader(reader);
+    let joint_positions: HashMap<_, _> = [("shoulder".to_owned(), FRAC_PI_2)].into_iter().collect();
+    let scene = loader
+        .load_urdf("robot/arm.urdf", &joint_positions)
+        .unwrap();
+    let names: Vec<_> = scene.meshes.iter().map(|m| &*m.name).collect();
+    assert_eq!(
+        names,
+        [
+            "base/visual",
+            "upper/visual/tri",
+            "upper/collision",
+            "finger/collision"
+        ]
+    );
+    assert_eq!(scene.materials.len(), scene.meshes.len());
+
+    // Box
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.faces.len(), 12);
+    assert_near(center(&mesh.vertices), [0., 0., 0.5]);
+    assert_eq!(scene.materials[0].name, "red");
+    assert_eq!(scene.materials[0].color.diffuse, Some([1., 0., 0., 1.]));
+
+    // Mesh rotated by the joint and scaled; the URDF material fills in the color.
+    let mesh = &scene.meshes[1];
+    assert_near(mesh.vertices[0], [0., 1., 1.]);
+    assert_near(mesh.vertices[1], [0., 3., 1.]);
+    assert_near(mesh.vertices[2], [-2., 1., 1.]);
+    assert_eq!(scene.materials[1].color.diffuse, Some([1., 0., 0., 1.]));
+
+    // Cylinder along the z-axis
+    let mesh = &scene.meshes[2];
+    assert_near(center(&mesh.vertices), [0., 0., 1.]);
+    assert!(mesh.vertices.iter().all(|v| (v[2] - 1.).abs() <= 1. + 1e-5));
+    assert_eq!(scene.materials[2].color.diffuse, Some([0.6, 0.6, 0.6, 1.]));
+
+    // Sphere moved by the mimic joint: 2 * (pi / 2) + 1 along the rotated x-axis.
+    let mesh = &scene.meshes[3];
+    let c = [0., 2. * FRAC_PI_2 + 1., 1.];
+    assert_near(center(&mesh.vertices), c);
+    for (v, n) in mesh.vertices.iter().zip(&mesh.normals) {
+        let d = [v[0] - c[0], v[1] - c[1], v[2] - c[2]];
+        assert_near(d, [n[0] * 0.25, n[1] * 0.25, n[2] * 0.25]);
+    }
+
+    // Joints not given are at position 0, and .urdf files are detected.
+    let scene = loader.load("robot/arm.urdf").unwrap();
+    assert_near(scene.meshes[1].vertices[1], [3., 0., 1.]);
+    assert_near(center(&scene.meshes[3].vertices), [1., 0., 1.]);
+}
+
+#[test]
+fn non_uniform_scale() {
+    let loader = Loader::default().custom_reader(reader);
+    let scene = loader
+        .load_urdf_from_slice(
+            b"<robot><link name=\"a\"><visual><geometry><mesh filename=\"meshes/slope.obj\" scale=\"2 1 1\"/></geometry></visual></link></robot>",
+            "robot/a.urdf",
+            &HashMap::new(),
+        )
+        .unwrap();
+    // The plane is scaled to x / 2 + y = 1.
+    let n = [1. / 5_f32.sqrt(), 2. / 5_f32.sqrt(), 0.];
+    assert_eq!(scene.meshes[0].normals.len(), 3);
+    for &normal in &scene.meshes[0].normals {
+        assert_near(normal, n);
+    }
+}
+
+#[test]
+fn error() {
+    let loader = Loader::default().custom_reader(reader);
+    let load = |s: &str| {
+        loader
+            .load_urdf_from_slice(s.as_bytes(), PathBuf::from("robot/a.urdf"), &HashMap::new())
+            .unwrap_err()
+            .to_string()
+    };
+    assert_eq!(
+        load("<model/>"),
+        "expected <robot> element, but found <model> element at 1:1"
+    );
+    assert_eq!(
+        load("<robot><link name=\"a\"><visual/></link></robot>"),
+        "expected <geometry> element in <visual> element at 1:23"
+    );
+    assert_eq!(
+        load("<robot><link name=\"a\"><visual><geometry><box size=\"1 1\"/></geometry></visual></link></robot>"),
+        "error while parsing a float in <box> element at 1:52: \"1 1\""
+    );
+    assert_eq!(
+        load("<robot><joint name=\"j\" type=\"fixed\"><child link=\"a\"/></joint></robot>"),
+        "expected <parent> element in <joint> element at 1:8"
+    );
+    // Errors from the mesh files are returned as is.
+    let e = loader
+        .load_urdf_from_slice(
+            b"<robot><link name=\"a\"><visual><geometry><mesh filename=\"b.stl\"/></geometry></visual></link></robot>",
+            "robot/a.urdf",
+            &HashMap::new(),
+        )
+        .unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::NotFound);
+    // Meshes outside of the sandbox are not read.
+    let e = loader
+        .sandbox("robot/urdf")
+        .load_urdf_from_slice(
+            b"<robot><link name=\"a\"><visual><geometry><mesh filename=\"../meshes/tri.obj\"/></geometry></visual></link></robot>",
+            "robot/urdf/a.urdf",
+            &HashMap::new(),
+        )
+        .unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::PermissionDenied);
+}
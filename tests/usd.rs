@@ -0,0 +1,260 @@
+#![cfg(feature = "usd")]
+
+use mesh_loader::usd::from_slice;
+
+const QUAD_USDA: &str = r#"#usda 1.0
+(
+    defaultPrim = "World"
+    doc = """Quad
+with two materials"""
+    metersPerUnit = 1
+    upAxis = "Z"
+)
+
+def Xform "World" (
+    kind = "component"
+)
+{
+    double3 xformOp:translate = (0, 0, 1)
+    float xformOp:rotateZ.timeSamples = {
+        0: 90,
+        1: 180,
+    }
+    uniform token[] xformOpOrder = ["xformOp:translate", "xformOp:rotateZ"]
+    rel material:binding = </World/Looks/Red>
+
+    def Mesh "Quad" (
+        prepend apiSchemas = ["MaterialBindingAPI"]
+    )
+    {
+        int[] faceVertexCounts = [3, 3]
+        int[] faceVertexIndices = [0, 1, 2, 0, 2, 3]
+        point3f[] points = [(0, 0, 0), (1, 0, 0), (1, 1, 0), (0, 1, 0)]
+        normal3f[] normals = [(0, 0, 1)] (
+            interpolation = "constant"
+        )
+        texCoord2f[] primvars:st = [(0, 0), (1, 0), (1, 1), (0, 1)] (
+            interpolation = "faceVarying"
+        )
+        int[] primvars:st:indices = [0, 1, 2, 0, 2, 3]
+        color3f[] primvars:displayColor = [(1, 0, 0), (0, 1, 0)] (
+            interpolation = "uniform"
+        )
+
+        def GeomSubset "Second"
+        {
+            uniform token elementType = "face"
+            uniform token familyName = "materialBind"
+            int[] indices = [1]
+            rel material:binding = </World/Looks/Textured>
+        }
+    }
+
+    def Scope "Looks"
+    {
+        def Material "Red"
+        {
+            token outputs:surface.connect = </World/Looks/Red/Shader.outputs:surface>
+
+            def Shader "Shader"
+            {
+                uniform token info:id = "UsdPreviewSurface"
+                color3f inputs:diffuseColor = (1, 0, 0)
+                float inputs:opacity = 0.5
+                token outputs:surface
+            }
+        }
+
+        def Material "Textured"
+        {
+            def Shader "Shader"
+            {
+                uniform token info:id = "UsdPreviewSurface"
+                color3f inputs:diffuseColor.connect = </World/Looks/Textured/Texture.outputs:rgb>
+            }
+
+            def Shader "Texture"
+            {
+                uniform token info:id = "UsdUVTexture"
+                asset inputs:file = @textures/wood.png@
+                float3 outputs:rgb
+            }
+        }
+    }
+}
+"#;
+
+#[test]
+fn quad() {
+    let scene = from_slice(QUAD_USDA.as_bytes()).unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+    assert_eq!(scene.materials.len(), 2);
+
+    // The mesh is rotated 90 degrees around the Z axis (the first time
+    // sample), and then translated by (0, 0, 1).
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name, "Quad");
+    assert_eq!(mesh.faces, vec![[0, 1, 2]]);
+    let expected = [[0., 0., 1.], [0., 1., 1.], [-1., 1., 1.]];
+    for (v, e) in mesh.vertices.iter().zip(expected) {
+        assert!(v.iter().zip(e).all(|(v, e)| (v - e).abs() < 1e-6), "{v:?}");
+    }
+    assert_eq!(mesh.normals, vec![[0., 0., 1.]; 3]);
+    assert_eq!(mesh.texcoords[0], vec![[0., 0.], [1., 0.], [1., 1.]]);
+    assert_eq!(mesh.colors[0], vec![[1., 0., 0., 1.]; 3]);
+    let material = &scene.materials[0];
+    assert_eq!(material.name, "Red");
+    assert_eq!(material.color.diffuse, Some([1., 0., 0., 0.5]));
+    assert_eq!(material.opacity, Some(0.5));
+
+    // The face in the GeomSubset.
+    let mesh = &scene.meshes[1];
+    assert_eq!(mesh.texcoords[0], vec![[0., 0.], [1., 1.], [0., 1.]]);
+    assert_eq!(mesh.colors[0], vec![[0., 1., 0., 1.]; 3]);
+    let material = &scene.materials[1];
+    assert_eq!(material.name, "Textured");
+    assert_eq!(
+        material.texture.diffuse.as_deref(),
+        Some(std::path::Path::new("textures/wood.png"))
+    );
+}
+
+#[test]
+fn units() {
+    // The fallback value of metersPerUnit is 0.01.
+    let scene = from_slice(
+        br#"#usda 1.0
+def Mesh "Triangle"
+{
+    int[] faceVertexCounts = [3]
+    int[] faceVertexIndices = [0, 1, 2]
+    point3f[] points = [(0, 0, 0), (100, 0, 0), (0, 100, 0)]
+}
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        scene.meshes[0].vertices,
+        vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]
+    );
+    assert_eq!(scene.materials[0].color.diffuse, Some([0.6, 0.6, 0.6, 1.]));
+}
+
+#[test]
+fn non_uniform_scale() {
+    // A triangle on the plane x + y = 1, scaled to the plane x / 2 + y = 1.
+    let scene = from_slice(
+        br#"#usda 1.0
+(
+    metersPerUnit = 1
+)
+def Mesh "Triangle"
+{
+    float3 xformOp:scale = (2, 1, 1)
+    uniform token[] xformOpOrder = ["xformOp:scale"]
+    int[] faceVertexCounts = [3]
+    int[] faceVertexIndices = [0, 1, 2]
+    point3f[] points = [(1, 0, 0), (0, 1, 0), (0, 1, 1)]
+    normal3f[] normals = [(1, 1, 0)] (
+        interpolation = "constant"
+    )
+}
+"#,
+    )
+    .unwrap();
+    let expected = [1. / 5_f32.sqrt(), 2. / 5_f32.sqrt(), 0.];
+    for n in &scene.meshes[0].normals {
+        for (n, expected) in n.iter().zip(expected) {
+            assert!((n - expected).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn error() {
+    let e = from_slice(b"#usda 1.0\ndef Mesh \"A\"\n{\n    int[] faceVertexCounts = [3\n}\n")
+        .unwrap_err();
+    assert_eq!(e.to_string(), "expected ',' or ']' (5:1)");
+    let e = from_slice(b"PXR-USDC").unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::Unsupported);
+    let e = from_slice(
+        b"#usda 1.0\ndef Mesh \"A\" {\n int[] faceVertexCounts = [3]\n int[] faceVertexIndices = [0, 1, 2]\n}\n",
+    )
+    .unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "index out of bounds (0) in faceVertexIndices of /A"
+    );
+    // Huge counts do not overflow.
+    let e = from_slice(
+        b"#usda 1.0\ndef Mesh \"A\" {\n int[] faceVertexCounts = [1e30, 3]\n int[] faceVertexIndices = [0, 1, 2]\n}\n",
+    )
+    .unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "faceVertexCounts and faceVertexIndices mismatch in /A"
+    );
+}
+
+#[test]
+fn xform_stack() {
+    let scene = from_slice(
+        br#"#usda 1.0
+(
+    metersPerUnit = 1
+)
+def Xform "Parent"
+{
+    double3 xformOp:translate = (10, 0, 0)
+    uniform token[] xformOpOrder = ["xformOp:translate"]
+
+    def Mesh "Reset"
+    {
+        double3 xformOp:translate:pivot = (1, 0, 0)
+        float3 xformOp:scale = (2, 2, 2)
+        uniform token[] xformOpOrder = ["!resetXformStack!", "xformOp:translate:pivot", "xformOp:scale", "!invert!xformOp:translate:pivot"]
+        int[] faceVertexCounts = [3]
+        int[] faceVertexIndices = [0, 1, 2]
+        point3f[] points = [(0, 0, 0), (1, 0, 0), (0, 1, 0)]
+    }
+
+    def Mesh "Hidden"
+    {
+        token visibility = "invisible"
+        int[] faceVertexCounts = [3]
+        int[] faceVertexIndices = [0, 1, 2]
+        point3f[] points = [(0, 0, 0), (1, 0, 0), (0, 1, 0)]
+    }
+
+    over "Over"
+    {
+    }
+
+    def Mesh "LeftHanded"
+    {
+        uniform token orientation = "leftHanded"
+        int[] faceVertexCounts = [3]
+        int[] faceVertexIndices = [0, 1, 2]
+        point3f[] points = [(0, 0, 0), (1, 0, 0), (0, 1, 0)]
+    }
+}
+"#,
+    )
+    .unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+    // The transform of the parent is ignored, and the mesh is scaled around
+    // the pivot.
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name, "Reset");
+    assert_eq!(
+        mesh.vertices,
+        vec![[-1., 0., 0.], [1., 0., 0.], [-1., 2., 0.]]
+    );
+    // The winding order of left-handed meshes is reversed.
+    let mesh = &scene.meshes[1];
+    assert_eq!(mesh.name, "LeftHanded");
+    assert_eq!(
+        mesh.vertices,
+        vec![[10., 0., 0.], [10., 1., 0.], [11., 0., 0.]]
+    );
+}
@@ -0,0 +1,107 @@
+#![cfg(all(feature = "obj", feature = "stl"))]
+
+use mesh_loader::Mesh;
+
+/// Returns the vertices of each triangle of the mesh.
+fn triangles(mesh: &Mesh) -> Vec<[[f32; 3]; 3]> {
+    mesh.faces
+        .iter()
+        .map(|f| f.map(|i| mesh.vertices[i as usize]))
+        .collect()
+}
+
+#[test]
+fn stl() {
+    let ascii = b"solid a
+facet normal 0 0 1
+outer loop
+vertex 0 0 0
+vertex 1 0 0
+vertex 0 1 0
+endloop
+endfacet
+endsolid a
+solid b
+facet normal 0 0 -1
+outer loop
+vertex 0 0 1
+vertex 0 1 1
+vertex 1 0 1
+endloop
+endfacet
+endsolid b
+";
+    let mut solids = vec![];
+    let mut vertices = vec![];
+    mesh_loader::stl::for_each_triangle(ascii, |triangle| {
+        solids.push(triangle.solid.to_owned());
+        vertices.push(triangle.vertices);
+        assert!(triangle.color.is_none());
+    })
+    .unwrap();
+    assert_eq!(solids, ["a", "b"]);
+    let scene = mesh_loader::stl::from_slice(ascii).unwrap();
+    assert_eq!(vertices[..1], triangles(&scene.meshes[0]));
+    assert_eq!(vertices[1..], triangles(&scene.meshes[1]));
+
+    // Binary STL with a color per triangle.
+    let mut binary = b"COLOR=\x01\x02\x03\x04".to_vec();
+    binary.resize(80, b' ');
+    binary.extend_from_slice(&2_u32.to_le_bytes());
+    for color in [0_u16, 1 << 15 | 31] {
+        for f in [0., 0., 1., 0., 0., 0., 1., 0., 0., 0., 1., 0.] {
+            binary.extend_from_slice(&f32::to_le_bytes(f));
+        }
+        binary.extend_from_slice(&color.to_le_bytes());
+    }
+    let mut colors = vec![];
+    mesh_loader::stl::for_each_triangle(&binary, |triangle| {
+        assert_eq!(triangle.normal, [0., 0., 1.]);
+        assert_eq!(
+            triangle.vertices,
+            [[0., 0., 0.], [1., 0., 0.], [0., 1., 0.]]
+        );
+        colors.push(triangle.color);
+    })
+    .unwrap();
+    assert_eq!(colors, [None, Some([1., 0., 0., 1.])]);
+
+    assert!(mesh_loader::stl::for_each_triangle(b"solid a\nfacet", |_| {}).is_err());
+}
+
+#[test]
+fn obj() {
+    let obj = b"v 0 0 0 1 0 0
+v 1 0 0 0 1 0
+v 1 1 0 0 0 1
+v 0 1 0 1 1 1
+vn 0 0 1
+f 1//1 2//1 3//1
+g a
+usemtl m
+f 1 2 3 4
+l 1 2
+";
+    let mut triangles_ = vec![];
+    mesh_loader::obj::for_each_triangle(obj, |triangle| {
+        triangles_.push((
+            triangle.vertices,
+            triangle.normals.is_some(),
+            triangle.colors.unwrap()[0],
+            triangle.group.to_owned(),
+            triangle.material.to_owned(),
+        ));
+    })
+    .unwrap();
+    assert_eq!(triangles_.len(), 3);
+    assert_eq!(triangles_[0].1, true);
+    assert_eq!(triangles_[0].2, [1., 0., 0., 1.]);
+    assert_eq!((&*triangles_[0].3, &*triangles_[0].4), ("default", ""));
+    assert_eq!((&*triangles_[2].3, &*triangles_[2].4), ("a", "m"));
+    let scene = mesh_loader::obj::from_slice(obj, None, |_| Ok(vec![])).unwrap();
+    let expected: Vec<_> = scene.meshes.iter().flat_map(triangles).collect();
+    let vertices: Vec<_> = triangles_.iter().map(|t| t.0).collect();
+    assert_eq!(vertices, expected);
+
+    assert!(mesh_loader::obj::for_each_triangle(b"v 0 0 0\nf 1 2 3\n", |_| {}).is_err());
+}
@@ -0,0 +1,226 @@
+#![cfg(feature = "vrml")]
+
+use mesh_loader::vrml::from_slice;
+
+const QUAD_WRL: &str = r#"#VRML V2.0 utf8
+# A quad translated by (0, 0, 1).
+PROTO Unused [ field SFFloat size 1 ] { Group {} }
+DEF Root Transform {
+  translation 0 0 1
+  children [
+    DEF Quad Shape {
+      appearance Appearance {
+        material DEF Red Material {
+          diffuseColor 1 0 0
+          transparency 0.25
+        }
+        texture ImageTexture { url [ "red.png" "red.jpg" ] }
+      }
+      geometry IndexedFaceSet {
+        coord Coordinate { point [ 0 0 0, 1 0 0, 1 1 0, 0 1 0 ] }
+        coordIndex [ 0, 1, 2, 3, -1 ]
+        texCoord TextureCoordinate { point [ 0 0, 1 0, 1 1, 0 1 ] }
+        normal Normal { vector [ 0 0 2 ] }
+        normalIndex [ 0 ]
+        normalPerVertex FALSE
+      }
+    }
+    Transform {
+      rotation 0 0 1 3.14159265
+      children USE Quad
+    }
+  ]
+}
+ROUTE Root.translation TO Root.translation
+"#;
+
+#[test]
+fn classic() {
+    let scene = from_slice(QUAD_WRL.as_bytes()).unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+    assert_eq!(scene.materials.len(), 2);
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name, "Quad");
+    assert_eq!(mesh.faces, vec![[0, 1, 2], [3, 4, 5]]);
+    assert_eq!(
+        mesh.vertices,
+        vec![
+            [0., 0., 1.],
+            [1., 0., 1.],
+            [1., 1., 1.],
+            [0., 0., 1.],
+            [1., 1., 1.],
+            [0., 1., 1.],
+        ]
+    );
+    assert_eq!(mesh.normals, vec![[0., 0., 1.]; 6]);
+    assert_eq!(
+        mesh.texcoords[0],
+        vec![[0., 0.], [1., 0.], [1., 1.], [0., 0.], [1., 1.], [0., 1.]]
+    );
+    let material = &scene.materials[0];
+    assert_eq!(material.name, "Red");
+    assert_eq!(material.color.diffuse, Some([1., 0., 0., 0.75]));
+    assert_eq!(material.opacity, Some(0.75));
+    assert_eq!(
+        material.texture.diffuse.as_deref(),
+        Some(std::path::Path::new("red.png"))
+    );
+
+    // The shape reused by USE is rotated 180 degrees around the z-axis.
+    let mesh = &scene.meshes[1];
+    assert_eq!(mesh.name, "Quad");
+    let [x, y, z] = mesh.vertices[2];
+    assert!((x + 1.).abs() < 1e-6 && (y + 1.).abs() < 1e-6 && z == 1.);
+}
+
+#[test]
+fn non_uniform_scale() {
+    // A triangle on the plane x + y = 1, scaled to the plane x / 2 + y = 1.
+    let wrl = r"#VRML V2.0 utf8
+Transform {
+  scale 2 1 1
+  children Shape {
+    geometry IndexedFaceSet {
+      coord Coordinate { point [ 1 0 0, 0 1 0, 0 1 1 ] }
+      coordIndex [ 0, 1, 2, -1 ]
+      normal Normal { vector [ 1 1 0 ] }
+      normalIndex [ 0 ]
+      normalPerVertex FALSE
+    }
+  }
+}
+";
+    let scene = from_slice(wrl.as_bytes()).unwrap();
+    let expected = [1. / 5_f32.sqrt(), 2. / 5_f32.sqrt(), 0.];
+    for n in &scene.meshes[0].normals {
+        for (n, expected) in n.iter().zip(expected) {
+            assert!((n - expected).abs() < 1e-6);
+        }
+    }
+}
+
+const QUAD_X3D: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<X3D profile="Interchange" version="3.3">
+  <Scene>
+    <Transform DEF="Root" scale="2 2 2">
+      <Shape DEF="Quad">
+        <Appearance>
+          <Material DEF="Green" diffuseColor="0 1 0"/>
+        </Appearance>
+        <IndexedFaceSet coordIndex="0 1 2 3 -1" colorIndex="0 1 2 3" ccw="false">
+          <Coordinate point="0 0 0, 1 0 0, 1 1 0, 0 1 0"/>
+          <ColorRGBA color="1 0 0 1, 0 1 0 1, 0 0 1 1, 1 1 1 0.5"/>
+        </IndexedFaceSet>
+      </Shape>
+    </Transform>
+    <ROUTE fromNode="Root" fromField="scale" toNode="Root" toField="scale"/>
+    <Group>
+      <Shape USE="Quad"/>
+    </Group>
+  </Scene>
+</X3D>
+"#;
+
+#[test]
+fn x3d() {
+    let scene = from_slice(QUAD_X3D.as_bytes()).unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name, "Quad");
+    // ccw="false" reverses the winding order.
+    assert_eq!(
+        mesh.vertices,
+        vec![
+            [0., 0., 0.],
+            [2., 2., 0.],
+            [2., 0., 0.],
+            [0., 0., 0.],
+            [0., 2., 0.],
+            [2., 2., 0.],
+        ]
+    );
+    assert!(mesh.normals.is_empty());
+    assert_eq!(
+        mesh.colors[0][..3],
+        [[1., 0., 0., 1.], [0., 0., 1., 1.], [0., 1., 0., 1.]]
+    );
+    assert_eq!(scene.materials[0].name, "Green");
+    assert_eq!(scene.materials[0].color.diffuse, Some([0., 1., 0., 1.]));
+
+    // The shape reused by USE is not scaled.
+    assert_eq!(scene.meshes[1].vertices[1], [1., 1., 0.]);
+}
+
+#[test]
+fn error() {
+    let e = from_slice(b"#VRML V2.0 utf8\nShape {\n  geometry USE Foo\n}\n").unwrap_err();
+    assert_eq!(e.to_string(), "USE of undefined node (3:16)");
+    let e = from_slice(b"#VRML V1.0 ascii\nSeparator {}\n").unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::Unsupported);
+    let e = from_slice(
+        b"#VRML V2.0 utf8\nShape { geometry IndexedFaceSet { coordIndex [ 0 1 2 ] } }\n",
+    )
+    .unwrap_err();
+    assert_eq!(e.to_string(), "index out of bounds (0) in coordIndex");
+}
+
+#[test]
+fn grouping_nodes() {
+    let wrl = r"#VRML V2.0 utf8
+Switch {
+  whichChoice 1
+  choice [
+    Shape {
+      geometry IndexedFaceSet {
+        coord DEF Square Coordinate { point [ 0 0 0, 1 0 0, 1 1 0, 0 1 0 ] }
+        coordIndex [ 0, 1, 2 ]
+      }
+    }
+    DEF Chosen Shape {
+      geometry IndexedFaceSet {
+        coord USE Square
+        coordIndex [ 0, 1, 2, -1, 0, 2, 3, -1 ]
+        color Color { color [ 1 0 0, 0 0 1 ] }
+        colorPerVertex FALSE
+      }
+    }
+  ]
+}
+# No choice is selected by default.
+Switch {
+  choice Shape {
+    geometry IndexedFaceSet { coord USE Square coordIndex [ 0, 1, 2 ] }
+  }
+}
+# The first level is the most detailed.
+LOD {
+  level [
+    DEF Detailed Transform {
+      translation 0 0 1
+      children Shape {
+        geometry IndexedFaceSet { coord USE Square coordIndex [ 0, 1, 2 ] }
+      }
+    }
+    Shape {
+      geometry IndexedFaceSet { coord USE Square coordIndex [ 0, 2, 3 ] }
+    }
+  ]
+}
+";
+    let scene = from_slice(wrl.as_bytes()).unwrap();
+    assert_eq!(scene.meshes.len(), 2);
+    let mesh = &scene.meshes[0];
+    assert_eq!(mesh.name, "Chosen");
+    // Colors are per face.
+    assert_eq!(
+        mesh.colors[0],
+        [[[1., 0., 0., 1.]; 3], [[0., 0., 1., 1.]; 3]].concat()
+    );
+    let mesh = &scene.meshes[1];
+    assert_eq!(mesh.name, "Detailed");
+    assert_eq!(
+        mesh.vertices,
+        vec![[0., 0., 1.], [1., 0., 1.], [1., 1., 1.]]
+    );
+}
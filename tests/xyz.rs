@@ -0,0 +1,61 @@
+#![cfg(feature = "xyz")]
+
+use mesh_loader::xyz::from_slice;
+
+#[test]
+fn columns() {
+    let scene = from_slice(b"# comment\n0 0 0\n1.5,2.5,3.5\n\n").unwrap();
+    let cloud = &scene.point_clouds[0];
+    assert_eq!(cloud.points, vec![[0., 0., 0.], [1.5, 2.5, 3.5]]);
+    assert!(cloud.colors.is_empty() && cloud.normals.is_empty());
+
+    // x y z r g b
+    let scene = from_slice(b"0 0 0 255 0 0\n1 1 1 0 255 0\n").unwrap();
+    let cloud = &scene.point_clouds[0];
+    assert_eq!(cloud.colors, vec![[1., 0., 0., 1.], [0., 1., 0., 1.]]);
+    assert!(cloud.normals.is_empty());
+
+    // x y z nx ny nz
+    let scene = from_slice(b"0 0 0 0.0 0.0 1.0\n").unwrap();
+    let cloud = &scene.point_clouds[0];
+    assert_eq!(cloud.normals, vec![[0., 0., 1.]]);
+    assert!(cloud.colors.is_empty());
+
+    // x y z r g b nx ny nz
+    let scene = from_slice(b"0 0 0 0 0 255 1 0 0\n").unwrap();
+    let cloud = &scene.point_clouds[0];
+    assert_eq!(cloud.colors, vec![[0., 0., 1., 1.]]);
+    assert_eq!(cloud.normals, vec![[1., 0., 0.]]);
+}
+
+#[test]
+fn pts() {
+    // The first line is the number of points.
+    let scene = from_slice(b"2\n0 0 0 -100 255 255 255\n1 0 0 200 0 0 0\n").unwrap();
+    let cloud = &scene.point_clouds[0];
+    assert_eq!(cloud.points, vec![[0., 0., 0.], [1., 0., 0.]]);
+    assert_eq!(cloud.intensities, vec![-100., 200.]);
+    assert_eq!(cloud.colors[0], [1., 1., 1., 1.]);
+}
+
+#[test]
+fn error() {
+    let e = from_slice(b"0 0 0\n0 0\n").unwrap_err();
+    assert_eq!(e.to_string(), "invalid number of values in point (2:1)");
+    let e = from_slice(b"0 0 0\n0 x 0\n").unwrap_err();
+    assert_eq!(e.to_string(), "error while parsing a float (2:3)");
+    let e = from_slice(b"0 0 0 0 0\n").unwrap_err();
+    assert_eq!(e.to_string(), "expected 3, 4, 6, 7, or 9 values (1:1)");
+}
+
+#[test]
+fn separators() {
+    // x y z intensity, separated by tabs, semicolons, or commas, with CRLF
+    // line endings and comments.
+    let s = b"// exported\r\n1\t2\t3\t10\r\n4;5;6;20\r\n# comment\r\n7, 8, 9, 30\r\n";
+    let scene = from_slice(s).unwrap();
+    let cloud = &scene.point_clouds[0];
+    assert_eq!(cloud.points, vec![[1., 2., 3.], [4., 5., 6.], [7., 8., 9.]]);
+    assert_eq!(cloud.intensities, vec![10., 20., 30.]);
+    assert!(cloud.colors.is_empty() && cloud.normals.is_empty());
+}